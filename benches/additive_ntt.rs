@@ -0,0 +1,108 @@
+//! Focused benchmarks of binius's additive NTT over binary tower fields.
+//!
+//! binius selects its SIMD implementation at compile time from the enabled target features, so
+//! the packing widths below map onto different code paths: 128-bit packings use SSE/NEON (and
+//! GFNI when available), 256-bit packings use AVX2, and 512-bit packings use AVX-512. Packings
+//! wider than the target supports fall back to composing narrower ones. To compare against the
+//! portable implementation, build once without `-Ctarget-cpu=native`.
+
+use std::iter::repeat_with;
+
+use binius_field::{
+	arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField32b, PackedBinaryField16x32b,
+	PackedBinaryField4x32b, PackedBinaryField8x32b, PackedField,
+};
+use binius_ntt::{AdditiveNTT, SingleThreadedNTT};
+use criterion::{
+	criterion_group, criterion_main, measurement::Measurement, BenchmarkGroup, BenchmarkId,
+	Criterion, Throughput,
+};
+use rand::{thread_rng, Rng};
+use rayon::ThreadPoolBuilder;
+use ulvt_snark_bench::threads::thread_counts;
+
+const LOG_N: usize = 20;
+
+fn bench_packing<P, M>(group: &mut BenchmarkGroup<M>, mut rng: impl Rng, name: &str)
+where
+	P: PackedField<Scalar = BinaryField32b>,
+	M: Measurement,
+{
+	let mut data = repeat_with(|| P::random(&mut rng))
+		.take(1 << (LOG_N - P::LOG_WIDTH))
+		.collect::<Vec<_>>();
+
+	let on_the_fly = SingleThreadedNTT::<BinaryField32b>::new(LOG_N).unwrap();
+	group.bench_function(format!("{name} on-the-fly twiddles"), |b| {
+		b.iter(|| on_the_fly.forward_transform(&mut data, 0, 0).unwrap())
+	});
+
+	let precomputed = SingleThreadedNTT::<BinaryField32b>::new(LOG_N)
+		.unwrap()
+		.precompute_twiddles();
+	group.bench_function(format!("{name} precomputed twiddles"), |b| {
+		b.iter(|| precomputed.forward_transform(&mut data, 0, 0).unwrap())
+	});
+}
+
+/// Single-threaded transforms at each packing width.
+fn bench_packings(c: &mut Criterion) {
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("binius additive ntt packing");
+	group.throughput(Throughput::Elements(1 << LOG_N));
+
+	bench_packing::<PackedBinaryField4x32b, _>(&mut group, &mut rng, "128-bit 4x32b");
+	bench_packing::<PackedBinaryField8x32b, _>(&mut group, &mut rng, "256-bit 8x32b");
+	bench_packing::<PackedBinaryField16x32b, _>(&mut group, &mut rng, "512-bit 16x32b");
+
+	group.finish()
+}
+
+/// The single-threaded transform against the multithreaded one at the optimal packing, sweeping
+/// the rayon pool size.
+fn bench_threads(c: &mut Criterion) {
+	type P = PackedType<OptimalUnderlier, BinaryField32b>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("binius additive ntt threads");
+	group.sample_size(10);
+
+	for log_n in [20, 24] {
+		let mut data = repeat_with(|| P::random(&mut rng))
+			.take(1 << (log_n - P::LOG_WIDTH))
+			.collect::<Vec<_>>();
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		let single_threaded = SingleThreadedNTT::<BinaryField32b>::new(log_n)
+			.unwrap()
+			.precompute_twiddles();
+		group.bench_function(format!("single-threaded log_n={log_n}"), |b| {
+			b.iter(|| single_threaded.forward_transform(&mut data, 0, 0).unwrap())
+		});
+
+		for n_threads in thread_counts() {
+			let pool = ThreadPoolBuilder::new()
+				.num_threads(n_threads)
+				.build()
+				.unwrap();
+			group.bench_with_input(
+				BenchmarkId::new(format!("multithreaded log_n={log_n}"), n_threads),
+				&n_threads,
+				|b, _| {
+					pool.install(|| {
+						let ntt = SingleThreadedNTT::<BinaryField32b>::new(log_n)
+							.unwrap()
+							.precompute_twiddles()
+							.multithreaded();
+						b.iter(|| ntt.forward_transform(&mut data, 0, 0).unwrap())
+					})
+				},
+			);
+		}
+	}
+
+	group.finish()
+}
+
+criterion_group!(additive_ntt, bench_packings, bench_threads);
+criterion_main!(additive_ntt);
@@ -1,14 +1,20 @@
+use std::mem::size_of;
+
 use binius_field::{AESTowerField8b, BinaryField8b};
 use criterion::{
-	black_box, criterion_group, criterion_main, measurement::Measurement, BenchmarkGroup,
-	Criterion, Throughput,
+	black_box, criterion_group, measurement::Measurement, BenchmarkGroup, Criterion, Throughput,
 };
 use rand::{
 	distributions::{Distribution, Standard},
 	thread_rng, Rng,
 };
+use ulvt_snark_bench::{
+	cycles::report_cycles,
+	measurement::{perf_measurement, set_element_bytes, PerfMeasurement},
+	simd::report_simd,
+};
 
-pub fn bench_ark_bn254(c: &mut Criterion) {
+pub fn bench_ark_bn254(c: &mut Criterion<PerfMeasurement>) {
 	use ark_bn254::Fr;
 	use ark_std::UniformRand;
 
@@ -17,13 +23,15 @@ pub fn bench_ark_bn254(c: &mut Criterion) {
 
 	let x = Fr::rand(&mut rng);
 	let y = Fr::rand(&mut rng);
+	set_element_bytes(size_of::<Fr>() as u64);
 	group.throughput(Throughput::Elements(1));
 	group.bench_function("B254 Fr", |b| b.iter(|| black_box(x) * black_box(y)));
+	report_cycles("ark_bn254 B254 Fr", 1, || black_box(x) * black_box(y));
 
 	group.finish()
 }
 
-fn bench_risc0(c: &mut Criterion) {
+fn bench_risc0(c: &mut Criterion<PerfMeasurement>) {
 	use risc0_core::field::{baby_bear::BabyBear, Elem, Field};
 
 	fn benchmark_mul<F: Elem, M: Measurement>(
@@ -33,8 +41,10 @@ fn bench_risc0(c: &mut Criterion) {
 	) {
 		let x = F::random(&mut rng);
 		let y = F::random(&mut rng);
+		set_element_bytes(size_of::<F>() as u64);
 		group.throughput(Throughput::Elements(1));
 		group.bench_function(name, |b| b.iter(|| black_box(x) * black_box(y)));
+		report_cycles(&format!("risc0 {name}"), 1, || black_box(x) * black_box(y));
 	}
 
 	let mut rng = thread_rng();
@@ -46,7 +56,7 @@ fn bench_risc0(c: &mut Criterion) {
 	group.finish()
 }
 
-fn bench_binius(c: &mut Criterion) {
+fn bench_binius(c: &mut Criterion<PerfMeasurement>) {
 	use binius_field::{
 		arch::OptimalUnderlier, as_packed_field::PackedType, AESTowerField128b, AESTowerField32b,
 		BinaryField128b, BinaryField128bPolyval, BinaryField32b, PackedField,
@@ -59,8 +69,10 @@ fn bench_binius(c: &mut Criterion) {
 	) {
 		let x = P::random(&mut rng);
 		let y = P::random(&mut rng);
+		set_element_bytes(size_of::<P::Scalar>() as u64);
 		group.throughput(Throughput::Elements(P::WIDTH as u64));
 		group.bench_function(name, |b| b.iter(|| black_box(x) * black_box(y)));
+		report_cycles(&format!("binius {name}"), P::WIDTH as u64, || black_box(x) * black_box(y));
 	}
 
 	let mut rng = thread_rng();
@@ -86,7 +98,7 @@ fn bench_binius(c: &mut Criterion) {
 	group.finish()
 }
 
-fn bench_plonky2(c: &mut Criterion) {
+fn bench_plonky2(c: &mut Criterion<PerfMeasurement>) {
 	use plonky2_field::{
 		extension::quadratic::QuadraticExtension, goldilocks_field::GoldilocksField,
 		packable::Packable, packed::PackedField, types::Field,
@@ -99,8 +111,10 @@ fn bench_plonky2(c: &mut Criterion) {
 	) {
 		let x = F::sample(&mut rng);
 		let y = F::sample(&mut rng);
+		set_element_bytes(size_of::<F>() as u64);
 		group.throughput(Throughput::Elements(1));
 		group.bench_function(name, |b| b.iter(|| black_box(x) * black_box(y)));
+		report_cycles(&format!("plonky2 {name}"), 1, || black_box(x) * black_box(y));
 	}
 
 	fn benchmark_packed_mul<F: Packable, M: Measurement>(
@@ -118,8 +132,12 @@ fn bench_plonky2(c: &mut Criterion) {
 			*y_i = F::sample(&mut rng);
 		}
 
+		set_element_bytes(size_of::<F>() as u64);
 		group.throughput(Throughput::Elements(F::Packing::WIDTH as u64));
 		group.bench_function(name, |b| b.iter(|| black_box(x) * black_box(y)));
+		report_cycles(&format!("plonky2 {name}"), F::Packing::WIDTH as u64, || {
+			black_box(x) * black_box(y)
+		});
 	}
 
 	let mut rng = thread_rng();
@@ -131,7 +149,7 @@ fn bench_plonky2(c: &mut Criterion) {
 	group.finish()
 }
 
-fn bench_plonky3(c: &mut Criterion) {
+fn bench_plonky3(c: &mut Criterion<PerfMeasurement>) {
 	use p3_baby_bear::BabyBear;
 	use p3_field::{
 		extension::{BinomialExtensionField, Complex},
@@ -149,8 +167,12 @@ fn bench_plonky3(c: &mut Criterion) {
 	{
 		let x = F::Packing::from_fn(|_| rng.gen());
 		let y = F::Packing::from_fn(|_| rng.gen());
+		set_element_bytes(size_of::<F>() as u64);
 		group.throughput(Throughput::Elements(F::Packing::WIDTH as u64));
 		group.bench_function(name, |b| b.iter(|| black_box(x) * black_box(y)));
+		report_cycles(&format!("plonky3 {name}"), F::Packing::WIDTH as u64, || {
+			black_box(x) * black_box(y)
+		});
 	}
 
 	let mut rng = thread_rng();
@@ -168,12 +190,14 @@ fn bench_plonky3(c: &mut Criterion) {
 	group.finish()
 }
 
-criterion_group!(
-	field_ops,
-	bench_ark_bn254,
-	bench_risc0,
-	bench_binius,
-	bench_plonky2,
-	bench_plonky3
-);
-criterion_main!(field_ops);
+criterion_group! {
+	name = field_ops;
+	config = Criterion::default().with_measurement(perf_measurement());
+	targets = bench_ark_bn254, bench_risc0, bench_binius, bench_plonky2, bench_plonky3
+}
+
+fn main() {
+	report_simd();
+	field_ops();
+	Criterion::default().configure_from_args().final_summary();
+}
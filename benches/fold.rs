@@ -0,0 +1,125 @@
+//! Benchmarks of partially evaluating a dense multilinear at its low (or top) variables.
+//!
+//! This is the fold performed in every sumcheck round. Libraries implement it either in-place,
+//! halving the buffer they already own, or out-of-place, allocating a fresh buffer for the folded
+//! result, so the variants are benchmarked side by side. A fold reads every element once with
+//! little arithmetic, so throughputs also show the fraction of peak memory bandwidth reached.
+//! With the `alloc_count` feature, each variant's peak heap usage above the input is also measured
+//! once and printed before its benchmark runs, as in the `in_place` bench.
+//!
+//! plonky3 and stwo are not covered: they commit to univariate polynomials, so their provers never
+//! fold a multilinear in a sumcheck round. Their FRI folds, which halve a univariate codeword, are
+//! timed as part of the PCS benchmarks.
+
+use std::{iter::repeat_with, mem::size_of};
+
+use criterion::{criterion_group, BatchSize, Criterion, Throughput};
+use rand::thread_rng;
+use ulvt_snark_bench::{
+	bandwidth::report_bandwidth,
+	heap::peak_heap_during,
+	measurement::{set_element_bytes, FieldThroughput},
+};
+
+const LOG_SIZES: [usize; 3] = [16, 20, 24];
+
+fn bench_binius(c: &mut Criterion<FieldThroughput>) {
+	use binius_field::{
+		arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField128b, Field, PackedField,
+	};
+	use binius_hal::{make_portable_backend, ComputationBackendExt};
+	use binius_math::MultilinearExtension;
+
+	type P = PackedType<OptimalUnderlier, BinaryField128b>;
+
+	let mut rng = thread_rng();
+	let backend = make_portable_backend();
+	let mut group = c.benchmark_group("binius fold");
+
+	for n_vars in LOG_SIZES {
+		let multilin = MultilinearExtension::from_values(
+			repeat_with(|| P::random(&mut rng))
+				.take(1 << (n_vars - P::LOG_WIDTH))
+				.collect(),
+		)
+		.unwrap();
+
+		set_element_bytes(size_of::<BinaryField128b>() as u64);
+		group.throughput(Throughput::Elements(1 << n_vars));
+		for n_fixed in [1, 4] {
+			let point = repeat_with(|| BinaryField128b::random(&mut rng))
+				.take(n_fixed)
+				.collect::<Vec<_>>();
+			let query = backend.multilinear_query::<P>(&point).unwrap();
+
+			// evaluate_partial_low always allocates the folded multilinear.
+			let name = format!("Tower 128b out-of-place n_vars={n_vars} k={n_fixed}");
+			println!(
+				"{name}: peak extra heap = {}",
+				peak_heap_during(|| multilin.evaluate_partial_low(&query).unwrap())
+			);
+			group.bench_function(name, |b| {
+				b.iter(|| multilin.evaluate_partial_low(&query).unwrap())
+			});
+		}
+	}
+
+	group.finish()
+}
+
+fn bench_jolt(c: &mut Criterion<FieldThroughput>) {
+	use ark_bn254::Fr;
+	use ark_std::UniformRand;
+	use jolt_core::poly::dense_mlpoly::DensePolynomial;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("jolt fold");
+
+	for n_vars in LOG_SIZES {
+		let poly = DensePolynomial::new(
+			repeat_with(|| Fr::rand(&mut rng))
+				.take(1 << n_vars)
+				.collect(),
+		);
+		let r = Fr::rand(&mut rng);
+
+		set_element_bytes(size_of::<Fr>() as u64);
+		group.throughput(Throughput::Elements(1 << n_vars));
+		let mut input = poly.clone();
+		println!(
+			"BN254 Fr in-place n_vars={n_vars} k=1: peak extra heap = {}",
+			peak_heap_during(|| input.bound_poly_var_top(&r))
+		);
+		println!(
+			"BN254 Fr out-of-place n_vars={n_vars} k=1: peak extra heap = {}",
+			peak_heap_during(|| poly.new_poly_from_bound_poly_var_top(&r))
+		);
+		group.bench_function(format!("BN254 Fr in-place n_vars={n_vars} k=1"), |b| {
+			b.iter_batched(
+				|| poly.clone(),
+				|mut poly| {
+					poly.bound_poly_var_top(&r);
+					poly
+				},
+				BatchSize::LargeInput,
+			)
+		});
+		group.bench_function(format!("BN254 Fr out-of-place n_vars={n_vars} k=1"), |b| {
+			b.iter(|| poly.new_poly_from_bound_poly_var_top(&r))
+		});
+	}
+
+	group.finish()
+}
+
+criterion_group! {
+	name = fold;
+	config = Criterion::default().with_measurement(FieldThroughput);
+	targets = bench_binius, bench_jolt
+}
+
+fn main() {
+	report_bandwidth();
+	fold();
+	Criterion::default().configure_from_args().final_summary();
+}
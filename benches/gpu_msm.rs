@@ -0,0 +1,58 @@
+//! GPU multi-scalar multiplication benchmarks using ICICLE. Requires the `cuda` feature and a CUDA
+//! device.
+//!
+//! MSMs over BN254 G1 dominate the commitments of the elliptic curve backends, such as the Hyrax
+//! commitments of the `jolt_hyrax_pcs` example. As in `gpu_ntt.rs`, each size is measured both with
+//! the scalars and bases already resident on the device and including their host-to-device copy.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use icicle_bn254::curve::{CurveCfg, G1Projective, ScalarCfg};
+use icicle_core::{
+	curve::Curve,
+	msm::{msm, MSMConfig},
+	traits::GenerateRandom,
+};
+use icicle_cuda_runtime::memory::{DeviceVec, HostSlice};
+
+const LOG_SIZES: [usize; 4] = [16, 18, 20, 22];
+
+fn bench_bn254_g1(c: &mut Criterion) {
+	let mut group = c.benchmark_group("icicle BN254 G1");
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		let scalars = ScalarCfg::generate_random(1 << log_n);
+		let bases = CurveCfg::generate_random_affine_points(1 << log_n);
+		let mut device_scalars = DeviceVec::cuda_malloc(1 << log_n).unwrap();
+		device_scalars
+			.copy_from_host(HostSlice::from_slice(&scalars))
+			.unwrap();
+		let mut device_bases = DeviceVec::cuda_malloc(1 << log_n).unwrap();
+		device_bases
+			.copy_from_host(HostSlice::from_slice(&bases))
+			.unwrap();
+		let mut result = DeviceVec::<G1Projective>::cuda_malloc(1).unwrap();
+		let cfg = MSMConfig::default();
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("msm device-resident log_n={log_n}"), |b| {
+			b.iter(|| msm(&device_scalars[..], &device_bases[..], &cfg, &mut result[..]).unwrap())
+		});
+		group.bench_function(format!("msm from host log_n={log_n}"), |b| {
+			b.iter(|| {
+				msm(
+					HostSlice::from_slice(&scalars),
+					HostSlice::from_slice(&bases),
+					&cfg,
+					&mut result[..],
+				)
+				.unwrap()
+			})
+		});
+	}
+
+	group.finish()
+}
+
+criterion_group!(gpu_msm, bench_bn254_g1);
+criterion_main!(gpu_msm);
@@ -0,0 +1,94 @@
+//! GPU NTT and coset LDE benchmarks using ICICLE. Requires the `cuda` feature and a CUDA device.
+//!
+//! Sizes and throughput units match the CPU groups in `ntt.rs` and `lde.rs`, so CPU and GPU
+//! encoding can be compared from the same Criterion report. Each transform is measured both with
+//! the input already resident on the device and including the host-to-device copy, since a prover
+//! that generates its witness on the CPU pays for the transfer.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use icicle_core::{
+	ntt::{self, get_root_of_unity, initialize_domain, NTTConfig, NTTDir, NTTDomain, NTT},
+	traits::{FieldImpl, GenerateRandom},
+};
+use icicle_cuda_runtime::{
+	device_context::DeviceContext,
+	memory::{DeviceVec, HostSlice},
+};
+
+const LOG_SIZES: [usize; 4] = [20, 22, 24, 26];
+const LOG_BLOWUPS: [usize; 2] = [1, 2];
+
+fn bench_field<F, C>(c: &mut Criterion, name: &str, coset_gen: F)
+where
+	F: FieldImpl,
+	C: GenerateRandom<F>,
+	<F as FieldImpl>::Config: NTT<F, F> + NTTDomain<F>,
+{
+	let ctx = DeviceContext::default();
+	let max_log_size = LOG_SIZES[LOG_SIZES.len() - 1] + LOG_BLOWUPS[LOG_BLOWUPS.len() - 1];
+	initialize_domain(get_root_of_unity::<F>(1 << max_log_size), &ctx, true).unwrap();
+
+	let mut group = c.benchmark_group(format!("icicle {name}"));
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		let scalars = C::generate_random(1 << log_n);
+		let mut input = DeviceVec::<F>::cuda_malloc(1 << log_n).unwrap();
+		input
+			.copy_from_host(HostSlice::from_slice(&scalars))
+			.unwrap();
+		let mut output = DeviceVec::<F>::cuda_malloc(1 << log_n).unwrap();
+		let cfg = NTTConfig::<F>::default();
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("ntt device-resident log_n={log_n}"), |b| {
+			b.iter(|| ntt::ntt(&input[..], NTTDir::kForward, &cfg, &mut output[..]).unwrap())
+		});
+		group.bench_function(format!("ntt from host log_n={log_n}"), |b| {
+			b.iter(|| {
+				ntt::ntt(HostSlice::from_slice(&scalars), NTTDir::kForward, &cfg, &mut output[..])
+					.unwrap()
+			})
+		});
+
+		for log_blowup in LOG_BLOWUPS {
+			// A coset LDE of the coefficients is a coset NTT of the zero-padded coefficients.
+			let mut padded = scalars.clone();
+			padded.resize(1 << (log_n + log_blowup), F::zero());
+			let mut lde_input = DeviceVec::<F>::cuda_malloc(padded.len()).unwrap();
+			lde_input
+				.copy_from_host(HostSlice::from_slice(&padded))
+				.unwrap();
+			let mut lde_output = DeviceVec::<F>::cuda_malloc(padded.len()).unwrap();
+			let mut lde_cfg = NTTConfig::<F>::default();
+			lde_cfg.coset_gen = coset_gen;
+
+			group.bench_function(
+				format!("coset lde device-resident log_n={log_n} blowup={}", 1 << log_blowup),
+				|b| {
+					b.iter(|| {
+						ntt::ntt(&lde_input[..], NTTDir::kForward, &lde_cfg, &mut lde_output[..])
+							.unwrap()
+					})
+				},
+			);
+		}
+	}
+
+	group.finish()
+}
+
+fn bench_babybear(c: &mut Criterion) {
+	use icicle_babybear::field::{ScalarCfg, ScalarField};
+
+	bench_field::<ScalarField, ScalarCfg>(c, "BB31", ScalarField::from_u32(31));
+}
+
+fn bench_bn254(c: &mut Criterion) {
+	use icicle_bn254::curve::{ScalarCfg, ScalarField};
+
+	bench_field::<ScalarField, ScalarCfg>(c, "BN254 Fr", ScalarField::from_u32(5));
+}
+
+criterion_group!(gpu_ntt, bench_babybear, bench_bn254);
+criterion_main!(gpu_ntt);
@@ -0,0 +1,89 @@
+//! GPU Poseidon2 hashing benchmarks using ICICLE. Requires the `cuda` feature and a CUDA device.
+//!
+//! A GPU hashes many independent inputs at once, so this measures the Merkle leaf layer rather
+//! than one long sponge: every leaf is 8 BabyBear elements, the 32-byte leaves of `merkle.rs`,
+//! absorbed into a width-16 Poseidon2 sponge of rate 8 with an 8-element digest, the parameters of
+//! plonky3's `PaddingFreeSponge<_, 16, 8, 8>`. Throughput is in leaves per second, as in
+//! `merkle.rs`. ICICLE's round constants differ from plonky3's, which does not change the cost.
+//! As in `gpu_ntt.rs`, each size is measured both with the leaves already resident on the device
+//! and including their host-to-device copy.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use icicle_babybear::field::{ScalarCfg, ScalarField};
+use icicle_core::{
+	hash::{HashConfig, SpongeHash},
+	poseidon2::{DiffusionStrategy, MdsType, Poseidon2},
+	traits::GenerateRandom,
+};
+use icicle_cuda_runtime::{
+	device_context::DeviceContext,
+	memory::{DeviceVec, HostSlice},
+};
+
+const WIDTH: usize = 16;
+const RATE: usize = 8;
+const DIGEST_LEN: usize = 8;
+
+/// Log2 of the number of leaves: 1 MiB of leaves, as hashed by `hashing.rs`, and larger layers.
+const LOG_LEAVES: [usize; 3] = [15, 20, 22];
+
+fn bench_babybear(c: &mut Criterion) {
+	let ctx = DeviceContext::default();
+	let poseidon = Poseidon2::<ScalarField>::load(
+		WIDTH as u32,
+		RATE as u32,
+		MdsType::Default,
+		DiffusionStrategy::Default,
+		&ctx,
+	)
+	.unwrap();
+	let cfg = HashConfig::default();
+
+	let mut group = c.benchmark_group("icicle Poseidon2-BB31");
+	group.sample_size(10);
+
+	for log_leaves in LOG_LEAVES {
+		let n_leaves = 1 << log_leaves;
+		let leaves = ScalarCfg::generate_random(n_leaves * RATE);
+		let mut device_leaves = DeviceVec::<ScalarField>::cuda_malloc(leaves.len()).unwrap();
+		device_leaves
+			.copy_from_host(HostSlice::from_slice(&leaves))
+			.unwrap();
+		let mut digests = DeviceVec::<ScalarField>::cuda_malloc(n_leaves * DIGEST_LEN).unwrap();
+
+		group.throughput(Throughput::Elements(n_leaves as u64));
+		group.bench_function(format!("leaves device-resident log_n={log_leaves}"), |b| {
+			b.iter(|| {
+				poseidon
+					.hash_many(
+						&device_leaves[..],
+						&mut digests[..],
+						n_leaves,
+						RATE as u32,
+						DIGEST_LEN as u32,
+						&cfg,
+					)
+					.unwrap()
+			})
+		});
+		group.bench_function(format!("leaves from host log_n={log_leaves}"), |b| {
+			b.iter(|| {
+				poseidon
+					.hash_many(
+						HostSlice::from_slice(&leaves),
+						&mut digests[..],
+						n_leaves,
+						RATE as u32,
+						DIGEST_LEN as u32,
+						&cfg,
+					)
+					.unwrap()
+			})
+		});
+	}
+
+	group.finish()
+}
+
+criterion_group!(gpu_poseidon2, bench_babybear);
+criterion_main!(gpu_poseidon2);
@@ -1,12 +1,17 @@
-use std::array;
+use std::{array, mem::size_of};
 
-use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use criterion::{criterion_group, Criterion, Throughput};
 use rand::{thread_rng, Rng};
+use ulvt_snark_bench::{
+	measurement::{perf_measurement, set_element_bytes, PerfMeasurement},
+	simd::report_simd,
+};
 
-fn bench_sha2(c: &mut Criterion) {
+fn bench_sha2(c: &mut Criterion<PerfMeasurement>) {
 	use sha2::{Digest, Sha256};
 
 	let mut group = c.benchmark_group("SHA2");
+	set_element_bytes(0);
 	let mut rng = thread_rng();
 
 	group.throughput(Throughput::Bytes(1024 * 1024));
@@ -17,10 +22,11 @@ fn bench_sha2(c: &mut Criterion) {
 	group.finish()
 }
 
-fn bench_keccak(c: &mut Criterion) {
+fn bench_keccak(c: &mut Criterion<PerfMeasurement>) {
 	use tiny_keccak::{Hasher, Keccak};
 
 	let mut group = c.benchmark_group("Keccak-256");
+	set_element_bytes(0);
 	let mut rng = thread_rng();
 
 	group.throughput(Throughput::Bytes(1024 * 1024));
@@ -37,11 +43,12 @@ fn bench_keccak(c: &mut Criterion) {
 	group.finish()
 }
 
-fn bench_groestl(c: &mut Criterion) {
+fn bench_groestl(c: &mut Criterion<PerfMeasurement>) {
 	use binius_field::{AESTowerField8b, PackedField};
 	use binius_hash::{Groestl256, HashDigest, HasherDigest};
 
 	let mut group = c.benchmark_group("Groestl");
+	set_element_bytes(size_of::<AESTowerField8b>() as u64);
 	let mut rng = thread_rng();
 
 	group.throughput(Throughput::Bytes(1024 * 1024));
@@ -54,10 +61,11 @@ fn bench_groestl(c: &mut Criterion) {
 	group.finish()
 }
 
-fn bench_blake2(c: &mut Criterion) {
+fn bench_blake2(c: &mut Criterion<PerfMeasurement>) {
 	use blake2::{digest::consts::U32, Blake2b, Digest};
 
 	let mut group = c.benchmark_group("Blake2");
+	set_element_bytes(0);
 	let mut rng = thread_rng();
 
 	group.throughput(Throughput::Bytes(1024 * 1024));
@@ -68,8 +76,9 @@ fn bench_blake2(c: &mut Criterion) {
 	group.finish()
 }
 
-fn bench_blake3(c: &mut Criterion) {
+fn bench_blake3(c: &mut Criterion<PerfMeasurement>) {
 	let mut group = c.benchmark_group("Blake3");
+	set_element_bytes(0);
 	let mut rng = thread_rng();
 
 	group.throughput(Throughput::Bytes(1024 * 1024));
@@ -80,11 +89,12 @@ fn bench_blake3(c: &mut Criterion) {
 	group.finish()
 }
 
-fn bench_poseidon_gl64(c: &mut Criterion) {
+fn bench_poseidon_gl64(c: &mut Criterion<PerfMeasurement>) {
 	use plonky2::{hash::poseidon::PoseidonHash, plonk::config::Hasher};
 	use plonky2_field::{goldilocks_field::GoldilocksField, types::Sample};
 
 	let mut group = c.benchmark_group("Poseidon-GL64");
+	set_element_bytes(size_of::<GoldilocksField>() as u64);
 
 	group.throughput(Throughput::Bytes(1024 * 1024));
 	let data = GoldilocksField::rand_vec(1024 * 1024 / 8);
@@ -93,11 +103,12 @@ fn bench_poseidon_gl64(c: &mut Criterion) {
 	group.finish()
 }
 
-fn bench_poseidon2_bb31(c: &mut Criterion) {
+fn bench_poseidon2_bb31(c: &mut Criterion<PerfMeasurement>) {
 	use risc0_core::field::{baby_bear::BabyBearElem, Elem};
 	use risc0_zkp::core::hash::poseidon2::Poseidon2HashSuite;
 
 	let mut group = c.benchmark_group("Poseidon2-BB31");
+	set_element_bytes(size_of::<BabyBearElem>() as u64);
 	let mut rng = thread_rng();
 
 	group.throughput(Throughput::Bytes(1024 * 1024));
@@ -109,13 +120,14 @@ fn bench_poseidon2_bb31(c: &mut Criterion) {
 	group.finish()
 }
 
-fn bench_vision32(c: &mut Criterion) {
+fn bench_vision32(c: &mut Criterion<PerfMeasurement>) {
 	use binius_field::{
 		BinaryField32b, BinaryField8b, ExtensionField, PackedBinaryField4x32b, PackedField,
 	};
 	use binius_hash::{FixedLenHasherDigest, HashDigest, Vision32b};
 
 	let mut group = c.benchmark_group("Vision");
+	set_element_bytes(size_of::<BinaryField32b>() as u64);
 
 	let mut rng = thread_rng();
 
@@ -134,13 +146,14 @@ fn bench_vision32(c: &mut Criterion) {
 	group.finish()
 }
 
-fn p3_bench_poseidon2_m31(c: &mut Criterion) {
+fn p3_bench_poseidon2_m31(c: &mut Criterion<PerfMeasurement>) {
 	use p3_field::{Field, PackedValue};
 	use p3_mersenne_31::{DiffusionMatrixMersenne31, Mersenne31};
 	use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 	use p3_symmetric::{CryptographicHasher, PaddingFreeSponge};
 
 	let mut group = c.benchmark_group("Plonky3-Poseidon2-MR31");
+	set_element_bytes(size_of::<Mersenne31>() as u64);
 
 	let mut rng = thread_rng();
 
@@ -171,13 +184,14 @@ fn p3_bench_poseidon2_m31(c: &mut Criterion) {
 	group.finish()
 }
 
-fn p3_bench_poseidon2_bb31(c: &mut Criterion) {
+fn p3_bench_poseidon2_bb31(c: &mut Criterion<PerfMeasurement>) {
 	use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
 	use p3_field::{Field, PackedValue};
 	use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 	use p3_symmetric::{CryptographicHasher, PaddingFreeSponge};
 
 	let mut group = c.benchmark_group("Plonky3-Poseidon2-BB31");
+	set_element_bytes(size_of::<BabyBear>() as u64);
 
 	let mut rng = thread_rng();
 
@@ -207,17 +221,24 @@ fn p3_bench_poseidon2_bb31(c: &mut Criterion) {
 	group.finish()
 }
 
-criterion_group!(
-	bench_hashing,
-	bench_sha2,
-	bench_groestl,
-	bench_blake2,
-	bench_blake3,
-	bench_keccak,
-	bench_poseidon_gl64,
-	bench_poseidon2_bb31,
-	bench_vision32,
-	p3_bench_poseidon2_m31,
-	p3_bench_poseidon2_bb31
-);
-criterion_main!(bench_hashing);
+criterion_group! {
+	name = bench_hashing;
+	config = Criterion::default().with_measurement(perf_measurement());
+	targets =
+		bench_sha2,
+		bench_groestl,
+		bench_blake2,
+		bench_blake3,
+		bench_keccak,
+		bench_poseidon_gl64,
+		bench_poseidon2_bb31,
+		bench_vision32,
+		p3_bench_poseidon2_m31,
+		p3_bench_poseidon2_bb31
+}
+
+fn main() {
+	report_simd();
+	bench_hashing();
+	Criterion::default().configure_from_args().final_summary();
+}
@@ -0,0 +1,113 @@
+//! Paired benchmarks of in-place and out-of-place transforms at 2^24 elements.
+//!
+//! Besides time, each variant's peak heap usage above the input is measured once and printed before
+//! its benchmark runs, so users with tight memory budgets can see what the in-place variant saves
+//! and what it costs. The heap is measured by the counting allocator of the `alloc_count` feature:
+//!
+//! ```sh
+//! cargo bench --features alloc_count --bench in_place
+//! ```
+
+use std::iter::repeat_with;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rand::thread_rng;
+use ulvt_snark_bench::heap::peak_heap_during;
+
+const LOG_N: usize = 24;
+
+fn bench_arkworks(c: &mut Criterion) {
+	use ark_bn254::Fr;
+	use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+	use ark_std::UniformRand;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("arkworks in-place vs out-of-place");
+	group.sample_size(10);
+
+	let domain = Radix2EvaluationDomain::<Fr>::new(1 << LOG_N).unwrap();
+	let mut values = repeat_with(|| Fr::rand(&mut rng))
+		.take(1 << LOG_N)
+		.collect::<Vec<_>>();
+
+	println!(
+		"BN254 Fr fft out-of-place: peak extra heap = {}",
+		peak_heap_during(|| domain.fft(&values))
+	);
+	println!(
+		"BN254 Fr fft in-place: peak extra heap = {}",
+		peak_heap_during(|| domain.fft_in_place(&mut values))
+	);
+	println!(
+		"BN254 Fr ifft out-of-place: peak extra heap = {}",
+		peak_heap_during(|| domain.ifft(&values))
+	);
+	println!(
+		"BN254 Fr ifft in-place: peak extra heap = {}",
+		peak_heap_during(|| domain.ifft_in_place(&mut values))
+	);
+
+	group.throughput(Throughput::Elements(1 << LOG_N));
+	group.bench_function(format!("BN254 Fr fft out-of-place log_n={LOG_N}"), |b| {
+		b.iter(|| domain.fft(&values))
+	});
+	group.bench_function(format!("BN254 Fr fft in-place log_n={LOG_N}"), |b| {
+		b.iter(|| domain.fft_in_place(&mut values))
+	});
+	group.bench_function(format!("BN254 Fr ifft out-of-place log_n={LOG_N}"), |b| {
+		b.iter(|| domain.ifft(&values))
+	});
+	group.bench_function(format!("BN254 Fr ifft in-place log_n={LOG_N}"), |b| {
+		b.iter(|| domain.ifft_in_place(&mut values))
+	});
+
+	group.finish()
+}
+
+fn bench_plonky3(c: &mut Criterion) {
+	use p3_baby_bear::BabyBear;
+	use p3_dft::{Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
+	use p3_matrix::dense::RowMajorMatrix;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("plonky3 in-place vs out-of-place");
+	group.sample_size(10);
+
+	// Radix2Dit transforms the matrix it is given in place, while Radix2DitParallel works through
+	// additional buffers. Both consume their input, so each iteration clones it first.
+	let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << LOG_N, 1);
+	let in_place = Radix2Dit::<BabyBear>::default();
+	let out_of_place = Radix2DitParallel::<BabyBear>::default();
+
+	let input = matrix.clone();
+	println!(
+		"BB31 Radix2Dit: peak extra heap = {}",
+		peak_heap_during(|| in_place.dft_batch(input))
+	);
+	let input = matrix.clone();
+	println!(
+		"BB31 Radix2DitParallel: peak extra heap = {}",
+		peak_heap_during(|| out_of_place.dft_batch(input))
+	);
+
+	group.throughput(Throughput::Elements(1 << LOG_N));
+	group.bench_function(format!("BB31 Radix2Dit log_n={LOG_N}"), |b| {
+		b.iter_batched(
+			|| matrix.clone(),
+			|matrix| in_place.dft_batch(matrix),
+			BatchSize::LargeInput,
+		)
+	});
+	group.bench_function(format!("BB31 Radix2DitParallel log_n={LOG_N}"), |b| {
+		b.iter_batched(
+			|| matrix.clone(),
+			|matrix| out_of_place.dft_batch(matrix),
+			BatchSize::LargeInput,
+		)
+	});
+
+	group.finish()
+}
+
+criterion_group!(in_place, bench_arkworks, bench_plonky3);
+criterion_main!(in_place);
@@ -0,0 +1,290 @@
+//! Benchmarks of coset low-degree extension at the blowup factors used by FRI-based provers.
+//!
+//! Throughput is reported in message (input) elements per second, so that the cost of a higher
+//! blowup shows up as lower throughput for the same amount of committed data. The Reed–Solomon
+//! groups encode a fixed 256 MiB message at each rate and report input bytes per second, which
+//! isolates the encoding side of the rate tradeoff from Merkle hashing.
+
+use std::{iter::repeat_with, mem::size_of};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rand::thread_rng;
+
+const LOG_SIZES: [usize; 3] = [16, 20, 22];
+const LOG_BLOWUPS: [usize; 4] = [1, 2, 3, 4];
+
+fn bench_plonky2(c: &mut Criterion) {
+	use plonky2_field::{
+		fft::fft_root_table,
+		goldilocks_field::GoldilocksField,
+		polynomial::PolynomialCoeffs,
+		types::{Field, Sample},
+	};
+
+	let mut group = c.benchmark_group("plonky2 lde");
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		let coeffs = PolynomialCoeffs::new(GoldilocksField::rand_vec(1 << log_n));
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		for log_blowup in LOG_BLOWUPS {
+			let root_table = fft_root_table(1 << (log_n + log_blowup));
+
+			// This mirrors the LDE performed by PolynomialBatch::from_coeffs.
+			group.bench_function(format!("GL64 log_n={log_n} blowup={}", 1 << log_blowup), |b| {
+				b.iter(|| {
+					coeffs.lde(log_blowup).coset_fft_with_options(
+						GoldilocksField::coset_shift(),
+						Some(log_blowup),
+						Some(&root_table),
+					)
+				})
+			});
+		}
+	}
+
+	group.finish()
+}
+
+fn bench_plonky3(c: &mut Criterion) {
+	use p3_baby_bear::BabyBear;
+	use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+	use p3_field::Field;
+	use p3_matrix::dense::RowMajorMatrix;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("plonky3 lde");
+	group.sample_size(10);
+
+	let dft = Radix2DitParallel::<BabyBear>::default();
+	for log_n in LOG_SIZES {
+		let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << log_n, 1);
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		for log_blowup in LOG_BLOWUPS {
+			group.bench_function(format!("BB31 log_n={log_n} blowup={}", 1 << log_blowup), |b| {
+				b.iter_batched(
+					|| matrix.clone(),
+					|matrix| dft.coset_lde_batch(matrix, log_blowup, BabyBear::generator()),
+					BatchSize::LargeInput,
+				)
+			});
+		}
+	}
+
+	group.finish()
+}
+
+#[cfg(feature = "stwo")]
+fn bench_stwo(c: &mut Criterion) {
+	use ark_std::UniformRand;
+	use stwo::core::{
+		backend::{simd::SimdBackend, Col},
+		fields::m31::BaseField,
+		poly::circle::{CanonicCoset, CirclePoly, PolyOps},
+	};
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("stwo lde");
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		let coeffs = repeat_with(|| BaseField::rand(&mut rng))
+			.take(1 << log_n)
+			.collect::<Col<SimdBackend, BaseField>>();
+		let poly = CirclePoly::<SimdBackend>::new(coeffs);
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		for log_blowup in LOG_BLOWUPS {
+			let domain = CanonicCoset::new((log_n + log_blowup) as u32).circle_domain();
+			let twiddles = SimdBackend::precompute_twiddles(domain.half_coset);
+
+			group.bench_function(format!("M31 log_n={log_n} blowup={}", 1 << log_blowup), |b| {
+				b.iter(|| poly.evaluate_with_twiddles(domain, &twiddles))
+			});
+		}
+	}
+
+	group.finish()
+}
+
+#[cfg(not(feature = "stwo"))]
+fn bench_stwo(_c: &mut Criterion) {}
+
+fn bench_binius(c: &mut Criterion) {
+	use binius_core::reed_solomon::reed_solomon::ReedSolomonCode;
+	use binius_field::{
+		arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField32b, PackedField,
+	};
+	use binius_ntt::NTTOptions;
+
+	type P = PackedType<OptimalUnderlier, BinaryField32b>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("binius lde");
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		let message = repeat_with(|| P::random(&mut rng))
+			.take(1 << (log_n - P::LOG_WIDTH))
+			.collect::<Vec<_>>();
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		for log_blowup in LOG_BLOWUPS {
+			let rs_code =
+				ReedSolomonCode::<BinaryField32b>::new(log_n, log_blowup, &NTTOptions::default())
+					.unwrap();
+
+			// Reed–Solomon encoding in the novel polynomial basis is the binius analogue of a
+			// coset LDE: the message fills the start of the codeword buffer, which is then
+			// encoded in place.
+			group.bench_function(
+				format!("Tower 32b log_n={log_n} blowup={}", 1 << log_blowup),
+				|b| {
+					b.iter_batched(
+						|| {
+							let mut codeword = message.clone();
+							codeword.resize(message.len() << log_blowup, P::zero());
+							codeword
+						},
+						|mut codeword| {
+							rs_code.encode_batch_inplace(&mut codeword, 0).unwrap();
+							codeword
+						},
+						BatchSize::LargeInput,
+					)
+				},
+			);
+		}
+	}
+
+	group.finish()
+}
+
+/// Size of the message encoded by the Reed–Solomon throughput benchmarks.
+const RS_MESSAGE_BYTES: usize = 256 << 20;
+/// Number of columns the message is split into, matching the PCS examples.
+const RS_LOG_BATCH_SIZE: usize = 4;
+
+fn bench_rs_encode_plonky2(c: &mut Criterion) {
+	use plonky2_field::{
+		fft::fft_root_table,
+		goldilocks_field::GoldilocksField,
+		polynomial::PolynomialCoeffs,
+		types::{Field, Sample},
+	};
+	use rayon::prelude::*;
+
+	let mut group = c.benchmark_group("plonky2 reed-solomon encode");
+	group.sample_size(10);
+
+	let log_n =
+		(RS_MESSAGE_BYTES / size_of::<GoldilocksField>()).ilog2() as usize - RS_LOG_BATCH_SIZE;
+	let columns = repeat_with(|| PolynomialCoeffs::new(GoldilocksField::rand_vec(1 << log_n)))
+		.take(1 << RS_LOG_BATCH_SIZE)
+		.collect::<Vec<_>>();
+
+	group.throughput(Throughput::Bytes(RS_MESSAGE_BYTES as u64));
+	for log_blowup in LOG_BLOWUPS {
+		let root_table = fft_root_table(1 << (log_n + log_blowup));
+		group.bench_function(format!("GL64 256MiB rate=1/{}", 1 << log_blowup), |b| {
+			b.iter(|| {
+				columns
+					.par_iter()
+					.map(|coeffs| {
+						coeffs.lde(log_blowup).coset_fft_with_options(
+							GoldilocksField::coset_shift(),
+							Some(log_blowup),
+							Some(&root_table),
+						)
+					})
+					.collect::<Vec<_>>()
+			})
+		});
+	}
+
+	group.finish()
+}
+
+fn bench_rs_encode_plonky3(c: &mut Criterion) {
+	use p3_baby_bear::BabyBear;
+	use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+	use p3_field::Field;
+	use p3_matrix::dense::RowMajorMatrix;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("plonky3 reed-solomon encode");
+	group.sample_size(10);
+
+	let log_n = (RS_MESSAGE_BYTES / size_of::<BabyBear>()).ilog2() as usize - RS_LOG_BATCH_SIZE;
+	let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << log_n, 1 << RS_LOG_BATCH_SIZE);
+	let dft = Radix2DitParallel::<BabyBear>::default();
+
+	group.throughput(Throughput::Bytes(RS_MESSAGE_BYTES as u64));
+	for log_blowup in LOG_BLOWUPS {
+		group.bench_function(format!("BB31 256MiB rate=1/{}", 1 << log_blowup), |b| {
+			b.iter_batched(
+				|| matrix.clone(),
+				|matrix| dft.coset_lde_batch(matrix, log_blowup, BabyBear::generator()),
+				BatchSize::LargeInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+fn bench_rs_encode_binius(c: &mut Criterion) {
+	use binius_core::reed_solomon::reed_solomon::ReedSolomonCode;
+	use binius_field::{
+		arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField32b, PackedField,
+	};
+	use binius_ntt::NTTOptions;
+
+	type P = PackedType<OptimalUnderlier, BinaryField32b>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("binius reed-solomon encode");
+	group.sample_size(10);
+
+	let message = repeat_with(|| P::random(&mut rng))
+		.take(RS_MESSAGE_BYTES / size_of::<P>())
+		.collect::<Vec<_>>();
+	let log_dim =
+		(RS_MESSAGE_BYTES / size_of::<BinaryField32b>()).ilog2() as usize - RS_LOG_BATCH_SIZE;
+
+	group.throughput(Throughput::Bytes(RS_MESSAGE_BYTES as u64));
+	for log_blowup in LOG_BLOWUPS {
+		let rs_code =
+			ReedSolomonCode::<BinaryField32b>::new(log_dim, log_blowup, &NTTOptions::default())
+				.unwrap();
+		group.bench_function(format!("Tower 32b 256MiB rate=1/{}", 1 << log_blowup), |b| {
+			b.iter_batched(
+				|| {
+					let mut codeword = message.clone();
+					codeword.resize(message.len() << log_blowup, P::zero());
+					codeword
+				},
+				|mut codeword| {
+					rs_code
+						.encode_batch_inplace(&mut codeword, RS_LOG_BATCH_SIZE)
+						.unwrap();
+					codeword
+				},
+				BatchSize::LargeInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+criterion_group!(lde, bench_plonky2, bench_plonky3, bench_stwo, bench_binius);
+criterion_group!(
+	rs_encode,
+	bench_rs_encode_plonky2,
+	bench_rs_encode_plonky3,
+	bench_rs_encode_binius
+);
+criterion_main!(lde, rs_encode);
@@ -0,0 +1,2057 @@
+//! Benchmarks of Merkle tree construction, the other half of commit time next to encoding, and of
+//! opening and verifying authentication paths, which make up the query phase.
+//!
+//! Every backend commits the same number of 32-byte leaves with each hash it supports, so
+//! throughput is reported in leaves per second, or paths per second for openings, and compares
+//! directly across backends. The exception is plonky2 with Poseidon: its `MerkleTree` stores leaves
+//! no wider than a digest, 32 bytes, in place of their hash, so those series hash no leaves, are
+//! labeled `unhashed leaves`, and do not compare with the others. Before benchmarking, each
+//! backend's tree is built once to print its in-memory size from its digest layout, and with the
+//! `alloc_count` feature as the heap it retains.
+//!
+//! The cap height groups sweep how much of the tree the verifier holds in place of the root.
+//! plonky2 exposes this as the Merkle cap and binius as the layer depth that openings are checked
+//! against, while plonky3's MMCS always opens to the root and is not included.
+//!
+//! The arity group compares binary trees against 4-, 8-, and 16-ary trees at the same data size,
+//! and the leaf size group trades the number of leaves against their width at a fixed data size.
+//! The same-hash group commits identical bytes with binius and plonky3 under one hash function,
+//! and the thread-scaling group sweeps the rayon pool size for tree building. The streaming group
+//! builds trees from leaves that arrive in chunks, and the salted group measures hiding
+//! commitments with a random salt in every leaf. The SIMD group compares hashing one leaf at a
+//! time against hashing a leaf per vector lane, reporting the fraction of peak memory bandwidth
+//! reached, and the constrained group verifies paths on one pinned core with a small stack. On
+//! multi-socket machines, the NUMA group builds trees on the cores of one node from data on each
+//! node in turn, comparing local against remote memory. The cache mode group commits leaves both
+//! still in cache from their generation and evicted from it, as freshly generated witness data
+//! would be.
+
+use std::{
+	array,
+	hint::black_box,
+	iter::{self, repeat_with},
+	mem::{size_of, MaybeUninit},
+	time::Instant,
+};
+
+use binius_field::{AESTowerField32b, AESTowerField8b, PackedField};
+use binius_hash::{Groestl256, GroestlDigest, HashDigest, Hasher, HasherDigest};
+use bytesize::ByteSize;
+use criterion::{
+	criterion_group, measurement::WallTime, BatchSize, BenchmarkGroup, Criterion, Throughput,
+};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_field::{Field, PackedValue};
+use p3_keccak::Keccak256Hash;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{
+	CompressionFunction, CompressionFunctionFromHasher, CryptographicHasher, PaddingFreeSponge,
+	PseudoCompressionFunction, SerializingHasher32, TruncatedPermutation,
+};
+use rand::{thread_rng, Rng};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use tiny_keccak::{Hasher as _, Keccak};
+use ulvt_snark_bench::{
+	bandwidth::report_bandwidth,
+	cache::bench_cache_modes,
+	heap::{peak_heap_during, retained_heap, HeapBytes},
+	measurement::{set_element_bytes, FieldThroughput},
+	numa::{numa_nodes, pin_to_node, set_memory_policy, MemoryPolicy, NumaNode},
+	threads::{run_pinned, thread_counts},
+};
+
+const LOG_LEAVES: [usize; 4] = [20, 22, 24, 26];
+
+/// Bytes per leaf, shared by every backend.
+const LEAF_BYTES: usize = 32;
+
+/// Leaves per tree in the opening benchmarks.
+const OPENING_LOG_LEAVES: usize = 20;
+
+/// Numbers of paths opened together: a single query, and a batch the size of a FRI query phase.
+const QUERY_COUNTS: [usize; 2] = [1, 64];
+
+/// Cap heights swept by the cap height benchmarks, where the verifier holds `2^cap_height` digests
+/// in place of the root.
+const CAP_HEIGHTS: [usize; 5] = [0, 2, 4, 6, 8];
+
+/// Bytes per leaf swept by the leaf size benchmarks, from single digests up to wide trace rows.
+const SWEEP_LEAF_BYTES: [usize; 5] = [32, 128, 512, 2048, 4096];
+
+/// Total bytes committed at every leaf size in the leaf size benchmarks.
+const SWEEP_TOTAL_BYTES: usize = 1 << 28;
+
+/// Leaf counts for the same-hash comparison.
+const SAME_HASH_LOG_LEAVES: [usize; 2] = [20, 24];
+
+/// Leaves per tree in the thread-scaling benchmarks.
+const THREADS_LOG_LEAVES: usize = 22;
+
+/// Leaves per tree in the streaming benchmarks.
+const STREAM_LOG_LEAVES: usize = 24;
+
+/// Leaves per chunk delivered to the streaming builders.
+const STREAM_LOG_CHUNKS: [usize; 3] = [12, 16, 20];
+
+/// Leaves per tree in the salted commitment benchmarks.
+const SALTED_LOG_LEAVES: usize = 22;
+
+/// Salt bytes hashed into each leaf of a hiding commitment, enough to hide a leaf at 128-bit
+/// security.
+const SALT_BYTES: usize = 32;
+
+/// Bytes per leaf in the salted commitment benchmarks, wider than a plonky2 Poseidon digest so that
+/// the unsalted baselines hash their leaves too.
+const SALTED_LEAF_BYTES: usize = 64;
+
+/// Tree arities compared by the arity benchmarks.
+const ARITIES: [usize; 4] = [2, 4, 8, 16];
+
+/// Leaves per tree in the arity benchmarks, divisible by the log of every arity so that all the
+/// trees are complete.
+const ARITY_LOG_LEAVES: usize = 24;
+
+/// Leaves per tree in the SIMD leaf hashing benchmarks.
+const SIMD_LOG_LEAVES: usize = 20;
+
+/// Stack size of the thread that runs the constrained verification benchmarks, in the range of
+/// embedded targets and light clients.
+const CONSTRAINED_STACK_BYTES: usize = 64 << 10;
+
+/// Log number of leaves in the NUMA comparison, large enough to exceed the last-level cache.
+const NUMA_LOG_LEAVES: usize = 24;
+
+/// Tree sizes for the cache mode comparison, with the smallest fitting in the last-level cache.
+const CACHE_LOG_LEAVES: [usize; 3] = [16, 20, 24];
+
+type GroestlDigestAES = GroestlDigest<AESTowerField8b>;
+type GroestlHasher<P> = Groestl256<P, AESTowerField8b>;
+
+#[derive(Debug, Default, Clone)]
+struct GroestlDigestCompression;
+
+impl PseudoCompressionFunction<GroestlDigestAES, 2> for GroestlDigestCompression {
+	fn compress(&self, input: [GroestlDigestAES; 2]) -> GroestlDigestAES {
+		HasherDigest::<GroestlDigestAES, GroestlHasher<GroestlDigestAES>>::hash(&input[..])
+	}
+}
+
+impl CompressionFunction<GroestlDigestAES, 2> for GroestlDigestCompression {}
+
+/// Keccak-256 over the little-endian bytes of 32-bit tower field elements, for binius's Merkle
+/// prover. Digests are packed into the same 32-byte type as binius's Groestl digests.
+#[derive(Clone)]
+struct KeccakHasher(Keccak);
+
+impl KeccakHasher {
+	fn digest_from_bytes(bytes: [u8; 32]) -> GroestlDigestAES {
+		GroestlDigestAES::from_fn(|i| AESTowerField8b::new(bytes[i]))
+	}
+}
+
+impl Hasher<AESTowerField32b> for KeccakHasher {
+	type Digest = GroestlDigestAES;
+
+	fn new() -> Self {
+		Self(Keccak::v256())
+	}
+
+	fn update(&mut self, data: impl AsRef<[AESTowerField32b]>) {
+		for elem in data.as_ref() {
+			self.0.update(&elem.val().to_le_bytes());
+		}
+	}
+
+	fn chain_update(mut self, data: impl AsRef<[AESTowerField32b]>) -> Self {
+		self.update(data);
+		self
+	}
+
+	fn finalize(self) -> Self::Digest {
+		let mut bytes = [0u8; 32];
+		self.0.finalize(&mut bytes);
+		Self::digest_from_bytes(bytes)
+	}
+
+	fn finalize_into(self, out: &mut MaybeUninit<Self::Digest>) {
+		out.write(self.finalize());
+	}
+
+	fn finalize_reset(&mut self) -> Self::Digest {
+		std::mem::replace(self, Self::new()).finalize()
+	}
+
+	fn finalize_into_reset(&mut self, out: &mut MaybeUninit<Self::Digest>) {
+		out.write(self.finalize_reset());
+	}
+
+	fn reset(&mut self) {
+		*self = Self::new();
+	}
+}
+
+/// Keccak-256 of the concatenated child digests, matching plonky3's
+/// `CompressionFunctionFromHasher<Keccak256Hash, 2, 32>`.
+#[derive(Debug, Default, Clone)]
+struct KeccakDigestCompression;
+
+impl PseudoCompressionFunction<GroestlDigestAES, 2> for KeccakDigestCompression {
+	fn compress(&self, input: [GroestlDigestAES; 2]) -> GroestlDigestAES {
+		let bytes: [u8; 64] = array::from_fn(|i| input[i / 32].get(i % 32).val());
+		let mut keccak = Keccak::v256();
+		keccak.update(&bytes);
+		let mut digest = [0u8; 32];
+		keccak.finalize(&mut digest);
+		KeccakHasher::digest_from_bytes(digest)
+	}
+}
+
+impl CompressionFunction<GroestlDigestAES, 2> for KeccakDigestCompression {}
+
+/// Groestl-256 over bytes, for plonky3's MMCS.
+#[derive(Debug, Default, Clone, Copy)]
+struct GroestlByteHash;
+
+impl CryptographicHasher<u8, [u8; 32]> for GroestlByteHash {
+	fn hash_iter<I>(&self, input: I) -> [u8; 32]
+	where
+		I: IntoIterator<Item = u8>,
+	{
+		use groestl::Digest;
+
+		let mut hasher = groestl::Groestl256::default();
+		let mut block = [0u8; 64];
+		let mut len = 0;
+		for byte in input {
+			block[len] = byte;
+			len += 1;
+			if len == block.len() {
+				hasher.update(block);
+				len = 0;
+			}
+		}
+		hasher.update(&block[..len]);
+		hasher.finalize().into()
+	}
+}
+
+type Poseidon2Perm =
+	Poseidon2<BabyBear, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type Poseidon2Hash = PaddingFreeSponge<Poseidon2Perm, 16, 8, 8>;
+type Poseidon2Compress = TruncatedPermutation<Poseidon2Perm, 2, 8, 16>;
+type Poseidon2Mmcs = MerkleTreeMmcs<
+	<BabyBear as Field>::Packing,
+	<BabyBear as Field>::Packing,
+	Poseidon2Hash,
+	Poseidon2Compress,
+	8,
+>;
+
+type KeccakHash = SerializingHasher32<Keccak256Hash>;
+type KeccakCompress = CompressionFunctionFromHasher<Keccak256Hash, 2, 32>;
+type KeccakMmcs = MerkleTreeMmcs<BabyBear, u8, KeccakHash, KeccakCompress, 32>;
+
+fn plonky3_poseidon2_mmcs(rng: impl Rng) -> Poseidon2Mmcs {
+	let perm = Poseidon2Perm::new_from_rng_128(
+		Poseidon2ExternalMatrixGeneral,
+		DiffusionMatrixBabyBear::default(),
+		rng,
+	);
+	Poseidon2Mmcs::new(Poseidon2Hash::new(perm.clone()), Poseidon2Compress::new(perm))
+}
+
+fn plonky3_keccak_mmcs() -> KeccakMmcs {
+	KeccakMmcs::new(KeccakHash::new(Keccak256Hash {}), KeccakCompress::new(Keccak256Hash {}))
+}
+
+/// Builds the layers of a Merkle tree of the given arity over the rows of a BabyBear matrix with
+/// Poseidon2, from the leaf digests up to the root.
+fn build_poseidon2_tree(
+	values: &[BabyBear],
+	width: usize,
+	arity: usize,
+	hash: &Poseidon2Hash,
+	compress: &Poseidon2Compress,
+) -> Vec<Vec<[BabyBear; 8]>> {
+	let leaves = values
+		.par_chunks_exact(width)
+		.map(|row| hash.hash_slice(row))
+		.collect::<Vec<_>>();
+
+	let mut layers = vec![leaves];
+	while layers[layers.len() - 1].len() > 1 {
+		let next = compress_poseidon2_layer(&layers[layers.len() - 1], arity, hash, compress);
+		layers.push(next);
+	}
+	layers
+}
+
+/// Computes the parent layer of a layer of Poseidon2 digests.
+///
+/// Binary trees compress sibling digests with one truncated permutation, as `MerkleTreeMmcs`
+/// does. Wider trees absorb all of a node's children into the sponge instead.
+fn compress_poseidon2_layer(
+	layer: &[[BabyBear; 8]],
+	arity: usize,
+	hash: &Poseidon2Hash,
+	compress: &Poseidon2Compress,
+) -> Vec<[BabyBear; 8]> {
+	layer
+		.par_chunks_exact(arity)
+		.map(|children| {
+			if arity == 2 {
+				compress.compress([children[0], children[1]])
+			} else {
+				hash.hash_iter(children.iter().flatten().copied())
+			}
+		})
+		.collect()
+}
+
+/// Builds the same binary tree as [`build_poseidon2_tree`], hashing one leaf, or one pair of
+/// siblings, per SIMD lane of the packed BabyBear field.
+///
+/// Rows are transposed into packed columns before hashing, as `MerkleTreeMmcs` does. Layers with
+/// fewer parents than lanes fall back to one compression at a time.
+fn build_poseidon2_tree_packed(
+	values: &[BabyBear],
+	width: usize,
+	hash: &Poseidon2Hash,
+	compress: &Poseidon2Compress,
+) -> Vec<Vec<[BabyBear; 8]>> {
+	type P = <BabyBear as Field>::Packing;
+
+	let unpack = |packed: [P; 8]| {
+		(0..P::WIDTH).map(move |lane| packed.map(|element| element.as_slice()[lane]))
+	};
+
+	let leaves = values
+		.par_chunks_exact(width * P::WIDTH)
+		.flat_map_iter(|rows| {
+			unpack(
+				hash.hash_iter((0..width).map(|col| P::from_fn(|lane| rows[lane * width + col]))),
+			)
+		})
+		.collect::<Vec<_>>();
+
+	let mut layers = vec![leaves];
+	while layers[layers.len() - 1].len() > 1 {
+		let layer = &layers[layers.len() - 1];
+		let next = if layer.len() >= 2 * P::WIDTH {
+			layer
+				.par_chunks_exact(2 * P::WIDTH)
+				.flat_map_iter(|children| {
+					let left = array::from_fn(|i| P::from_fn(|lane| children[2 * lane][i]));
+					let right = array::from_fn(|i| P::from_fn(|lane| children[2 * lane + 1][i]));
+					unpack(compress.compress([left, right]))
+				})
+				.collect()
+		} else {
+			compress_poseidon2_layer(layer, 2, hash, compress)
+		};
+		layers.push(next);
+	}
+	layers
+}
+
+/// Returns the sibling digests at each level of the path from a leaf of a Merkle tree of the given
+/// arity, built by [`build_poseidon2_tree`], up to the root.
+fn open_poseidon2_path(
+	layers: &[Vec<[BabyBear; 8]>],
+	index: usize,
+	arity: usize,
+) -> Vec<Vec<[BabyBear; 8]>> {
+	layers[..layers.len() - 1]
+		.iter()
+		.scan(index, |index, layer| {
+			let first = *index / arity * arity;
+			let siblings = (first..first + arity)
+				.filter(|&i| i != *index)
+				.map(|i| layer[i])
+				.collect();
+			*index /= arity;
+			Some(siblings)
+		})
+		.collect()
+}
+
+/// Checks a path returned by [`open_poseidon2_path`] against the root, without allocating.
+fn verify_poseidon2_path(
+	root: &[BabyBear; 8],
+	index: usize,
+	row: &[BabyBear],
+	path: &[Vec<[BabyBear; 8]>],
+	arity: usize,
+	hash: &Poseidon2Hash,
+	compress: &Poseidon2Compress,
+) -> bool {
+	let (digest, _) =
+		path.iter()
+			.fold((hash.hash_slice(row), index), |(digest, index), siblings| {
+				let position = index % arity;
+				let mut children = siblings[..position]
+					.iter()
+					.chain(iter::once(&digest))
+					.chain(&siblings[position..]);
+				let parent = if arity == 2 {
+					compress.compress([*children.next().unwrap(), *children.next().unwrap()])
+				} else {
+					hash.hash_iter(children.flatten().copied())
+				};
+				(parent, index / arity)
+			});
+	&digest == root
+}
+
+/// Folds the roots of complete binary subtrees, arriving left to right, into the root of the whole
+/// tree.
+///
+/// Only one pending root per height is kept, so the state is logarithmic in the number of leaves.
+struct MerkleFrontier<'a> {
+	compress: &'a Poseidon2Compress,
+	pending: Vec<(usize, [BabyBear; 8])>,
+}
+
+impl<'a> MerkleFrontier<'a> {
+	fn new(compress: &'a Poseidon2Compress) -> Self {
+		Self {
+			compress,
+			pending: Vec::new(),
+		}
+	}
+
+	/// Adds the root of the next subtree, which has `2^height` leaves.
+	fn push(&mut self, mut height: usize, mut root: [BabyBear; 8]) {
+		while let Some(&(pending_height, left)) = self.pending.last() {
+			if pending_height != height {
+				break;
+			}
+			self.pending.pop();
+			root = self.compress.compress([left, root]);
+			height += 1;
+		}
+		self.pending.push((height, root));
+	}
+
+	/// Returns the root, once a power-of-two number of leaves has been pushed.
+	fn finish(self) -> [BabyBear; 8] {
+		assert_eq!(self.pending.len(), 1, "the number of leaves must be a power of two");
+		self.pending[0].1
+	}
+}
+
+/// Prints the in-memory size of a built tree, as its digest layout and as the heap it retains.
+///
+/// `n_digests` counts the stored digests below the cap, and `cap_digests` those in the cap, which
+/// is the root alone for backends without one. The retained heap also covers allocator and
+/// container overhead that the layout does not account for.
+fn report_tree_memory(
+	label: &str,
+	digest_bytes: usize,
+	n_digests: usize,
+	cap_digests: usize,
+	retained: HeapBytes,
+) {
+	let layout_bytes = (n_digests + cap_digests) * digest_bytes;
+	println!(
+		"{label}: tree memory = {} ({n_digests} digests + {cap_digests} cap × {digest_bytes} B), retained heap = {}",
+		ByteSize(layout_bytes as u64),
+		retained,
+	);
+}
+
+/// Name of the plonky2 Poseidon series with leaves of `leaf_bytes`, marking leaves that plonky2
+/// stores unhashed because they fit in a digest.
+fn plonky2_poseidon(leaf_bytes: usize) -> &'static str {
+	use plonky2::{hash::poseidon::PoseidonHash, plonk::config::Hasher};
+	use plonky2_field::goldilocks_field::GoldilocksField;
+
+	if leaf_bytes <= <PoseidonHash as Hasher<GoldilocksField>>::HASH_SIZE {
+		"Poseidon GL64 unhashed leaves"
+	} else {
+		"Poseidon GL64"
+	}
+}
+
+fn bench_binius(c: &mut Criterion) {
+	use binius_core::merkle_tree::{BinaryMerkleTreeProver, MerkleTreeProver};
+	use binius_field::Field;
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<AESTowerField32b>();
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("binius merkle");
+	group.sample_size(10);
+
+	let merkle_prover =
+		BinaryMerkleTreeProver::<_, GroestlHasher<_>, _>::new(GroestlDigestCompression::default());
+
+	for log_n in LOG_LEAVES {
+		let data = repeat_with(|| AESTowerField32b::random(&mut rng))
+			.take(LEAF_SIZE << log_n)
+			.collect::<Vec<_>>();
+
+		// The tree stores every node, leaf digests included, with the root as the last one.
+		let (_, retained) = retained_heap(|| merkle_prover.commit(&data, LEAF_SIZE).unwrap());
+		report_tree_memory(
+			&format!("Groestl log_leaves={log_n}"),
+			size_of::<GroestlDigestAES>(),
+			(2 << log_n) - 2,
+			1,
+			retained,
+		);
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("Groestl log_leaves={log_n}"), |b| {
+			b.iter(|| merkle_prover.commit(&data, LEAF_SIZE).unwrap())
+		});
+	}
+
+	group.finish()
+}
+
+fn bench_plonky3(c: &mut Criterion) {
+	use p3_commit::Mmcs;
+	use p3_matrix::dense::RowMajorMatrix;
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<BabyBear>();
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("plonky3 merkle");
+	group.sample_size(10);
+
+	let poseidon2_mmcs = plonky3_poseidon2_mmcs(&mut rng);
+	let keccak_mmcs = plonky3_keccak_mmcs();
+
+	for log_n in LOG_LEAVES {
+		let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << log_n, LEAF_SIZE);
+
+		// The prover data keeps the committed matrix, which is moved in and so not counted as
+		// retained, and every digest layer from the leaf digests up to the root.
+		let input = matrix.clone();
+		let (_, retained) = retained_heap(|| poseidon2_mmcs.commit_matrix(input));
+		report_tree_memory(
+			&format!("Poseidon2 BB31 log_leaves={log_n}"),
+			size_of::<[BabyBear; 8]>(),
+			(2 << log_n) - 2,
+			1,
+			retained,
+		);
+		let input = matrix.clone();
+		let (_, retained) = retained_heap(|| keccak_mmcs.commit_matrix(input));
+		report_tree_memory(
+			&format!("Keccak-256 log_leaves={log_n}"),
+			size_of::<[u8; 32]>(),
+			(2 << log_n) - 2,
+			1,
+			retained,
+		);
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("Poseidon2 BB31 log_leaves={log_n}"), |b| {
+			b.iter_batched(
+				|| matrix.clone(),
+				|matrix| poseidon2_mmcs.commit_matrix(matrix),
+				BatchSize::LargeInput,
+			)
+		});
+		group.bench_function(format!("Keccak-256 log_leaves={log_n}"), |b| {
+			b.iter_batched(
+				|| matrix.clone(),
+				|matrix| keccak_mmcs.commit_matrix(matrix),
+				BatchSize::LargeInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+fn bench_plonky2(c: &mut Criterion) {
+	use plonky2::{
+		hash::{keccak::KeccakHash, merkle_tree::MerkleTree, poseidon::PoseidonHash},
+		plonk::config::Hasher,
+	};
+	use plonky2_field::{goldilocks_field::GoldilocksField, types::Sample};
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<GoldilocksField>();
+
+	let mut group = c.benchmark_group("plonky2 merkle");
+	group.sample_size(10);
+
+	for log_n in LOG_LEAVES {
+		let leaves = repeat_with(|| GoldilocksField::rand_vec(LEAF_SIZE))
+			.take(1 << log_n)
+			.collect::<Vec<_>>();
+
+		// The tree keeps the leaves, which are moved in and so not counted as retained, and stores
+		// sibling digest pairs below the cap. Its Keccak digests are truncated to 25 bytes.
+		let input = leaves.clone();
+		let (tree, retained) =
+			retained_heap(|| MerkleTree::<GoldilocksField, PoseidonHash>::new(input, 0));
+		report_tree_memory(
+			&format!("{} log_leaves={log_n}", plonky2_poseidon(LEAF_BYTES)),
+			size_of::<<PoseidonHash as Hasher<GoldilocksField>>::Hash>(),
+			tree.digests.len(),
+			tree.cap.0.len(),
+			retained,
+		);
+		drop(tree);
+		let input = leaves.clone();
+		let (tree, retained) =
+			retained_heap(|| MerkleTree::<GoldilocksField, KeccakHash<25>>::new(input, 0));
+		report_tree_memory(
+			&format!("Keccak-256 log_leaves={log_n}"),
+			size_of::<<KeccakHash<25> as Hasher<GoldilocksField>>::Hash>(),
+			tree.digests.len(),
+			tree.cap.0.len(),
+			retained,
+		);
+		drop(tree);
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("{} log_leaves={log_n}", plonky2_poseidon(LEAF_BYTES)), |b| {
+			b.iter_batched(
+				|| leaves.clone(),
+				|leaves| MerkleTree::<GoldilocksField, PoseidonHash>::new(leaves, 0),
+				BatchSize::LargeInput,
+			)
+		});
+		group.bench_function(format!("Keccak-256 log_leaves={log_n}"), |b| {
+			b.iter_batched(
+				|| leaves.clone(),
+				|leaves| MerkleTree::<GoldilocksField, KeccakHash<25>>::new(leaves, 0),
+				BatchSize::LargeInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+#[cfg(feature = "stwo")]
+fn bench_stwo(c: &mut Criterion) {
+	use ark_std::UniformRand;
+	use stwo::core::{
+		backend::{simd::SimdBackend, Col},
+		fields::m31::BaseField,
+		vcs::{blake2_hash::Blake2sHash, blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+	};
+
+	// Each leaf is one row across this many columns.
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<BaseField>();
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("stwo merkle");
+	group.sample_size(10);
+
+	for log_n in LOG_LEAVES {
+		let columns = repeat_with(|| {
+			repeat_with(|| BaseField::rand(&mut rng))
+				.take(1 << log_n)
+				.collect::<Col<SimdBackend, BaseField>>()
+		})
+		.take(LEAF_SIZE)
+		.collect::<Vec<_>>();
+
+		// The prover stores one column of digests per layer, from the root down to the leaves.
+		let (_, retained) = retained_heap(|| {
+			MerkleProver::<SimdBackend, Blake2sMerkleHasher>::commit(columns.iter().collect())
+		});
+		report_tree_memory(
+			&format!("Blake2s M31 log_leaves={log_n}"),
+			size_of::<Blake2sHash>(),
+			(2 << log_n) - 2,
+			1,
+			retained,
+		);
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("Blake2s M31 log_leaves={log_n}"), |b| {
+			b.iter(|| {
+				MerkleProver::<SimdBackend, Blake2sMerkleHasher>::commit(columns.iter().collect())
+			})
+		});
+	}
+
+	group.finish()
+}
+
+#[cfg(not(feature = "stwo"))]
+fn bench_stwo(_c: &mut Criterion) {}
+
+fn bench_binius_openings(c: &mut Criterion) {
+	use binius_core::{
+		fiat_shamir::HasherChallenger,
+		merkle_tree::{BinaryMerkleTreeProver, MerkleTreeProver, MerkleTreeScheme},
+		transcript::{TranscriptReader, TranscriptWriter},
+	};
+	use binius_field::Field;
+
+	type Challenger = HasherChallenger<groestl::Groestl256>;
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<AESTowerField32b>();
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("binius merkle openings");
+
+	let merkle_prover =
+		BinaryMerkleTreeProver::<_, GroestlHasher<_>, _>::new(GroestlDigestCompression::default());
+	let merkle_scheme = merkle_prover.scheme();
+
+	let data = repeat_with(|| AESTowerField32b::random(&mut rng))
+		.take(LEAF_SIZE << OPENING_LOG_LEAVES)
+		.collect::<Vec<_>>();
+	let (_, committed) = merkle_prover.commit(&data, LEAF_SIZE).unwrap();
+	let root_layer = merkle_prover.layer(&committed, 0).unwrap();
+
+	for n_queries in QUERY_COUNTS {
+		let indices = repeat_with(|| rng.gen_range(0..1 << OPENING_LOG_LEAVES))
+			.take(n_queries)
+			.collect::<Vec<_>>();
+
+		let prove = || {
+			let mut transcript = TranscriptWriter::<Challenger>::default();
+			for &index in &indices {
+				merkle_prover
+					.prove_opening(&committed, 0, index, &mut transcript)
+					.unwrap();
+			}
+			transcript.finalize()
+		};
+		let proof = prove();
+
+		group.throughput(Throughput::Elements(n_queries as u64));
+		group.bench_function(format!("Groestl prove n_queries={n_queries}"), |b| b.iter(prove));
+		group.bench_function(format!("Groestl verify n_queries={n_queries}"), |b| {
+			b.iter_batched(
+				|| TranscriptReader::<Challenger>::new(proof.clone()),
+				|mut transcript| {
+					for &index in &indices {
+						let values = &data[index * LEAF_SIZE..(index + 1) * LEAF_SIZE];
+						merkle_scheme
+							.verify_opening(
+								index,
+								values,
+								0,
+								OPENING_LOG_LEAVES,
+								root_layer,
+								&mut transcript,
+							)
+							.unwrap();
+					}
+					transcript.finalize().unwrap()
+				},
+				BatchSize::SmallInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+fn bench_plonky3_openings(c: &mut Criterion) {
+	use p3_commit::Mmcs;
+	use p3_matrix::{dense::RowMajorMatrix, Dimensions};
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<BabyBear>();
+
+	/// Opens and verifies each query as its own path, as the FRI query phase does.
+	fn bench_mmcs<M: Mmcs<BabyBear>>(c: &mut Criterion, name: &str, mmcs: &M, mut rng: impl Rng) {
+		let mut group = c.benchmark_group("plonky3 merkle openings");
+
+		let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << OPENING_LOG_LEAVES, LEAF_SIZE);
+		let dimensions = [Dimensions {
+			width: LEAF_SIZE,
+			height: 1 << OPENING_LOG_LEAVES,
+		}];
+		let (commitment, prover_data) = mmcs.commit_matrix(matrix);
+
+		for n_queries in QUERY_COUNTS {
+			let indices = repeat_with(|| rng.gen_range(0..1 << OPENING_LOG_LEAVES))
+				.take(n_queries)
+				.collect::<Vec<_>>();
+
+			let prove = || {
+				indices
+					.iter()
+					.map(|&index| mmcs.open_batch(index, &prover_data))
+					.collect::<Vec<_>>()
+			};
+			let openings = prove();
+
+			group.throughput(Throughput::Elements(n_queries as u64));
+			group.bench_function(format!("{name} prove n_queries={n_queries}"), |b| b.iter(prove));
+			group.bench_function(format!("{name} verify n_queries={n_queries}"), |b| {
+				b.iter(|| {
+					for (&index, (values, proof)) in indices.iter().zip(&openings) {
+						mmcs.verify_batch(&commitment, &dimensions, index, values, proof)
+							.unwrap();
+					}
+				})
+			});
+		}
+
+		group.finish()
+	}
+
+	let mut rng = thread_rng();
+	let poseidon2_mmcs = plonky3_poseidon2_mmcs(&mut rng);
+	bench_mmcs(c, "Poseidon2 BB31", &poseidon2_mmcs, &mut rng);
+	bench_mmcs(c, "Keccak-256", &plonky3_keccak_mmcs(), &mut rng);
+}
+
+fn bench_plonky2_openings(c: &mut Criterion) {
+	use plonky2::{
+		hash::{
+			keccak::KeccakHash, merkle_proofs::verify_merkle_proof_to_cap, merkle_tree::MerkleTree,
+			poseidon::PoseidonHash,
+		},
+		plonk::config::Hasher,
+	};
+	use plonky2_field::{goldilocks_field::GoldilocksField, types::Sample};
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<GoldilocksField>();
+
+	fn bench_hasher<H: Hasher<GoldilocksField>>(c: &mut Criterion, name: &str, mut rng: impl Rng) {
+		let mut group = c.benchmark_group("plonky2 merkle openings");
+
+		let leaves = repeat_with(|| GoldilocksField::rand_vec(LEAF_SIZE))
+			.take(1 << OPENING_LOG_LEAVES)
+			.collect::<Vec<_>>();
+		let tree = MerkleTree::<GoldilocksField, H>::new(leaves, 0);
+
+		for n_queries in QUERY_COUNTS {
+			let indices = repeat_with(|| rng.gen_range(0..1 << OPENING_LOG_LEAVES))
+				.take(n_queries)
+				.collect::<Vec<_>>();
+
+			let prove = || {
+				indices
+					.iter()
+					.map(|&index| tree.prove(index))
+					.collect::<Vec<_>>()
+			};
+			let proofs = prove();
+
+			group.throughput(Throughput::Elements(n_queries as u64));
+			group.bench_function(format!("{name} prove n_queries={n_queries}"), |b| b.iter(prove));
+			group.bench_function(format!("{name} verify n_queries={n_queries}"), |b| {
+				b.iter(|| {
+					for (&index, proof) in indices.iter().zip(&proofs) {
+						verify_merkle_proof_to_cap(
+							tree.leaves[index].clone(),
+							index,
+							&tree.cap,
+							proof,
+						)
+						.unwrap();
+					}
+				})
+			});
+		}
+
+		group.finish()
+	}
+
+	let mut rng = thread_rng();
+	bench_hasher::<PoseidonHash>(c, plonky2_poseidon(LEAF_BYTES), &mut rng);
+	bench_hasher::<KeccakHash<25>>(c, "Keccak-256", &mut rng);
+}
+
+/// stwo decommits all queries against one tree as a single batched proof, sharing the internal
+/// nodes that overlap between paths.
+#[cfg(feature = "stwo")]
+fn bench_stwo_openings(c: &mut Criterion) {
+	use std::collections::BTreeMap;
+
+	use ark_std::UniformRand;
+	use stwo::core::{
+		backend::{simd::SimdBackend, Col},
+		fields::m31::BaseField,
+		vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver, verifier::MerkleVerifier},
+	};
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<BaseField>();
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("stwo merkle openings");
+
+	let columns = repeat_with(|| {
+		repeat_with(|| BaseField::rand(&mut rng))
+			.take(1 << OPENING_LOG_LEAVES)
+			.collect::<Col<SimdBackend, BaseField>>()
+	})
+	.take(LEAF_SIZE)
+	.collect::<Vec<_>>();
+	let prover = MerkleProver::<SimdBackend, Blake2sMerkleHasher>::commit(columns.iter().collect());
+	let column_log_sizes = vec![OPENING_LOG_LEAVES as u32; LEAF_SIZE];
+
+	for n_queries in QUERY_COUNTS {
+		let mut indices = repeat_with(|| rng.gen_range(0..1 << OPENING_LOG_LEAVES))
+			.take(n_queries)
+			.collect::<Vec<_>>();
+		indices.sort_unstable();
+		indices.dedup();
+		let queries = BTreeMap::from([(OPENING_LOG_LEAVES as u32, indices)]);
+
+		let prove = || prover.decommit(queries.clone(), columns.iter().collect());
+		let (values, decommitment) = prove();
+
+		group.throughput(Throughput::Elements(n_queries as u64));
+		group.bench_function(format!("Blake2s M31 prove n_queries={n_queries}"), |b| b.iter(prove));
+		group.bench_function(format!("Blake2s M31 verify n_queries={n_queries}"), |b| {
+			let verifier = MerkleVerifier::new(prover.root(), column_log_sizes.clone());
+			b.iter_batched(
+				|| (queries.clone(), values.clone(), decommitment.clone()),
+				|(queries, values, decommitment)| {
+					verifier.verify(queries, values, decommitment).unwrap()
+				},
+				BatchSize::SmallInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+#[cfg(not(feature = "stwo"))]
+fn bench_stwo_openings(_c: &mut Criterion) {}
+
+/// Sweeps the plonky2 Merkle cap height, measuring commit time, proof size, and the time to verify
+/// a FRI-sized batch of paths.
+///
+/// A taller cap removes levels from every path at the cost of sending more digests once, so the
+/// total proof size printed for each height is the cap plus all the paths.
+fn bench_plonky2_cap_height(c: &mut Criterion) {
+	use plonky2::{
+		hash::{
+			merkle_proofs::verify_merkle_proof_to_cap, merkle_tree::MerkleTree,
+			poseidon::PoseidonHash,
+		},
+		plonk::config::Hasher,
+	};
+	use plonky2_field::{goldilocks_field::GoldilocksField, types::Sample};
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<GoldilocksField>();
+	let n_queries = QUERY_COUNTS[QUERY_COUNTS.len() - 1];
+	let name = plonky2_poseidon(LEAF_BYTES);
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("plonky2 merkle cap height");
+	group.sample_size(10);
+
+	let leaves = repeat_with(|| GoldilocksField::rand_vec(LEAF_SIZE))
+		.take(1 << OPENING_LOG_LEAVES)
+		.collect::<Vec<_>>();
+	let indices = repeat_with(|| rng.gen_range(0..1 << OPENING_LOG_LEAVES))
+		.take(n_queries)
+		.collect::<Vec<_>>();
+
+	for cap_height in CAP_HEIGHTS {
+		let tree = MerkleTree::<GoldilocksField, PoseidonHash>::new(leaves.clone(), cap_height);
+		let proofs = indices
+			.iter()
+			.map(|&index| tree.prove(index))
+			.collect::<Vec<_>>();
+
+		let cap_size = tree.cap.0.len() * PoseidonHash::HASH_SIZE;
+		let paths_size = proofs
+			.iter()
+			.map(|proof| proof.siblings.len() * PoseidonHash::HASH_SIZE)
+			.sum::<usize>();
+		println!(
+			"{name} cap_height={cap_height}: proof size = {} (cap = {}, {n_queries} paths = {})",
+			ByteSize((cap_size + paths_size) as u64),
+			ByteSize(cap_size as u64),
+			ByteSize(paths_size as u64),
+		);
+
+		group.throughput(Throughput::Elements(1 << OPENING_LOG_LEAVES));
+		group.bench_function(format!("{name} commit cap_height={cap_height}"), |b| {
+			b.iter_batched(
+				|| leaves.clone(),
+				|leaves| MerkleTree::<GoldilocksField, PoseidonHash>::new(leaves, cap_height),
+				BatchSize::LargeInput,
+			)
+		});
+
+		group.throughput(Throughput::Elements(n_queries as u64));
+		group.bench_function(format!("{name} verify cap_height={cap_height}"), |b| {
+			b.iter(|| {
+				for (&index, proof) in indices.iter().zip(&proofs) {
+					verify_merkle_proof_to_cap(tree.leaves[index].clone(), index, &tree.cap, proof)
+						.unwrap();
+				}
+			})
+		});
+	}
+
+	group.finish()
+}
+
+/// The binius equivalent of a cap: paths are opened against the tree layer at a given depth,
+/// which the verifier checks against the root once per batch before checking each path.
+fn bench_binius_cap_height(c: &mut Criterion) {
+	use binius_core::{
+		fiat_shamir::HasherChallenger,
+		merkle_tree::{BinaryMerkleTreeProver, MerkleTreeProver, MerkleTreeScheme},
+		transcript::{TranscriptReader, TranscriptWriter},
+	};
+	use binius_field::Field;
+
+	type Challenger = HasherChallenger<groestl::Groestl256>;
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<AESTowerField32b>();
+	let n_queries = QUERY_COUNTS[QUERY_COUNTS.len() - 1];
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("binius merkle layer depth");
+
+	let merkle_prover =
+		BinaryMerkleTreeProver::<_, GroestlHasher<_>, _>::new(GroestlDigestCompression::default());
+	let merkle_scheme = merkle_prover.scheme();
+
+	let data = repeat_with(|| AESTowerField32b::random(&mut rng))
+		.take(LEAF_SIZE << OPENING_LOG_LEAVES)
+		.collect::<Vec<_>>();
+	let (commitment, committed) = merkle_prover.commit(&data, LEAF_SIZE).unwrap();
+	let indices = repeat_with(|| rng.gen_range(0..1 << OPENING_LOG_LEAVES))
+		.take(n_queries)
+		.collect::<Vec<_>>();
+
+	for layer_depth in CAP_HEIGHTS {
+		let layer = merkle_prover.layer(&committed, layer_depth).unwrap();
+		let mut transcript = TranscriptWriter::<Challenger>::default();
+		for &index in &indices {
+			merkle_prover
+				.prove_opening(&committed, layer_depth, index, &mut transcript)
+				.unwrap();
+		}
+		let proof = transcript.finalize();
+
+		let layer_size = layer.len() * size_of::<GroestlDigestAES>();
+		println!(
+			"Groestl layer_depth={layer_depth}: proof size = {} (layer = {}, {n_queries} paths = {})",
+			ByteSize((layer_size + proof.len()) as u64),
+			ByteSize(layer_size as u64),
+			ByteSize(proof.len() as u64),
+		);
+
+		group.throughput(Throughput::Elements(n_queries as u64));
+		group.bench_function(format!("Groestl verify layer_depth={layer_depth}"), |b| {
+			b.iter_batched(
+				|| TranscriptReader::<Challenger>::new(proof.clone()),
+				|mut transcript| {
+					merkle_scheme
+						.verify_layer(&commitment.root, layer_depth, layer)
+						.unwrap();
+					for &index in &indices {
+						let values = &data[index * LEAF_SIZE..(index + 1) * LEAF_SIZE];
+						merkle_scheme
+							.verify_opening(
+								index,
+								values,
+								layer_depth,
+								OPENING_LOG_LEAVES,
+								layer,
+								&mut transcript,
+							)
+							.unwrap();
+					}
+					transcript.finalize().unwrap()
+				},
+				BatchSize::SmallInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+/// Compares binary Merkle trees against higher-arity trees over the same rows and hash.
+///
+/// None of the backends expose a configurable arity, so the trees are built by a reference
+/// builder on plonky3's Poseidon2 primitives, with `MerkleTreeMmcs` included as the library
+/// baseline. The path length printed for each arity is the number of sibling digests a single
+/// opening carries.
+fn bench_arity(c: &mut Criterion) {
+	use p3_commit::Mmcs;
+	use p3_matrix::dense::RowMajorMatrix;
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<BabyBear>();
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("merkle arity");
+	group.sample_size(10);
+	group.throughput(Throughput::Elements(1 << ARITY_LOG_LEAVES));
+
+	let perm = Poseidon2Perm::new_from_rng_128(
+		Poseidon2ExternalMatrixGeneral,
+		DiffusionMatrixBabyBear::default(),
+		&mut rng,
+	);
+	let hash = Poseidon2Hash::new(perm.clone());
+	let compress = Poseidon2Compress::new(perm.clone());
+	let mmcs = Poseidon2Mmcs::new(hash.clone(), compress.clone());
+
+	let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << ARITY_LOG_LEAVES, LEAF_SIZE);
+
+	group.bench_function(
+		format!("Poseidon2 BB31 MerkleTreeMmcs arity=2 log_leaves={ARITY_LOG_LEAVES}"),
+		|b| {
+			b.iter_batched(
+				|| matrix.clone(),
+				|matrix| mmcs.commit_matrix(matrix),
+				BatchSize::LargeInput,
+			)
+		},
+	);
+
+	for arity in ARITIES {
+		let depth = ARITY_LOG_LEAVES / arity.ilog2() as usize;
+		let siblings = depth * (arity - 1);
+		println!(
+			"Poseidon2 BB31 arity={arity}: path length = {depth} levels, {siblings} sibling digests ({})",
+			ByteSize((siblings * 8 * size_of::<BabyBear>()) as u64),
+		);
+
+		group.bench_function(
+			format!("Poseidon2 BB31 arity={arity} log_leaves={ARITY_LOG_LEAVES}"),
+			|b| b.iter(|| build_poseidon2_tree(&matrix.values, LEAF_SIZE, arity, &hash, &compress)),
+		);
+	}
+
+	group.finish()
+}
+
+/// Sweeps the bytes per leaf at a fixed total data size, for each backend and hash.
+///
+/// Wider leaves mean fewer tree nodes but longer leaf hashes, so the balance between leaf hashing
+/// and compression shifts with the row width. Throughput is reported in committed bytes.
+fn bench_leaf_size(c: &mut Criterion) {
+	use binius_core::merkle_tree::{BinaryMerkleTreeProver, MerkleTreeProver};
+	use p3_commit::Mmcs;
+	use p3_matrix::dense::RowMajorMatrix;
+	use plonky2::hash::{keccak::KeccakHash, merkle_tree::MerkleTree, poseidon::PoseidonHash};
+	use plonky2_field::{goldilocks_field::GoldilocksField, types::Sample};
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("merkle leaf size");
+	group.sample_size(10);
+	group.throughput(Throughput::Bytes(SWEEP_TOTAL_BYTES as u64));
+
+	let merkle_prover =
+		BinaryMerkleTreeProver::<_, GroestlHasher<_>, _>::new(GroestlDigestCompression::default());
+	let poseidon2_mmcs = plonky3_poseidon2_mmcs(&mut rng);
+	let keccak_mmcs = plonky3_keccak_mmcs();
+
+	for leaf_bytes in SWEEP_LEAF_BYTES {
+		let n_leaves = SWEEP_TOTAL_BYTES / leaf_bytes;
+
+		let leaf_size = leaf_bytes / size_of::<AESTowerField32b>();
+		let data = repeat_with(|| <AESTowerField32b as binius_field::Field>::random(&mut rng))
+			.take(SWEEP_TOTAL_BYTES / size_of::<AESTowerField32b>())
+			.collect::<Vec<_>>();
+		group.bench_function(format!("binius Groestl leaf_bytes={leaf_bytes}"), |b| {
+			b.iter(|| merkle_prover.commit(&data, leaf_size).unwrap())
+		});
+		drop(data);
+
+		let leaf_size = leaf_bytes / size_of::<BabyBear>();
+		let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, n_leaves, leaf_size);
+		group.bench_function(format!("plonky3 Poseidon2 BB31 leaf_bytes={leaf_bytes}"), |b| {
+			b.iter_batched(
+				|| matrix.clone(),
+				|matrix| poseidon2_mmcs.commit_matrix(matrix),
+				BatchSize::LargeInput,
+			)
+		});
+		group.bench_function(format!("plonky3 Keccak-256 leaf_bytes={leaf_bytes}"), |b| {
+			b.iter_batched(
+				|| matrix.clone(),
+				|matrix| keccak_mmcs.commit_matrix(matrix),
+				BatchSize::LargeInput,
+			)
+		});
+		drop(matrix);
+
+		let leaf_size = leaf_bytes / size_of::<GoldilocksField>();
+		let leaves = repeat_with(|| GoldilocksField::rand_vec(leaf_size))
+			.take(n_leaves)
+			.collect::<Vec<_>>();
+		let name = format!("plonky2 {} leaf_bytes={leaf_bytes}", plonky2_poseidon(leaf_bytes));
+		group.bench_function(name, |b| {
+			b.iter_batched(
+				|| leaves.clone(),
+				|leaves| MerkleTree::<GoldilocksField, PoseidonHash>::new(leaves, 0),
+				BatchSize::LargeInput,
+			)
+		});
+		group.bench_function(format!("plonky2 Keccak-256 leaf_bytes={leaf_bytes}"), |b| {
+			b.iter_batched(
+				|| leaves.clone(),
+				|leaves| MerkleTree::<GoldilocksField, KeccakHash<25>>::new(leaves, 0),
+				BatchSize::LargeInput,
+			)
+		});
+		drop(leaves);
+
+		#[cfg(feature = "stwo")]
+		{
+			use ark_std::UniformRand;
+			use stwo::core::{
+				backend::{simd::SimdBackend, Col},
+				fields::m31::BaseField,
+				vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+			};
+
+			let columns = repeat_with(|| {
+				repeat_with(|| BaseField::rand(&mut rng))
+					.take(n_leaves)
+					.collect::<Col<SimdBackend, BaseField>>()
+			})
+			.take(leaf_bytes / size_of::<BaseField>())
+			.collect::<Vec<_>>();
+			group.bench_function(format!("stwo Blake2s M31 leaf_bytes={leaf_bytes}"), |b| {
+				b.iter(|| {
+					MerkleProver::<SimdBackend, Blake2sMerkleHasher>::commit(
+						columns.iter().collect(),
+					)
+				})
+			});
+		}
+	}
+
+	group.finish()
+}
+
+/// Commits identical data with binius's Merkle prover and plonky3's `MerkleTreeMmcs` configured
+/// with the same hash, isolating framework overhead from hash cost.
+///
+/// Leaves are eight 32-bit values, stored as tower field elements for binius and as canonical
+/// BabyBear elements for plonky3, so both frameworks hash exactly the same bytes. Both trees use
+/// the byte hash of the concatenated children as their compression function.
+fn bench_same_hash(c: &mut Criterion) {
+	use binius_core::merkle_tree::{BinaryMerkleTreeProver, MerkleTreeProver};
+	use p3_commit::Mmcs;
+	use p3_field::{AbstractField, PrimeField32};
+	use p3_matrix::dense::RowMajorMatrix;
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<u32>();
+
+	type GroestlMmcs = MerkleTreeMmcs<
+		BabyBear,
+		u8,
+		SerializingHasher32<GroestlByteHash>,
+		CompressionFunctionFromHasher<GroestlByteHash, 2, 32>,
+		32,
+	>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("merkle same hash");
+	group.sample_size(10);
+
+	let binius_keccak =
+		BinaryMerkleTreeProver::<_, KeccakHasher, _>::new(KeccakDigestCompression::default());
+	let binius_groestl =
+		BinaryMerkleTreeProver::<_, GroestlHasher<_>, _>::new(GroestlDigestCompression::default());
+	let plonky3_keccak = plonky3_keccak_mmcs();
+	let plonky3_groestl = GroestlMmcs::new(
+		SerializingHasher32::new(GroestlByteHash),
+		CompressionFunctionFromHasher::new(GroestlByteHash),
+	);
+
+	for log_n in SAME_HASH_LOG_LEAVES {
+		let values = repeat_with(|| rng.gen_range(0..BabyBear::ORDER_U32))
+			.take(LEAF_SIZE << log_n)
+			.collect::<Vec<_>>();
+		let binius_data = values
+			.iter()
+			.map(|&value| AESTowerField32b::new(value))
+			.collect::<Vec<_>>();
+		let plonky3_matrix = RowMajorMatrix::new(
+			values
+				.iter()
+				.map(|&value| BabyBear::from_canonical_u32(value))
+				.collect(),
+			LEAF_SIZE,
+		);
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("binius Keccak-256 log_leaves={log_n}"), |b| {
+			b.iter(|| binius_keccak.commit(&binius_data, LEAF_SIZE).unwrap())
+		});
+		group.bench_function(format!("plonky3 Keccak-256 log_leaves={log_n}"), |b| {
+			b.iter_batched(
+				|| plonky3_matrix.clone(),
+				|matrix| plonky3_keccak.commit_matrix(matrix),
+				BatchSize::LargeInput,
+			)
+		});
+		group.bench_function(format!("binius Groestl log_leaves={log_n}"), |b| {
+			b.iter(|| binius_groestl.commit(&binius_data, LEAF_SIZE).unwrap())
+		});
+		group.bench_function(format!("plonky3 Groestl log_leaves={log_n}"), |b| {
+			b.iter_batched(
+				|| plonky3_matrix.clone(),
+				|matrix| plonky3_groestl.commit_matrix(matrix),
+				BatchSize::LargeInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+/// Prints the parallel speedup of compressing each layer of a binary Poseidon2 tree, from the
+/// root down, using all threads against one.
+///
+/// Leaf hashing parallelizes perfectly, but the layers near the root have fewer nodes than there
+/// are threads, so this locates the depth at which tree building collapses to the sequential
+/// root path.
+fn report_layer_scaling(
+	layers: &[Vec<[BabyBear; 8]>],
+	hash: &Poseidon2Hash,
+	compress: &Poseidon2Compress,
+) {
+	let time_layer = |pool: &ThreadPool, layer: &[[BabyBear; 8]]| {
+		pool.install(|| {
+			(0..3)
+				.map(|_| {
+					let start = Instant::now();
+					black_box(compress_poseidon2_layer(layer, 2, hash, compress));
+					start.elapsed()
+				})
+				.min()
+				.unwrap()
+		})
+	};
+
+	let max_threads = thread_counts().pop().unwrap();
+	let single = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+	let parallel = ThreadPoolBuilder::new()
+		.num_threads(max_threads)
+		.build()
+		.unwrap();
+
+	let mut collapse_depth = None;
+	for layer in layers[..layers.len() - 1].iter().rev() {
+		let depth = layer.len().ilog2() - 1;
+		let speedup =
+			time_layer(&single, layer).as_secs_f64() / time_layer(&parallel, layer).as_secs_f64();
+		let efficiency = speedup / max_threads as f64;
+		println!(
+			"Poseidon2 BB31 depth={depth}: speedup = {speedup:.2}x on {max_threads} threads, efficiency = {:.0}%",
+			efficiency * 100.0
+		);
+		if efficiency < 0.5 {
+			collapse_depth = Some(depth);
+		}
+	}
+	if let Some(depth) = collapse_depth {
+		println!("Parallel efficiency is below 50% at depth {depth} and above");
+	}
+}
+
+/// Sweeps the rayon pool size for Merkle tree building in each backend.
+///
+/// plonky3 only parallelizes with the `p3_parallel` feature enabled.
+fn bench_thread_scaling(c: &mut Criterion) {
+	use binius_core::merkle_tree::{BinaryMerkleTreeProver, MerkleTreeProver};
+	use binius_field::Field;
+	use criterion::BenchmarkId;
+	use p3_commit::Mmcs;
+	use p3_matrix::dense::RowMajorMatrix;
+	use plonky2::hash::{merkle_tree::MerkleTree, poseidon::PoseidonHash};
+	use plonky2_field::{goldilocks_field::GoldilocksField, types::Sample};
+
+	let mut rng = thread_rng();
+
+	let perm = Poseidon2Perm::new_from_rng_128(
+		Poseidon2ExternalMatrixGeneral,
+		DiffusionMatrixBabyBear::default(),
+		&mut rng,
+	);
+	let hash = Poseidon2Hash::new(perm.clone());
+	let compress = Poseidon2Compress::new(perm.clone());
+	let poseidon2_mmcs = Poseidon2Mmcs::new(hash.clone(), compress.clone());
+	let binius_prover =
+		BinaryMerkleTreeProver::<_, GroestlHasher<_>, _>::new(GroestlDigestCompression::default());
+
+	let bb_leaf_size = LEAF_BYTES / size_of::<BabyBear>();
+	let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << THREADS_LOG_LEAVES, bb_leaf_size);
+	let binius_leaf_size = LEAF_BYTES / size_of::<AESTowerField32b>();
+	let binius_data = repeat_with(|| AESTowerField32b::random(&mut rng))
+		.take(binius_leaf_size << THREADS_LOG_LEAVES)
+		.collect::<Vec<_>>();
+	let gl_leaves =
+		repeat_with(|| GoldilocksField::rand_vec(LEAF_BYTES / size_of::<GoldilocksField>()))
+			.take(1 << THREADS_LOG_LEAVES)
+			.collect::<Vec<_>>();
+
+	report_layer_scaling(
+		&build_poseidon2_tree(&matrix.values, bb_leaf_size, 2, &hash, &compress),
+		&hash,
+		&compress,
+	);
+
+	let mut group = c.benchmark_group("merkle thread scaling");
+	group.sample_size(10);
+	group.throughput(Throughput::Elements(1 << THREADS_LOG_LEAVES));
+
+	#[cfg(feature = "stwo")]
+	let stwo_columns = {
+		use ark_std::UniformRand;
+		use stwo::core::{
+			backend::{simd::SimdBackend, Col},
+			fields::m31::BaseField,
+		};
+
+		repeat_with(|| {
+			repeat_with(|| BaseField::rand(&mut rng))
+				.take(1 << THREADS_LOG_LEAVES)
+				.collect::<Col<SimdBackend, BaseField>>()
+		})
+		.take(LEAF_BYTES / size_of::<BaseField>())
+		.collect::<Vec<_>>()
+	};
+
+	for n_threads in thread_counts() {
+		let pool = ThreadPoolBuilder::new()
+			.num_threads(n_threads)
+			.build()
+			.unwrap();
+
+		group.bench_with_input(
+			BenchmarkId::new("binius Groestl", n_threads),
+			&n_threads,
+			|b, _| {
+				pool.install(|| {
+					b.iter(|| {
+						binius_prover
+							.commit(&binius_data, binius_leaf_size)
+							.unwrap()
+					})
+				})
+			},
+		);
+		group.bench_with_input(
+			BenchmarkId::new("plonky3 Poseidon2 BB31", n_threads),
+			&n_threads,
+			|b, _| {
+				pool.install(|| {
+					b.iter_batched(
+						|| matrix.clone(),
+						|matrix| poseidon2_mmcs.commit_matrix(matrix),
+						BatchSize::LargeInput,
+					)
+				})
+			},
+		);
+		group.bench_with_input(
+			BenchmarkId::new(format!("plonky2 {}", plonky2_poseidon(LEAF_BYTES)), n_threads),
+			&n_threads,
+			|b, _| {
+				pool.install(|| {
+					b.iter_batched(
+						|| gl_leaves.clone(),
+						|leaves| MerkleTree::<GoldilocksField, PoseidonHash>::new(leaves, 0),
+						BatchSize::LargeInput,
+					)
+				})
+			},
+		);
+
+		#[cfg(feature = "stwo")]
+		{
+			use stwo::core::{
+				backend::simd::SimdBackend,
+				vcs::{blake2_merkle::Blake2sMerkleHasher, prover::MerkleProver},
+			};
+
+			group.bench_with_input(
+				BenchmarkId::new("stwo Blake2s M31", n_threads),
+				&n_threads,
+				|b, _| {
+					pool.install(|| {
+						b.iter(|| {
+							MerkleProver::<SimdBackend, Blake2sMerkleHasher>::commit(
+								stwo_columns.iter().collect(),
+							)
+						})
+					})
+				},
+			);
+		}
+	}
+
+	group.finish()
+}
+
+/// Builds trees from leaves that arrive in chunks, as when witness generation and commitment
+/// overlap, against building the whole tree at once.
+///
+/// None of the backends build trees incrementally, so each chunk is committed as a subtree,
+/// either with `MerkleTreeMmcs` or with the reference builder, and the subtree roots are folded
+/// into the full root as they arrive. Both streaming builders are checked to produce the same
+/// root as `MerkleTreeMmcs` over the whole matrix. Peak heap above the input is printed for each
+/// variant, since bounded memory is the point of streaming.
+fn bench_streaming(c: &mut Criterion) {
+	use p3_commit::Mmcs;
+	use p3_matrix::dense::RowMajorMatrix;
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<BabyBear>();
+
+	let mut rng = thread_rng();
+
+	let perm = Poseidon2Perm::new_from_rng_128(
+		Poseidon2ExternalMatrixGeneral,
+		DiffusionMatrixBabyBear::default(),
+		&mut rng,
+	);
+	let hash = Poseidon2Hash::new(perm.clone());
+	let compress = Poseidon2Compress::new(perm.clone());
+	let mmcs = Poseidon2Mmcs::new(hash.clone(), compress.clone());
+
+	let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << STREAM_LOG_LEAVES, LEAF_SIZE);
+
+	let stream_plonky3 = |log_chunk: usize| {
+		let mut frontier = MerkleFrontier::new(&compress);
+		for chunk in matrix.values.chunks(LEAF_SIZE << log_chunk) {
+			let (root, _) = mmcs.commit_matrix(RowMajorMatrix::new(chunk.to_vec(), LEAF_SIZE));
+			frontier.push(log_chunk, root.into());
+		}
+		frontier.finish()
+	};
+	let stream_reference = |log_chunk: usize| {
+		let mut frontier = MerkleFrontier::new(&compress);
+		for chunk in matrix.values.chunks(LEAF_SIZE << log_chunk) {
+			let layers = build_poseidon2_tree(chunk, LEAF_SIZE, 2, &hash, &compress);
+			frontier.push(log_chunk, layers[layers.len() - 1][0]);
+		}
+		frontier.finish()
+	};
+
+	let (root, _) = mmcs.commit_matrix(matrix.clone());
+	let root: [BabyBear; 8] = root.into();
+	let input = matrix.clone();
+	println!(
+		"Poseidon2 BB31 whole tree: peak extra heap = {}",
+		peak_heap_during(|| mmcs.commit_matrix(input))
+	);
+	for log_chunk in STREAM_LOG_CHUNKS {
+		assert_eq!(stream_plonky3(log_chunk), root);
+		assert_eq!(stream_reference(log_chunk), root);
+		println!(
+			"Poseidon2 BB31 MerkleTreeMmcs chunk=2^{log_chunk}: peak extra heap = {}",
+			peak_heap_during(|| stream_plonky3(log_chunk))
+		);
+		println!(
+			"Poseidon2 BB31 reference chunk=2^{log_chunk}: peak extra heap = {}",
+			peak_heap_during(|| stream_reference(log_chunk))
+		);
+	}
+
+	let mut group = c.benchmark_group("merkle streaming");
+	group.sample_size(10);
+	group.throughput(Throughput::Elements(1 << STREAM_LOG_LEAVES));
+
+	group.bench_function("Poseidon2 BB31 MerkleTreeMmcs whole tree", |b| {
+		b.iter_batched(
+			|| matrix.clone(),
+			|matrix| mmcs.commit_matrix(matrix),
+			BatchSize::LargeInput,
+		)
+	});
+	for log_chunk in STREAM_LOG_CHUNKS {
+		group.bench_function(format!("Poseidon2 BB31 MerkleTreeMmcs chunk=2^{log_chunk}"), |b| {
+			b.iter(|| stream_plonky3(log_chunk))
+		});
+		group.bench_function(format!("Poseidon2 BB31 reference chunk=2^{log_chunk}"), |b| {
+			b.iter(|| stream_reference(log_chunk))
+		});
+	}
+
+	group.finish()
+}
+
+/// Measures the cost of hiding Merkle commitments, where each leaf is hashed together with a fresh
+/// random salt.
+///
+/// plonky2 salts leaves when polynomial batches are committed with blinding, which is reproduced
+/// here by appending random elements to each leaf. plonky3's MMCS has no hiding mode at this
+/// revision, so salts are committed as a second matrix of the same height, which hashes each salt
+/// into its row's leaf digest. The salts are generated in the untimed setup of every iteration, so
+/// that the salted commitments only differ from the unsalted ones by the salt hashing, and salt
+/// generation is benchmarked on its own.
+fn bench_salted(c: &mut Criterion) {
+	use p3_commit::Mmcs;
+	use p3_matrix::dense::RowMajorMatrix;
+	use plonky2::hash::{merkle_tree::MerkleTree, poseidon::PoseidonHash};
+	use plonky2_field::{goldilocks_field::GoldilocksField, types::Sample};
+
+	const BB_LEAF_SIZE: usize = SALTED_LEAF_BYTES / size_of::<BabyBear>();
+	const BB_SALT_SIZE: usize = SALT_BYTES / size_of::<BabyBear>();
+	const GL_LEAF_SIZE: usize = SALTED_LEAF_BYTES / size_of::<GoldilocksField>();
+	const GL_SALT_SIZE: usize = SALT_BYTES / size_of::<GoldilocksField>();
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("merkle salted");
+	group.sample_size(10);
+	group.throughput(Throughput::Elements(1 << SALTED_LOG_LEAVES));
+
+	let poseidon2_mmcs = plonky3_poseidon2_mmcs(&mut rng);
+	let keccak_mmcs = plonky3_keccak_mmcs();
+	let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << SALTED_LOG_LEAVES, BB_LEAF_SIZE);
+
+	group.bench_function("plonky3 BB31 salt generation", |b| {
+		b.iter(|| {
+			RowMajorMatrix::<BabyBear>::rand(
+				&mut thread_rng(),
+				1 << SALTED_LOG_LEAVES,
+				BB_SALT_SIZE,
+			)
+		})
+	});
+	group.bench_function("plonky3 Poseidon2 BB31 unsalted", |b| {
+		b.iter_batched(
+			|| matrix.clone(),
+			|matrix| poseidon2_mmcs.commit_matrix(matrix),
+			BatchSize::LargeInput,
+		)
+	});
+	let salted_matrices = || {
+		let salts = RowMajorMatrix::rand(&mut thread_rng(), 1 << SALTED_LOG_LEAVES, BB_SALT_SIZE);
+		vec![matrix.clone(), salts]
+	};
+	group.bench_function("plonky3 Poseidon2 BB31 salted", |b| {
+		b.iter_batched(
+			salted_matrices,
+			|matrices| poseidon2_mmcs.commit(matrices),
+			BatchSize::LargeInput,
+		)
+	});
+	group.bench_function("plonky3 Keccak-256 unsalted", |b| {
+		b.iter_batched(
+			|| matrix.clone(),
+			|matrix| keccak_mmcs.commit_matrix(matrix),
+			BatchSize::LargeInput,
+		)
+	});
+	group.bench_function("plonky3 Keccak-256 salted", |b| {
+		b.iter_batched(
+			salted_matrices,
+			|matrices| keccak_mmcs.commit(matrices),
+			BatchSize::LargeInput,
+		)
+	});
+
+	let leaves = repeat_with(|| GoldilocksField::rand_vec(GL_LEAF_SIZE))
+		.take(1 << SALTED_LOG_LEAVES)
+		.collect::<Vec<_>>();
+
+	group.bench_function("plonky2 GL64 salt generation", |b| {
+		b.iter(|| {
+			repeat_with(|| GoldilocksField::rand_vec(GL_SALT_SIZE))
+				.take(1 << SALTED_LOG_LEAVES)
+				.collect::<Vec<_>>()
+		})
+	});
+	group.bench_function("plonky2 Poseidon GL64 unsalted", |b| {
+		b.iter_batched(
+			|| leaves.clone(),
+			|leaves| MerkleTree::<GoldilocksField, PoseidonHash>::new(leaves, 0),
+			BatchSize::LargeInput,
+		)
+	});
+	let salted_leaves = || {
+		leaves
+			.iter()
+			.map(|leaf| [leaf.as_slice(), &GoldilocksField::rand_vec(GL_SALT_SIZE)].concat())
+			.collect::<Vec<_>>()
+	};
+	group.bench_function("plonky2 Poseidon GL64 salted", |b| {
+		b.iter_batched(
+			salted_leaves,
+			|leaves| MerkleTree::<GoldilocksField, PoseidonHash>::new(leaves, 0),
+			BatchSize::LargeInput,
+		)
+	});
+
+	group.finish()
+}
+
+/// Compares building a Poseidon2 tree one hash at a time against hashing a leaf per SIMD lane,
+/// and reports how much of that gain plonky3's `MerkleTreeMmcs` captures.
+///
+/// plonky3 is the only backend that hashes several leaves per call, through its packed field
+/// types, and Poseidon2 is the only hash it vectorizes this way at this revision; plonky2, binius,
+/// and stwo hash one leaf at a time. Everything runs on a single thread so that the comparison is
+/// of SIMD width alone.
+fn bench_simd_leaf_hashing(c: &mut Criterion<FieldThroughput>) {
+	use p3_commit::Mmcs;
+	use p3_matrix::dense::RowMajorMatrix;
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<BabyBear>();
+	let lanes = <BabyBear as Field>::Packing::WIDTH;
+
+	let mut rng = thread_rng();
+	let perm = Poseidon2Perm::new_from_rng_128(
+		Poseidon2ExternalMatrixGeneral,
+		DiffusionMatrixBabyBear::default(),
+		&mut rng,
+	);
+	let hash = Poseidon2Hash::new(perm.clone());
+	let compress = Poseidon2Compress::new(perm.clone());
+	let mmcs = Poseidon2Mmcs::new(hash.clone(), compress.clone());
+
+	let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << SIMD_LOG_LEAVES, LEAF_SIZE);
+	let pool = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+
+	let scalar = || build_poseidon2_tree(&matrix.values, LEAF_SIZE, 2, &hash, &compress);
+	let packed = || build_poseidon2_tree_packed(&matrix.values, LEAF_SIZE, &hash, &compress);
+	let library = || mmcs.commit_matrix(matrix.clone());
+
+	let (root, _) = library();
+	let root: [BabyBear; 8] = root.into();
+	assert_eq!(pool.install(scalar).pop().unwrap()[0], root);
+	assert_eq!(pool.install(packed).pop().unwrap()[0], root);
+
+	let time = |build: &dyn Fn()| {
+		pool.install(|| {
+			(0..3)
+				.map(|_| {
+					let start = Instant::now();
+					build();
+					start.elapsed().as_secs_f64()
+				})
+				.fold(f64::INFINITY, f64::min)
+		})
+	};
+	let scalar_time = time(&|| drop(black_box(scalar())));
+	let packed_time = time(&|| drop(black_box(packed())));
+	let library_time = time(&|| drop(black_box(library())));
+	println!(
+		"Poseidon2 BB31 {lanes}-lane hashing: speedup = {:.2}x, MerkleTreeMmcs speedup = {:.2}x, capturing {:.0}% of the time saved",
+		scalar_time / packed_time,
+		scalar_time / library_time,
+		(scalar_time - library_time) / (scalar_time - packed_time) * 100.0,
+	);
+
+	let mut group = c.benchmark_group("merkle simd leaf hashing");
+	group.sample_size(10);
+	set_element_bytes(LEAF_BYTES as u64);
+	group.throughput(Throughput::Elements(1 << SIMD_LOG_LEAVES));
+
+	group.bench_function(
+		format!("Poseidon2 BB31 one hash at a time log_leaves={SIMD_LOG_LEAVES}"),
+		|b| pool.install(|| b.iter(scalar)),
+	);
+	group.bench_function(
+		format!("Poseidon2 BB31 {lanes}-lane packed log_leaves={SIMD_LOG_LEAVES}"),
+		|b| pool.install(|| b.iter(packed)),
+	);
+	group.bench_function(
+		format!("Poseidon2 BB31 MerkleTreeMmcs log_leaves={SIMD_LOG_LEAVES}"),
+		|b| {
+			pool.install(|| {
+				b.iter_batched(
+					|| matrix.clone(),
+					|matrix| mmcs.commit_matrix(matrix),
+					BatchSize::LargeInput,
+				)
+			})
+		},
+	);
+
+	group.finish()
+}
+
+/// Benchmarks `verify` on a single thread pinned to one core with a small stack, after printing
+/// the peak heap it uses.
+fn bench_pinned_verify(group: &mut BenchmarkGroup<WallTime>, name: &str, verify: impl Fn() + Sync) {
+	let peak = run_pinned(0, CONSTRAINED_STACK_BYTES, || peak_heap_during(&verify));
+	println!("{name}: peak heap during verification = {}", peak);
+
+	group.bench_function(name, |b| {
+		b.iter_custom(|iters| {
+			run_pinned(0, CONSTRAINED_STACK_BYTES, || {
+				let start = Instant::now();
+				for _ in 0..iters {
+					verify();
+				}
+				start.elapsed()
+			})
+		})
+	});
+}
+
+/// Verifies a FRI-sized batch of Merkle paths the way a light client or embedded verifier would:
+/// on one pinned core, with a small stack, and holding only the paths rather than the tree.
+///
+/// Paths are compared per hash function through the plonky3 and plonky2 trees, and per arity
+/// through the reference Poseidon2 builder. A stack overflow aborts the run, so completing at all
+/// shows that each verifier fits the stack budget.
+fn bench_constrained_verification(c: &mut Criterion) {
+	use p3_commit::Mmcs;
+	use p3_matrix::{dense::RowMajorMatrix, Dimensions};
+	use plonky2::hash::{
+		keccak::KeccakHash, merkle_proofs::verify_merkle_proof_to_cap, merkle_tree::MerkleTree,
+		poseidon::PoseidonHash,
+	};
+	use plonky2_field::{goldilocks_field::GoldilocksField, types::Sample};
+
+	const BB_LEAF_SIZE: usize = LEAF_BYTES / size_of::<BabyBear>();
+	const GL_LEAF_SIZE: usize = LEAF_BYTES / size_of::<GoldilocksField>();
+	let n_queries = QUERY_COUNTS[QUERY_COUNTS.len() - 1];
+
+	fn bench_mmcs<M: Mmcs<BabyBear> + Sync>(
+		group: &mut BenchmarkGroup<WallTime>,
+		name: &str,
+		mmcs: &M,
+		indices: &[usize],
+		mut rng: impl Rng,
+	) where
+		M::Commitment: Sync,
+		M::Proof: Sync,
+	{
+		let matrix =
+			RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << OPENING_LOG_LEAVES, BB_LEAF_SIZE);
+		let dimensions = [Dimensions {
+			width: BB_LEAF_SIZE,
+			height: 1 << OPENING_LOG_LEAVES,
+		}];
+		let (commitment, prover_data) = mmcs.commit_matrix(matrix);
+		let openings = indices
+			.iter()
+			.map(|&index| mmcs.open_batch(index, &prover_data))
+			.collect::<Vec<_>>();
+		drop(prover_data);
+
+		bench_pinned_verify(group, &format!("{name} arity=2"), || {
+			for (&index, (values, proof)) in indices.iter().zip(&openings) {
+				mmcs.verify_batch(&commitment, &dimensions, index, values, proof)
+					.unwrap();
+			}
+		});
+	}
+
+	fn bench_plonky2<H: plonky2::plonk::config::Hasher<GoldilocksField>>(
+		group: &mut BenchmarkGroup<WallTime>,
+		name: &str,
+		indices: &[usize],
+	) {
+		let leaves = repeat_with(|| GoldilocksField::rand_vec(GL_LEAF_SIZE))
+			.take(1 << OPENING_LOG_LEAVES)
+			.collect::<Vec<_>>();
+		let tree = MerkleTree::<GoldilocksField, H>::new(leaves, 0);
+		let openings = indices
+			.iter()
+			.map(|&index| (tree.leaves[index].clone(), tree.prove(index)))
+			.collect::<Vec<_>>();
+		let cap = tree.cap.clone();
+		drop(tree);
+
+		bench_pinned_verify(group, &format!("{name} arity=2"), || {
+			for (&index, (leaf, proof)) in indices.iter().zip(&openings) {
+				verify_merkle_proof_to_cap(leaf.clone(), index, &cap, proof).unwrap();
+			}
+		});
+	}
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("merkle constrained verification");
+	group.sample_size(10);
+	group.throughput(Throughput::Elements(n_queries as u64));
+
+	let indices = repeat_with(|| rng.gen_range(0..1 << OPENING_LOG_LEAVES))
+		.take(n_queries)
+		.collect::<Vec<_>>();
+	let poseidon2_mmcs = plonky3_poseidon2_mmcs(&mut rng);
+	bench_mmcs(&mut group, "plonky3 Poseidon2 BB31", &poseidon2_mmcs, &indices, &mut rng);
+	bench_mmcs(&mut group, "plonky3 Keccak-256", &plonky3_keccak_mmcs(), &indices, &mut rng);
+	let name = format!("plonky2 {}", plonky2_poseidon(LEAF_BYTES));
+	bench_plonky2::<PoseidonHash>(&mut group, &name, &indices);
+	bench_plonky2::<KeccakHash<25>>(&mut group, "plonky2 Keccak-256", &indices);
+
+	let perm = Poseidon2Perm::new_from_rng_128(
+		Poseidon2ExternalMatrixGeneral,
+		DiffusionMatrixBabyBear::default(),
+		&mut rng,
+	);
+	let hash = Poseidon2Hash::new(perm.clone());
+	let compress = Poseidon2Compress::new(perm);
+	let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << ARITY_LOG_LEAVES, BB_LEAF_SIZE);
+	let indices = repeat_with(|| rng.gen_range(0..1 << ARITY_LOG_LEAVES))
+		.take(n_queries)
+		.collect::<Vec<_>>();
+	let rows = indices
+		.iter()
+		.map(|&index| matrix.values[index * BB_LEAF_SIZE..(index + 1) * BB_LEAF_SIZE].to_vec())
+		.collect::<Vec<_>>();
+
+	for arity in ARITIES {
+		let layers = build_poseidon2_tree(&matrix.values, BB_LEAF_SIZE, arity, &hash, &compress);
+		let root = layers[layers.len() - 1][0];
+		let paths = indices
+			.iter()
+			.map(|&index| open_poseidon2_path(&layers, index, arity))
+			.collect::<Vec<_>>();
+		drop(layers);
+
+		bench_pinned_verify(&mut group, &format!("reference Poseidon2 BB31 arity={arity}"), || {
+			for ((&index, row), path) in indices.iter().zip(&rows).zip(&paths) {
+				assert!(verify_poseidon2_path(&root, index, row, path, arity, &hash, &compress));
+			}
+		});
+	}
+
+	group.finish()
+}
+
+/// Builds a thread pool with a worker on each CPU of `cpu_node`, allocating from `memory_node`.
+fn numa_pool(cpu_node: &NumaNode, memory_node: usize) -> ThreadPool {
+	let cpu_node_id = cpu_node.id;
+	ThreadPoolBuilder::new()
+		.num_threads(cpu_node.cpus.len())
+		.start_handler(move |_| {
+			pin_to_node(cpu_node_id);
+			set_memory_policy(MemoryPolicy::Bind(memory_node));
+		})
+		.build()
+		.unwrap()
+}
+
+/// Builds Poseidon2 trees on the cores of the first NUMA node, from leaves generated on each node
+/// in turn, so the gap between the node-local run and the others is the cost of remote memory.
+fn bench_numa(c: &mut Criterion) {
+	use p3_matrix::dense::RowMajorMatrix;
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<BabyBear>();
+
+	let nodes = numa_nodes();
+	if nodes.len() < 2 {
+		println!("Skipping NUMA comparison: {} NUMA node(s) found", nodes.len());
+		return;
+	}
+	let cpu_node = &nodes[0];
+
+	let mut rng = thread_rng();
+	let perm = Poseidon2Perm::new_from_rng_128(
+		Poseidon2ExternalMatrixGeneral,
+		DiffusionMatrixBabyBear::default(),
+		&mut rng,
+	);
+	let hash = Poseidon2Hash::new(perm.clone());
+	let compress = Poseidon2Compress::new(perm);
+
+	let mut group = c.benchmark_group("merkle numa");
+	group.sample_size(10);
+	group.throughput(Throughput::Elements(1 << NUMA_LOG_LEAVES));
+
+	for memory_node in nodes.iter().filter(|node| node.memory_bytes > 0) {
+		let pool = numa_pool(cpu_node, memory_node.id);
+		// Pages are placed when first touched, so the leaves are generated on a pool thread.
+		let matrix = pool.install(|| {
+			RowMajorMatrix::<BabyBear>::rand(&mut thread_rng(), 1 << NUMA_LOG_LEAVES, LEAF_SIZE)
+		});
+		let placement = if memory_node.id == cpu_node.id {
+			"local"
+		} else {
+			"remote"
+		};
+		group.bench_function(
+			format!(
+				"Poseidon2 BB31 cpu=node{} memory=node{} ({placement}) log_leaves={NUMA_LOG_LEAVES}",
+				cpu_node.id, memory_node.id
+			),
+			|b| {
+				pool.install(|| {
+					b.iter(|| build_poseidon2_tree(&matrix.values, LEAF_SIZE, 2, &hash, &compress))
+				})
+			},
+		);
+	}
+
+	group.finish()
+}
+
+/// Commits matrices still in cache from their generation, against matrices evicted from every
+/// cache level first. Provers commit witness data generated well before, so the cold figures are
+/// the realistic ones for trees that fit in cache.
+fn bench_cache(c: &mut Criterion) {
+	use p3_commit::Mmcs;
+	use p3_matrix::dense::RowMajorMatrix;
+
+	const LEAF_SIZE: usize = LEAF_BYTES / size_of::<BabyBear>();
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("merkle cache modes");
+	group.sample_size(10);
+
+	let poseidon2_mmcs = plonky3_poseidon2_mmcs(&mut rng);
+	let keccak_mmcs = plonky3_keccak_mmcs();
+
+	for log_n in CACHE_LOG_LEAVES {
+		let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << log_n, LEAF_SIZE);
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		bench_cache_modes(
+			&mut group,
+			&format!("Poseidon2 BB31 log_leaves={log_n}"),
+			|| matrix.clone(),
+			|matrix| poseidon2_mmcs.commit_matrix(matrix),
+		);
+		bench_cache_modes(
+			&mut group,
+			&format!("Keccak-256 log_leaves={log_n}"),
+			|| matrix.clone(),
+			|matrix| keccak_mmcs.commit_matrix(matrix),
+		);
+	}
+
+	group.finish()
+}
+
+criterion_group!(merkle, bench_binius, bench_plonky3, bench_plonky2, bench_stwo);
+criterion_group!(
+	merkle_openings,
+	bench_binius_openings,
+	bench_plonky3_openings,
+	bench_plonky2_openings,
+	bench_stwo_openings
+);
+criterion_group!(merkle_cap_height, bench_plonky2_cap_height, bench_binius_cap_height);
+criterion_group!(merkle_arity, bench_arity);
+criterion_group!(merkle_leaf_size, bench_leaf_size);
+criterion_group!(merkle_same_hash, bench_same_hash);
+criterion_group!(merkle_threads, bench_thread_scaling);
+criterion_group!(merkle_streaming, bench_streaming);
+criterion_group!(merkle_salted, bench_salted);
+criterion_group! {
+	name = merkle_simd;
+	config = Criterion::default().with_measurement(FieldThroughput);
+	targets = bench_simd_leaf_hashing
+}
+criterion_group!(merkle_constrained, bench_constrained_verification);
+criterion_group!(merkle_numa, bench_numa);
+criterion_group!(merkle_cache, bench_cache);
+fn main() {
+	report_bandwidth();
+	merkle();
+	merkle_openings();
+	merkle_cap_height();
+	merkle_arity();
+	merkle_leaf_size();
+	merkle_same_hash();
+	merkle_threads();
+	merkle_streaming();
+	merkle_salted();
+	merkle_simd();
+	merkle_constrained();
+	merkle_numa();
+	merkle_cache();
+	Criterion::default().configure_from_args().final_summary();
+}
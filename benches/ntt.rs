@@ -0,0 +1,634 @@
+//! Benchmarks of the number-theoretic transforms underlying Reed–Solomon encoding.
+//!
+//! Each library's single-column transform is measured at the same sizes so that the encoding
+//! share of commit time can be compared directly, in field elements per second. Inverse
+//! transforms (interpolation) are measured alongside the forward direction, since their cost is
+//! not always symmetric. Single-column throughputs also show the fraction of peak memory bandwidth
+//! reached, measured before the benchmarks start, and the cache mode group compares transforms of
+//! inputs still in cache against inputs evicted from it.
+
+use std::{iter::repeat_with, mem::size_of};
+
+use bytesize::ByteSize;
+use criterion::{criterion_group, BatchSize, Criterion, Throughput};
+use rand::{thread_rng, Rng};
+use ulvt_snark_bench::{
+	bandwidth::report_bandwidth,
+	cache::bench_cache_modes,
+	heap::retained_heap,
+	measurement::{set_element_bytes, FieldThroughput},
+	threads::thread_counts,
+};
+
+const LOG_SIZES: [usize; 6] = [16, 18, 20, 22, 24, 26];
+
+fn bench_plonky2(c: &mut Criterion<FieldThroughput>) {
+	use plonky2_field::{
+		fft::{fft, fft_root_table, fft_with_options, ifft, ifft_with_options},
+		goldilocks_field::GoldilocksField,
+		polynomial::{PolynomialCoeffs, PolynomialValues},
+		types::Sample,
+	};
+
+	let mut group = c.benchmark_group("plonky2 fft");
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		let coeffs = PolynomialCoeffs::new(GoldilocksField::rand_vec(1 << log_n));
+		let values = PolynomialValues::new(GoldilocksField::rand_vec(1 << log_n));
+		let root_table = fft_root_table(1 << log_n);
+
+		set_element_bytes(size_of::<GoldilocksField>() as u64);
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("GL64 log_n={log_n}"), |b| {
+			b.iter_batched(|| coeffs.clone(), fft, BatchSize::LargeInput)
+		});
+		group.bench_function(format!("GL64 precomputed roots log_n={log_n}"), |b| {
+			b.iter_batched(
+				|| coeffs.clone(),
+				|coeffs| fft_with_options(coeffs, None, Some(&root_table)),
+				BatchSize::LargeInput,
+			)
+		});
+		group.bench_function(format!("GL64 inverse log_n={log_n}"), |b| {
+			b.iter_batched(|| values.clone(), ifft, BatchSize::LargeInput)
+		});
+		group.bench_function(format!("GL64 inverse precomputed roots log_n={log_n}"), |b| {
+			b.iter_batched(
+				|| values.clone(),
+				|values| ifft_with_options(values, None, Some(&root_table)),
+				BatchSize::LargeInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+fn bench_plonky3(c: &mut Criterion<FieldThroughput>) {
+	use p3_baby_bear::BabyBear;
+	use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+	use p3_goldilocks::Goldilocks;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("plonky3 dft");
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		let bb_values = repeat_with(|| rng.gen::<BabyBear>())
+			.take(1 << log_n)
+			.collect::<Vec<_>>();
+		let gl_values = repeat_with(|| rng.gen::<Goldilocks>())
+			.take(1 << log_n)
+			.collect::<Vec<_>>();
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		set_element_bytes(size_of::<BabyBear>() as u64);
+		group.bench_function(format!("BB31 Radix2DitParallel log_n={log_n}"), |b| {
+			let dft = Radix2DitParallel::<BabyBear>::default();
+			b.iter_batched(|| bb_values.clone(), |values| dft.dft(values), BatchSize::LargeInput)
+		});
+		set_element_bytes(size_of::<Goldilocks>() as u64);
+		group.bench_function(format!("GL64 Radix2DitParallel log_n={log_n}"), |b| {
+			let dft = Radix2DitParallel::<Goldilocks>::default();
+			b.iter_batched(|| gl_values.clone(), |values| dft.dft(values), BatchSize::LargeInput)
+		});
+		set_element_bytes(size_of::<BabyBear>() as u64);
+		group.bench_function(format!("BB31 Radix2DitParallel inverse log_n={log_n}"), |b| {
+			let dft = Radix2DitParallel::<BabyBear>::default();
+			b.iter_batched(|| bb_values.clone(), |values| dft.idft(values), BatchSize::LargeInput)
+		});
+		set_element_bytes(size_of::<Goldilocks>() as u64);
+		group.bench_function(format!("GL64 Radix2DitParallel inverse log_n={log_n}"), |b| {
+			let dft = Radix2DitParallel::<Goldilocks>::default();
+			b.iter_batched(|| gl_values.clone(), |values| dft.idft(values), BatchSize::LargeInput)
+		});
+	}
+
+	group.finish()
+}
+
+#[cfg(feature = "stwo")]
+fn bench_stwo(c: &mut Criterion<FieldThroughput>) {
+	use ark_std::UniformRand;
+	use stwo::core::{
+		backend::{simd::SimdBackend, Col},
+		fields::m31::BaseField,
+		poly::{
+			circle::{CanonicCoset, CircleEvaluation, CirclePoly, PolyOps},
+			BitReversedOrder,
+		},
+	};
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("stwo circle fft");
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		let domain = CanonicCoset::new(log_n as u32).circle_domain();
+		let twiddles = SimdBackend::precompute_twiddles(domain.half_coset);
+		let coeffs = repeat_with(|| BaseField::rand(&mut rng))
+			.take(1 << log_n)
+			.collect::<Col<SimdBackend, BaseField>>();
+		let poly = CirclePoly::<SimdBackend>::new(coeffs);
+		let evals = repeat_with(|| BaseField::rand(&mut rng))
+			.take(1 << log_n)
+			.collect::<Col<SimdBackend, BaseField>>();
+		let eval = CircleEvaluation::<SimdBackend, BaseField, BitReversedOrder>::new(domain, evals);
+
+		set_element_bytes(size_of::<BaseField>() as u64);
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("M31 SimdBackend log_n={log_n}"), |b| {
+			b.iter(|| poly.evaluate_with_twiddles(domain, &twiddles))
+		});
+		group.bench_function(format!("M31 SimdBackend inverse log_n={log_n}"), |b| {
+			b.iter_batched(
+				|| eval.clone(),
+				|eval| eval.interpolate_with_twiddles(&twiddles),
+				BatchSize::LargeInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+#[cfg(not(feature = "stwo"))]
+fn bench_stwo(_c: &mut Criterion<FieldThroughput>) {}
+
+fn bench_binius(c: &mut Criterion<FieldThroughput>) {
+	use binius_field::{
+		arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField32b, PackedField,
+	};
+	use binius_ntt::{AdditiveNTT, SingleThreadedNTT};
+
+	type P = PackedType<OptimalUnderlier, BinaryField32b>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("binius additive ntt");
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		let ntt = SingleThreadedNTT::<BinaryField32b>::new(log_n)
+			.unwrap()
+			.precompute_twiddles()
+			.multithreaded();
+		let mut data = repeat_with(|| P::random(&mut rng))
+			.take(1 << (log_n - P::LOG_WIDTH))
+			.collect::<Vec<_>>();
+
+		// The additive NTT works in place, and transforming random data again is as good as
+		// transforming fresh random data.
+		set_element_bytes(size_of::<BinaryField32b>() as u64);
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("Tower 32b log_n={log_n}"), |b| {
+			b.iter(|| ntt.forward_transform(&mut data, 0, 0).unwrap())
+		});
+		group.bench_function(format!("Tower 32b inverse log_n={log_n}"), |b| {
+			b.iter(|| ntt.inverse_transform(&mut data, 0, 0).unwrap())
+		});
+	}
+
+	group.finish()
+}
+
+fn bench_arkworks(c: &mut Criterion<FieldThroughput>) {
+	use ark_bn254::Fr;
+	use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+	use ark_std::UniformRand;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("arkworks fft");
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		let domain = Radix2EvaluationDomain::<Fr>::new(1 << log_n).unwrap();
+		let mut values = repeat_with(|| Fr::rand(&mut rng))
+			.take(1 << log_n)
+			.collect::<Vec<_>>();
+
+		set_element_bytes(size_of::<Fr>() as u64);
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("BN254 Fr radix-2 log_n={log_n}"), |b| {
+			b.iter(|| domain.fft_in_place(&mut values))
+		});
+		group.bench_function(format!("BN254 Fr radix-2 inverse log_n={log_n}"), |b| {
+			b.iter(|| domain.ifft_in_place(&mut values))
+		});
+	}
+
+	group.finish()
+}
+
+/// Number of rows in the batched transform benchmarks.
+const BATCH_LOG_ROWS: usize = 20;
+const BATCH_LOG_COLS: [usize; 4] = [0, 4, 6, 8];
+
+fn bench_plonky2_batch(c: &mut Criterion) {
+	use plonky2_field::{
+		fft::{fft_root_table, fft_with_options},
+		goldilocks_field::GoldilocksField,
+		polynomial::PolynomialCoeffs,
+		types::Sample,
+	};
+	use rayon::prelude::*;
+
+	let mut group = c.benchmark_group("plonky2 batch fft");
+	group.sample_size(10);
+
+	let root_table = fft_root_table(1 << BATCH_LOG_ROWS);
+	for log_cols in BATCH_LOG_COLS {
+		let columns =
+			repeat_with(|| PolynomialCoeffs::new(GoldilocksField::rand_vec(1 << BATCH_LOG_ROWS)))
+				.take(1 << log_cols)
+				.collect::<Vec<_>>();
+
+		// plonky2 has no batched transform; PolynomialBatch transforms each polynomial
+		// independently in parallel, which is what this measures.
+		group.throughput(Throughput::Elements(1 << (BATCH_LOG_ROWS + log_cols)));
+		group.bench_function(
+			format!("GL64 log_rows={BATCH_LOG_ROWS} cols={}", 1 << log_cols),
+			|b| {
+				b.iter_batched(
+					|| columns.clone(),
+					|columns| {
+						columns
+							.into_par_iter()
+							.map(|coeffs| fft_with_options(coeffs, None, Some(&root_table)))
+							.collect::<Vec<_>>()
+					},
+					BatchSize::LargeInput,
+				)
+			},
+		);
+	}
+
+	group.finish()
+}
+
+fn bench_plonky3_batch(c: &mut Criterion) {
+	use p3_baby_bear::BabyBear;
+	use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+	use p3_matrix::dense::RowMajorMatrix;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("plonky3 batch dft");
+	group.sample_size(10);
+
+	let dft = Radix2DitParallel::<BabyBear>::default();
+	for log_cols in BATCH_LOG_COLS {
+		let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, 1 << BATCH_LOG_ROWS, 1 << log_cols);
+
+		group.throughput(Throughput::Elements(1 << (BATCH_LOG_ROWS + log_cols)));
+		group.bench_function(
+			format!("BB31 log_rows={BATCH_LOG_ROWS} cols={}", 1 << log_cols),
+			|b| {
+				b.iter_batched(
+					|| matrix.clone(),
+					|matrix| dft.dft_batch(matrix),
+					BatchSize::LargeInput,
+				)
+			},
+		);
+	}
+
+	group.finish()
+}
+
+fn bench_binius_batch(c: &mut Criterion) {
+	use binius_field::{
+		arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField32b, PackedField,
+	};
+	use binius_ntt::{AdditiveNTT, SingleThreadedNTT};
+
+	type P = PackedType<OptimalUnderlier, BinaryField32b>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("binius batch additive ntt");
+	group.sample_size(10);
+
+	let ntt = SingleThreadedNTT::<BinaryField32b>::new(BATCH_LOG_ROWS)
+		.unwrap()
+		.precompute_twiddles()
+		.multithreaded();
+	for log_cols in BATCH_LOG_COLS {
+		// The additive NTT transforms interleaved batches natively, with the batch index in the
+		// low bits of the element index.
+		let mut data = repeat_with(|| P::random(&mut rng))
+			.take(1 << (BATCH_LOG_ROWS + log_cols - P::LOG_WIDTH))
+			.collect::<Vec<_>>();
+
+		group.throughput(Throughput::Elements(1 << (BATCH_LOG_ROWS + log_cols)));
+		group.bench_function(
+			format!("Tower 32b log_rows={BATCH_LOG_ROWS} cols={}", 1 << log_cols),
+			|b| b.iter(|| ntt.forward_transform(&mut data, 0, log_cols).unwrap()),
+		);
+	}
+
+	group.finish()
+}
+
+/// Head-to-head comparison of stwo's circle FFT over M31 and plonky3's two-adic radix-2 DIT over
+/// BabyBear.
+///
+/// Both fields have 4-byte elements, so equal element counts are equal data sizes and throughput
+/// is reported in bytes. stwo leaves its output in bit-reversed order, whereas plonky3 returns
+/// natural order, so the plonky3 numbers include a bit-reversal permutation. The circle FFT side
+/// only runs when the `stwo` feature is enabled.
+fn bench_circle_vs_two_adic(c: &mut Criterion) {
+	use p3_baby_bear::BabyBear;
+	use p3_dft::{Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("circle vs two-adic fft");
+	group.sample_size(10);
+
+	for log_n in [16, 18, 20, 22, 24] {
+		let bb_values = repeat_with(|| rng.gen::<BabyBear>())
+			.take(1 << log_n)
+			.collect::<Vec<_>>();
+
+		group.throughput(Throughput::Bytes((size_of::<BabyBear>() << log_n) as u64));
+		group.bench_function(format!("BB31 Radix2Dit log_n={log_n}"), |b| {
+			let dft = Radix2Dit::<BabyBear>::default();
+			b.iter_batched(|| bb_values.clone(), |values| dft.dft(values), BatchSize::LargeInput)
+		});
+		group.bench_function(format!("BB31 Radix2DitParallel log_n={log_n}"), |b| {
+			let dft = Radix2DitParallel::<BabyBear>::default();
+			b.iter_batched(|| bb_values.clone(), |values| dft.dft(values), BatchSize::LargeInput)
+		});
+
+		#[cfg(feature = "stwo")]
+		{
+			use ark_std::UniformRand;
+			use stwo::core::{
+				backend::{simd::SimdBackend, Col},
+				fields::m31::BaseField,
+				poly::circle::{CanonicCoset, CirclePoly, PolyOps},
+			};
+
+			let domain = CanonicCoset::new(log_n as u32).circle_domain();
+			let twiddles = SimdBackend::precompute_twiddles(domain.half_coset);
+			let coeffs = repeat_with(|| BaseField::rand(&mut rng))
+				.take(1 << log_n)
+				.collect::<Col<SimdBackend, BaseField>>();
+			let poly = CirclePoly::<SimdBackend>::new(coeffs);
+
+			group.bench_function(format!("M31 circle SimdBackend log_n={log_n}"), |b| {
+				b.iter(|| poly.evaluate_with_twiddles(domain, &twiddles))
+			});
+		}
+	}
+
+	group.finish()
+}
+
+/// One-time twiddle and root-table precomputation, reported separately from the transforms that
+/// use them. The size of each table is printed before its benchmark runs, which for binius, whose
+/// tables are private, is measured as retained heap with the `alloc_count` feature.
+fn bench_precompute(c: &mut Criterion) {
+	use binius_field::BinaryField32b;
+	use binius_ntt::SingleThreadedNTT;
+	use plonky2_field::{fft::fft_root_table, goldilocks_field::GoldilocksField};
+
+	let mut group = c.benchmark_group("twiddle precomputation");
+	group.sample_size(10);
+
+	for log_n in LOG_SIZES {
+		group.throughput(Throughput::Elements(1 << log_n));
+		let size = fft_root_table::<GoldilocksField>(1 << log_n)
+			.iter()
+			.map(|roots| roots.len() * size_of::<GoldilocksField>())
+			.sum::<usize>();
+		println!("plonky2 GL64 root table log_n={log_n}: {}", ByteSize(size as u64));
+		group.bench_function(format!("plonky2 GL64 fft_root_table log_n={log_n}"), |b| {
+			b.iter(|| fft_root_table::<GoldilocksField>(1 << log_n))
+		});
+		let (_, size) = retained_heap(|| {
+			SingleThreadedNTT::<BinaryField32b>::new(log_n)
+				.unwrap()
+				.precompute_twiddles()
+		});
+		println!("binius Tower 32b twiddles log_n={log_n}: {size}");
+		group.bench_function(format!("binius Tower 32b precompute_twiddles log_n={log_n}"), |b| {
+			b.iter(|| {
+				SingleThreadedNTT::<BinaryField32b>::new(log_n)
+					.unwrap()
+					.precompute_twiddles()
+			})
+		});
+
+		#[cfg(feature = "stwo")]
+		{
+			use stwo::core::{
+				backend::simd::SimdBackend,
+				poly::circle::{CanonicCoset, PolyOps},
+			};
+
+			let half_coset = CanonicCoset::new(log_n as u32).circle_domain().half_coset;
+			let twiddles = SimdBackend::precompute_twiddles(half_coset);
+			let size = (twiddles.twiddles.len() + twiddles.itwiddles.len())
+				* std::mem::size_of_val(&twiddles.twiddles[0]);
+			println!("stwo M31 twiddles log_n={log_n}: {}", ByteSize(size as u64));
+			group.bench_function(format!("stwo M31 precompute_twiddles log_n={log_n}"), |b| {
+				b.iter(|| SimdBackend::precompute_twiddles(half_coset))
+			});
+		}
+	}
+
+	group.finish()
+}
+
+/// Transforms over the extension fields in which FRI's later fold layers operate, next to the same
+/// transforms over their base fields.
+fn bench_extension(c: &mut Criterion) {
+	use p3_baby_bear::BabyBear;
+	use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+	use p3_field::extension::BinomialExtensionField;
+	use p3_fri::fold_even_odd;
+	use plonky2_field::{
+		extension::quadratic::QuadraticExtension, fft::fft, goldilocks_field::GoldilocksField,
+		polynomial::PolynomialCoeffs, types::Sample,
+	};
+
+	type BB31x4 = BinomialExtensionField<BabyBear, 4>;
+	type GL64x2 = QuadraticExtension<GoldilocksField>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("extension field ntt");
+	group.sample_size(10);
+
+	for log_n in [16, 20, 22] {
+		let bb_values = repeat_with(|| rng.gen::<BabyBear>())
+			.take(1 << log_n)
+			.collect::<Vec<_>>();
+		let bb4_values = repeat_with(|| rng.gen::<BB31x4>())
+			.take(1 << log_n)
+			.collect::<Vec<_>>();
+		let gl_coeffs = PolynomialCoeffs::new(GoldilocksField::rand_vec(1 << log_n));
+		let gl2_coeffs = PolynomialCoeffs::new(GL64x2::rand_vec(1 << log_n));
+		let beta = rng.gen::<BB31x4>();
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		group.bench_function(format!("plonky3 BB31 log_n={log_n}"), |b| {
+			let dft = Radix2DitParallel::<BabyBear>::default();
+			b.iter_batched(|| bb_values.clone(), |values| dft.dft(values), BatchSize::LargeInput)
+		});
+		group.bench_function(format!("plonky3 BB31^4 log_n={log_n}"), |b| {
+			let dft = Radix2DitParallel::<BB31x4>::default();
+			b.iter_batched(|| bb4_values.clone(), |values| dft.dft(values), BatchSize::LargeInput)
+		});
+		group.bench_function(format!("plonky3 BB31^4 fold_even_odd log_n={log_n}"), |b| {
+			b.iter_batched(
+				|| bb4_values.clone(),
+				|values| fold_even_odd(values, beta),
+				BatchSize::LargeInput,
+			)
+		});
+		group.bench_function(format!("plonky2 GL64 log_n={log_n}"), |b| {
+			b.iter_batched(|| gl_coeffs.clone(), fft, BatchSize::LargeInput)
+		});
+		group.bench_function(format!("plonky2 GL64^2 log_n={log_n}"), |b| {
+			b.iter_batched(|| gl2_coeffs.clone(), fft, BatchSize::LargeInput)
+		});
+	}
+
+	group.finish()
+}
+
+/// Sweeps the rayon pool size for the parallel transforms.
+///
+/// Benchmark IDs are parameterized by thread count, so Criterion's summary plots show one speedup
+/// curve per size. Small sizes are included to locate the threshold below which adding threads
+/// stops helping.
+fn bench_thread_scaling(c: &mut Criterion) {
+	use binius_field::{
+		arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField32b, PackedField,
+	};
+	use binius_ntt::{AdditiveNTT, SingleThreadedNTT};
+	use criterion::BenchmarkId;
+	use p3_baby_bear::BabyBear;
+	use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+	use rayon::ThreadPoolBuilder;
+
+	type P = PackedType<OptimalUnderlier, BinaryField32b>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("ntt thread scaling");
+	group.sample_size(10);
+
+	for log_n in [12, 16, 20, 24] {
+		let bb_values = repeat_with(|| rng.gen::<BabyBear>())
+			.take(1 << log_n)
+			.collect::<Vec<_>>();
+		let mut binius_data = repeat_with(|| P::random(&mut rng))
+			.take(1 << (log_n - P::LOG_WIDTH))
+			.collect::<Vec<_>>();
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		for n_threads in thread_counts() {
+			let pool = ThreadPoolBuilder::new()
+				.num_threads(n_threads)
+				.build()
+				.unwrap();
+
+			group.bench_with_input(
+				BenchmarkId::new(
+					format!("plonky3 BB31 Radix2DitParallel log_n={log_n}"),
+					n_threads,
+				),
+				&n_threads,
+				|b, _| {
+					let dft = Radix2DitParallel::<BabyBear>::default();
+					pool.install(|| {
+						b.iter_batched(
+							|| bb_values.clone(),
+							|values| dft.dft(values),
+							BatchSize::LargeInput,
+						)
+					})
+				},
+			);
+			group.bench_with_input(
+				BenchmarkId::new(format!("binius Tower 32b log_n={log_n}"), n_threads),
+				&n_threads,
+				|b, _| {
+					pool.install(|| {
+						// The multithreaded NTT sizes its work split from the current pool.
+						let ntt = SingleThreadedNTT::<BinaryField32b>::new(log_n)
+							.unwrap()
+							.precompute_twiddles()
+							.multithreaded();
+						b.iter(|| ntt.forward_transform(&mut binius_data, 0, 0).unwrap())
+					})
+				},
+			);
+		}
+	}
+
+	group.finish()
+}
+
+/// Sizes for the cache mode comparison, from fitting in L2 to exceeding the last-level cache.
+const CACHE_LOG_SIZES: [usize; 3] = [16, 20, 24];
+
+/// Transforms of inputs that are still in cache from their generation, against inputs evicted
+/// from every cache level first, as when committing witness data generated earlier.
+fn bench_cache(c: &mut Criterion) {
+	use p3_baby_bear::BabyBear;
+	use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+	use plonky2_field::{
+		fft::fft, goldilocks_field::GoldilocksField, polynomial::PolynomialCoeffs, types::Sample,
+	};
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("ntt cache modes");
+	group.sample_size(10);
+
+	for log_n in CACHE_LOG_SIZES {
+		let bb_values = repeat_with(|| rng.gen::<BabyBear>())
+			.take(1 << log_n)
+			.collect::<Vec<_>>();
+		let gl_coeffs = PolynomialCoeffs::new(GoldilocksField::rand_vec(1 << log_n));
+		let dft = Radix2DitParallel::<BabyBear>::default();
+
+		group.throughput(Throughput::Elements(1 << log_n));
+		bench_cache_modes(
+			&mut group,
+			&format!("plonky3 BB31 Radix2DitParallel log_n={log_n}"),
+			|| bb_values.clone(),
+			|values| dft.dft(values),
+		);
+		bench_cache_modes(
+			&mut group,
+			&format!("plonky2 GL64 log_n={log_n}"),
+			|| gl_coeffs.clone(),
+			fft,
+		);
+	}
+
+	group.finish()
+}
+
+criterion_group! {
+	name = ntt;
+	config = Criterion::default().with_measurement(FieldThroughput);
+	targets = bench_plonky2, bench_plonky3, bench_stwo, bench_binius, bench_arkworks
+}
+criterion_group!(ntt_batch, bench_plonky2_batch, bench_plonky3_batch, bench_binius_batch);
+criterion_group!(circle_vs_two_adic, bench_circle_vs_two_adic);
+criterion_group!(precompute, bench_precompute);
+criterion_group!(ntt_extension, bench_extension);
+criterion_group!(ntt_threads, bench_thread_scaling);
+criterion_group!(ntt_cache, bench_cache);
+fn main() {
+	report_bandwidth();
+	ntt();
+	ntt_batch();
+	circle_vs_two_adic();
+	precompute();
+	ntt_extension();
+	ntt_threads();
+	ntt_cache();
+	Criterion::default().configure_from_args().final_summary();
+}
@@ -0,0 +1,739 @@
+//! Benchmarks of the commit, prove, and verify phases of the polynomial commitment schemes.
+//!
+//! The PCS examples time each phase once per run, so a single noisy measurement stands for every
+//! configuration. Here the same configurations, at the default security target and at smaller
+//! sizes, run each phase repeatedly on fixed data so that criterion reports confidence intervals.
+//! Every system has one group, with a benchmark per configuration, phase, and size, and throughput
+//! in committed elements per second. Proving takes seconds at the larger sizes, so commit and prove
+//! take the minimum of 10 samples there, while verification keeps the default sample count.
+//! Inputs that a phase consumes are rebuilt outside the timed routine.
+
+use std::iter::repeat_with;
+
+use binius_core::{
+	fiat_shamir::HasherChallenger,
+	merkle_tree::{BinaryMerkleTreeProver, MerkleTreeProver},
+	oracle::MultilinearOracleSet,
+	piop,
+	protocols::{evalcheck::EvalcheckMultilinearClaim, fri::CommitOutput},
+	ring_switch,
+	ring_switch::{EvalClaimSystem, ReducedClaim, ReducedWitness},
+	tower::{AESTowerFamily, PackedTop, TowerFamily, TowerUnderlier},
+	transcript::{
+		AdviceReader, AdviceWriter, CanRead, CanWrite, Proof, TranscriptReader, TranscriptWriter,
+	},
+};
+use binius_field::{
+	arch::OptimalUnderlier,
+	as_packed_field::{PackScalar, PackedType},
+	underlier::UnderlierType,
+	AESTowerField32b, AESTowerField8b, BinaryField1b, ExtensionField, Field, PackedExtension,
+	PackedField, PackedFieldIndexable, TowerField,
+};
+use binius_hal::ComputationBackendExt;
+use binius_hash::{Groestl256, GroestlDigest, HashDigest, HasherDigest};
+use binius_math::{DefaultEvaluationDomainFactory, MultilinearExtension};
+use criterion::{
+	criterion_group, criterion_main, measurement::WallTime, BatchSize, BenchmarkGroup, Criterion,
+	Throughput,
+};
+use p3_challenger::{CanObserve, FieldChallenger};
+use p3_commit::{Pcs, PolynomialSpace};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_symmetric::{CompressionFunction, PseudoCompressionFunction};
+use plonky2::{
+	field::{goldilocks_field::GoldilocksField, polynomial::PolynomialValues, types::Sample},
+	plonk::config::GenericConfig,
+};
+use rand::{
+	distributions::{Distribution, Standard},
+	thread_rng, Rng,
+};
+use ulvt_snark_bench::security::SecurityTarget;
+
+/// Log2 of the number of elements committed by every configuration.
+const LOG_SIZES: [usize; 2] = [16, 20];
+
+/// Log2 of the size from which commit and prove take the minimum number of samples.
+const LONG_LOG_SIZE: usize = 20;
+
+/// Samples of verification, which takes milliseconds at every size.
+const VERIFY_SAMPLES: usize = 100;
+
+/// The samples to take of the commit and prove phases of `2^n_vars` elements.
+fn prover_samples(n_vars: usize) -> usize {
+	if n_vars >= LONG_LOG_SIZE {
+		10
+	} else {
+		100
+	}
+}
+
+type GroestlDigestAES = GroestlDigest<AESTowerField8b>;
+type GroestlHasher<P> = Groestl256<P, AESTowerField8b>;
+
+#[derive(Debug, Default, Clone)]
+struct GroestlDigestCompression;
+
+impl PseudoCompressionFunction<GroestlDigestAES, 2> for GroestlDigestCompression {
+	fn compress(&self, input: [GroestlDigestAES; 2]) -> GroestlDigestAES {
+		HasherDigest::<GroestlDigestAES, GroestlHasher<GroestlDigestAES>>::hash(&input[..])
+	}
+}
+
+impl CompressionFunction<GroestlDigestAES, 2> for GroestlDigestCompression {}
+
+type FExt<Tower> = <Tower as TowerFamily>::B128;
+type FDomain<Tower> = <Tower as TowerFamily>::B8;
+type FEncode<Tower> = <Tower as TowerFamily>::B32;
+
+/// Log2 inverse code rate of the binius configurations, as in `fri_binius_pcs`.
+const BINIUS_LOG_INV_RATE: usize = 2;
+
+fn bench_binius_field<U, Tower, F>(group: &mut BenchmarkGroup<WallTime>, field: &str, n_vars: usize)
+where
+	U: UnderlierType + TowerUnderlier<Tower> + PackScalar<F> + PackScalar<AESTowerField8b>,
+	Tower: TowerFamily,
+	F: TowerField,
+	FExt<Tower>: PackedTop<Tower>
+		+ ExtensionField<F>
+		+ ExtensionField<AESTowerField8b>
+		+ PackedExtension<F>
+		+ PackedExtension<AESTowerField8b, PackedSubfield: PackedFieldIndexable>,
+	PackedType<U, FExt<Tower>>: PackedFieldIndexable,
+{
+	let mut rng = thread_rng();
+	let backend = binius_hal::make_portable_backend();
+
+	let multilin = MultilinearExtension::from_values(
+		repeat_with(|| <PackedType<U, F>>::random(&mut rng))
+			.take(1 << (n_vars - <PackedType<U, F>>::LOG_WIDTH))
+			.collect(),
+	)
+	.unwrap();
+	let eval_point = repeat_with(|| <FExt<Tower> as Field>::random(&mut rng))
+		.take(n_vars)
+		.collect::<Vec<_>>();
+	let eval_query = backend
+		.multilinear_query::<PackedType<U, FExt<Tower>>>(&eval_point)
+		.unwrap();
+	let eval = multilin.evaluate(&eval_query).unwrap();
+
+	let mut oracles = MultilinearOracleSet::new();
+	let oracle_id = oracles.add_committed(n_vars, F::TOWER_LEVEL);
+	let merkle_prover =
+		BinaryMerkleTreeProver::<_, GroestlHasher<_>, _>::new(GroestlDigestCompression::default());
+	let merkle_scheme = merkle_prover.scheme();
+	let (commit_meta, oracle_to_commit_index) = piop::make_oracle_commit_meta(&oracles).unwrap();
+	let fri_params = piop::make_commit_params_with_optimal_arity::<_, FEncode<Tower>, _>(
+		&commit_meta,
+		merkle_scheme,
+		SecurityTarget::default().binius_security_bits(BINIUS_LOG_INV_RATE),
+		BINIUS_LOG_INV_RATE,
+	)
+	.unwrap();
+	let committed_multilins = [multilin.specialize_arc_dyn::<PackedType<U, FExt<Tower>>>()];
+
+	group.sample_size(prover_samples(n_vars));
+	group.bench_function(format!("{field} commit n_vars={n_vars}"), |b| {
+		b.iter(|| piop::commit(&fri_params, &merkle_prover, &committed_multilins).unwrap())
+	});
+
+	let eval_claims = [EvalcheckMultilinearClaim {
+		poly: oracles.oracle(oracle_id),
+		eval_point: eval_point.into(),
+		eval,
+	}];
+	let system = EvalClaimSystem::new(&commit_meta, oracle_to_commit_index, &eval_claims).unwrap();
+	let domain_factory = DefaultEvaluationDomainFactory::<FDomain<Tower>>::default();
+
+	// Proving consumes the committed tree, so every iteration commits anew outside the routine.
+	let commit = || {
+		let CommitOutput {
+			commitment,
+			committed,
+			codeword,
+		} = piop::commit(&fri_params, &merkle_prover, &committed_multilins).unwrap();
+		let mut proof = Proof {
+			transcript: TranscriptWriter::<HasherChallenger<groestl::Groestl256>>::default(),
+			advice: AdviceWriter::default(),
+		};
+		proof.transcript.write_packed(commitment);
+		(committed, codeword, proof)
+	};
+	let prove = |(committed, codeword, mut proof)| {
+		let ReducedWitness {
+			transparents: transparent_multilins,
+			sumcheck_claims,
+		} = ring_switch::prove::<_, _, _, Tower, _, _, _>(
+			&system,
+			&committed_multilins,
+			&mut proof,
+			&backend,
+		)
+		.unwrap();
+		piop::prove(
+			&fri_params,
+			&merkle_prover,
+			domain_factory.clone(),
+			&commit_meta,
+			committed,
+			&codeword,
+			&committed_multilins,
+			&transparent_multilins,
+			&sumcheck_claims,
+			&mut proof,
+			&backend,
+		)
+		.unwrap();
+		proof
+	};
+	group.bench_function(format!("{field} prove n_vars={n_vars}"), |b| {
+		b.iter_batched(&commit, &prove, BatchSize::LargeInput)
+	});
+
+	let proof = prove(commit());
+	let transcript = proof.transcript.finalize();
+	let advice = proof.advice.finalize();
+
+	group.sample_size(VERIFY_SAMPLES);
+	group.bench_function(format!("{field} verify n_vars={n_vars}"), |b| {
+		b.iter_batched(
+			|| {
+				let mut proof = Proof {
+					transcript: TranscriptReader::<HasherChallenger<groestl::Groestl256>>::new(
+						transcript.clone(),
+					),
+					advice: AdviceReader::new(advice.clone()),
+				};
+				let commitment = proof.transcript.read_packed().unwrap();
+				(commitment, proof)
+			},
+			|(commitment, mut proof)| {
+				let ReducedClaim {
+					transparents,
+					sumcheck_claims,
+				} = ring_switch::verify::<_, Tower, _, _>(&system, &mut proof).unwrap();
+				piop::verify(
+					&commit_meta,
+					merkle_scheme,
+					&fri_params,
+					&commitment,
+					&transparents,
+					&sumcheck_claims,
+					&mut proof,
+				)
+				.unwrap();
+				proof.transcript.finalize().unwrap();
+			},
+			BatchSize::SmallInput,
+		)
+	});
+}
+
+fn bench_binius(c: &mut Criterion) {
+	let mut group = c.benchmark_group("binius pcs");
+
+	for n_vars in LOG_SIZES {
+		group.throughput(Throughput::Elements(1 << n_vars));
+		bench_binius_field::<OptimalUnderlier, AESTowerFamily, BinaryField1b>(
+			&mut group,
+			"BinaryField1b",
+			n_vars,
+		);
+		bench_binius_field::<OptimalUnderlier, AESTowerFamily, AESTowerField8b>(
+			&mut group,
+			"AESTowerField8b",
+			n_vars,
+		);
+		bench_binius_field::<OptimalUnderlier, AESTowerFamily, AESTowerField32b>(
+			&mut group,
+			"AESTowerField32b",
+			n_vars,
+		);
+	}
+
+	group.finish()
+}
+
+/// Bit widths of the values committed with Hyrax.
+const JOLT_N_BITS: [usize; 2] = [8, 64];
+
+fn bench_jolt(c: &mut Criterion) {
+	use ark_bn254::{Fr, G1Projective};
+	use ark_std::UniformRand;
+	use jolt_core::{
+		poly::{
+			commitment::{
+				commitment_scheme::{BatchType, CommitShape, CommitmentScheme},
+				hyrax::HyraxScheme,
+			},
+			dense_mlpoly::DensePolynomial,
+		},
+		utils::transcript::ProofTranscript,
+	};
+
+	type PCS = HyraxScheme<G1Projective>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("jolt hyrax pcs");
+
+	for n_vars in LOG_SIZES {
+		let pcs_setup = PCS::setup(&[CommitShape::new(1 << n_vars, BatchType::Small)]);
+
+		group.throughput(Throughput::Elements(1 << n_vars));
+		for n_bits in JOLT_N_BITS {
+			let poly = DensePolynomial::new(
+				repeat_with(|| Fr::from(rng.gen_range(0..(1u128 << n_bits))))
+					.take(1 << n_vars)
+					.collect(),
+			);
+			let r = repeat_with(|| Fr::rand(&mut rng))
+				.take(n_vars)
+				.collect::<Vec<_>>();
+			let eval = poly.evaluate(&r);
+
+			group.sample_size(prover_samples(n_vars));
+			group.bench_function(format!("n_bits={n_bits} commit n_vars={n_vars}"), |b| {
+				b.iter(|| PCS::commit(&poly, &pcs_setup))
+			});
+			group.bench_function(format!("n_bits={n_bits} prove n_vars={n_vars}"), |b| {
+				b.iter(|| PCS::prove(&pcs_setup, &poly, &r, &mut ProofTranscript::new(b"example")))
+			});
+
+			let commitment = PCS::commit(&poly, &pcs_setup);
+			let proof = PCS::prove(&pcs_setup, &poly, &r, &mut ProofTranscript::new(b"example"));
+			group.sample_size(VERIFY_SAMPLES);
+			group.bench_function(format!("n_bits={n_bits} verify n_vars={n_vars}"), |b| {
+				b.iter(|| {
+					let mut transcript = ProofTranscript::new(b"example");
+					let result =
+						PCS::verify(&proof, &pcs_setup, &mut transcript, &r, &eval, &commitment);
+					assert!(result.is_ok());
+				})
+			});
+		}
+	}
+
+	group.finish()
+}
+
+/// Log2 of the number of columns of the plonky3 and stwo configurations, as in their examples.
+const LOG_BATCH_SIZE: usize = 4;
+
+/// Log2 inverse code rate of the plonky3 configurations, as in `plonky3_fri_pcs`.
+const PLONKY3_LOG_INV_RATE: usize = 2;
+
+fn bench_plonky3_config<Val, Challenge, Challenger, P>(
+	group: &mut BenchmarkGroup<WallTime>,
+	config: &str,
+	pcs: P,
+	mut challenger: Challenger,
+	n_vars: usize,
+) where
+	P: Pcs<Challenge, Challenger>,
+	P::Domain: PolynomialSpace<Val = Val>,
+	Val: p3_field::Field,
+	Standard: Distribution<Val>,
+	Challenge: p3_field::ExtensionField<Val>,
+	Challenger: Clone + CanObserve<P::Commitment> + FieldChallenger<Val>,
+{
+	let mut rng = thread_rng();
+	let degree = 1 << (n_vars - LOG_BATCH_SIZE);
+	let domain = pcs.natural_domain_for_degree(degree);
+	let matrix = RowMajorMatrix::<Val>::rand(&mut rng, degree, 1 << LOG_BATCH_SIZE);
+
+	group.sample_size(prover_samples(n_vars));
+	group.bench_function(format!("{config} commit n_vars={n_vars}"), |b| {
+		b.iter_batched(
+			|| matrix.clone(),
+			|matrix| pcs.commit(vec![(domain, matrix)]),
+			BatchSize::LargeInput,
+		)
+	});
+
+	let (commitment, committed) = pcs.commit(vec![(domain, matrix)]);
+	challenger.observe(commitment.clone());
+	let zeta: Challenge = challenger.sample_ext_element();
+
+	// The prover and the verifier both start from the challenger that sampled zeta.
+	group.bench_function(format!("{config} prove n_vars={n_vars}"), |b| {
+		b.iter_batched(
+			|| challenger.clone(),
+			|mut challenger| pcs.open(vec![(&committed, vec![vec![zeta]])], &mut challenger),
+			BatchSize::SmallInput,
+		)
+	});
+
+	let (opening_by_round, proof) =
+		pcs.open(vec![(&committed, vec![vec![zeta]])], &mut challenger.clone());
+	let point_openings = opening_by_round[0][0][0].clone();
+
+	group.sample_size(VERIFY_SAMPLES);
+	group.bench_function(format!("{config} verify n_vars={n_vars}"), |b| {
+		b.iter_batched(
+			|| {
+				let rounds = vec![(
+					commitment.clone(),
+					vec![(domain, vec![(zeta, point_openings.clone())])],
+				)];
+				(rounds, challenger.clone())
+			},
+			|(rounds, mut challenger)| pcs.verify(rounds, &proof, &mut challenger).unwrap(),
+			BatchSize::SmallInput,
+		)
+	});
+}
+
+fn bench_plonky3(c: &mut Criterion) {
+	use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+	use p3_challenger::{DuplexChallenger, HashChallenger, SerializingChallenger32};
+	use p3_commit::ExtensionMmcs;
+	use p3_dft::Radix2DitParallel;
+	use p3_field::{extension::BinomialExtensionField, Field};
+	use p3_fri::{FriConfig, TwoAdicFriPcs};
+	use p3_keccak::Keccak256Hash;
+	use p3_merkle_tree::MerkleTreeMmcs;
+	use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+	use p3_symmetric::{
+		CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher32, TruncatedPermutation,
+	};
+
+	type Val = BabyBear;
+	type Challenge = BinomialExtensionField<Val, 4>;
+	type Dft = Radix2DitParallel<Val>;
+
+	type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+	type Poseidon2Mmcs = MerkleTreeMmcs<
+		<Val as Field>::Packing,
+		<Val as Field>::Packing,
+		PaddingFreeSponge<Perm, 16, 8, 8>,
+		TruncatedPermutation<Perm, 2, 8, 16>,
+		8,
+	>;
+
+	type KeccakMmcs = MerkleTreeMmcs<
+		Val,
+		u8,
+		SerializingHasher32<Keccak256Hash>,
+		CompressionFunctionFromHasher<Keccak256Hash, 2, 32>,
+		32,
+	>;
+
+	let security = SecurityTarget::default().fri(PLONKY3_LOG_INV_RATE);
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("plonky3 pcs");
+
+	for n_vars in LOG_SIZES {
+		group.throughput(Throughput::Elements(1 << n_vars));
+
+		let perm = Perm::new_from_rng_128(
+			Poseidon2ExternalMatrixGeneral,
+			DiffusionMatrixBabyBear::default(),
+			&mut rng,
+		);
+		let val_mmcs = Poseidon2Mmcs::new(
+			PaddingFreeSponge::new(perm.clone()),
+			TruncatedPermutation::new(perm.clone()),
+		);
+		let fri_config = FriConfig {
+			log_blowup: PLONKY3_LOG_INV_RATE,
+			num_queries: security.n_queries,
+			proof_of_work_bits: security.pow_bits,
+			mmcs: ExtensionMmcs::<Val, Challenge, _>::new(val_mmcs.clone()),
+		};
+		let pcs = TwoAdicFriPcs::<Val, Dft, _, _>::new(Dft::default(), val_mmcs, fri_config);
+		bench_plonky3_config::<Val, Challenge, _, _>(
+			&mut group,
+			"poseidon2 merkle",
+			pcs,
+			DuplexChallenger::<Val, Perm, 16, 8>::new(perm),
+			n_vars,
+		);
+
+		let val_mmcs = KeccakMmcs::new(
+			SerializingHasher32::new(Keccak256Hash {}),
+			CompressionFunctionFromHasher::new(Keccak256Hash {}),
+		);
+		let fri_config = FriConfig {
+			log_blowup: PLONKY3_LOG_INV_RATE,
+			num_queries: security.n_queries,
+			proof_of_work_bits: security.pow_bits,
+			mmcs: ExtensionMmcs::<Val, Challenge, _>::new(val_mmcs.clone()),
+		};
+		let pcs = TwoAdicFriPcs::<Val, Dft, _, _>::new(Dft::default(), val_mmcs, fri_config);
+		bench_plonky3_config::<Val, Challenge, _, _>(
+			&mut group,
+			"keccak merkle",
+			pcs,
+			SerializingChallenger32::<Val, HashChallenger<u8, Keccak256Hash, 32>>::from_hasher(
+				vec![],
+				Keccak256Hash {},
+			),
+			n_vars,
+		);
+	}
+
+	group.finish()
+}
+
+/// Log2 of the number of polynomials of the plonky2 configurations, as in `goldilocks_fri_pcs`.
+const PLONKY2_LOG_BATCH_SIZE: usize = 8;
+
+fn bench_plonky2_config<C: GenericConfig<2, F = GoldilocksField>>(
+	group: &mut BenchmarkGroup<WallTime>,
+	config: &str,
+	n_vars: usize,
+) {
+	use plonky2::{
+		field::fft::fft_root_table,
+		fri::{
+			oracle::PolynomialBatch,
+			structure::{
+				FriBatchInfo, FriInstanceInfo, FriOpeningBatch, FriOpenings, FriOracleInfo,
+				FriPolynomialInfo,
+			},
+			verifier::verify_fri_proof,
+		},
+		iop::challenger::Challenger,
+		util::timing::TimingTree,
+	};
+	use starky::config::StarkConfig;
+
+	// Keep the rate of the standard configuration, but query and grind for the common target.
+	let mut fri_config = StarkConfig::standard_fast_config().fri_config;
+	fri_config.cap_height = 0;
+	let security = SecurityTarget::default().fri(fri_config.rate_bits);
+	fri_config.num_query_rounds = security.n_queries;
+	fri_config.proof_of_work_bits = security.pow_bits as u32;
+
+	let degree_bits = n_vars - PLONKY2_LOG_BATCH_SIZE;
+	let batch_size = 1 << PLONKY2_LOG_BATCH_SIZE;
+	let fri_params = fri_config.fri_params(degree_bits, false);
+	let root_table = fft_root_table(1 << (degree_bits + fri_config.rate_bits));
+	let poly_values =
+		repeat_with(|| PolynomialValues::new(GoldilocksField::rand_vec(1 << degree_bits)))
+			.take(batch_size)
+			.collect::<Vec<_>>();
+
+	let commit = |poly_values| {
+		PolynomialBatch::<_, C, 2>::from_values(
+			poly_values,
+			fri_config.rate_bits,
+			false,
+			fri_config.cap_height,
+			&mut TimingTree::default(),
+			Some(&root_table),
+		)
+	};
+	group.sample_size(prover_samples(n_vars));
+	group.bench_function(format!("{config} commit n_vars={n_vars}"), |b| {
+		b.iter_batched(|| poly_values.clone(), &commit, BatchSize::LargeInput)
+	});
+
+	let committed = commit(poly_values);
+	let mut challenger = Challenger::<GoldilocksField, C::Hasher>::new();
+	challenger.observe_cap::<C::Hasher>(&committed.merkle_tree.cap);
+	let zeta = challenger.get_extension_challenge::<2>();
+	let instance = FriInstanceInfo {
+		oracles: vec![FriOracleInfo {
+			num_polys: batch_size,
+			blinding: false,
+		}],
+		batches: vec![FriBatchInfo {
+			point: zeta,
+			polynomials: (0..batch_size)
+				.map(|i| FriPolynomialInfo {
+					oracle_index: 0,
+					polynomial_index: i,
+				})
+				.collect(),
+		}],
+	};
+
+	group.bench_function(format!("{config} prove n_vars={n_vars}"), |b| {
+		b.iter_batched(
+			|| challenger.clone(),
+			|mut challenger| {
+				PolynomialBatch::prove_openings(
+					&instance,
+					&[&committed],
+					&mut challenger,
+					&fri_params,
+					&mut TimingTree::default(),
+				)
+			},
+			BatchSize::SmallInput,
+		)
+	});
+
+	let proof = PolynomialBatch::prove_openings(
+		&instance,
+		&[&committed],
+		&mut challenger.clone(),
+		&fri_params,
+		&mut TimingTree::default(),
+	);
+
+	// As in the example, the verify phase starts from the FRI challenges derived from the proof.
+	let challenges = challenger.fri_challenges::<C, 2>(
+		&proof.commit_phase_merkle_caps,
+		&proof.final_poly,
+		proof.pow_witness,
+		fri_params.degree_bits,
+		&fri_config,
+	);
+	let openings = FriOpenings {
+		batches: vec![FriOpeningBatch {
+			values: committed
+				.polynomials
+				.iter()
+				.map(|poly| poly.to_extension::<2>().eval(zeta))
+				.collect(),
+		}],
+	};
+	let caps = [committed.merkle_tree.cap.clone()];
+
+	group.sample_size(VERIFY_SAMPLES);
+	group.bench_function(format!("{config} verify n_vars={n_vars}"), |b| {
+		b.iter(|| {
+			verify_fri_proof::<GoldilocksField, C, 2>(
+				&instance,
+				&openings,
+				&challenges,
+				&caps,
+				&proof,
+				&fri_params,
+			)
+			.unwrap()
+		})
+	});
+}
+
+fn bench_plonky2(c: &mut Criterion) {
+	use plonky2::plonk::config::{KeccakGoldilocksConfig, PoseidonGoldilocksConfig};
+
+	let mut group = c.benchmark_group("plonky2 pcs");
+
+	for n_vars in LOG_SIZES {
+		group.throughput(Throughput::Elements(1 << n_vars));
+		bench_plonky2_config::<PoseidonGoldilocksConfig>(&mut group, "poseidon", n_vars);
+		bench_plonky2_config::<KeccakGoldilocksConfig>(&mut group, "keccak", n_vars);
+	}
+
+	group.finish()
+}
+
+#[cfg(feature = "stwo")]
+fn bench_stwo(c: &mut Criterion) {
+	use ark_std::UniformRand;
+	use stwo::core::{
+		backend::{simd::SimdBackend, Col},
+		channel::Blake2sChannel,
+		circle::CirclePoint,
+		fields::{m31::BaseField, qm31::SecureField},
+		fri::FriConfig,
+		pcs::{CommitmentSchemeProver, CommitmentSchemeVerifier, PcsConfig, TreeVec},
+		poly::{
+			circle::{CanonicCoset, CircleEvaluation, PolyOps},
+			BitReversedOrder,
+		},
+		vcs::blake2_merkle::Blake2sMerkleChannel,
+	};
+
+	// The log2 blowup factor of `stwo_pcs`.
+	let log_blowup_factor = 1;
+	let security = SecurityTarget::default().fri(log_blowup_factor as usize);
+	let pcs_config = PcsConfig {
+		pow_bits: security.pow_bits as u32,
+		fri_config: FriConfig {
+			log_last_layer_degree_bound: 0,
+			log_blowup_factor,
+			n_queries: security.n_queries,
+		},
+	};
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("stwo pcs");
+
+	for n_vars in LOG_SIZES {
+		let log_n_rows = (n_vars - LOG_BATCH_SIZE) as u32;
+		let twiddles = SimdBackend::precompute_twiddles(
+			CanonicCoset::new(log_n_rows + log_blowup_factor)
+				.circle_domain()
+				.half_coset,
+		);
+		let domain = CanonicCoset::new(log_n_rows).circle_domain();
+		let trace = repeat_with(|| {
+			let values = repeat_with(|| BaseField::rand(&mut rng))
+				.take(1 << log_n_rows)
+				.collect::<Col<SimdBackend, BaseField>>();
+			CircleEvaluation::<SimdBackend, BaseField, BitReversedOrder>::new(domain, values)
+		})
+		.take(1 << LOG_BATCH_SIZE)
+		.collect::<Vec<_>>();
+
+		let commit = |trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>| {
+			let mut channel = Blake2sChannel::default();
+			let mut scheme =
+				CommitmentSchemeProver::<_, Blake2sMerkleChannel>::new(pcs_config, &twiddles);
+			let mut tree_builder = scheme.tree_builder();
+			tree_builder.extend_evals(trace);
+			tree_builder.commit(&mut channel);
+			(scheme, channel)
+		};
+
+		group.throughput(Throughput::Elements(1 << n_vars));
+		group.sample_size(prover_samples(n_vars));
+		group.bench_function(format!("commit n_vars={n_vars}"), |b| {
+			b.iter_batched(|| trace.clone(), &commit, BatchSize::LargeInput)
+		});
+
+		let (scheme, mut channel) = commit(trace);
+		let sample_point = CirclePoint::<SecureField>::get_random_point(&mut channel);
+		let sample_points = TreeVec::new(vec![vec![vec![sample_point]; 1 << LOG_BATCH_SIZE]]);
+
+		group.bench_function(format!("prove n_vars={n_vars}"), |b| {
+			b.iter_batched(
+				|| (sample_points.clone(), channel.clone()),
+				|(sample_points, mut channel)| scheme.prove_values(sample_points, &mut channel),
+				BatchSize::SmallInput,
+			)
+		});
+
+		// The verifier consumes the proof, so every iteration deserializes a copy of it.
+		let proof = scheme.prove_values(sample_points.clone(), &mut channel.clone());
+		let proof_bytes = bincode::serialize(&proof).unwrap();
+		let root = scheme.roots()[0];
+		let column_log_sizes = vec![log_n_rows; 1 << LOG_BATCH_SIZE];
+
+		group.sample_size(VERIFY_SAMPLES);
+		group.bench_function(format!("verify n_vars={n_vars}"), |b| {
+			b.iter_batched(
+				|| {
+					let proof = bincode::deserialize(&proof_bytes).unwrap();
+					(sample_points.clone(), proof)
+				},
+				|(sample_points, proof)| {
+					let mut channel = Blake2sChannel::default();
+					let mut verifier =
+						CommitmentSchemeVerifier::<Blake2sMerkleChannel>::new(pcs_config);
+					verifier.commit(root, &column_log_sizes, &mut channel);
+					verifier
+						.verify_values(sample_points, proof, &mut channel)
+						.unwrap()
+				},
+				BatchSize::SmallInput,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+#[cfg(not(feature = "stwo"))]
+fn bench_stwo(_c: &mut Criterion) {}
+
+criterion_group!(pcs, bench_binius, bench_jolt, bench_plonky3, bench_plonky2, bench_stwo);
+criterion_main!(pcs);
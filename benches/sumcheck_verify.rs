@@ -0,0 +1,164 @@
+//! Benchmarks of sumcheck verification alone.
+//!
+//! The sumcheck examples time verification once per run, next to a prover whose witness takes
+//! gigabytes. Verifying costs only a few field operations and transcript samples per round, so
+//! it is what a recursive verifier or a verifier inside a zkVM pays, and this bench measures it on
+//! small statements that also fit under emulation, see `scripts/riscv.sh`. The proofs are produced
+//! once per configuration outside the timed loop.
+
+use std::{iter::repeat_with, slice};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use rand::thread_rng;
+
+/// Numbers of variables of the statements, which is the number of sumcheck rounds.
+const N_VARS: [usize; 3] = [12, 16, 20];
+
+/// Degrees of the product compositions.
+const DEGREES: [usize; 2] = [2, 3];
+
+fn bench_binius(c: &mut Criterion) {
+	use binius_core::{
+		fiat_shamir::HasherChallenger,
+		polynomial::MultilinearComposite,
+		protocols::{
+			sumcheck::{
+				batch_prove, batch_verify, immediate_switchover_heuristic,
+				prove::RegularSumcheckProver, CompositeSumClaim, SumcheckClaim,
+			},
+			test_utils::TestProductComposition,
+		},
+		transcript::{TranscriptReader, TranscriptWriter},
+	};
+	use binius_field::{
+		arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField128b, BinaryField8b,
+		PackedField,
+	};
+	use binius_hal::make_portable_backend;
+	use binius_math::{IsomorphicEvaluationDomainFactory, MLEDirectAdapter, MultilinearExtension};
+	use groestl::Groestl256;
+
+	type F = BinaryField128b;
+	type P = PackedType<OptimalUnderlier, F>;
+	type Challenger = HasherChallenger<Groestl256>;
+
+	let mut rng = thread_rng();
+	let backend = make_portable_backend();
+	let mut group = c.benchmark_group("binius sumcheck verify");
+
+	for degree in DEGREES {
+		let composition = TestProductComposition::new(degree);
+		for n_vars in N_VARS {
+			let multilins = repeat_with(|| {
+				let values = repeat_with(|| P::random(&mut rng))
+					.take(1 << (n_vars - P::LOG_WIDTH))
+					.collect::<Vec<_>>();
+				MLEDirectAdapter::from(MultilinearExtension::from_values(values).unwrap())
+			})
+			.take(degree)
+			.collect::<Vec<_>>();
+			let witness =
+				MultilinearComposite::new(n_vars, &composition, multilins.iter().collect())
+					.unwrap();
+			let sum = (0..1 << n_vars)
+				.map(|index| witness.evaluate_on_hypercube(index).unwrap())
+				.sum::<F>();
+
+			let prover = RegularSumcheckProver::<BinaryField8b, _, _, _, _>::new(
+				multilins.iter().collect(),
+				[CompositeSumClaim {
+					composition: &composition,
+					sum,
+				}],
+				IsomorphicEvaluationDomainFactory::<BinaryField8b>::default(),
+				immediate_switchover_heuristic,
+				&backend,
+			)
+			.unwrap();
+			let mut transcript = TranscriptWriter::<Challenger>::default();
+			batch_prove(vec![prover], &mut transcript).unwrap();
+			let proof = transcript.finalize();
+
+			let claim = SumcheckClaim::new(
+				n_vars,
+				degree,
+				vec![CompositeSumClaim {
+					composition: &composition,
+					sum,
+				}],
+			)
+			.unwrap();
+			group.bench_function(format!("128b tower degree={degree} n_vars={n_vars}"), |b| {
+				b.iter_batched(
+					|| TranscriptReader::<Challenger>::new(proof.clone()),
+					|mut transcript| {
+						let reduced =
+							batch_verify(slice::from_ref(&claim), &mut transcript).unwrap();
+						transcript.finalize().unwrap();
+						reduced
+					},
+					BatchSize::SmallInput,
+				)
+			});
+		}
+	}
+
+	group.finish()
+}
+
+fn bench_jolt(c: &mut Criterion) {
+	use ark_bn254::Fr;
+	use ark_std::{One, UniformRand};
+	use jolt_core::{
+		poly::dense_mlpoly::DensePolynomial, subprotocols::sumcheck::SumcheckInstanceProof,
+		utils::transcript::ProofTranscript,
+	};
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("jolt sumcheck verify");
+
+	let product = |values: &[Fr]| values.iter().fold(Fr::one(), |acc, value| acc * *value);
+	for degree in DEGREES {
+		for n_vars in N_VARS {
+			let mut polys = repeat_with(|| {
+				DensePolynomial::new(
+					repeat_with(|| Fr::rand(&mut rng))
+						.take(1 << n_vars)
+						.collect(),
+				)
+			})
+			.take(degree)
+			.collect::<Vec<_>>();
+			let claim = (0..1 << n_vars)
+				.map(|index| polys.iter().map(|poly| poly[index]).product::<Fr>())
+				.sum::<Fr>();
+
+			let mut transcript = ProofTranscript::new(b"bench");
+			let (proof, _, _) = SumcheckInstanceProof::<Fr>::prove_arbitrary(
+				&claim,
+				n_vars,
+				&mut polys,
+				product,
+				degree,
+				&mut transcript,
+			);
+
+			group.bench_function(format!("BN254 Fr degree={degree} n_vars={n_vars}"), |b| {
+				b.iter_batched(
+					|| ProofTranscript::new(b"bench"),
+					|mut transcript| {
+						proof
+							.verify(claim, n_vars, degree, &mut transcript)
+							.unwrap()
+					},
+					BatchSize::SmallInput,
+				)
+			});
+		}
+	}
+
+	group.finish()
+}
+
+criterion_group!(sumcheck_verify, bench_binius, bench_jolt);
+criterion_main!(sumcheck_verify);
@@ -0,0 +1,286 @@
+//! Benchmarks of the Fiat-Shamir transcripts used by each proof system.
+//!
+//! Each group measures absorbing a batch of field elements into a fresh transcript and sampling a
+//! challenge from it. For small and recursive proofs the transcript is a measurable share of the
+//! verifier's work.
+
+use std::iter::repeat_with;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rand::{thread_rng, Rng};
+
+/// Number of field elements absorbed per "observe" iteration.
+const N_OBSERVED: usize = 64;
+
+fn bench_binius(c: &mut Criterion) {
+	use binius_core::{
+		fiat_shamir::{CanSample, HasherChallenger},
+		transcript::{CanWrite, TranscriptWriter},
+	};
+	use binius_field::{BinaryField128b, Field};
+	use groestl::Groestl256;
+
+	type Transcript = TranscriptWriter<HasherChallenger<Groestl256>>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("binius transcript");
+
+	let values = repeat_with(|| BinaryField128b::random(&mut rng))
+		.take(N_OBSERVED)
+		.collect::<Vec<_>>();
+
+	group.throughput(Throughput::Elements(N_OBSERVED as u64));
+	group.bench_function("Groestl observe 128b", |b| {
+		b.iter_batched(
+			Transcript::default,
+			|mut transcript| {
+				transcript.write_scalar_slice(&values);
+				transcript
+			},
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.throughput(Throughput::Elements(1));
+	group.bench_function("Groestl sample 128b", |b| {
+		b.iter_batched(
+			|| {
+				let mut transcript = Transcript::default();
+				transcript.write_scalar_slice(&values);
+				transcript
+			},
+			|mut transcript| CanSample::<BinaryField128b>::sample(&mut transcript),
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.finish()
+}
+
+fn bench_plonky3(c: &mut Criterion) {
+	use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+	use p3_challenger::{
+		CanObserve, DuplexChallenger, FieldChallenger, HashChallenger, SerializingChallenger32,
+	};
+	use p3_field::extension::BinomialExtensionField;
+	use p3_keccak::Keccak256Hash;
+	use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+
+	type Val = BabyBear;
+	type Challenge = BinomialExtensionField<Val, 4>;
+	type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+	type Duplex = DuplexChallenger<Val, Perm, 16, 8>;
+	type Serializing = SerializingChallenger32<Val, HashChallenger<u8, Keccak256Hash, 32>>;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("plonky3 transcript");
+
+	let perm = Perm::new_from_rng_128(
+		Poseidon2ExternalMatrixGeneral,
+		DiffusionMatrixBabyBear::default(),
+		&mut rng,
+	);
+	let values = repeat_with(|| rng.gen::<Val>())
+		.take(N_OBSERVED)
+		.collect::<Vec<_>>();
+
+	group.throughput(Throughput::Elements(N_OBSERVED as u64));
+	group.bench_function("DuplexChallenger Poseidon2 observe BB31", |b| {
+		b.iter_batched(
+			|| Duplex::new(perm.clone()),
+			|mut challenger| {
+				challenger.observe_slice(&values);
+				challenger
+			},
+			BatchSize::SmallInput,
+		)
+	});
+	group.bench_function("SerializingChallenger32 Keccak observe BB31", |b| {
+		b.iter_batched(
+			|| Serializing::from_hasher(vec![], Keccak256Hash {}),
+			|mut challenger| {
+				challenger.observe_slice(&values);
+				challenger
+			},
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.throughput(Throughput::Elements(1));
+	group.bench_function("DuplexChallenger Poseidon2 sample BB31^4", |b| {
+		b.iter_batched(
+			|| {
+				let mut challenger = Duplex::new(perm.clone());
+				challenger.observe_slice(&values);
+				challenger
+			},
+			|mut challenger| challenger.sample_ext_element::<Challenge>(),
+			BatchSize::SmallInput,
+		)
+	});
+	group.bench_function("SerializingChallenger32 Keccak sample BB31^4", |b| {
+		b.iter_batched(
+			|| {
+				let mut challenger = Serializing::from_hasher(vec![], Keccak256Hash {});
+				challenger.observe_slice(&values);
+				challenger
+			},
+			|mut challenger| challenger.sample_ext_element::<Challenge>(),
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.finish()
+}
+
+fn bench_plonky2(c: &mut Criterion) {
+	use plonky2::{
+		hash::{keccak::KeccakHash, poseidon::PoseidonHash},
+		iop::challenger::Challenger,
+	};
+	use plonky2_field::{goldilocks_field::GoldilocksField, types::Sample};
+
+	let mut group = c.benchmark_group("plonky2 transcript");
+
+	let values = GoldilocksField::rand_vec(N_OBSERVED);
+
+	group.throughput(Throughput::Elements(N_OBSERVED as u64));
+	group.bench_function("Challenger Poseidon observe GL64", |b| {
+		b.iter_batched(
+			Challenger::<GoldilocksField, PoseidonHash>::new,
+			|mut challenger| {
+				challenger.observe_elements(&values);
+				challenger
+			},
+			BatchSize::SmallInput,
+		)
+	});
+	group.bench_function("Challenger Keccak observe GL64", |b| {
+		b.iter_batched(
+			Challenger::<GoldilocksField, KeccakHash<25>>::new,
+			|mut challenger| {
+				challenger.observe_elements(&values);
+				challenger
+			},
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.throughput(Throughput::Elements(1));
+	group.bench_function("Challenger Poseidon sample GL64^2", |b| {
+		b.iter_batched(
+			|| {
+				let mut challenger = Challenger::<GoldilocksField, PoseidonHash>::new();
+				challenger.observe_elements(&values);
+				challenger
+			},
+			|mut challenger| challenger.get_extension_challenge::<2>(),
+			BatchSize::SmallInput,
+		)
+	});
+	group.bench_function("Challenger Keccak sample GL64^2", |b| {
+		b.iter_batched(
+			|| {
+				let mut challenger = Challenger::<GoldilocksField, KeccakHash<25>>::new();
+				challenger.observe_elements(&values);
+				challenger
+			},
+			|mut challenger| challenger.get_extension_challenge::<2>(),
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.finish()
+}
+
+#[cfg(feature = "stwo")]
+fn bench_stwo(c: &mut Criterion) {
+	use std::array;
+
+	use ark_std::UniformRand;
+	use stwo::core::{
+		channel::{Blake2sChannel, Channel},
+		fields::{m31::BaseField, qm31::SecureField},
+	};
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("stwo transcript");
+
+	let values =
+		repeat_with(|| SecureField::from_m31_array(array::from_fn(|_| BaseField::rand(&mut rng))))
+			.take(N_OBSERVED)
+			.collect::<Vec<_>>();
+
+	group.throughput(Throughput::Elements(N_OBSERVED as u64));
+	group.bench_function("Blake2sChannel observe QM31", |b| {
+		b.iter_batched(
+			Blake2sChannel::default,
+			|mut channel| {
+				channel.mix_felts(&values);
+				channel
+			},
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.throughput(Throughput::Elements(1));
+	group.bench_function("Blake2sChannel sample QM31", |b| {
+		b.iter_batched(
+			|| {
+				let mut channel = Blake2sChannel::default();
+				channel.mix_felts(&values);
+				channel
+			},
+			|mut channel| channel.draw_felt(),
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.finish()
+}
+
+#[cfg(not(feature = "stwo"))]
+fn bench_stwo(_c: &mut Criterion) {}
+
+fn bench_jolt(c: &mut Criterion) {
+	use ark_bn254::Fr;
+	use ark_std::UniformRand;
+	use jolt_core::utils::transcript::ProofTranscript;
+
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("jolt transcript");
+
+	let values = repeat_with(|| Fr::rand(&mut rng))
+		.take(N_OBSERVED)
+		.collect::<Vec<_>>();
+
+	group.throughput(Throughput::Elements(N_OBSERVED as u64));
+	group.bench_function("ProofTranscript Keccak observe BN254 Fr", |b| {
+		b.iter_batched(
+			|| ProofTranscript::new(b"bench"),
+			|mut transcript| {
+				transcript.append_scalars(&values);
+				transcript
+			},
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.throughput(Throughput::Elements(1));
+	group.bench_function("ProofTranscript Keccak sample BN254 Fr", |b| {
+		b.iter_batched(
+			|| {
+				let mut transcript = ProofTranscript::new(b"bench");
+				transcript.append_scalars(&values);
+				transcript
+			},
+			|mut transcript| transcript.challenge_scalar::<Fr>(),
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.finish()
+}
+
+criterion_group!(transcript, bench_binius, bench_plonky3, bench_plonky2, bench_stwo, bench_jolt);
+criterion_main!(transcript);
@@ -0,0 +1,80 @@
+//! Benchmarks of transposing large row-major matrices of field elements.
+//!
+//! Merkle leaf layout and byte-slicing both depend on transpose throughput. plonky3's built-in
+//! transpose is compared against a naive loop and a simple cache-blocked implementation.
+
+use std::mem::size_of;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use p3_baby_bear::BabyBear;
+use p3_matrix::dense::RowMajorMatrix;
+use rand::thread_rng;
+use rayon::prelude::*;
+
+/// Matrix shapes as (log rows, log columns).
+const LOG_SHAPES: [(usize, usize); 3] = [(20, 6), (16, 10), (24, 2)];
+
+/// Side length of the square tiles used by the blocked transpose.
+const BLOCK_SIZE: usize = 32;
+
+fn transpose_naive<T: Copy>(input: &[T], output: &mut [T], rows: usize, cols: usize) {
+	for (i, row) in input.chunks_exact(cols).enumerate() {
+		for (j, &value) in row.iter().enumerate() {
+			output[j * rows + i] = value;
+		}
+	}
+}
+
+/// Transposes tile by tile, parallelizing over bands of output rows.
+fn transpose_blocked<T: Copy + Send + Sync>(
+	input: &[T],
+	output: &mut [T],
+	rows: usize,
+	cols: usize,
+) {
+	output
+		.par_chunks_mut(BLOCK_SIZE * rows)
+		.enumerate()
+		.for_each(|(band, out_band)| {
+			let col_start = band * BLOCK_SIZE;
+			let col_end = (col_start + BLOCK_SIZE).min(cols);
+			for row_start in (0..rows).step_by(BLOCK_SIZE) {
+				let row_end = (row_start + BLOCK_SIZE).min(rows);
+				for i in row_start..row_end {
+					for j in col_start..col_end {
+						out_band[(j - col_start) * rows + i] = input[i * cols + j];
+					}
+				}
+			}
+		});
+}
+
+fn bench_transpose(c: &mut Criterion) {
+	let mut rng = thread_rng();
+	let mut group = c.benchmark_group("transpose");
+	group.sample_size(10);
+
+	for (log_rows, log_cols) in LOG_SHAPES {
+		let (rows, cols) = (1 << log_rows, 1 << log_cols);
+		let matrix = RowMajorMatrix::<BabyBear>::rand(&mut rng, rows, cols);
+		let mut output = vec![BabyBear::default(); rows * cols];
+
+		group.throughput(Throughput::Bytes((rows * cols * size_of::<BabyBear>()) as u64));
+		group.bench_function(format!("BB31 plonky3 {rows}x{cols}"), |b| {
+			b.iter(|| matrix.transpose())
+		});
+		group.bench_function(format!("BB31 naive {rows}x{cols}"), |b| {
+			b.iter(|| transpose_naive(&matrix.values, &mut output, rows, cols))
+		});
+		group.bench_function(format!("BB31 blocked {rows}x{cols}"), |b| {
+			b.iter(|| transpose_blocked(&matrix.values, &mut output, rows, cols))
+		});
+
+		assert_eq!(output, matrix.transpose().values);
+	}
+
+	group.finish()
+}
+
+criterion_group!(transpose, bench_transpose);
+criterion_main!(transpose);
@@ -0,0 +1,258 @@
+//! Comparison of a KZG vector commitment against a Merkle tree for answering FRI-style queries.
+//!
+//! Both schemes commit the same column of BN254 scalars, then open and verify the same random
+//! positions. KZG opens any number of positions with a single group element, while Merkle proofs
+//! grow with both the number of queries and the tree depth, so the proof sizes printed for each
+//! query count show where a hybrid design would switch from one to the other.
+//!
+//! The Merkle tree hashes 32-byte leaves with Keccak-256, as an EVM-friendly verifier would.
+
+use std::iter::{repeat_with, successors};
+
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{
+	pairing::Pairing, scalar_mul::fixed_base::FixedBase, CurveGroup, Group, VariableBaseMSM,
+};
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_poly::{
+	univariate::{DenseOrSparsePolynomial, DensePolynomial},
+	DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain,
+};
+use ark_serialize::CanonicalSerialize;
+use bytesize::ByteSize;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::{thread_rng, Rng};
+use tiny_keccak::{Hasher, Keccak};
+
+const LOG_N: usize = 16;
+
+/// Numbers of positions opened together, up to the query count of a typical FRI configuration.
+const QUERY_COUNTS: [usize; 4] = [1, 16, 64, 128];
+
+struct Srs {
+	g1_powers: Vec<G1Affine>,
+	g2_powers: Vec<G2Affine>,
+}
+
+impl Srs {
+	/// Generates powers of a trapdoor that is known to the caller, which is only fit for
+	/// benchmarking.
+	fn insecure_setup(n_g1: usize, n_g2: usize, mut rng: impl Rng) -> Self {
+		let tau = Fr::rand(&mut rng);
+		let powers = successors(Some(Fr::one()), |power| Some(*power * tau))
+			.take(n_g1)
+			.collect::<Vec<_>>();
+
+		let scalar_bits = Fr::MODULUS_BIT_SIZE as usize;
+		let window = FixedBase::get_mul_window_size(n_g1);
+		let table = FixedBase::get_window_table(scalar_bits, window, G1Projective::generator());
+		let g1_powers = G1Projective::normalize_batch(&FixedBase::msm::<G1Projective>(
+			scalar_bits,
+			window,
+			&table,
+			&powers,
+		));
+		let g2_powers = powers[..n_g2]
+			.iter()
+			.map(|power| (G2Projective::generator() * power).into_affine())
+			.collect();
+
+		Self {
+			g1_powers,
+			g2_powers,
+		}
+	}
+
+	fn commit(&self, poly: &DensePolynomial<Fr>) -> G1Projective {
+		G1Projective::msm(&self.g1_powers[..poly.coeffs.len()], &poly.coeffs).unwrap()
+	}
+}
+
+fn vanishing_poly(points: &[Fr]) -> DensePolynomial<Fr> {
+	points
+		.iter()
+		.fold(DensePolynomial::from_coefficients_vec(vec![Fr::one()]), |acc, &point| {
+			&acc * &DensePolynomial::from_coefficients_vec(vec![-point, Fr::one()])
+		})
+}
+
+fn interpolate(points: &[Fr], values: &[Fr]) -> DensePolynomial<Fr> {
+	let vanishing = vanishing_poly(points);
+	points
+		.iter()
+		.zip(values)
+		.fold(DensePolynomial::zero(), |acc, (&point, &value)| {
+			let basis =
+				&vanishing / &DensePolynomial::from_coefficients_vec(vec![-point, Fr::one()]);
+			let scale = value / basis.evaluate(&point);
+			&acc + &(&basis * scale)
+		})
+}
+
+/// Opens `poly` at `points` with a single proof, the commitment to `(p - I) / Z` where `I`
+/// interpolates the opened values and `Z` vanishes on the points.
+fn kzg_open(srs: &Srs, poly: &DensePolynomial<Fr>, points: &[Fr], values: &[Fr]) -> G1Projective {
+	let numerator = poly - &interpolate(points, values);
+	let (quotient, _) = DenseOrSparsePolynomial::from(numerator)
+		.divide_with_q_and_r(&vanishing_poly(points).into())
+		.unwrap();
+	srs.commit(&quotient)
+}
+
+fn kzg_verify(
+	srs: &Srs,
+	commitment: G1Projective,
+	points: &[Fr],
+	values: &[Fr],
+	proof: G1Projective,
+) -> bool {
+	let interpolant = interpolate(points, values);
+	let vanishing = vanishing_poly(points);
+	let vanishing_commitment =
+		G2Projective::msm(&srs.g2_powers[..vanishing.coeffs.len()], &vanishing.coeffs).unwrap();
+	Bn254::pairing(commitment - srs.commit(&interpolant), srs.g2_powers[0])
+		== Bn254::pairing(proof, vanishing_commitment)
+}
+
+fn keccak(inputs: &[&[u8]]) -> [u8; 32] {
+	let mut keccak = Keccak::v256();
+	for input in inputs {
+		keccak.update(input);
+	}
+	let mut digest = [0u8; 32];
+	keccak.finalize(&mut digest);
+	digest
+}
+
+fn hash_leaf(value: &Fr) -> [u8; 32] {
+	let mut bytes = [0u8; 32];
+	value.serialize_compressed(&mut bytes[..]).unwrap();
+	keccak(&[&bytes])
+}
+
+/// Builds the layers of a binary Keccak-256 Merkle tree, from the leaf digests up to the root.
+fn merkle_build(values: &[Fr]) -> Vec<Vec<[u8; 32]>> {
+	let mut layers = vec![values.iter().map(hash_leaf).collect::<Vec<_>>()];
+	while layers[layers.len() - 1].len() > 1 {
+		let next = layers[layers.len() - 1]
+			.chunks_exact(2)
+			.map(|pair| keccak(&[&pair[0], &pair[1]]))
+			.collect();
+		layers.push(next);
+	}
+	layers
+}
+
+fn merkle_open(layers: &[Vec<[u8; 32]>], index: usize) -> Vec<[u8; 32]> {
+	layers[..layers.len() - 1]
+		.iter()
+		.enumerate()
+		.map(|(depth, layer)| layer[(index >> depth) ^ 1])
+		.collect()
+}
+
+fn merkle_verify(root: &[u8; 32], index: usize, value: &Fr, path: &[[u8; 32]]) -> bool {
+	let digest = path
+		.iter()
+		.enumerate()
+		.fold(hash_leaf(value), |digest, (depth, sibling)| {
+			if (index >> depth) & 1 == 0 {
+				keccak(&[&digest, sibling])
+			} else {
+				keccak(&[sibling, &digest])
+			}
+		});
+	&digest == root
+}
+
+fn bench_vector_commitment(c: &mut Criterion) {
+	let mut rng = thread_rng();
+	let max_queries = QUERY_COUNTS[QUERY_COUNTS.len() - 1];
+
+	let srs = Srs::insecure_setup(1 << LOG_N, max_queries + 1, &mut rng);
+	let domain = Radix2EvaluationDomain::<Fr>::new(1 << LOG_N).unwrap();
+	let values = repeat_with(|| Fr::rand(&mut rng))
+		.take(1 << LOG_N)
+		.collect::<Vec<_>>();
+
+	let mut group = c.benchmark_group("vector commitment");
+	group.sample_size(10);
+
+	group.throughput(Throughput::Elements(1 << LOG_N));
+	group.bench_function(format!("KZG BN254 commit log_n={LOG_N}"), |b| {
+		b.iter(|| {
+			let poly = DensePolynomial::from_coefficients_vec(domain.ifft(&values));
+			srs.commit(&poly)
+		})
+	});
+	group.bench_function(format!("Keccak-256 merkle commit log_n={LOG_N}"), |b| {
+		b.iter(|| merkle_build(&values))
+	});
+
+	let poly = DensePolynomial::from_coefficients_vec(domain.ifft(&values));
+	let commitment = srs.commit(&poly);
+	let layers = merkle_build(&values);
+	let root = layers[layers.len() - 1][0];
+
+	for n_queries in QUERY_COUNTS {
+		let indices = repeat_with(|| rng.gen_range(0..1 << LOG_N))
+			.take(n_queries)
+			.collect::<Vec<_>>();
+		let points = indices
+			.iter()
+			.map(|&index| domain.element(index))
+			.collect::<Vec<_>>();
+		let opened = indices
+			.iter()
+			.map(|&index| values[index])
+			.collect::<Vec<_>>();
+
+		let kzg_proof = kzg_open(&srs, &poly, &points, &opened);
+		assert!(kzg_verify(&srs, commitment, &points, &opened, kzg_proof));
+		let merkle_paths = indices
+			.iter()
+			.map(|&index| merkle_open(&layers, index))
+			.collect::<Vec<_>>();
+
+		let values_size = opened.compressed_size();
+		let kzg_size = kzg_proof.into_affine().compressed_size();
+		let merkle_size = merkle_paths
+			.iter()
+			.map(|path| path.len() * 32)
+			.sum::<usize>();
+		println!(
+			"n_queries={n_queries}: opened values = {}, KZG proof = {}, Merkle paths = {}",
+			ByteSize(values_size as u64),
+			ByteSize(kzg_size as u64),
+			ByteSize(merkle_size as u64),
+		);
+
+		group.throughput(Throughput::Elements(n_queries as u64));
+		group.bench_function(format!("KZG BN254 open n_queries={n_queries}"), |b| {
+			b.iter(|| kzg_open(&srs, &poly, &points, &opened))
+		});
+		group.bench_function(format!("Keccak-256 merkle open n_queries={n_queries}"), |b| {
+			b.iter(|| {
+				indices
+					.iter()
+					.map(|&index| merkle_open(&layers, index))
+					.collect::<Vec<_>>()
+			})
+		});
+		group.bench_function(format!("KZG BN254 verify n_queries={n_queries}"), |b| {
+			b.iter(|| assert!(kzg_verify(&srs, commitment, &points, &opened, kzg_proof)))
+		});
+		group.bench_function(format!("Keccak-256 merkle verify n_queries={n_queries}"), |b| {
+			b.iter(|| {
+				for ((&index, value), path) in indices.iter().zip(&opened).zip(&merkle_paths) {
+					assert!(merkle_verify(&root, index, value, path));
+				}
+			})
+		});
+	}
+
+	group.finish()
+}
+
+criterion_group!(vector_commitment, bench_vector_commitment);
+criterion_main!(vector_commitment);
@@ -0,0 +1,278 @@
+//! Benchmarks of loading a witness from a file into each backend's native layout.
+//!
+//! At 2^28 elements and beyond, reading the witness takes seconds, as long as some of the phases
+//! the examples time. Every benchmark loads the same file of little-endian 32-bit words into a
+//! binius vector of packed `BinaryField32b`, a plonky3 BabyBear matrix, and a plonky2 vector of
+//! Goldilocks elements, which read the words in pairs. The words are below the BabyBear modulus,
+//! so the file is a valid witness of all three. binius reads the bytes straight into its packed
+//! elements, while plonky3 and plonky2 convert every word into their field representation.
+//!
+//! Each layout is loaded with buffered reads of [`CHUNK_BYTES`] at a time, by copying from a
+//! memory mapping, and, on Linux, with reads of the same chunks in flight on an io_uring queue of
+//! depth [`QUEUE_DEPTH`]. binius can also use the mapping in place, with every page touched so
+//! that the page faults count. Each mode is measured from the page cache and from disk, with the
+//! file evicted from the page cache before every iteration. Throughput is in bytes of the file.
+//!
+//! The files are written once under Cargo's temporary directory for benches and reused.
+
+use std::{
+	fs::{self, File},
+	hint::black_box,
+	io::{self, BufWriter, Read, Write},
+	mem::size_of,
+	path::{Path, PathBuf},
+};
+
+use binius_field::{
+	arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField32b, PackedField,
+};
+use bytesize::ByteSize;
+use criterion::{
+	criterion_group, criterion_main, measurement::WallTime, BatchSize, BenchmarkGroup, Criterion,
+	Throughput,
+};
+use memmap2::{Advice, Mmap};
+use p3_baby_bear::BabyBear;
+use p3_field::AbstractField;
+use p3_matrix::dense::RowMajorMatrix;
+use plonky2_field::{goldilocks_field::GoldilocksField, types::Field};
+use rand::{thread_rng, Rng};
+
+/// Log2 of the sizes of the witness files in bytes, 1 GiB and 4 GiB.
+const LOG_BYTES: [usize; 2] = [30, 32];
+
+/// Bytes of every read, and of every request on the io_uring queue.
+const CHUNK_BYTES: usize = 1 << 20;
+
+/// Number of reads in flight on the io_uring queue.
+#[cfg(target_os = "linux")]
+const QUEUE_DEPTH: usize = 32;
+
+/// Columns of the plonky3 matrix, eight words to a row as in the Merkle benches.
+const WIDTH: usize = 8;
+
+/// Bytes of a page, of which the in-place mapping touches one element each.
+const PAGE_BYTES: usize = 4096;
+
+/// The BabyBear modulus, above every word of the files.
+const BABY_BEAR_MODULUS: u32 = 0x78000001;
+
+type P = PackedType<OptimalUnderlier, BinaryField32b>;
+
+/// A way to read a file into a buffer of its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+	Read,
+	Mmap,
+	#[cfg(target_os = "linux")]
+	IoUring,
+}
+
+impl Mode {
+	const ALL: &'static [Self] = &[
+		Self::Read,
+		Self::Mmap,
+		#[cfg(target_os = "linux")]
+		Self::IoUring,
+	];
+
+	fn name(self) -> &'static str {
+		match self {
+			Self::Read => "read",
+			Self::Mmap => "mmap",
+			#[cfg(target_os = "linux")]
+			Self::IoUring => "io_uring",
+		}
+	}
+
+	/// Reads the file at `path` into `dst`, which is as long as the file.
+	fn load(self, path: &Path, dst: &mut [u8]) -> io::Result<()> {
+		match self {
+			Self::Read => {
+				let mut file = File::open(path)?;
+				for chunk in dst.chunks_mut(CHUNK_BYTES) {
+					file.read_exact(chunk)?;
+				}
+				Ok(())
+			}
+			Self::Mmap => {
+				let map = map_file(path)?;
+				dst.copy_from_slice(&map);
+				Ok(())
+			}
+			#[cfg(target_os = "linux")]
+			Self::IoUring => load_io_uring(path, dst),
+		}
+	}
+}
+
+fn map_file(path: &Path) -> io::Result<Mmap> {
+	// SAFETY: nothing writes to the witness files while they are benchmarked.
+	let map = unsafe { Mmap::map(&File::open(path)?)? };
+	#[cfg(unix)]
+	map.advise(Advice::Sequential)?;
+	Ok(map)
+}
+
+/// Reads the file at `path` into `dst` with [`QUEUE_DEPTH`] reads of [`CHUNK_BYTES`] in flight.
+#[cfg(target_os = "linux")]
+fn load_io_uring(path: &Path, dst: &mut [u8]) -> io::Result<()> {
+	use std::os::fd::AsRawFd;
+
+	use io_uring::{opcode, types, IoUring};
+
+	let file = File::open(path)?;
+	let fd = types::Fd(file.as_raw_fd());
+	let mut ring = IoUring::new(QUEUE_DEPTH as u32)?;
+	let mut chunks = dst.chunks_mut(CHUNK_BYTES).enumerate();
+	let mut in_flight = 0;
+	loop {
+		while in_flight < QUEUE_DEPTH {
+			let Some((index, chunk)) = chunks.next() else {
+				break;
+			};
+			let read = opcode::Read::new(fd, chunk.as_mut_ptr(), chunk.len() as u32)
+				.offset((index * CHUNK_BYTES) as u64)
+				.build()
+				.user_data(chunk.len() as u64);
+			// SAFETY: the chunk outlives the read, which completes before this function returns.
+			unsafe { ring.submission().push(&read) }.expect("the submission queue is full");
+			in_flight += 1;
+		}
+		if in_flight == 0 {
+			return Ok(());
+		}
+		ring.submit_and_wait(1)?;
+		for completion in ring.completion() {
+			let read = completion.result();
+			if read < 0 {
+				return Err(io::Error::from_raw_os_error(-read));
+			}
+			if read as u64 != completion.user_data() {
+				return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "short read"));
+			}
+			in_flight -= 1;
+		}
+	}
+}
+
+/// Drops the pages of the file at `path` from the page cache, so that the next load reads it from
+/// disk. The pages must be clean, which they are once the file is synced.
+fn evict_from_page_cache(path: &Path) {
+	#[cfg(target_os = "linux")]
+	{
+		use std::os::fd::AsRawFd;
+
+		let file = File::open(path).unwrap();
+		// SAFETY: the descriptor is open for the duration of the call.
+		unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+	}
+	#[cfg(not(target_os = "linux"))]
+	{
+		let _ = path;
+	}
+}
+
+/// The witness file of `1 << log_bytes` bytes, written on first use.
+fn witness_file(log_bytes: usize) -> PathBuf {
+	let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("witness-{log_bytes}.bin"));
+	let bytes = 1u64 << log_bytes;
+	if fs::metadata(&path).is_ok_and(|metadata| metadata.len() == bytes) {
+		return path;
+	}
+
+	println!("Writing a witness of {} to {}", ByteSize(bytes), path.display());
+	let mut rng = thread_rng();
+	let mut file = BufWriter::new(File::create(&path).unwrap());
+	let mut chunk = vec![0u8; CHUNK_BYTES];
+	for _ in 0..bytes / CHUNK_BYTES as u64 {
+		for word in chunk.chunks_exact_mut(4) {
+			word.copy_from_slice(&rng.gen_range(0..BABY_BEAR_MODULUS).to_le_bytes());
+		}
+		file.write_all(&chunk).unwrap();
+	}
+	file.into_inner().unwrap().sync_all().unwrap();
+	path
+}
+
+/// Benchmarks `load` reading the file at `path` in every mode, from the page cache and from disk.
+fn bench_modes<T>(
+	group: &mut BenchmarkGroup<WallTime>,
+	layout: &str,
+	path: &Path,
+	log_bytes: usize,
+	load: impl Fn(Mode) -> T,
+) {
+	for &mode in Mode::ALL {
+		let name = format!("{layout} {} log_bytes={log_bytes}", mode.name());
+		group.bench_function(format!("{name} page cache"), |b| {
+			b.iter_with_large_drop(|| load(mode))
+		});
+		group.bench_function(format!("{name} disk"), |b| {
+			b.iter_batched(|| evict_from_page_cache(path), |()| load(mode), BatchSize::PerIteration)
+		});
+	}
+}
+
+fn bench_witness_loading(c: &mut Criterion) {
+	let mut group = c.benchmark_group("witness loading");
+	group.sample_size(10);
+
+	for log_bytes in LOG_BYTES {
+		let path = witness_file(log_bytes);
+		let bytes = 1 << log_bytes;
+		group.throughput(Throughput::Bytes(bytes as u64));
+
+		bench_modes(&mut group, "binius BinaryField32b", &path, log_bytes, |mode| {
+			let mut witness = vec![P::zero(); bytes / size_of::<P>()];
+			mode.load(&path, bytemuck::cast_slice_mut(&mut witness))
+				.unwrap();
+			witness
+		});
+		bench_modes(&mut group, "plonky3 BB31", &path, log_bytes, |mode| {
+			let mut words = vec![0u32; bytes / size_of::<u32>()];
+			mode.load(&path, bytemuck::cast_slice_mut(&mut words))
+				.unwrap();
+			let values = words
+				.into_iter()
+				.map(|word| BabyBear::from_canonical_u32(u32::from_le(word)))
+				.collect();
+			RowMajorMatrix::new(values, WIDTH)
+		});
+		bench_modes(&mut group, "plonky2 GL64", &path, log_bytes, |mode| {
+			let mut words = vec![0u64; bytes / size_of::<u64>()];
+			mode.load(&path, bytemuck::cast_slice_mut(&mut words))
+				.unwrap();
+			words
+				.into_iter()
+				.map(|word| GoldilocksField::from_canonical_u64(u64::from_le(word)))
+				.collect::<Vec<_>>()
+		});
+
+		// binius reads its packed elements from the mapping without copying them.
+		let in_place = || {
+			let map = map_file(&path).unwrap();
+			let witness = bytemuck::cast_slice::<u8, P>(&map);
+			let touched = witness
+				.iter()
+				.step_by(PAGE_BYTES / size_of::<P>())
+				.fold(P::zero(), |acc, &packed| acc + packed);
+			black_box(touched);
+			map
+		};
+		let name = format!("binius BinaryField32b mmap in place log_bytes={log_bytes}");
+		group.bench_function(format!("{name} page cache"), |b| b.iter_with_large_drop(in_place));
+		group.bench_function(format!("{name} disk"), |b| {
+			b.iter_batched(
+				|| evict_from_page_cache(&path),
+				|()| in_place(),
+				BatchSize::PerIteration,
+			)
+		});
+	}
+
+	group.finish()
+}
+
+criterion_group!(witness_loading, bench_witness_loading);
+criterion_main!(witness_loading);
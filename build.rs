@@ -0,0 +1,17 @@
+//! Records the version of the compiler for the description of the build that every run records,
+//! see `src/environment.rs`.
+
+use std::{env, process::Command};
+
+fn main() {
+	let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+	let version = Command::new(rustc)
+		.arg("--version")
+		.output()
+		.ok()
+		.filter(|output| output.status.success())
+		.map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+		.unwrap_or_default();
+	println!("cargo:rustc-env=SNARK_BENCH_RUSTC_VERSION={version}");
+	println!("cargo:rerun-if-env-changed=RUSTC");
+}
@@ -0,0 +1,7 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Runs the benchmarks of [`ulvt_snark_bench::systems::huge_ntt`].
+
+fn main() {
+	ulvt_snark_bench::systems::huge_ntt::run();
+}
@@ -0,0 +1,73 @@
+#!/bin/sh
+# Runs one example under the system allocator, jemalloc, and mimalloc, and prints the time of
+# every timed phase under each, with the change relative to the system allocator.
+#
+# Usage: scripts/allocators.sh <example> [cargo args...]
+# Extra arguments are passed to cargo, e.g. `--features stwo`. Phases are matched by name and
+# order, so repeated phases such as `commit` in a sweep are compared run by run.
+set -e
+
+if [ $# -lt 1 ]; then
+	echo "usage: $0 <example> [cargo args...]" >&2
+	exit 1
+fi
+EXAMPLE=$1
+shift
+
+OUT=$(mktemp -d)
+trap 'rm -rf "$OUT"' EXIT
+
+for allocator in system jemalloc mimalloc; do
+	case $allocator in
+	system) features="" ;;
+	*) features="--features $allocator" ;;
+	esac
+	echo "== $EXAMPLE with $allocator" >&2
+	# shellcheck disable=SC2086
+	NO_COLOR=1 CLICOLOR=0 cargo run --release --example "$EXAMPLE" $features "$@" \
+		| tee "$OUT/$allocator.log" >&2
+done
+
+# Extracts "<phase>#<occurrence> <seconds>" from the ark-std timer lines of a log.
+phases() {
+	sed 's/\x1b\[[0-9;]*m//g' "$1" | awk '
+		/End:/ {
+			line = $0
+			sub(/.*End:[ ]*/, "", line)
+			time = line
+			sub(/.*[^0-9.]\.+/, "", time)
+			if (time == line) next
+			label = substr(line, 1, length(line) - length(time))
+			sub(/\.+$/, "", label)
+			sub(/[ ]+$/, "", label)
+			if (time ~ /ns$/) scale = 1e-9
+			else if (time ~ /ms$/) scale = 1e-3
+			else if (time ~ /s$/ && time !~ /[0-9]s$/) scale = 1e-6
+			else scale = 1
+			gsub(/ /, "_", label)
+			count[label]++
+			printf "%s#%d %.9f\n", label, count[label], time * scale
+		}'
+}
+
+phases "$OUT/system.log" > "$OUT/system.txt"
+phases "$OUT/jemalloc.log" > "$OUT/jemalloc.txt"
+phases "$OUT/mimalloc.log" > "$OUT/mimalloc.txt"
+
+printf "%-48s %12s %12s %8s %12s %8s\n" phase system jemalloc delta mimalloc delta
+awk '
+	FILENAME ~ /jemalloc.txt$/ { jemalloc[$1] = $2; next }
+	FILENAME ~ /mimalloc.txt$/ { mimalloc[$1] = $2; next }
+	{ order[++n] = $1; baseline[$1] = $2 }
+	END {
+		for (i = 1; i <= n; i++) {
+			key = order[i]
+			base = baseline[key]
+			printf "%-48s %11.4fs", key, base
+			if (key in jemalloc) printf " %11.4fs %+7.1f%%", jemalloc[key], (jemalloc[key] / base - 1) * 100
+			else printf " %12s %8s", "-", "-"
+			if (key in mimalloc) printf " %11.4fs %+7.1f%%", mimalloc[key], (mimalloc[key] / base - 1) * 100
+			else printf " %12s %8s", "-", "-"
+			printf "\n"
+		}
+	}' "$OUT/jemalloc.txt" "$OUT/mimalloc.txt" "$OUT/system.txt"
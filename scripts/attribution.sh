@@ -0,0 +1,75 @@
+#!/bin/sh
+# Attributes CPU samples of one example run to subprotocols by the modules on their stacks, giving
+# a breakdown even for backends without tracing spans.
+#
+# Usage: scripts/attribution.sh <example> [cargo args...]
+#        scripts/attribution.sh --data <perf.data>
+# The first form builds the example with frame pointers, records it with `perf record -g`, and
+# prints the share of samples per subprotocol. The second post-processes an existing recording.
+# Each sample goes to the innermost frame from a known module, so field arithmetic and hashing
+# count towards the subprotocol that called them; samples with no known frame count as `other`.
+# Recording needs /proc/sys/kernel/perf_event_paranoid at 1 or lower.
+set -e
+
+if [ $# -lt 1 ]; then
+	echo "usage: $0 <example> [cargo args...] | $0 --data <perf.data>" >&2
+	exit 1
+fi
+
+if [ "$1" = "--data" ]; then
+	DATA=$2
+else
+	EXAMPLE=$1
+	shift
+	OUT=$(mktemp -d)
+	trap 'rm -rf "$OUT"' EXIT
+	DATA=$OUT/perf.data
+	RUSTFLAGS="${RUSTFLAGS} -C force-frame-pointers=yes" \
+		cargo build --release --example "$EXAMPLE" "$@"
+	echo "== recording $EXAMPLE" >&2
+	perf record -g -F 999 -o "$DATA" -- "${CARGO_TARGET_DIR:-target}/release/examples/$EXAMPLE" >&2
+fi
+
+perf script -i "$DATA" -F ip,sym 2>/dev/null | awk '
+	BEGIN {
+		# Buckets in matching order, with more specific module paths first.
+		n = 0
+		name[++n] = "binius ring_switch"; prefix[n] = "binius_core::ring_switch"
+		name[++n] = "binius piop"; prefix[n] = "binius_core::piop"
+		name[++n] = "binius merkle_tree"; prefix[n] = "binius_core::merkle_tree"
+		name[++n] = "plonky3 dft"; prefix[n] = "p3_dft::"
+		name[++n] = "plonky3 merkle_tree"; prefix[n] = "p3_merkle_tree::"
+		name[++n] = "stwo fri"; prefix[n] = "stwo_prover::core::fri"
+		name[++n] = "plonky2 fri"; prefix[n] = "plonky2::fri"
+	}
+	# Frames are printed from the innermost outwards, with a blank line after each sample.
+	function finish_sample() {
+		if (!in_sample) return
+		total++
+		count[bucket == "" ? "other" : bucket]++
+		in_sample = 0
+		bucket = ""
+	}
+	/^[ \t]*$/ { finish_sample(); next }
+	{
+		in_sample = 1
+		if (bucket != "") next
+		for (i = 1; i <= n; i++) {
+			if (index($0, " " prefix[i]) || index($0, "<" prefix[i])) {
+				bucket = name[i]
+				break
+			}
+		}
+	}
+	END {
+		finish_sample()
+		if (total == 0) {
+			print "no samples recorded" > "/dev/stderr"
+			exit 1
+		}
+		printf "%-24s %10s %8s\n", "subprotocol", "samples", "share"
+		for (i = 1; i <= n; i++) {
+			if (name[i] in count) printf "%-24s %10d %7.1f%%\n", name[i], count[name[i]], count[name[i]] * 100 / total
+		}
+		printf "%-24s %10d %7.1f%%\n", "other", count["other"], count["other"] * 100 / total
+	}'
@@ -0,0 +1,70 @@
+#!/bin/sh
+# Runs one example with the `huge_pages` feature under each huge page mode, and prints the time
+# of every timed phase under each, with the change relative to base pages only.
+#
+# Usage: scripts/huge_pages.sh <example> [cargo args...]
+# Extra arguments are passed to cargo, e.g. `--features jemalloc`. The effect shows at 2^26
+# elements and above, so `huge_ntt` or the large sizes of a PCS example are the useful targets.
+# The `explicit` mode needs pages reserved in /proc/sys/vm/nr_hugepages beforehand.
+set -e
+
+if [ $# -lt 1 ]; then
+	echo "usage: $0 <example> [cargo args...]" >&2
+	exit 1
+fi
+EXAMPLE=$1
+shift
+
+OUT=$(mktemp -d)
+trap 'rm -rf "$OUT"' EXIT
+
+for mode in off thp explicit; do
+	echo "== $EXAMPLE with huge pages $mode" >&2
+	NO_COLOR=1 CLICOLOR=0 SNARK_BENCH_HUGE_PAGES=$mode \
+		cargo run --release --example "$EXAMPLE" --features huge_pages "$@" \
+		| tee "$OUT/$mode.log" >&2
+done
+
+# Extracts "<phase>#<occurrence> <seconds>" from the ark-std timer lines of a log.
+phases() {
+	sed 's/\x1b\[[0-9;]*m//g' "$1" | awk '
+		/End:/ {
+			line = $0
+			sub(/.*End:[ ]*/, "", line)
+			time = line
+			sub(/.*[^0-9.]\.+/, "", time)
+			if (time == line) next
+			label = substr(line, 1, length(line) - length(time))
+			sub(/\.+$/, "", label)
+			sub(/[ ]+$/, "", label)
+			if (time ~ /ns$/) scale = 1e-9
+			else if (time ~ /ms$/) scale = 1e-3
+			else if (time ~ /s$/ && time !~ /[0-9]s$/) scale = 1e-6
+			else scale = 1
+			gsub(/ /, "_", label)
+			count[label]++
+			printf "%s#%d %.9f\n", label, count[label], time * scale
+		}'
+}
+
+phases "$OUT/off.log" > "$OUT/off.txt"
+phases "$OUT/thp.log" > "$OUT/thp.txt"
+phases "$OUT/explicit.log" > "$OUT/explicit.txt"
+
+printf "%-48s %12s %12s %8s %12s %8s\n" phase off thp delta explicit delta
+awk '
+	FILENAME ~ /thp.txt$/ { thp[$1] = $2; next }
+	FILENAME ~ /explicit.txt$/ { explicit[$1] = $2; next }
+	{ order[++n] = $1; baseline[$1] = $2 }
+	END {
+		for (i = 1; i <= n; i++) {
+			key = order[i]
+			base = baseline[key]
+			printf "%-48s %11.4fs", key, base
+			if (key in thp) printf " %11.4fs %+7.1f%%", thp[key], (thp[key] / base - 1) * 100
+			else printf " %12s %8s", "-", "-"
+			if (key in explicit) printf " %11.4fs %+7.1f%%", explicit[key], (explicit[key] / base - 1) * 100
+			else printf " %12s %8s", "-", "-"
+			printf "\n"
+		}
+	}' "$OUT/thp.txt" "$OUT/explicit.txt" "$OUT/off.txt"
@@ -0,0 +1,38 @@
+#!/bin/sh
+# Cross-compiles the verifier-side benchmarks for riscv64 and runs them under qemu user-mode
+# emulation: the hash functions, the Merkle path verifications, and sumcheck verification. They
+# are what a verifier recursed inside a RISC-V zkVM runs, so their cost on RISC-V bounds what the
+# recursion costs. Results go to target/criterion-riscv64, apart from the native ones.
+#
+# Emulated times are not RISC-V hardware times, so compare them with each other, such as one hash
+# against another. On RISC-V hardware, run the benches natively instead. The riscv64gc target has
+# no vector extension, so every backend runs its portable code.
+#
+# Needs the target and a cross toolchain and qemu, such as on Debian or Ubuntu:
+#   rustup target add riscv64gc-unknown-linux-gnu
+#   apt install gcc-riscv64-linux-gnu qemu-user
+# RISCV_CC, QEMU, and RISCV_SYSROOT override the cross compiler, the emulator, and the sysroot the
+# emulator loads the C libraries from.
+#
+# Usage: scripts/riscv.sh [bench...]    (default: hashing merkle sumcheck_verify)
+set -e
+
+TARGET=riscv64gc-unknown-linux-gnu
+BENCHES=${*:-hashing merkle sumcheck_verify}
+
+export CARGO_TARGET_RISCV64GC_UNKNOWN_LINUX_GNU_LINKER="${RISCV_CC:-riscv64-linux-gnu-gcc}"
+export CARGO_TARGET_RISCV64GC_UNKNOWN_LINUX_GNU_RUNNER="${QEMU:-qemu-riscv64} -L ${RISCV_SYSROOT:-/usr/riscv64-linux-gnu}"
+# The bundled SQLite of the history database is C, built with the same cross compiler.
+export CC_riscv64gc_unknown_linux_gnu="${RISCV_CC:-riscv64-linux-gnu-gcc}"
+export CRITERION_HOME="$PWD/target/criterion-riscv64"
+
+for bench in $BENCHES; do
+	# Only the verification groups of the Merkle benches are measured, tree construction is not
+	# what a verifier runs.
+	filter=
+	if [ "$bench" = merkle ]; then
+		filter=verif
+	fi
+	echo "== riscv64: $bench"
+	cargo bench --target "$TARGET" --bench "$bench" -- $filter
+done
@@ -0,0 +1,38 @@
+#!/bin/sh
+# Runs the field and hashing microbenchmarks once per SIMD level, rebuilding with the target
+# features above that level disabled, since binius and plonky3 choose their code paths at compile
+# time. Each level is saved as a Criterion baseline named simd-<level> in target/criterion, and
+# each run prints the code paths it used.
+#
+# Usage: scripts/simd_levels.sh [bench...]    (default: field_ops hashing)
+set -e
+
+BENCHES=${*:-field_ops hashing}
+export CRITERION_HOME="$PWD/target/criterion"
+
+run_level() {
+	level=$1
+	flags=$2
+	for bench in $BENCHES; do
+		echo "== SIMD level $level: $bench"
+		RUSTFLAGS="-Ctarget-cpu=native $flags" CARGO_TARGET_DIR="target/simd-$level" \
+			cargo bench --bench "$bench" -- --save-baseline "simd-$level"
+	done
+}
+
+case $(uname -m) in
+x86_64)
+	run_level native ""
+	run_level avx2 "-Ctarget-feature=-avx512f,-gfni,-vpclmulqdq,-vaes"
+	run_level sse4 "-Ctarget-feature=-avx,-avx2,-fma,-avx512f,-gfni,-vpclmulqdq,-vaes"
+	;;
+aarch64 | arm64)
+	# NEON is part of the aarch64 baseline ABI and cannot be disabled, but binius only uses it
+	# together with the AES extension, so disabling AES measures its portable code.
+	run_level native ""
+	run_level noaes "-Ctarget-feature=-aes"
+	;;
+*)
+	run_level native ""
+	;;
+esac
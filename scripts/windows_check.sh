@@ -0,0 +1,20 @@
+#!/bin/sh
+# Type-checks the library, the snark_bench binary, and the examples for Windows. The Win32 paths of
+# the resource counters, the machine details, and the host name are never compiled by Linux or
+# macOS builds, so this is what keeps the Windows build working.
+#
+# Needs the target and, for the C sources of the bundled SQLite, a C compiler for it: the MSVC
+# build tools on Windows, or cargo-xwin elsewhere, which this script uses when it is installed:
+#   rustup target add x86_64-pc-windows-msvc
+#   cargo install cargo-xwin
+#
+# Usage: scripts/windows_check.sh [cargo check args...]
+set -e
+
+TARGET=x86_64-pc-windows-msvc
+
+if command -v cargo-xwin >/dev/null 2>&1; then
+	cargo xwin check --target "$TARGET" --lib --bins --examples "$@"
+else
+	cargo check --target "$TARGET" --lib --bins --examples "$@"
+fi
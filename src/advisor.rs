@@ -0,0 +1,145 @@
+// Copyright 2024 Irreducible Inc.
+
+//! First-order predictions of prover phases from the speed of their primitives on this machine.
+//!
+//! A commitment is mostly an NTT over the extended trace followed by hashing it into a Merkle
+//! tree, and an opening mostly field arithmetic over the same data plus more hashing. Each example
+//! measures its backend's own NTT butterflies, hash calls, and field multiplications with
+//! [`seconds_per_op`] into [`PrimitiveCosts`], counts how many of each a phase performs in a
+//! [`Workload`], and [`report_prediction`] compares the sum of their costs to the measured time. A
+//! phase that takes more than [`UNDERPERFORMANCE_RATIO`] times its primitive budget spends its time
+//! on something the model does not see, such as memory traffic, transposes, or serial sections,
+//! and is flagged.
+//!
+//! Every primitive is calibrated on a single-threaded rayon pool, so that parallel implementations
+//! run serially, and the prediction assumes they scale perfectly over the threads the backend runs
+//! on. Predictions are therefore optimistic, and the ratio to them is a measure of how far a phase
+//! is from its primitive budget rather than an expected slowdown.
+
+use std::{hint::black_box, time::Instant};
+
+use rayon::ThreadPoolBuilder;
+
+/// Ratio of measured to predicted time above which a phase is flagged.
+pub const UNDERPERFORMANCE_RATIO: f64 = 2.0;
+
+/// Timed calibration runs, of which the fastest is kept.
+const CALIBRATION_RUNS: usize = 5;
+
+/// Runs `f`, which performs `n_ops` operations, on one thread and returns the seconds per
+/// operation of the fastest of a few runs.
+pub fn seconds_per_op(n_ops: u64, mut f: impl FnMut() + Send) -> f64 {
+	let pool = ThreadPoolBuilder::new()
+		.num_threads(1)
+		.build()
+		.expect("failed to build the calibration thread pool");
+	let best = pool.install(|| {
+		(0..CALIBRATION_RUNS)
+			.map(|_| {
+				let start = Instant::now();
+				f();
+				start.elapsed().as_secs_f64()
+			})
+			.fold(f64::INFINITY, f64::min)
+	});
+	best / n_ops as f64
+}
+
+/// The number of butterflies of radix-2 NTTs of `2^log_n` elements over `batch_size` columns.
+pub fn ntt_butterflies(log_n: usize, batch_size: usize) -> u64 {
+	((batch_size as u64) << log_n.saturating_sub(1)) * log_n as u64
+}
+
+/// Seconds per primitive operation of one backend on this machine.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PrimitiveCosts {
+	/// Seconds per NTT butterfly.
+	pub butterfly: f64,
+	/// Seconds per hash call over leaf data.
+	pub digest: f64,
+	/// Seconds per hash call compressing two digests.
+	pub compression: f64,
+	/// Seconds per field multiplication.
+	pub field_mul: f64,
+	/// Threads the backend spreads its work over.
+	pub threads: usize,
+}
+
+/// Primitive operations performed by one phase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Workload {
+	/// NTT butterflies.
+	pub butterflies: u64,
+	/// Hash calls over leaf data.
+	pub digests: u64,
+	/// Hash calls compressing two digests.
+	pub compressions: u64,
+	/// Field multiplications.
+	pub field_muls: u64,
+}
+
+/// Predicted time of a phase, split by primitive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prediction {
+	/// Seconds in NTTs.
+	pub ntt: f64,
+	/// Seconds hashing.
+	pub hashing: f64,
+	/// Seconds in field arithmetic.
+	pub field: f64,
+}
+
+impl Prediction {
+	/// Total predicted seconds.
+	pub fn total(&self) -> f64 {
+		self.ntt + self.hashing + self.field
+	}
+}
+
+impl Workload {
+	/// Predicts the time of the workload from the costs of its primitives.
+	pub fn predict(&self, costs: &PrimitiveCosts) -> Prediction {
+		let threads = costs.threads as f64;
+		Prediction {
+			ntt: self.butterflies as f64 * costs.butterfly / threads,
+			hashing: (self.digests as f64 * costs.digest
+				+ self.compressions as f64 * costs.compression)
+				/ threads,
+			field: self.field_muls as f64 * costs.field_mul / threads,
+		}
+	}
+}
+
+/// Prints the predicted time of a phase next to its measured time, flagging phases slower than
+/// [`UNDERPERFORMANCE_RATIO`] times the prediction.
+pub fn report_prediction(
+	phase: &str,
+	workload: &Workload,
+	costs: &PrimitiveCosts,
+	measured_seconds: f64,
+) {
+	let prediction = workload.predict(costs);
+	let ratio = measured_seconds / prediction.total();
+	println!(
+		"Predicted {phase}: {:.4}s (ntt {:.4}s, hashing {:.4}s, field {:.4}s), measured {:.2}x{}",
+		prediction.total(),
+		prediction.ntt,
+		prediction.hashing,
+		prediction.field,
+		ratio,
+		if ratio > UNDERPERFORMANCE_RATIO {
+			" UNDERPERFORMS primitive budget"
+		} else {
+			""
+		}
+	);
+}
+
+/// Repeats `f` on its own output `n` times, for calibrating dependent chains of operations that
+/// the compiler cannot overlap or remove.
+pub fn chain<T>(n: u64, mut value: T, mut f: impl FnMut(T) -> T) -> T {
+	for _ in 0..n {
+		value = f(black_box(value));
+	}
+	value
+}
@@ -0,0 +1,78 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Side-by-side heap traffic of the backends, from runs built with the `alloc_count` feature.
+//!
+//! Two provers with the same wall time can differ tenfold in how they use the heap: one allocates
+//! its buffers once and reuses them, another allocates a vector per round or per Merkle layer and
+//! spends its time in the allocator and in page faults. [`write_allocations`] tabulates, for every
+//! phase and size, the allocations, bytes allocated, and peak heap of each backend and
+//! configuration, with the bytes allocated per element so that phases of different sizes compare.
+//! Values repeated across repetitions or runs are reported as their median.
+
+use std::{collections::BTreeMap, io};
+
+use bytesize::ByteSize;
+
+use crate::{
+	report::{label, median_of},
+	results::{Record, Results},
+};
+
+/// Writes one table per phase and size of the allocations of every backend and configuration.
+pub fn write_allocations(results: &[Results], mut out: impl io::Write) -> Result<(), String> {
+	type Values = (Vec<f64>, Vec<f64>, Vec<f64>);
+	let mut phases = BTreeMap::<(String, u64), BTreeMap<String, Values>>::new();
+	for run in results {
+		for record in &run.records {
+			let Record::Phase(phase) = record else {
+				continue;
+			};
+			let (Some(allocations), Some(allocated_bytes)) =
+				(phase.allocations, phase.allocated_bytes)
+			else {
+				continue;
+			};
+			let (counts, bytes, peaks) = phases
+				.entry((phase.phase.clone(), phase.n_elements))
+				.or_default()
+				.entry(label(&phase.backend, &phase.config))
+				.or_default();
+			counts.push(allocations as f64);
+			bytes.push(allocated_bytes as f64);
+			peaks.extend(phase.peak_heap_bytes.map(|peak| peak as f64));
+		}
+	}
+	if phases.is_empty() {
+		return Err("no phase counted its allocations, build with the alloc_count feature".into());
+	}
+
+	let mut write = || -> io::Result<()> {
+		for ((phase, n_elements), series) in &phases {
+			writeln!(out, "{phase}, n={n_elements}:")?;
+			let width = series.keys().map(String::len).max().unwrap_or(0);
+			writeln!(
+				out,
+				"  {:width$} {:>14} {:>12} {:>12} {:>12}",
+				"", "allocations", "allocated", "bytes/elem", "peak heap"
+			)?;
+			for (label, (counts, bytes, peaks)) in series {
+				let allocated_bytes = median_of(bytes);
+				let peak_heap = if peaks.is_empty() {
+					"-".to_string()
+				} else {
+					ByteSize(median_of(peaks) as u64).to_string()
+				};
+				writeln!(
+					out,
+					"  {label:width$} {:>14.0} {:>12} {:>12.2} {peak_heap:>12}",
+					median_of(counts),
+					ByteSize(allocated_bytes as u64).to_string(),
+					allocated_bytes / (*n_elements).max(1) as f64,
+				)?;
+			}
+			writeln!(out)?;
+		}
+		out.flush()
+	};
+	write().map_err(|err| format!("failed to write the allocation report: {err}"))
+}
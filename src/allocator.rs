@@ -0,0 +1,71 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Selection of the global allocator.
+//!
+//! Provers for large traces allocate and free many large buffers, so the allocator measurably
+//! shifts their performance. The `jemalloc` and `mimalloc` features replace the system allocator
+//! for every target linking this crate, and `scripts/allocators.sh` runs an example under each
+//! allocator and reports the difference per phase. The `huge_pages` feature maps large allocations
+//! onto huge pages in front of either, as described in [`crate::huge_pages`]. The selected
+//! allocator is installed directly, or behind [`crate::heap::PeakAllocator`] with the `alloc_count`
+//! feature, so that heap peaks can be measured under each of them. The `dhat` feature replaces all
+//! of them with the heap profiler of [`crate::heap_profile`].
+
+#[cfg(all(feature = "jemalloc", feature = "mimalloc"))]
+compile_error!("the `jemalloc` and `mimalloc` features select different allocators");
+
+#[cfg(all(
+	feature = "dhat",
+	any(
+		feature = "alloc_count",
+		feature = "jemalloc",
+		feature = "mimalloc",
+		feature = "huge_pages"
+	)
+))]
+compile_error!("the `dhat` feature profiles the system allocator and installs its own");
+
+/// The general-purpose allocator selected by the enabled features.
+#[cfg(feature = "jemalloc")]
+pub type BaseAllocator = tikv_jemallocator::Jemalloc;
+#[cfg(feature = "jemalloc")]
+pub const BASE_ALLOCATOR: BaseAllocator = tikv_jemallocator::Jemalloc;
+
+/// The general-purpose allocator selected by the enabled features.
+#[cfg(feature = "mimalloc")]
+pub type BaseAllocator = mimalloc::MiMalloc;
+#[cfg(feature = "mimalloc")]
+pub const BASE_ALLOCATOR: BaseAllocator = mimalloc::MiMalloc;
+
+/// The general-purpose allocator selected by the enabled features.
+#[cfg(not(any(feature = "jemalloc", feature = "mimalloc")))]
+pub type BaseAllocator = std::alloc::System;
+#[cfg(not(any(feature = "jemalloc", feature = "mimalloc")))]
+pub const BASE_ALLOCATOR: BaseAllocator = std::alloc::System;
+
+/// The allocator selected by the enabled features, with large allocations on huge pages.
+#[cfg(feature = "huge_pages")]
+pub type SelectedAllocator = crate::huge_pages::HugePageAllocator;
+#[cfg(feature = "huge_pages")]
+pub const SELECTED_ALLOCATOR: SelectedAllocator = crate::huge_pages::HugePageAllocator;
+
+/// The allocator selected by the enabled features.
+#[cfg(not(feature = "huge_pages"))]
+pub type SelectedAllocator = BaseAllocator;
+#[cfg(not(feature = "huge_pages"))]
+pub const SELECTED_ALLOCATOR: SelectedAllocator = BASE_ALLOCATOR;
+
+/// Name of the selected allocator.
+pub const ALLOCATOR_NAME: &str = if cfg!(feature = "jemalloc") {
+	"jemalloc"
+} else if cfg!(feature = "mimalloc") {
+	"mimalloc"
+} else {
+	"system"
+};
+
+/// Prints the selected allocator, and its huge page backing with the `huge_pages` feature.
+pub fn report_allocator() {
+	println!("Allocator: {ALLOCATOR_NAME}");
+	crate::huge_pages::report_huge_pages();
+}
@@ -0,0 +1,81 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Memory bandwidth baseline for roofline annotations.
+//!
+//! NTTs, folds, and Merkle leaf hashing stream through more data than fits in cache, so at large
+//! sizes their speed is bounded by memory bandwidth rather than arithmetic. [`peak_bandwidth`]
+//! runs a STREAM triad over arrays far larger than the last-level cache on every rayon thread,
+//! keeping the best of a few runs, and results are annotated with the fraction of it they reach.
+//! Those fractions count only the compulsory traffic of reading the input once, so they are lower
+//! bounds on the bandwidth a backend actually used.
+
+use std::{hint::black_box, mem::size_of, sync::OnceLock, time::Instant};
+
+use rayon::prelude::*;
+
+/// Elements of each triad array, 128 MiB of `f64`.
+const STREAM_ELEMENTS: usize = 1 << 24;
+
+/// Elements handled by each rayon task.
+const STREAM_CHUNK: usize = 1 << 14;
+
+/// Timed triad runs, of which the fastest is kept.
+const STREAM_RUNS: usize = 5;
+
+static PEAK_BANDWIDTH: OnceLock<f64> = OnceLock::new();
+
+/// Runs a STREAM triad, `a[i] = b[i] + s * c[i]`, and returns the bandwidth it reached in bytes
+/// per second, counting one read of `b` and `c` and one write of `a` per element.
+pub fn measure_bandwidth() -> f64 {
+	// Fill the arrays in parallel so that their pages are spread like the triad's accesses.
+	let fill = |value: f64| {
+		(0..STREAM_ELEMENTS)
+			.into_par_iter()
+			.map(|_| value)
+			.collect::<Vec<_>>()
+	};
+	let mut a = fill(0.0);
+	let b = fill(1.0);
+	let c = fill(2.0);
+	let scalar = black_box(3.0);
+
+	let best = (0..STREAM_RUNS)
+		.map(|_| {
+			let start = Instant::now();
+			a.par_chunks_mut(STREAM_CHUNK)
+				.zip(b.par_chunks(STREAM_CHUNK))
+				.zip(c.par_chunks(STREAM_CHUNK))
+				.for_each(|((a, b), c)| {
+					for ((a, b), c) in a.iter_mut().zip(b).zip(c) {
+						*a = b + scalar * c;
+					}
+				});
+			black_box(&a);
+			start.elapsed().as_secs_f64()
+		})
+		.fold(f64::INFINITY, f64::min);
+	(3 * STREAM_ELEMENTS * size_of::<f64>()) as f64 / best
+}
+
+/// The peak memory bandwidth in bytes per second, measured on the first call.
+pub fn peak_bandwidth() -> f64 {
+	*PEAK_BANDWIDTH.get_or_init(measure_bandwidth)
+}
+
+/// The peak memory bandwidth, if it has been measured, so that annotations do not trigger the
+/// measurement in the middle of a benchmark.
+pub fn measured_peak_bandwidth() -> Option<f64> {
+	PEAK_BANDWIDTH.get().copied()
+}
+
+/// The fraction of peak bandwidth reached by moving `bytes` in `seconds`, if the peak has been
+/// measured.
+pub fn bandwidth_fraction(bytes: f64, seconds: f64) -> Option<f64> {
+	measured_peak_bandwidth().map(|peak| bytes / seconds / peak)
+}
+
+/// Measures and prints the peak memory bandwidth.
+pub fn report_bandwidth() {
+	println!("Memory bandwidth (STREAM triad): {:.1} GB/s", peak_bandwidth() / 1e9);
+	println!();
+}
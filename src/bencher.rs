@@ -0,0 +1,78 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Export of the results in the Bencher Metric Format (BMF), for hosted continuous benchmarking.
+//!
+//! BMF is a JSON object from benchmark names to measures, each with a value and optional lower and
+//! upper bounds. Every phase of every configuration becomes a benchmark named
+//! `<backend>/<config>/<phase>/<n>`, whose wall time is reported under Bencher's built-in
+//! `latency` measure in nanoseconds, and whose memory and allocations under `peak-rss`,
+//! `average-rss`, `allocations`, `allocated`, and `peak-heap`. The metrics of a configuration, such
+//! as `proof_size`, become measures of the benchmark `<backend>/<config>/<n>`, with underscores in
+//! their names replaced by dashes as Bencher's measure slugs require. Bencher creates the measures
+//! it does not know on upload; their units are those of [`crate::report::Row`]. Values repeated
+//! across repetitions or runs are reported as their median, bounded by their minimum and maximum.
+
+use std::{collections::BTreeMap, io};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+	report::{rows, Row},
+	results::Results,
+	stability::median,
+};
+
+/// The benchmark name of a row.
+fn benchmark_name(row: &Row) -> String {
+	let mut parts = vec![row.backend.as_str()];
+	if !row.config.is_empty() {
+		parts.push(&row.config);
+	}
+	if let Some(phase) = &row.phase {
+		parts.push(phase);
+	}
+	let mut name = parts.join("/");
+	if let Some(n) = row.n_elements {
+		name = format!("{name}/{n}");
+	}
+	name
+}
+
+/// The measure slug and scale of a row's value, converting wall times to Bencher's nanoseconds.
+fn measure(row: &Row) -> (String, f64) {
+	match row.metric.as_str() {
+		"seconds" => ("latency".to_string(), 1e9),
+		metric => (metric.replace('_', "-"), 1.0),
+	}
+}
+
+/// Writes the results as a Bencher Metric Format document.
+pub fn write_bencher(results: &[Results], mut out: impl io::Write) -> io::Result<()> {
+	let mut values = BTreeMap::<(String, String), Vec<f64>>::new();
+	for row in rows(results) {
+		let (measure, scale) = measure(&row);
+		values
+			.entry((benchmark_name(&row), measure))
+			.or_default()
+			.push(row.value * scale);
+	}
+
+	let mut document = Map::new();
+	for ((benchmark, measure), mut values) in values {
+		values.sort_by(f64::total_cmp);
+		let mut value = json!({"value": median(&values)});
+		if values.len() > 1 {
+			value["lower_value"] = json!(values[0]);
+			value["upper_value"] = json!(values[values.len() - 1]);
+		}
+		document
+			.entry(benchmark)
+			.or_insert_with(|| Value::Object(Map::new()))
+			.as_object_mut()
+			.unwrap()
+			.insert(measure, value);
+	}
+	serde_json::to_writer_pretty(&mut out, &document)?;
+	writeln!(out)?;
+	out.flush()
+}
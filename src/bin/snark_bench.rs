@@ -0,0 +1,1076 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Command-line tools over the results files written by the examples.
+//!
+//! Run `snark_bench help` for the subcommands and `snark_bench help <subcommand>` for the options
+//! of each.
+//!
+//! `report` reads the results files or directories given, or the directory in `SNARK_BENCH_RESULTS`
+//! without any. CSV, JSON, and speedup reports are written to standard output by default, HTML
+//! reports to `report.html` in the first results directory, and summaries to `SUMMARY.md` there.
+//! Speedup reports compare against the series named by `--baseline`, such as `plonky3-keccak`, or
+//! the first series that ran. LaTeX reports tabulate the phases and metrics listed by `--columns`,
+//! separated by commas, or every phase and the proof size, with values rounded to `--precision`
+//! decimal places. Efficiency reports recommend, for every phase that ran at several thread counts,
+//! the most cores it used before adding more delivered less than `--min-efficiency` percent of
+//! their ideal speedup, 50% by default. Allocation reports compare the heap traffic of the backends
+//! in every phase, from runs built with the `alloc_count` feature.
+//!
+//! `history` prints a metric of a backend across the runs stored in the database in
+//! `SNARK_BENCH_HISTORY` by default, such as `--metric prove_time` for the wall time of the `prove`
+//! phase.
+//!
+//! `baseline` designates a stored run, the latest one by default, as a named baseline, and `check`
+//! compares the latest other run of the same example against it. `check` exits with status 1 if
+//! any phase regressed by more than the threshold, 5% by default, so that it can gate upgrades of
+//! the proving libraries in CI.
+//!
+//! `diff` compares a later run, by default the latest other run of the same example, against an
+//! earlier one backend by backend, next to the dependencies whose locked commits changed between
+//! them, to tell which upstream bump a regression came with.
+//!
+//! `compare` does the same as `check` for two results files or directories rather than runs in the
+//! database, and also fails if the proof of any configuration grew by more than the threshold.
+//!
+//! `import-criterion` reads the results of the Criterion benches, from `target/criterion` by
+//! default, and writes them as a run of the `criterion` pseudo-example to the results directory
+//! and history database, `SNARK_BENCH_RESULTS` and `SNARK_BENCH_HISTORY` by default.
+//!
+//! `isa` rebuilds and runs the given examples and benches once per instruction set level of the
+//! CPU, or the levels listed by `--levels`, and prints the speedup of each level for every backend,
+//! see [`ulvt_snark_bench::isa`]. The results of each level are written under
+//! `<output>/isa-<unix time>/<level>`, where the output is `SNARK_BENCH_RESULTS` or `target/isa`
+//! by default.
+//!
+//! `numa` runs the given examples and benches, or the large PCS examples by default, first on the
+//! CPUs and memory of one NUMA node, node 0 unless `--node` says otherwise, and then on all CPUs
+//! with memory interleaved over all nodes. It prints the speedup of every backend from the second
+//! placement, next to the ideal speedup from the ratio of CPUs, see [`ulvt_snark_bench::numa`].
+//! The results of each placement are written under `<output>/numa-<unix time>/<placement>`, where
+//! the output is `SNARK_BENCH_RESULTS` or `target/numa` by default.
+//!
+//! `memcap` runs the given examples, or the large PCS examples by default, once under each memory
+//! cap listed by `--caps`, in GiB or with a unit, 16, 32, and 64 GiB by default. It prints, for
+//! every configuration and trace size, whether it completed, swapped, or ran out of memory under
+//! each cap, and the smallest cap it completed under, see [`ulvt_snark_bench::memory_cap`]. The
+//! caps are applied with cgroups created under the directory given by `--cgroup`, or with
+//! `RLIMIT_DATA` without it. The results of each cap are written under
+//! `<output>/memcap-<unix time>/<cap>`, where the output is `SNARK_BENCH_RESULTS` or
+//! `target/memcap` by default.
+//!
+//! `smt` runs the given examples and benches first on one hardware thread of every physical core
+//! and then on all logical CPUs, and prints the speedup SMT gives every backend and phase, see
+//! [`ulvt_snark_bench::smt`]. The results of each CPU set are written under
+//! `<output>/smt-<unix time>/<physical or logical>`, where the output is `SNARK_BENCH_RESULTS` or
+//! `target/smt` by default.
+//!
+//! `<system>` runs the benchmarks of one proof system, `fri-binius`, `plonky3-fri`, `plonky2-fri`,
+//! `stwo`, `jolt-hyrax`, `sumcheck`, or `ntt`, in this process, see [`ulvt_snark_bench::systems`].
+//! The parameter options replace the sizes and parameters the examples sweep, see
+//! [`ulvt_snark_bench::params`], and the others, such as `--output ndjson`, work as they do when
+//! the examples are run directly. `--threads` takes a list of thread counts, such as `1,2,4,max`,
+//! where `max` is the available parallelism, and runs the benchmarks once in a rayon pool of each
+//! count. It prints the speedup of every count over the first, and the marginal efficiency of every
+//! step, see [`ulvt_snark_bench::core_efficiency`]. The results of each count are written under
+//! `<output>/thread-<unix time>/threads=<count>`, where the output is `SNARK_BENCH_RESULTS` or
+//! `target/threads` by default.
+//!
+//! `suite` runs the proof systems and parameters listed in a TOML file in one invocation, each as
+//! its subcommand would, thread count sweeps included, see [`ulvt_snark_bench::suite`].
+//!
+//! `isa`, `numa`, `memcap`, and `smt` compare settings that only take effect for a whole process
+//! or build, so they run the example and bench targets in child `cargo` processes, see
+//! [`ulvt_snark_bench::variants`], and need the source checkout and a toolchain where they run.
+
+use std::{
+	env,
+	ffi::OsStr,
+	fs::File,
+	io, iter,
+	path::{Path, PathBuf},
+	process, slice,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::{Args, Parser, Subcommand};
+use rayon::ThreadPoolBuilder;
+use ulvt_snark_bench::{
+	core_efficiency::{write_core_efficiency, DEFAULT_MIN_EFFICIENCY},
+	criterion_import::{load_criterion, DEFAULT_CRITERION_DIR},
+	environment::machine_mismatches,
+	history::{
+		append_history, baseline_run, latest_run_like, open_history, query_history, run_revisions,
+		run_rows, set_baseline, write_history, HISTORY_DB_VAR,
+	},
+	isa::{run_at_level, supported_levels, ISA_LEVELS},
+	memory_cap::{
+		cap_name, parse_cap, physical_memory, run_capped, write_instance_table, CapMethod,
+		INSTANCE_SIZES_GIB,
+	},
+	numa::{numa_nodes, NumaStudy, NUMA_VAR},
+	regression::{backend_changes, compare_phases, compare_proof_sizes},
+	report::{rows, write_report, ReportFormat, ReportOptions},
+	results::{load_results, Record, Results, RESULTS_DIR_VAR},
+	revisions::diff_revisions,
+	security::Soundness,
+	smt::{with_cpus, SmtStudy},
+	suite::Suite,
+	systems::{run_example, system, System},
+	threads::parse_thread_counts,
+	variants::{run_variant, write_variant_table, BenchTarget},
+};
+
+/// Directory `isa` writes the results of each level under, without `SNARK_BENCH_RESULTS`.
+const DEFAULT_ISA_DIR: &str = "target/isa";
+
+/// Directory `numa` writes the results of each placement under, without `SNARK_BENCH_RESULTS`.
+const DEFAULT_NUMA_DIR: &str = "target/numa";
+
+/// Directory `memcap` writes the results of each cap under, without `SNARK_BENCH_RESULTS`.
+const DEFAULT_MEMCAP_DIR: &str = "target/memcap";
+
+/// Directory `smt` writes the results of each CPU set under, without `SNARK_BENCH_RESULTS`.
+const DEFAULT_SMT_DIR: &str = "target/smt";
+
+/// Directory `<system> --threads` writes the results of each thread count under, without
+/// `SNARK_BENCH_RESULTS`.
+const DEFAULT_THREADS_DIR: &str = "target/threads";
+
+/// The large PCS examples that `numa` and `memcap` run by default.
+const LARGE_PCS_EXAMPLES: [&str; 4] = [
+	"fri_binius_pcs",
+	"plonky3_fri_pcs",
+	"goldilocks_fri_pcs",
+	"jolt_hyrax_pcs",
+];
+
+/// Name of the baseline that `baseline` sets and `check` compares against by default.
+const DEFAULT_BASELINE: &str = "default";
+
+/// Largest slowdown of a phase that `check` tolerates by default, in percent.
+const DEFAULT_THRESHOLD_PERCENT: f64 = 5.0;
+
+/// Runs the benchmarks, and reports, stores, and compares their results.
+#[derive(Parser)]
+#[command(name = "snark_bench")]
+struct Cli {
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Writes a report of the results.
+	Report(ReportArgs),
+	/// Prints a metric of a backend across the runs in the history database.
+	History(HistoryArgs),
+	/// Designates a stored run as a named baseline.
+	Baseline(BaselineArgs),
+	/// Compares the latest run against a baseline, exiting with status 1 if a phase regressed.
+	Check(CheckArgs),
+	/// Compares two runs backend by backend, next to the dependency revisions that changed.
+	Diff(DiffArgs),
+	/// Compares two results files or directories, exiting with status 1 if anything regressed.
+	Compare(CompareArgs),
+	/// Imports the results of the Criterion benches as a run of the `criterion` pseudo-example.
+	ImportCriterion(ImportCriterionArgs),
+	/// Runs examples and benches once per ISA level and prints the speedup of each level.
+	Isa(IsaArgs),
+	/// Runs examples and benches on one NUMA node, then on all, and prints the speedup.
+	Numa(NumaArgs),
+	/// Runs examples under memory caps and prints the smallest cap each configuration fits in.
+	Memcap(MemcapArgs),
+	/// Runs examples and benches with one thread per core, then on all logical CPUs.
+	Smt(SmtArgs),
+	/// Runs the proof systems and parameters listed in a TOML file.
+	Suite {
+		/// The suite file.
+		path: PathBuf,
+	},
+	/// Runs the binius FRI PCS benchmarks.
+	FriBinius(SystemArgs),
+	/// Runs the Plonky3 FRI PCS benchmarks.
+	#[command(name = "plonky3-fri")]
+	Plonky3Fri(SystemArgs),
+	/// Runs the plonky2 FRI PCS benchmarks.
+	#[command(name = "plonky2-fri")]
+	Plonky2Fri(SystemArgs),
+	/// Runs the stwo PCS benchmarks, if built with the `stwo` feature.
+	Stwo(SystemArgs),
+	/// Runs the jolt Hyrax PCS benchmarks.
+	JoltHyrax(SystemArgs),
+	/// Runs the binius and jolt sumcheck benchmarks.
+	Sumcheck(SystemArgs),
+	/// Runs the large NTT benchmarks.
+	Ntt(SystemArgs),
+}
+
+/// Warns on standard error when the results were taken on machines that differ, so that their
+/// numbers are not mixed unknowingly.
+fn warn_mixed_machines(results: &[Results]) {
+	let mismatches = machine_mismatches(results);
+	if mismatches.is_empty() {
+		return;
+	}
+	eprintln!("Warning: the results were taken on different machines");
+	for mismatch in mismatches {
+		eprintln!("  {mismatch}");
+	}
+}
+
+/// The results files or directories named by `paths`, or the results directory of the
+/// environment if there are none.
+fn results_paths(paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, String> {
+	if !paths.is_empty() {
+		return Ok(paths);
+	}
+	env::var(RESULTS_DIR_VAR)
+		.map(|dir| vec![PathBuf::from(dir)])
+		.map_err(|_| format!("no results given and {RESULTS_DIR_VAR} is not set"))
+}
+
+#[derive(Args)]
+struct ReportArgs {
+	/// Format of the report: csv, json, html, speedup, google-benchmark, bencher, latex,
+	/// throughput, summary, efficiency, or allocations.
+	#[arg(long, default_value = "csv")]
+	format: ReportFormat,
+	/// Series that speedup reports compare against, such as `plonky3-keccak`.
+	#[arg(long)]
+	baseline: Option<String>,
+	/// Phases and metrics that LaTeX reports tabulate, separated by commas.
+	#[arg(long, value_delimiter = ',')]
+	columns: Option<Vec<String>>,
+	/// Decimal places of the values of LaTeX reports.
+	#[arg(long)]
+	precision: Option<usize>,
+	/// Marginal efficiency in percent below which efficiency reports stop adding cores.
+	#[arg(long)]
+	min_efficiency: Option<f64>,
+	/// File to write the report to.
+	#[arg(long)]
+	output: Option<PathBuf>,
+	/// Results files or directories.
+	paths: Vec<PathBuf>,
+}
+
+fn report(args: ReportArgs) -> Result<(), String> {
+	let options = ReportOptions {
+		baseline: args.baseline,
+		columns: args.columns.map(|columns| {
+			columns
+				.iter()
+				.map(|column| column.trim().to_string())
+				.collect()
+		}),
+		precision: args.precision,
+		min_efficiency: args.min_efficiency.map(|percent| percent / 100.0),
+	};
+	let format = args.format;
+	let mut output = args.output;
+
+	let paths = results_paths(args.paths)?;
+	let results = load_results(&paths)?;
+	warn_mixed_machines(&results);
+	if output.is_none() {
+		output = format.default_file_name().and_then(|name| {
+			let dir = paths.iter().find(|path| path.is_dir())?;
+			Some(dir.join(name))
+		});
+	}
+	match &output {
+		Some(path) => {
+			let file = File::create(path)
+				.map_err(|err| format!("failed to create {}: {err}", path.display()))?;
+			write_report(&results, format, &options, file)?;
+		}
+		None => write_report(&results, format, &options, io::stdout().lock())?,
+	}
+	if let Some(path) = output {
+		eprintln!("Wrote the report to {}", path.display());
+	}
+	Ok(())
+}
+
+/// The history database named by `db`, or the one of the environment if there is none.
+fn history_db(db: Option<PathBuf>) -> Result<PathBuf, String> {
+	let db = db
+		.or_else(|| env::var(HISTORY_DB_VAR).ok().map(PathBuf::from))
+		.ok_or_else(|| format!("no database given and {HISTORY_DB_VAR} is not set"))?;
+	if !db.exists() {
+		return Err(format!("{} does not exist", db.display()));
+	}
+	Ok(db)
+}
+
+#[derive(Args)]
+struct HistoryArgs {
+	/// Backend whose metric to print.
+	#[arg(long)]
+	backend: String,
+	/// Metric to print, such as `prove_time` for the wall time of the `prove` phase.
+	#[arg(long)]
+	metric: String,
+	/// Host name of the machine whose runs to print.
+	#[arg(long)]
+	machine: Option<String>,
+	/// History database, `SNARK_BENCH_HISTORY` by default.
+	#[arg(long)]
+	db: Option<PathBuf>,
+}
+
+fn history(args: HistoryArgs) -> Result<(), String> {
+	let HistoryArgs {
+		backend,
+		metric,
+		machine,
+		db,
+	} = args;
+	let db = history_db(db)?;
+	let points = query_history(&open_history(&db)?, &backend, &metric, machine.as_deref())?;
+	if points.is_empty() {
+		return Err(format!("no values of {metric} for {backend} in {}", db.display()));
+	}
+	write_history(&backend, &points, io::stdout().lock())
+		.map_err(|err| format!("failed to write the history: {err}"))
+}
+
+#[derive(Args)]
+struct BaselineArgs {
+	/// Name of the baseline.
+	#[arg(long, default_value = DEFAULT_BASELINE)]
+	name: String,
+	/// History database, `SNARK_BENCH_HISTORY` by default.
+	#[arg(long)]
+	db: Option<PathBuf>,
+	/// The run to designate, the latest one by default.
+	run_id: Option<String>,
+}
+
+fn baseline(args: BaselineArgs) -> Result<(), String> {
+	let conn = open_history(&history_db(args.db)?)?;
+	let run_id = set_baseline(&conn, &args.name, args.run_id.as_deref())?;
+	println!("Baseline {} is {run_id}", args.name);
+	Ok(())
+}
+
+/// Parses a threshold in percent, with or without a trailing `%`, into a fraction.
+fn parse_threshold(value: &str) -> Result<f64, String> {
+	let percent = value
+		.strip_suffix('%')
+		.unwrap_or(value)
+		.parse::<f64>()
+		.map_err(|err| format!("invalid threshold {value:?}: {err}"))?;
+	if !percent.is_finite() || percent < 0.0 {
+		return Err(format!("invalid threshold {value:?}: must be a non-negative number"));
+	}
+	Ok(percent / 100.0)
+}
+
+#[derive(Args)]
+struct CheckArgs {
+	/// Name of the baseline to compare against.
+	#[arg(long = "baseline", default_value = DEFAULT_BASELINE)]
+	name: String,
+	/// The run to compare, the latest other run of the same example by default.
+	#[arg(long = "run")]
+	run_id: Option<String>,
+	/// Largest slowdown in percent that is not a regression, 5% by default.
+	#[arg(long, value_parser = parse_threshold)]
+	threshold: Option<f64>,
+	/// History database, `SNARK_BENCH_HISTORY` by default.
+	#[arg(long)]
+	db: Option<PathBuf>,
+}
+
+/// Compares the latest run against a baseline and returns whether no phase regressed.
+fn check(args: CheckArgs) -> Result<bool, String> {
+	let name = args.name;
+	let threshold = args.threshold.unwrap_or(DEFAULT_THRESHOLD_PERCENT / 100.0);
+	let conn = open_history(&history_db(args.db)?)?;
+	let baseline_id = baseline_run(&conn, &name)?;
+	let run_id = match args.run_id {
+		Some(run_id) => run_id,
+		None => latest_run_like(&conn, &baseline_id)?,
+	};
+	let changes = compare_phases(&run_rows(&conn, &baseline_id)?, &run_rows(&conn, &run_id)?);
+	if changes.is_empty() {
+		return Err(format!("{run_id} has no phases in common with {baseline_id}"));
+	}
+
+	println!("Comparing {run_id} against baseline {name} ({baseline_id}):");
+	let mut regressions = 0;
+	for change in &changes {
+		if change.is_regression(threshold) {
+			regressions += 1;
+			println!("  {change}  REGRESSED");
+		} else {
+			println!("  {change}");
+		}
+	}
+	println!(
+		"{regressions} of {} phases regressed by more than {}%",
+		changes.len(),
+		threshold * 100.0
+	);
+	Ok(regressions == 0)
+}
+
+#[derive(Args)]
+struct CompareArgs {
+	/// Results file or directory to compare against.
+	#[arg(long)]
+	baseline: PathBuf,
+	/// Results file or directory to compare.
+	#[arg(long)]
+	current: PathBuf,
+	/// Largest slowdown or growth in percent that is not a regression, 5% by default.
+	#[arg(long, value_parser = parse_threshold)]
+	threshold: Option<f64>,
+}
+
+/// Compares the results in `--current` against those in `--baseline` and returns whether no phase
+/// got slower and no proof grew.
+fn compare(args: CompareArgs) -> Result<bool, String> {
+	let CompareArgs {
+		baseline,
+		current,
+		threshold,
+	} = args;
+	let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD_PERCENT / 100.0);
+
+	let mut results = load_results(slice::from_ref(&baseline))?;
+	let baseline_runs = results.len();
+	results.extend(load_results(slice::from_ref(&current))?);
+	warn_mixed_machines(&results);
+	let baseline_rows = rows(&results[..baseline_runs]);
+	let current_rows = rows(&results[baseline_runs..]);
+	let changes = compare_phases(&baseline_rows, &current_rows);
+	let sizes = compare_proof_sizes(&baseline_rows, &current_rows);
+	if changes.is_empty() && sizes.is_empty() {
+		return Err(format!(
+			"{} has no phases or proof sizes in common with {}",
+			current.display(),
+			baseline.display()
+		));
+	}
+
+	println!("Comparing {} against {}:", current.display(), baseline.display());
+	let mut regressions = 0;
+	let lines = changes
+		.iter()
+		.map(|change| (change.to_string(), change.is_regression(threshold)))
+		.chain(
+			sizes
+				.iter()
+				.map(|change| (change.to_string(), change.is_regression(threshold))),
+		);
+	for (line, regressed) in lines {
+		if regressed {
+			regressions += 1;
+			println!("  {line}  REGRESSED");
+		} else {
+			println!("  {line}");
+		}
+	}
+	println!(
+		"{regressions} of {} phases and proof sizes regressed by more than {}%",
+		changes.len() + sizes.len(),
+		threshold * 100.0
+	);
+	Ok(regressions == 0)
+}
+
+#[derive(Args)]
+struct DiffArgs {
+	/// Largest slowdown in percent that is not a regression, 5% by default.
+	#[arg(long, value_parser = parse_threshold)]
+	threshold: Option<f64>,
+	/// History database, `SNARK_BENCH_HISTORY` by default.
+	#[arg(long)]
+	db: Option<PathBuf>,
+	/// The earlier run.
+	before: String,
+	/// The later run, the latest other run of the same example by default.
+	after: Option<String>,
+}
+
+/// Compares two runs backend by backend, alongside the changes of their dependency revisions.
+fn diff(args: DiffArgs) -> Result<(), String> {
+	let threshold = args.threshold.unwrap_or(DEFAULT_THRESHOLD_PERCENT / 100.0);
+	let conn = open_history(&history_db(args.db)?)?;
+	let before_id = args.before;
+	let after_id = match args.after {
+		Some(after_id) => after_id,
+		None => latest_run_like(&conn, &before_id)?,
+	};
+	let revisions =
+		diff_revisions(&run_revisions(&conn, &before_id)?, &run_revisions(&conn, &after_id)?);
+	let changes = compare_phases(&run_rows(&conn, &before_id)?, &run_rows(&conn, &after_id)?);
+	if changes.is_empty() {
+		return Err(format!("{after_id} has no phases in common with {before_id}"));
+	}
+
+	println!("Comparing {after_id} against {before_id}:");
+	if revisions.is_empty() {
+		println!("No dependency revisions changed");
+	} else {
+		println!("Changed dependency revisions:");
+		for revision in &revisions {
+			println!("  {}: {revision}", revision.dependency);
+		}
+	}
+	for backend in backend_changes(changes, &revisions) {
+		let regressions = backend.regressions(threshold).collect::<Vec<_>>();
+		let revision = match &backend.revision {
+			Some(revision) => format!("revision {revision}"),
+			None => "revision unchanged".to_string(),
+		};
+		println!(
+			"\n{}: {revision}, {:.3}x geometric mean over {} phases, {} regressed by more than {}%",
+			backend.backend,
+			backend.mean_ratio(),
+			backend.phases.len(),
+			regressions.len(),
+			threshold * 100.0
+		);
+		if !regressions.is_empty() && backend.revision.is_some() {
+			println!("  The regressions came with the revision change of {}", backend.backend);
+		}
+		for change in regressions {
+			println!("  {change}  REGRESSED");
+		}
+	}
+	Ok(())
+}
+
+#[derive(Args)]
+struct ImportCriterionArgs {
+	/// Results directory to write the run to, `SNARK_BENCH_RESULTS` by default.
+	#[arg(long)]
+	results: Option<PathBuf>,
+	/// History database to append the run to, `SNARK_BENCH_HISTORY` by default.
+	#[arg(long)]
+	db: Option<PathBuf>,
+	/// Criterion directory to read.
+	#[arg(default_value = DEFAULT_CRITERION_DIR)]
+	criterion_dir: PathBuf,
+}
+
+fn import_criterion(args: ImportCriterionArgs) -> Result<(), String> {
+	let results_dir = args
+		.results
+		.or_else(|| env::var(RESULTS_DIR_VAR).ok().map(PathBuf::from));
+	let db = args
+		.db
+		.or_else(|| env::var(HISTORY_DB_VAR).ok().map(PathBuf::from));
+	let criterion_dir = args.criterion_dir;
+	if results_dir.is_none() && db.is_none() {
+		return Err(format!(
+			"no results directory or database given, and neither {RESULTS_DIR_VAR} nor \
+			 {HISTORY_DB_VAR} is set"
+		));
+	}
+
+	let results = load_criterion(&criterion_dir)?;
+	let benchmarks = results
+		.records
+		.iter()
+		.filter(|record| matches!(record, Record::Phase(_)))
+		.count();
+	println!("Read {benchmarks} Criterion benchmarks from {}", criterion_dir.display());
+	if let Some(dir) = results_dir {
+		let path = results.write_to_dir(&dir)?;
+		println!("Wrote results to {}", path.display());
+	}
+	if let Some(db) = db {
+		append_history(&db, &results)?;
+		println!("Appended results to {}", db.display());
+	}
+	Ok(())
+}
+
+/// Options of the subcommands that run the targets under several settings.
+#[derive(Args)]
+struct VariantArgs {
+	/// Cargo features to build the targets with.
+	#[arg(long)]
+	features: Option<String>,
+	/// Directory to write the results under, `SNARK_BENCH_RESULTS` by default.
+	#[arg(long)]
+	output: Option<PathBuf>,
+}
+
+impl VariantArgs {
+	/// The directory to write the results under, or `default` without `--output` and
+	/// `SNARK_BENCH_RESULTS`.
+	fn output_dir(&self, default: &str) -> PathBuf {
+		self.output.clone().unwrap_or_else(|| {
+			env::var(RESULTS_DIR_VAR).map_or_else(|_| PathBuf::from(default), PathBuf::from)
+		})
+	}
+}
+
+/// The example and bench targets to run.
+#[derive(Args)]
+struct TargetArgs {
+	/// Example to run, repeatable.
+	#[arg(long = "example", value_name = "NAME")]
+	examples: Vec<String>,
+	/// Bench to run, repeatable.
+	#[arg(long = "bench", value_name = "NAME")]
+	benches: Vec<String>,
+}
+
+impl TargetArgs {
+	/// The examples and then the benches.
+	fn targets(self) -> Vec<BenchTarget> {
+		let examples = self.examples.into_iter().map(BenchTarget::Example);
+		examples
+			.chain(self.benches.into_iter().map(BenchTarget::Bench))
+			.collect()
+	}
+}
+
+#[derive(Args)]
+struct IsaArgs {
+	/// ISA levels to run, separated by commas, every level this CPU supports by default.
+	#[arg(long, value_delimiter = ',')]
+	levels: Vec<String>,
+	#[command(flatten)]
+	variant: VariantArgs,
+	#[command(flatten)]
+	targets: TargetArgs,
+}
+
+/// Runs the examples and benches once per ISA level and prints the speedup of each level.
+fn isa(args: IsaArgs) -> Result<(), String> {
+	let output = args.variant.output_dir(DEFAULT_ISA_DIR);
+	let features = args.variant.features;
+	let targets = args.targets.targets();
+	if targets.is_empty() {
+		return Err("isa needs at least one --example or --bench".to_string());
+	}
+
+	let supported = supported_levels();
+	let levels = if args.levels.is_empty() {
+		supported
+	} else {
+		args.levels
+			.iter()
+			.map(|name| {
+				let level = ISA_LEVELS
+					.iter()
+					.find(|level| level.name == name)
+					.ok_or_else(|| {
+						let names = ISA_LEVELS
+							.iter()
+							.map(|level| level.name)
+							.collect::<Vec<_>>();
+						format!("unknown ISA level {name:?}, expected one of {names:?}")
+					})?;
+				if !supported.contains(level) {
+					return Err(format!("this CPU does not support ISA level {name}"));
+				}
+				Ok(*level)
+			})
+			.collect::<Result<Vec<_>, String>>()?
+	};
+
+	let names = levels
+		.iter()
+		.map(|level| level.name.to_string())
+		.collect::<Vec<_>>();
+	compare_variants("ISA level", &names, &targets, &output, |index, target, dir| {
+		run_at_level(&levels[index], target, features.as_deref(), dir)
+	})
+	.map(drop)
+}
+
+#[derive(Args)]
+struct NumaArgs {
+	/// NUMA node to run on first.
+	#[arg(long, default_value_t = 0)]
+	node: usize,
+	#[command(flatten)]
+	variant: VariantArgs,
+	#[command(flatten)]
+	targets: TargetArgs,
+}
+
+/// Runs the examples and benches on the CPUs and memory of one NUMA node, then on all CPUs with
+/// memory interleaved over all nodes, and prints the speedup of the second placement.
+fn numa(args: NumaArgs) -> Result<(), String> {
+	let node = args.node;
+	let output = args.variant.output_dir(DEFAULT_NUMA_DIR);
+	let features = args.variant.features;
+	let mut targets = args.targets.targets();
+	if targets.is_empty() {
+		targets = LARGE_PCS_EXAMPLES
+			.iter()
+			.map(|name| BenchTarget::Example(name.to_string()))
+			.collect();
+	}
+
+	let study = NumaStudy::new(&numa_nodes(), node)?;
+	let placements = study.placements();
+	let names = placements
+		.iter()
+		.map(|(name, _)| name.clone())
+		.collect::<Vec<_>>();
+	compare_variants("NUMA placement", &names, &targets, &output, |index, target, dir| {
+		let (name, value) = &placements[index];
+		let setting = format!("NUMA placement {name} ({NUMA_VAR}={value})");
+		let envs = [(NUMA_VAR, OsStr::new(value))];
+		run_variant(&setting, target, features.as_deref(), &envs, &[], dir)
+	})?;
+	println!(
+		"Ideal speedup with the {} CPUs of all nodes over the {} of node{node}: {:.2}x",
+		study.total_cpus,
+		study.node_cpus,
+		study.ideal_speedup()
+	);
+	Ok(())
+}
+
+#[derive(Args)]
+struct MemcapArgs {
+	/// Memory caps separated by commas, in GiB or with a unit, 16, 32, and 64 GiB by default.
+	#[arg(long, value_delimiter = ',', value_parser = parse_cap)]
+	caps: Vec<u64>,
+	/// Directory to create the cgroups under, instead of capping with `RLIMIT_DATA`.
+	#[arg(long)]
+	cgroup: Option<PathBuf>,
+	#[command(flatten)]
+	variant: VariantArgs,
+	/// Example to run, repeatable, the large PCS examples by default.
+	#[arg(long = "example", value_name = "NAME")]
+	examples: Vec<String>,
+}
+
+/// Runs each example under each memory cap and prints the outcome of every configuration.
+fn memcap(args: MemcapArgs) -> Result<(), String> {
+	let mut caps = args.caps;
+	if caps.is_empty() {
+		caps = INSTANCE_SIZES_GIB.iter().map(|gib| gib << 30).collect();
+	}
+	let method = args.cgroup.map_or(CapMethod::DataLimit, CapMethod::Cgroup);
+	let output = args.variant.output_dir(DEFAULT_MEMCAP_DIR);
+	let features = args.variant.features;
+	let mut examples = args.examples;
+	if examples.is_empty() {
+		examples = LARGE_PCS_EXAMPLES.map(str::to_string).to_vec();
+	}
+	caps.sort_unstable();
+	caps.dedup();
+	if let Some(memory) = physical_memory() {
+		for &cap in caps.iter().filter(|&&cap| cap > memory) {
+			println!(
+				"Warning: the cap of {} exceeds the {} of memory of this machine and will not \
+				 constrain the runs",
+				cap_name(cap),
+				cap_name(memory)
+			);
+		}
+	}
+
+	let unix_time = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap()
+		.as_secs();
+	let dir = output.join(format!("memcap-{unix_time}"));
+	let mut tables = Vec::new();
+	for example in &examples {
+		let runs = caps
+			.iter()
+			.map(|&cap| {
+				let cap_dir = dir.join(cap_name(cap).replace(' ', "")).join(example);
+				run_capped(example, features.as_deref(), cap, &method, &cap_dir)
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		tables.push((example, runs));
+	}
+	for (example, runs) in tables {
+		println!();
+		write_instance_table(example, &runs, io::stdout().lock())?;
+	}
+	println!("\nWrote the results of each cap under {}", dir.display());
+	Ok(())
+}
+
+#[derive(Args)]
+struct SmtArgs {
+	#[command(flatten)]
+	variant: VariantArgs,
+	#[command(flatten)]
+	targets: TargetArgs,
+}
+
+/// Runs the examples and benches on one hardware thread per physical core, then on all logical
+/// CPUs, and prints the speedup of the second.
+fn smt(args: SmtArgs) -> Result<(), String> {
+	let output = args.variant.output_dir(DEFAULT_SMT_DIR);
+	let features = args.variant.features;
+	let targets = args.targets.targets();
+	if targets.is_empty() {
+		return Err("smt needs at least one --example or --bench".to_string());
+	}
+
+	let study = SmtStudy::new()?;
+	println!("{} physical cores, {} logical CPUs", study.physical.len(), study.logical.len());
+	let cpu_sets = study.cpu_sets();
+	let names = cpu_sets
+		.iter()
+		.map(|(name, _)| name.clone())
+		.collect::<Vec<_>>();
+	compare_variants("SMT setting", &names, &targets, &output, |index, target, dir| {
+		let (name, cpus) = &cpu_sets[index];
+		let setting = format!("SMT setting {name} ({} CPUs)", cpus.len());
+		with_cpus(cpus, || run_variant(&setting, target, features.as_deref(), &[], &[], dir))?
+	})
+	.map(drop)
+}
+
+/// Options of the proof system subcommands, which they pass on to the benchmarks, see
+/// [`ulvt_snark_bench::params`].
+#[derive(Parser)]
+struct SystemArgs {
+	/// Log2 of the problem sizes, separated by commas.
+	#[arg(long, value_delimiter = ',')]
+	n_vars: Vec<usize>,
+	/// Log2 of the inverse code rate of the PCS examples.
+	#[arg(long)]
+	log_inv_rate: Option<usize>,
+	/// Degrees of the sumcheck compositions, separated by commas.
+	#[arg(long, value_delimiter = ',')]
+	degree: Vec<usize>,
+	/// Bit widths of the committed values, separated by commas.
+	#[arg(long, value_delimiter = ',')]
+	n_bits: Vec<usize>,
+	/// Fields to run, separated by commas, such as `AESTowerField8b`.
+	#[arg(long, value_delimiter = ',')]
+	field: Vec<String>,
+	/// Hash functions to run, separated by commas, such as `keccak`.
+	#[arg(long, value_delimiter = ',')]
+	hash: Vec<String>,
+	/// Bits of security the FRI configurations target.
+	#[arg(long)]
+	security_bits: Option<usize>,
+	/// Bits of that security the FRI configurations reach by grinding.
+	#[arg(long)]
+	pow_bits: Option<usize>,
+	/// Bound on the soundness of FRI queries, `proven` or `conjectured`.
+	#[arg(long)]
+	soundness: Option<Soundness>,
+	/// Number of runs of every configuration.
+	#[arg(long)]
+	repeats: Option<usize>,
+	/// Minimum number of runs of every configuration, as `SNARK_BENCH_RUNS` sets.
+	#[arg(long)]
+	min_runs: Option<usize>,
+	/// Thread counts to run the benchmarks with, separated by commas, such as `1,2,4,max`.
+	#[arg(long)]
+	threads: Option<String>,
+	/// Output format, `human` or `ndjson`.
+	#[arg(long, value_parser = ["human", "ndjson"])]
+	output: Option<String>,
+	/// CSV file to append a row per phase to.
+	#[arg(long)]
+	csv: Option<String>,
+	/// Profiler to run the benchmarks under, `heap` for dhat.
+	#[arg(long, value_parser = ["heap"])]
+	profile: Option<String>,
+}
+
+impl SystemArgs {
+	/// The command line of the benchmarks, every option but `--threads`.
+	fn example_args(&self) -> Vec<String> {
+		let list = |values: &[usize]| {
+			let values = values.iter().map(usize::to_string).collect::<Vec<_>>();
+			(!values.is_empty()).then(|| values.join(","))
+		};
+		let names = |names: &[String]| (!names.is_empty()).then(|| names.join(","));
+		let number = |number: Option<usize>| number.map(|number| number.to_string());
+		[
+			("--n-vars", list(&self.n_vars)),
+			("--log-inv-rate", number(self.log_inv_rate)),
+			("--degree", list(&self.degree)),
+			("--n-bits", list(&self.n_bits)),
+			("--field", names(&self.field)),
+			("--hash", names(&self.hash)),
+			("--security-bits", number(self.security_bits)),
+			("--pow-bits", number(self.pow_bits)),
+			("--soundness", self.soundness.map(|soundness| soundness.to_string())),
+			("--repeats", number(self.repeats)),
+			("--min-runs", number(self.min_runs)),
+			("--output", self.output.clone()),
+			("--csv", self.csv.clone()),
+			("--profile", self.profile.clone()),
+		]
+		.into_iter()
+		.filter_map(|(flag, value)| Some([flag.to_string(), value?]))
+		.flatten()
+		.collect()
+	}
+}
+
+/// Runs the examples of `system` in this process with the options in `args`, once per thread
+/// count listed by `--threads` if it is given.
+fn system_command(system: &System, args: &SystemArgs) -> Result<(), String> {
+	system.check_built()?;
+	let example_args = args.example_args();
+	let Some(threads) = &args.threads else {
+		for example in system.examples {
+			run_example(example, &example_args)?;
+		}
+		return Ok(());
+	};
+	let threads = parse_thread_counts(threads)?;
+
+	let output = env::var(RESULTS_DIR_VAR)
+		.map_or_else(|_| PathBuf::from(DEFAULT_THREADS_DIR), PathBuf::from);
+	let targets = system
+		.examples
+		.iter()
+		.map(|example| BenchTarget::Example(example.to_string()))
+		.collect::<Vec<_>>();
+	let names = threads
+		.iter()
+		.map(|threads| format!("threads={threads}"))
+		.collect::<Vec<_>>();
+	let variants =
+		compare_variants("thread count", &names, &targets, &output, |index, target, dir| {
+			let pool = ThreadPoolBuilder::new()
+				.num_threads(threads[index])
+				.build()
+				.map_err(|err| {
+					format!("failed to build a pool of {} threads: {err}", threads[index])
+				})?;
+			let dir_arg = dir
+				.to_str()
+				.ok_or_else(|| format!("{} is not valid UTF-8", dir.display()))?;
+			let args = example_args
+				.iter()
+				.cloned()
+				.chain(["--results".to_string(), dir_arg.to_string()])
+				.collect::<Vec<_>>();
+			println!("== {}: {}", names[index], target.name());
+			pool.install(|| run_example(target.name(), &args))?;
+			load_results(&[dir.to_path_buf()])
+		})?;
+
+	// The efficiency table needs the thread count each configuration recorded, which examples
+	// with their own pools may leave constant.
+	let results = variants
+		.into_iter()
+		.flat_map(|(_, results)| results)
+		.collect::<Vec<_>>();
+	println!();
+	if let Err(err) = write_core_efficiency(&results, DEFAULT_MIN_EFFICIENCY, io::stdout().lock()) {
+		println!("No parallel efficiency to report: {err}");
+	}
+	Ok(())
+}
+
+/// Runs every entry of the suite described by the file at `path`.
+fn suite(path: &Path) -> Result<(), String> {
+	let invocations = Suite::load(path)?.invocations();
+	// Check every entry before spending hours on the first ones.
+	let runs = invocations
+		.iter()
+		.map(|invocation| {
+			let system = system(&invocation.system).ok_or_else(|| {
+				format!("unknown system {:?} in {}", invocation.system, path.display())
+			})?;
+			system.check_built()?;
+			let command_line =
+				iter::once(system.name).chain(invocation.args.iter().map(String::as_str));
+			let mut args = SystemArgs::try_parse_from(command_line)
+				.map_err(|err| format!("invalid entry in {}: {err}", path.display()))?;
+			args.min_runs = invocation.repeats.or(args.min_runs);
+			Ok((system, args))
+		})
+		.collect::<Result<Vec<_>, String>>()?;
+
+	for (index, (invocation, (system, args))) in invocations.iter().zip(&runs).enumerate() {
+		println!(
+			"== {}/{}: {} {}",
+			index + 1,
+			invocations.len(),
+			invocation.system,
+			invocation.args.join(" ")
+		);
+		system_command(system, args)?;
+	}
+	Ok(())
+}
+
+/// Runs every target under every variant, with the results of each under
+/// `<output>/<kind>-<unix time>/<variant>/<target>`, prints the speedup of every variant over the
+/// first, and returns the named results of every variant.
+fn compare_variants(
+	kind: &str,
+	names: &[String],
+	targets: &[BenchTarget],
+	output: &Path,
+	run: impl Fn(usize, &BenchTarget, &Path) -> Result<Vec<Results>, String>,
+) -> Result<Vec<(String, Vec<Results>)>, String> {
+	let unix_time = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap()
+		.as_secs();
+	let prefix = kind.split(' ').next().unwrap_or(kind).to_lowercase();
+	let dir = output.join(format!("{prefix}-{unix_time}"));
+	let mut variants = Vec::new();
+	for (index, name) in names.iter().enumerate() {
+		let mut results = Vec::new();
+		for target in targets {
+			results.extend(run(index, target, &dir.join(name).join(target.name()))?);
+		}
+		variants.push((name.clone(), results));
+	}
+	println!();
+	write_variant_table(kind, &variants, io::stdout().lock())?;
+	println!("\nWrote the results of each {kind} under {}", dir.display());
+	Ok(variants)
+}
+
+/// Runs the examples of the proof system named `name`.
+fn run_system(name: &str, args: &SystemArgs) -> Result<(), String> {
+	let system = system(name).expect("every system subcommand names a system");
+	system_command(system, args)
+}
+
+fn main() {
+	let result = match Cli::parse().command {
+		Command::Report(args) => report(args),
+		Command::History(args) => history(args),
+		Command::Baseline(args) => baseline(args),
+		Command::Diff(args) => diff(args),
+		Command::ImportCriterion(args) => import_criterion(args),
+		Command::Isa(args) => isa(args),
+		Command::Numa(args) => numa(args),
+		Command::Memcap(args) => memcap(args),
+		Command::Smt(args) => smt(args),
+		Command::Check(args) => match check(args) {
+			Ok(false) => process::exit(1),
+			result => result.map(drop),
+		},
+		Command::Compare(args) => match compare(args) {
+			Ok(false) => process::exit(1),
+			result => result.map(drop),
+		},
+		Command::Suite { path } => suite(&path),
+		Command::FriBinius(args) => run_system("fri-binius", &args),
+		Command::Plonky3Fri(args) => run_system("plonky3-fri", &args),
+		Command::Plonky2Fri(args) => run_system("plonky2-fri", &args),
+		Command::Stwo(args) => run_system("stwo", &args),
+		Command::JoltHyrax(args) => run_system("jolt-hyrax", &args),
+		Command::Sumcheck(args) => run_system("sumcheck", &args),
+		Command::Ntt(args) => run_system("ntt", &args),
+	};
+	if let Err(err) = result {
+		eprintln!("{err}");
+		process::exit(2);
+	}
+}
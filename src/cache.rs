@@ -0,0 +1,88 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Warm- and cold-cache measurement of benchmarks over witness data.
+//!
+//! Criterion repeats a routine on inputs its setup just wrote, so inputs that fit in cache are
+//! measured hot. A prover commits witness data that was generated long before, or by another
+//! core, which is the cold case. [`bench_cache_modes`] measures a routine both ways: warm, as
+//! Criterion's batched iteration leaves the input, and cold, after evicting every cache level
+//! between setup and each timed call.
+
+use std::{
+	fs,
+	sync::{Mutex, OnceLock},
+	time::{Duration, Instant},
+};
+
+use criterion::{black_box, measurement::WallTime, BatchSize, BenchmarkGroup};
+use rayon::prelude::*;
+
+/// Cache size assumed when the platform does not report one.
+const DEFAULT_CACHE_BYTES: usize = 64 << 20;
+
+/// Bytes written by each rayon task while evicting.
+const EVICT_CHUNK: usize = 1 << 16;
+
+/// The size of the largest CPU cache, read from Linux sysfs.
+pub fn largest_cache_bytes() -> usize {
+	let Ok(entries) = fs::read_dir("/sys/devices/system/cpu/cpu0/cache") else {
+		return DEFAULT_CACHE_BYTES;
+	};
+	entries
+		.filter_map(|entry| {
+			let size = fs::read_to_string(entry.ok()?.path().join("size")).ok()?;
+			let size = size.trim();
+			let (digits, scale) = match size.strip_suffix('K') {
+				Some(kib) => (kib, 1 << 10),
+				None => match size.strip_suffix('M') {
+					Some(mib) => (mib, 1 << 20),
+					None => (size, 1),
+				},
+			};
+			Some(digits.parse::<usize>().ok()? * scale)
+		})
+		.max()
+		.unwrap_or(DEFAULT_CACHE_BYTES)
+}
+
+/// Evicts the caches of every core by writing a buffer twice the size of the largest cache from
+/// all rayon threads.
+pub fn evict_caches() {
+	static BUFFER: OnceLock<Mutex<Vec<u8>>> = OnceLock::new();
+
+	let buffer = BUFFER.get_or_init(|| Mutex::new(vec![0; 2 * largest_cache_bytes()]));
+	let mut buffer = buffer.lock().unwrap();
+	let value = buffer[0].wrapping_add(1);
+	buffer
+		.par_chunks_mut(EVICT_CHUNK)
+		.for_each(|chunk| chunk.fill(value));
+	black_box(&*buffer);
+}
+
+/// Benchmarks `routine` on inputs from `setup` with a warm cache, as `"{name} warm"`, and with the
+/// caches evicted before each call, as `"{name} cold"`.
+pub fn bench_cache_modes<I, O>(
+	group: &mut BenchmarkGroup<WallTime>,
+	name: &str,
+	mut setup: impl FnMut() -> I,
+	mut routine: impl FnMut(I) -> O,
+) {
+	group.bench_function(format!("{name} warm"), |b| {
+		b.iter_batched(&mut setup, &mut routine, BatchSize::LargeInput)
+	});
+	group.bench_function(format!("{name} cold"), |b| {
+		b.iter_custom(|iters| {
+			(0..iters)
+				.map(|_| {
+					let input = setup();
+					evict_caches();
+					let start = Instant::now();
+					let output = routine(input);
+					let elapsed = start.elapsed();
+					drop(black_box(output));
+					elapsed
+				})
+				.sum::<Duration>()
+		})
+	});
+}
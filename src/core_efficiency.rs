@@ -0,0 +1,217 @@
+// Copyright 2024 Irreducible Inc.
+
+//! The core count each workload runs most cost-efficiently on, from runs that sweep the number of
+//! threads.
+//!
+//! Cloud machines are priced per core-hour, so the machine to rent for a prover is the one past
+//! which more cores mostly sit idle. A workload is a phase of a backend and configuration at one
+//! size, run at several thread counts: the `thread scaling` groups of the Criterion benches, whose
+//! parameter is the thread count, or runs of an example in rayon pools of different sizes. The
+//! thread count of each comes from its `threads` metric, see [`crate::throughput`].
+//!
+//! Going from `c` to `c'` cores, the marginal efficiency is the share of the ideal extra speedup
+//! that the added cores deliver, `(T(c) / T(c') - 1) / (c' / c - 1)`: 1 for linear scaling, 0 when
+//! the added cores gain nothing. [`core_sweeps`] recommends, for every workload, the largest core
+//! count reached before the marginal efficiency of a step falls below a threshold,
+//! [`DEFAULT_MIN_EFFICIENCY`] by default, and [`write_core_efficiency`] tabulates the sweeps with
+//! the throughput per core-hour of every core count.
+
+use std::{collections::BTreeMap, io, time::Duration};
+
+use crate::{
+	report::{label, median_of},
+	results::{Record, Results},
+	throughput::THREADS_METRIC,
+};
+
+/// Marginal efficiency below which adding cores is not worth their cost, by default.
+pub const DEFAULT_MIN_EFFICIENCY: f64 = 0.5;
+
+/// One workload at one core count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoreCount {
+	/// Number of threads the workload ran on.
+	pub threads: usize,
+	/// Median wall time of the workload.
+	pub seconds: f64,
+	/// Speedup over the smallest core count of the sweep.
+	pub speedup: f64,
+	/// Marginal efficiency of the step from the previous core count, `None` for the smallest.
+	pub marginal_efficiency: Option<f64>,
+	/// Elements processed per core-hour.
+	pub elements_per_core_hour: f64,
+}
+
+/// A phase of a backend and configuration at one size, run at several core counts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoreSweep {
+	/// Proving library, or Criterion group.
+	pub backend: String,
+	/// Parameters of the configuration other than the problem size and thread count.
+	pub config: String,
+	/// Name of the phase.
+	pub phase: String,
+	/// Number of elements the phase processed.
+	pub n_elements: u64,
+	/// The core counts of the sweep, from the smallest.
+	pub counts: Vec<CoreCount>,
+	/// Index in `counts` of the most cost-efficient core count.
+	pub recommended: usize,
+}
+
+/// A workload, as the backend, configuration, phase, and size of its phases.
+type Workload = (String, String, String, u64);
+
+/// Collects the workloads that ran at two or more core counts, in the order they first ran, and
+/// recommends a core count for each by the marginal efficiency threshold `min_efficiency`.
+pub fn core_sweeps(results: &[Results], min_efficiency: f64) -> Vec<CoreSweep> {
+	let mut order = Vec::<Workload>::new();
+	let mut seconds = BTreeMap::<Workload, BTreeMap<usize, Vec<f64>>>::new();
+	for run in results {
+		let threads = run
+			.records
+			.iter()
+			.filter_map(|record| match record {
+				Record::Metric(metric)
+					if metric.metric == THREADS_METRIC && metric.value >= 1.0 =>
+				{
+					Some(((&metric.backend, &metric.config), metric.value.round() as usize))
+				}
+				_ => None,
+			})
+			.collect::<BTreeMap<_, _>>();
+		for record in &run.records {
+			let Record::Phase(phase) = record else {
+				continue;
+			};
+			let Some(&threads) = threads.get(&(&phase.backend, &phase.config)) else {
+				continue;
+			};
+			// The Criterion thread sweeps have the thread count as their configuration.
+			let config = if phase.config == threads.to_string() {
+				String::new()
+			} else {
+				phase.config.clone()
+			};
+			let workload = (phase.backend.clone(), config, phase.phase.clone(), phase.n_elements);
+			if !seconds.contains_key(&workload) {
+				order.push(workload.clone());
+			}
+			seconds
+				.entry(workload)
+				.or_default()
+				.entry(threads)
+				.or_default()
+				.push(phase.seconds);
+		}
+	}
+
+	order
+		.into_iter()
+		.filter_map(|workload| {
+			let sweep = &seconds[&workload];
+			if sweep.len() < 2 {
+				return None;
+			}
+			let (backend, config, phase, n_elements) = workload;
+			let medians = sweep
+				.iter()
+				.map(|(&threads, seconds)| (threads, median_of(seconds)))
+				.collect::<Vec<_>>();
+			let base_seconds = medians[0].1;
+			let counts = medians
+				.iter()
+				.enumerate()
+				.map(|(index, &(threads, seconds))| {
+					let marginal_efficiency = index.checked_sub(1).map(|previous| {
+						let (previous_threads, previous_seconds) = medians[previous];
+						(previous_seconds / seconds - 1.0)
+							/ (threads as f64 / previous_threads as f64 - 1.0)
+					});
+					CoreCount {
+						threads,
+						seconds,
+						speedup: base_seconds / seconds,
+						marginal_efficiency,
+						elements_per_core_hour: n_elements as f64 * 3600.0
+							/ (seconds * threads as f64),
+					}
+				})
+				.collect::<Vec<_>>();
+			// The core count before the first step below the threshold, or the largest one.
+			let recommended = counts
+				.iter()
+				.skip(1)
+				.position(|count| {
+					count
+						.marginal_efficiency
+						.is_some_and(|efficiency| efficiency < min_efficiency)
+				})
+				.unwrap_or(counts.len() - 1);
+			Some(CoreSweep {
+				backend,
+				config,
+				phase,
+				n_elements,
+				counts,
+				recommended,
+			})
+		})
+		.collect()
+}
+
+/// Writes, for every workload that ran at several core counts, its time, speedup, marginal
+/// efficiency, and throughput per core-hour at each, marking the most cost-efficient one.
+pub fn write_core_efficiency(
+	results: &[Results],
+	min_efficiency: f64,
+	mut out: impl io::Write,
+) -> Result<(), String> {
+	let sweeps = core_sweeps(results, min_efficiency);
+	if sweeps.is_empty() {
+		return Err("no phase ran at more than one thread count".to_string());
+	}
+
+	let mut write = || -> io::Result<()> {
+		writeln!(
+			out,
+			"Most cost-efficient core count (*), the largest before adding cores delivers less than \
+			 {:.0}% of their ideal speedup:",
+			min_efficiency * 100.0
+		)?;
+		for sweep in &sweeps {
+			let recommended = &sweep.counts[sweep.recommended];
+			writeln!(out)?;
+			writeln!(
+				out,
+				"{}, {} n={}: {} cores, {:.2}x",
+				label(&sweep.backend, &sweep.config),
+				sweep.phase,
+				sweep.n_elements,
+				recommended.threads,
+				recommended.speedup
+			)?;
+			writeln!(
+				out,
+				"  {:>8} {:>12} {:>10} {:>10} {:>18}",
+				"cores", "time", "speedup", "marginal", "Melem/core-hour"
+			)?;
+			for (index, count) in sweep.counts.iter().enumerate() {
+				let marker = if index == sweep.recommended { '*' } else { ' ' };
+				let marginal = count
+					.marginal_efficiency
+					.map_or("-".to_string(), |efficiency| format!("{:.0}%", efficiency * 100.0));
+				writeln!(
+					out,
+					"{marker} {:>8} {:>12} {:>10} {marginal:>10} {:>18.1}",
+					count.threads,
+					format!("{:.2?}", Duration::from_secs_f64(count.seconds)),
+					format!("{:.2}x", count.speedup),
+					count.elements_per_core_hour / 1e6,
+				)?;
+			}
+		}
+		out.flush()
+	};
+	write().map_err(|err| format!("failed to write the core efficiency report: {err}"))
+}
@@ -0,0 +1,167 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Conversion of verifier operation counts into downstream cost units.
+//!
+//! What a verifier costs where it is deployed, such as gas on the EVM or constraints in a
+//! recursive circuit, is roughly linear in the operations it performs. The examples count those
+//! operations during verification and pass them to [`report_verifier_cost`], which prints the cost
+//! under every registered [`CostModel`]. Models are registered from code with
+//! [`register_cost_model`], or read from the file named by `SNARK_BENCH_COST_MODEL`, one
+//! [`LinearCostModel`] per line:
+//!
+//! ```text
+//! # name unit op=cost...
+//! evm-keccak gas base=21000 digest=60 compression=60
+//! r1cs-poseidon2 constraints digest=300 compression=300 field_op=1
+//! ```
+//!
+//! The operations are `digest`, `compression`, `field_op`, and `pairing`, plus a fixed `base`
+//! cost. A model that prices an operation the example does not count reports no cost rather than
+//! an underestimate.
+
+use std::{
+	env, fs,
+	sync::{Mutex, OnceLock},
+};
+
+/// Environment variable holding the path of the cost model file.
+pub const COST_MODEL_VAR: &str = "SNARK_BENCH_COST_MODEL";
+
+/// Operations performed by one verification. Counts that an example does not measure are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VerifierOps {
+	/// Hash calls over leaf data.
+	pub digests: Option<u64>,
+	/// Hash calls compressing two digests.
+	pub compressions: Option<u64>,
+	/// Field multiplications.
+	pub field_ops: Option<u64>,
+	/// Pairing evaluations.
+	pub pairings: Option<u64>,
+}
+
+/// A conversion from verifier operations into a cost unit.
+pub trait CostModel: Send {
+	/// Name of the model, identifying the deployment target.
+	fn name(&self) -> &str;
+
+	/// Unit the cost is expressed in.
+	fn unit(&self) -> &str;
+
+	/// The cost of a verification, or `None` if it depends on an operation that was not counted.
+	fn cost(&self, ops: &VerifierOps) -> Option<f64>;
+}
+
+/// A cost model charging a fixed cost per operation, plus a base cost per verification.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinearCostModel {
+	/// Name of the model.
+	pub name: String,
+	/// Unit of the costs.
+	pub unit: String,
+	/// Cost of every verification, such as a transaction's base gas.
+	pub base: f64,
+	/// Cost per digest.
+	pub digest: f64,
+	/// Cost per compression.
+	pub compression: f64,
+	/// Cost per field multiplication.
+	pub field_op: f64,
+	/// Cost per pairing.
+	pub pairing: f64,
+}
+
+impl LinearCostModel {
+	/// Parses a model from a line of the form `name unit op=cost...`.
+	pub fn parse(line: &str) -> Result<Self, String> {
+		let mut words = line.split_whitespace();
+		let (Some(name), Some(unit)) = (words.next(), words.next()) else {
+			return Err(format!("expected a name and a unit in {line:?}"));
+		};
+		let mut model = Self {
+			name: name.to_string(),
+			unit: unit.to_string(),
+			..Self::default()
+		};
+		for word in words {
+			let (op, cost) = word
+				.split_once('=')
+				.ok_or_else(|| format!("expected op=cost, found {word:?}"))?;
+			let cost = cost
+				.parse::<f64>()
+				.map_err(|err| format!("invalid cost in {word:?}: {err}"))?;
+			match op {
+				"base" => model.base = cost,
+				"digest" => model.digest = cost,
+				"compression" => model.compression = cost,
+				"field_op" => model.field_op = cost,
+				"pairing" => model.pairing = cost,
+				_ => return Err(format!("unknown operation {op:?}")),
+			}
+		}
+		Ok(model)
+	}
+}
+
+impl CostModel for LinearCostModel {
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn unit(&self) -> &str {
+		&self.unit
+	}
+
+	fn cost(&self, ops: &VerifierOps) -> Option<f64> {
+		// Operations the model does not charge for need not have been counted.
+		let term = |cost: f64, count: Option<u64>| match count {
+			_ if cost == 0.0 => Some(0.0),
+			Some(count) => Some(cost * count as f64),
+			None => None,
+		};
+		Some(
+			self.base
+				+ term(self.digest, ops.digests)?
+				+ term(self.compression, ops.compressions)?
+				+ term(self.field_op, ops.field_ops)?
+				+ term(self.pairing, ops.pairings)?,
+		)
+	}
+}
+
+fn models() -> &'static Mutex<Vec<Box<dyn CostModel>>> {
+	static MODELS: OnceLock<Mutex<Vec<Box<dyn CostModel>>>> = OnceLock::new();
+	MODELS.get_or_init(|| {
+		let Ok(path) = env::var(COST_MODEL_VAR) else {
+			return Mutex::new(Vec::new());
+		};
+		let contents = fs::read_to_string(&path)
+			.unwrap_or_else(|err| panic!("failed to read cost models from {path:?}: {err}"));
+		let models = contents
+			.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty() && !line.starts_with('#'))
+			.map(|line| {
+				let model = LinearCostModel::parse(line)
+					.unwrap_or_else(|err| panic!("invalid cost model in {path:?}: {err}"));
+				Box::new(model) as Box<dyn CostModel>
+			})
+			.collect();
+		Mutex::new(models)
+	})
+}
+
+/// Registers a cost model alongside those read from `SNARK_BENCH_COST_MODEL`.
+pub fn register_cost_model(model: impl CostModel + 'static) {
+	models().lock().unwrap().push(Box::new(model));
+}
+
+/// Prints the cost of a verification under every registered model.
+pub fn report_verifier_cost(ops: &VerifierOps) {
+	for model in models().lock().unwrap().iter() {
+		match model.cost(ops) {
+			Some(cost) => println!("Verifier cost ({}): {cost:.0} {}", model.name(), model.unit()),
+			None => println!("Verifier cost ({}): n/a, operations not counted", model.name()),
+		}
+	}
+}
@@ -0,0 +1,95 @@
+// Copyright 2024 Irreducible Inc.
+
+//! CPU frequency scaling and thermal throttling checks, from Linux sysfs.
+//!
+//! Frequency governors other than `performance`, turbo boost, and thermal throttling all make
+//! timings depend on what ran before, so long sweeps on laptops and shared servers can skew
+//! comparisons between configurations. [`check_cpu_environment`] reports the settings before a
+//! run, and [`throttle_count`] lets phases flag results taken while the CPU was throttled.
+//! Other platforms report nothing.
+
+use std::{fs, path::Path};
+
+use crate::results::record_warning;
+
+pub(crate) const CPU_DIR: &str = "/sys/devices/system/cpu";
+
+fn read_trimmed(path: impl AsRef<Path>) -> Option<String> {
+	Some(fs::read_to_string(path).ok()?.trim().to_string())
+}
+
+/// Reads `file` under each CPU's sysfs directory, skipping CPUs where it is missing.
+fn per_cpu(file: &str) -> Vec<String> {
+	let Ok(entries) = fs::read_dir(CPU_DIR) else {
+		return Vec::new();
+	};
+	entries
+		.filter_map(|entry| {
+			let entry = entry.ok()?;
+			let name = entry.file_name();
+			let index = name.to_str()?.strip_prefix("cpu")?;
+			if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+				return None;
+			}
+			read_trimmed(entry.path().join(file))
+		})
+		.collect()
+}
+
+/// The distinct frequency governors in use across CPUs.
+pub fn governors() -> Vec<String> {
+	let mut governors = per_cpu("cpufreq/scaling_governor");
+	governors.sort();
+	governors.dedup();
+	governors
+}
+
+/// Whether turbo boost is enabled, if the frequency driver reports it.
+pub fn turbo_enabled() -> Option<bool> {
+	if let Some(no_turbo) = read_trimmed(Path::new(CPU_DIR).join("intel_pstate/no_turbo")) {
+		return Some(no_turbo == "0");
+	}
+	read_trimmed(Path::new(CPU_DIR).join("cpufreq/boost")).map(|boost| boost == "1")
+}
+
+/// The mean current frequency across CPUs, in kHz.
+pub fn mean_frequency_khz() -> Option<u64> {
+	let frequencies = per_cpu("cpufreq/scaling_cur_freq")
+		.iter()
+		.filter_map(|freq| freq.parse::<u64>().ok())
+		.collect::<Vec<_>>();
+	(!frequencies.is_empty()).then(|| frequencies.iter().sum::<u64>() / frequencies.len() as u64)
+}
+
+/// The number of thermal throttling events since boot, summed over cores, if the platform
+/// counts them.
+pub fn throttle_count() -> Option<u64> {
+	let counts = per_cpu("thermal_throttle/core_throttle_count");
+	(!counts.is_empty()).then(|| {
+		counts
+			.iter()
+			.filter_map(|count| count.parse::<u64>().ok())
+			.sum()
+	})
+}
+
+/// Prints the CPU frequency settings, with warnings for those that make timings unstable.
+pub fn check_cpu_environment() {
+	let governors = governors();
+	if !governors.is_empty() {
+		println!("CPU governor: {}", governors.join(", "));
+		if governors.iter().any(|governor| governor != "performance") {
+			record_warning(
+				"CPU frequency scaling is not set to `performance`, so timings depend on load \
+				 history",
+			);
+		}
+	}
+	if let Some(true) = turbo_enabled() {
+		record_warning("turbo boost is enabled, so timings depend on thermal headroom");
+	}
+	if let Some(freq) = mean_frequency_khz() {
+		println!("CPU frequency: {:.2} GHz", freq as f64 / 1e6);
+	}
+	println!();
+}
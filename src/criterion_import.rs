@@ -0,0 +1,174 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Import of the Criterion microbenchmark results into the results of the examples.
+//!
+//! The benches under `benches/` report through Criterion, which saves its estimates under
+//! `target/criterion/<group>/<function>/<value>/new/`. [`load_criterion`] reads every benchmark
+//! there into one [`Results`] document of a pseudo-example named `criterion`, so that the field,
+//! hash, and NTT microbenchmarks land in the same results directories, history database, and
+//! reports as the PCS and sumcheck examples. Each benchmark becomes a [`PhaseRecord`] with the
+//! group as its backend, the function as its phase, and the parameter value, if any, as its
+//! configuration. Its time is Criterion's median time per iteration, and its size is the
+//! throughput of an iteration in elements or bytes, or 1 without a declared throughput. Groups
+//! declaring bytes get an `element_bits` metric of 8, so that their throughput normalizes the same
+//! as that of the element groups, and every benchmark gets a `threads` metric: the parameter value
+//! in groups named after threads, which sweep the thread count, and 1 otherwise. The groups of the
+//! ICICLE benches, named `icicle ...`, are recorded as running on the `cuda` device.
+
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	time::UNIX_EPOCH,
+};
+
+use serde::Deserialize;
+
+use crate::{
+	results::{MetricRecord, PhaseRecord, Record, Results, CPU_DEVICE},
+	throughput::{ELEMENT_BITS_METRIC, THREADS_METRIC},
+};
+
+/// Name the Criterion results are recorded under, in place of an example name.
+pub const CRITERION_EXAMPLE: &str = "criterion";
+
+/// Directory Criterion saves its results to, relative to the crate root.
+pub const DEFAULT_CRITERION_DIR: &str = "target/criterion";
+
+/// Prefix of the groups of the GPU benches, which run on [`GPU_DEVICE`].
+pub const GPU_GROUP_PREFIX: &str = "icicle";
+
+/// Device of the benchmarks of the GPU benches, which do not record which GPU they ran on.
+pub const GPU_DEVICE: &str = "cuda";
+
+/// The fields of Criterion's `benchmark.json` that identify a benchmark.
+#[derive(Deserialize)]
+struct BenchmarkId {
+	group_id: String,
+	function_id: Option<String>,
+	value_str: Option<String>,
+	throughput: Option<Throughput>,
+}
+
+#[derive(Deserialize)]
+enum Throughput {
+	Bytes(u64),
+	BytesDecimal(u64),
+	Elements(u64),
+}
+
+#[derive(Deserialize)]
+struct Estimate {
+	point_estimate: f64,
+}
+
+/// The fields of Criterion's `estimates.json` that are imported.
+#[derive(Deserialize)]
+struct Estimates {
+	median: Estimate,
+}
+
+/// The `new` directories under `dir` that hold the latest results of a benchmark.
+fn benchmark_dirs(dir: &Path, found: &mut Vec<PathBuf>) -> Result<(), String> {
+	let entries =
+		fs::read_dir(dir).map_err(|err| format!("failed to list {}: {err}", dir.display()))?;
+	for entry in entries {
+		let path = entry
+			.map_err(|err| format!("failed to list {}: {err}", dir.display()))?
+			.path();
+		if !path.is_dir() {
+			continue;
+		}
+		if path.file_name().is_some_and(|name| name == "new")
+			&& path.join("benchmark.json").is_file()
+		{
+			found.push(path);
+		} else {
+			benchmark_dirs(&path, found)?;
+		}
+	}
+	Ok(())
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, String> {
+	let contents = fs::read_to_string(path)
+		.map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+	serde_json::from_str(&contents).map_err(|err| format!("invalid {}: {err}", path.display()))
+}
+
+/// Reads every benchmark saved under the Criterion directory `dir` into results dated by the
+/// latest of them.
+pub fn load_criterion(dir: &Path) -> Result<Results, String> {
+	let mut dirs = Vec::new();
+	benchmark_dirs(dir, &mut dirs)?;
+	if dirs.is_empty() {
+		return Err(format!("no Criterion results under {}", dir.display()));
+	}
+	dirs.sort();
+
+	let mut records = Vec::new();
+	let mut unix_time = 0;
+	for dir in dirs {
+		let id = read_json::<BenchmarkId>(&dir.join("benchmark.json"))?;
+		let estimates_path = dir.join("estimates.json");
+		let estimates = read_json::<Estimates>(&estimates_path)?;
+		let modified = fs::metadata(&estimates_path)
+			.and_then(|metadata| metadata.modified())
+			.ok()
+			.and_then(|time| time.duration_since(UNIX_EPOCH).ok());
+		if let Some(modified) = modified {
+			unix_time = unix_time.max(modified.as_secs());
+		}
+
+		let (n_elements, bytes) = match id.throughput {
+			Some(Throughput::Bytes(n) | Throughput::BytesDecimal(n)) => (n, true),
+			Some(Throughput::Elements(n)) => (n, false),
+			None => (1, false),
+		};
+		let threads = id
+			.value_str
+			.as_deref()
+			.filter(|_| id.group_id.contains("thread"))
+			.and_then(|value| value.parse::<f64>().ok())
+			.unwrap_or(1.0);
+		let config = id.value_str.unwrap_or_default();
+		let metric = |metric: &str, value: f64, unit: &str| {
+			Record::Metric(MetricRecord {
+				backend: id.group_id.clone(),
+				config: config.clone(),
+				n_elements: None,
+				metric: metric.to_string(),
+				value,
+				unit: unit.to_string(),
+			})
+		};
+		if bytes {
+			records.push(metric(ELEMENT_BITS_METRIC, 8.0, "bits"));
+		}
+		records.push(metric(THREADS_METRIC, threads, "count"));
+		let device = if id.group_id.starts_with(GPU_GROUP_PREFIX) {
+			GPU_DEVICE
+		} else {
+			CPU_DEVICE
+		};
+		records.push(Record::Phase(PhaseRecord {
+			phase: id.function_id.unwrap_or_else(|| id.group_id.clone()),
+			backend: id.group_id,
+			config,
+			n_elements,
+			device: device.to_string(),
+			seconds: estimates.median.point_estimate * 1e-9,
+			peak_rss_bytes: None,
+			average_rss_bytes: None,
+			allocations: None,
+			allocated_bytes: None,
+			peak_heap_bytes: None,
+			major_page_faults: None,
+			perf: None,
+		}));
+	}
+
+	Ok(Results {
+		records,
+		..Results::new(CRITERION_EXAMPLE, unix_time)
+	})
+}
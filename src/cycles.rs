@@ -0,0 +1,99 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Cycle counting for microbenchmarks of single field operations.
+//!
+//! A field multiplication takes a few cycles, below the resolution at which Criterion's
+//! nanosecond timings tell two implementations apart. When `SNARK_BENCH_CYCLES` is set,
+//! [`report_cycles`] times an operation with the x86 timestamp counter, with fences on both reads
+//! so that out-of-order execution cannot move work across them, and prints the cycles per call.
+//! Calls are independent, so this is reciprocal throughput rather than latency.
+//!
+//! The timestamp counter ticks at the base frequency rather than the core clock, so counts are
+//! only exact cycles with turbo boost disabled. Other architectures have no user-space cycle
+//! counter and report nothing.
+
+use std::{env, hint::black_box};
+
+/// Environment variable enabling cycle counts in the field operation benchmarks.
+pub const CYCLES_VAR: &str = "SNARK_BENCH_CYCLES";
+
+/// Calls timed between each pair of counter reads.
+const CALLS_PER_SAMPLE: u64 = 1000;
+
+/// Samples taken, of which the fastest is kept.
+const SAMPLES: usize = 1000;
+
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn start_cycles() -> u64 {
+	use std::arch::x86_64::{_mm_lfence, _rdtsc};
+
+	// The fences keep earlier instructions from finishing after the read, and later ones from
+	// starting before it.
+	unsafe {
+		_mm_lfence();
+		let cycles = _rdtsc();
+		_mm_lfence();
+		cycles
+	}
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn end_cycles() -> u64 {
+	use std::arch::x86_64::{__rdtscp, _mm_lfence};
+
+	// `rdtscp` waits for earlier instructions, and the fence keeps later ones from starting first.
+	unsafe {
+		let mut aux = 0;
+		let cycles = __rdtscp(&mut aux);
+		_mm_lfence();
+		cycles
+	}
+}
+
+/// Returns the fewest cycles taken by `calls` calls of `f` over [`SAMPLES`] samples.
+#[cfg(target_arch = "x86_64")]
+fn min_cycles<R>(calls: u64, f: &mut impl FnMut() -> R) -> u64 {
+	(0..SAMPLES)
+		.map(|_| {
+			let start = start_cycles();
+			for _ in 0..calls {
+				black_box(f());
+			}
+			end_cycles().wrapping_sub(start)
+		})
+		.min()
+		.unwrap_or(0)
+}
+
+/// Measures the cycles per call of `f`, less the cost of reading the counter, or returns `None`
+/// if the platform has no usable cycle counter.
+pub fn measure_cycles<R>(mut f: impl FnMut() -> R) -> Option<f64> {
+	#[cfg(target_arch = "x86_64")]
+	{
+		let overhead = min_cycles(0, &mut || ());
+		let total = min_cycles(CALLS_PER_SAMPLE, &mut f);
+		Some(total.saturating_sub(overhead) as f64 / CALLS_PER_SAMPLE as f64)
+	}
+	#[cfg(not(target_arch = "x86_64"))]
+	{
+		let _ = &mut f;
+		None
+	}
+}
+
+/// Prints the cycles per call of `f`, and per element for operations on `n_elements` packed
+/// elements, if [`CYCLES_VAR`] is set.
+pub fn report_cycles<R>(label: &str, n_elements: u64, f: impl FnMut() -> R) {
+	if env::var_os(CYCLES_VAR).is_none() {
+		return;
+	}
+	match measure_cycles(f) {
+		Some(cycles) => println!(
+			"{label}: {cycles:.2} cycles/call, {:.3} cycles/element",
+			cycles / n_elements as f64
+		),
+		None => println!("{label}: no cycle counter on this platform"),
+	}
+}
@@ -0,0 +1,208 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Description of the machine and build a run measured, for sharing results without context.
+//!
+//! Timings are only comparable with the CPU, its SIMD features, core count, and memory, the thread
+//! count, frequency settings, and build they were taken with. [`environment`] collects them into
+//! named entries that each run records in its [`crate::results::Results`], and that the run
+//! summary of [`crate::summary`] lists. [`machine_mismatches`] tells when results that are about
+//! to be compared or reported together were taken on different machines.
+
+use std::{collections::BTreeMap, env, thread};
+
+use bytesize::ByteSize;
+
+use crate::{
+	cpu_state::{governors, turbo_enabled},
+	memory_cap::physical_memory,
+	results::Results,
+	simd::{compiled_features, detected_features},
+	smt::physical_cores,
+};
+
+/// Entries of the environment that describe the machine rather than the build or its settings.
+pub const MACHINE_KEYS: &[&str] = &[
+	"os",
+	"cpu",
+	"cpu_features",
+	"physical_cores",
+	"logical_cpus",
+	"memory",
+];
+
+/// Cargo features of this crate that change what is measured, or how.
+const FEATURES: &[(&str, bool)] = &[
+	("ark_parallel", cfg!(feature = "ark_parallel")),
+	("p3_parallel", cfg!(feature = "p3_parallel")),
+	("stwo", cfg!(feature = "stwo")),
+	("cuda", cfg!(feature = "cuda")),
+	("alloc_count", cfg!(feature = "alloc_count")),
+	("jemalloc", cfg!(feature = "jemalloc")),
+	("mimalloc", cfg!(feature = "mimalloc")),
+	("huge_pages", cfg!(feature = "huge_pages")),
+	("dhat", cfg!(feature = "dhat")),
+	("perf_counters", cfg!(feature = "perf_counters")),
+];
+
+/// The raw value of a `sysctl` variable, on macOS.
+#[cfg(target_os = "macos")]
+fn sysctl(name: &str) -> Option<Vec<u8>> {
+	use std::{ffi::CString, ptr};
+
+	let name = CString::new(name).ok()?;
+	let mut len = 0;
+	// SAFETY: a null buffer asks for the length of the value only.
+	let status =
+		unsafe { libc::sysctlbyname(name.as_ptr(), ptr::null_mut(), &mut len, ptr::null_mut(), 0) };
+	if status != 0 {
+		return None;
+	}
+	let mut value = vec![0u8; len];
+	// SAFETY: the buffer is valid for writes of `len` bytes.
+	let status = unsafe {
+		libc::sysctlbyname(name.as_ptr(), value.as_mut_ptr().cast(), &mut len, ptr::null_mut(), 0)
+	};
+	if status != 0 {
+		return None;
+	}
+	value.truncate(len);
+	Some(value)
+}
+
+/// The model name of the CPU, such as `Apple M2 Pro` on macOS.
+#[cfg(target_os = "macos")]
+fn cpu_model() -> Option<String> {
+	let value = sysctl("machdep.cpu.brand_string")?;
+	let model = std::ffi::CStr::from_bytes_until_nul(&value).ok()?;
+	Some(model.to_string_lossy().into_owned())
+}
+
+/// The model name of the CPU, on Windows.
+#[cfg(windows)]
+fn cpu_model() -> Option<String> {
+	crate::windows::cpu_model()
+}
+
+/// The model name of the CPU, on Linux.
+#[cfg(not(any(target_os = "macos", windows)))]
+fn cpu_model() -> Option<String> {
+	let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+	cpuinfo.lines().find_map(|line| {
+		let (key, value) = line.split_once(':')?;
+		(key.trim() == "model name").then(|| value.trim().to_string())
+	})
+}
+
+/// The number of performance and efficiency cores of an Apple Silicon CPU. rayon spreads work over
+/// both, so the slowest efficiency core can bound a parallel phase.
+#[cfg(target_os = "macos")]
+fn core_types() -> Option<(u32, u32)> {
+	let count = |name| {
+		let value = sysctl(name)?;
+		Some(u32::from_ne_bytes(value.get(..4)?.try_into().ok()?))
+	};
+	Some((count("hw.perflevel0.physicalcpu")?, count("hw.perflevel1.physicalcpu")?))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn core_types() -> Option<(u32, u32)> {
+	None
+}
+
+/// The entries describing this machine and build, such as `cpu`, `rayon_threads`, and `features`.
+pub fn environment() -> BTreeMap<String, String> {
+	let mut entries = BTreeMap::new();
+	let mut entry = |key: &str, value: String| {
+		entries.insert(key.to_string(), value);
+	};
+	entry("os", format!("{} {}", env::consts::OS, env::consts::ARCH));
+	if let Some(model) = cpu_model() {
+		entry("cpu", model);
+	}
+	let list = |features: Vec<&str>| {
+		if features.is_empty() {
+			"none".to_string()
+		} else {
+			features.join(", ")
+		}
+	};
+	entry("cpu_features", list(detected_features()));
+	entry("compiled_features", list(compiled_features()));
+	if let Some(cores) = physical_cores() {
+		entry("physical_cores", cores.to_string());
+	}
+	if let Ok(cpus) = thread::available_parallelism() {
+		entry("logical_cpus", cpus.to_string());
+	}
+	if let Some((performance, efficiency)) = core_types() {
+		entry("performance_cores", performance.to_string());
+		entry("efficiency_cores", efficiency.to_string());
+	}
+	if let Some(bytes) = physical_memory() {
+		entry("memory", ByteSize(bytes).to_string());
+	}
+	entry("rayon_threads", rayon::current_num_threads().to_string());
+	let governors = governors();
+	if !governors.is_empty() {
+		entry("cpu_governor", governors.join(", "));
+	}
+	if let Some(turbo) = turbo_enabled() {
+		entry("turbo", if turbo { "enabled" } else { "disabled" }.to_string());
+	}
+	let profile = if cfg!(debug_assertions) {
+		"debug"
+	} else {
+		"release"
+	};
+	entry("profile", profile.to_string());
+	if let Some(version) = option_env!("SNARK_BENCH_RUSTC_VERSION").filter(|v| !v.is_empty()) {
+		entry("rustc", version.to_string());
+	}
+	let features = FEATURES
+		.iter()
+		.filter(|(_, enabled)| *enabled)
+		.map(|(feature, _)| *feature)
+		.collect::<Vec<_>>();
+	entry("features", list(features));
+	entries
+}
+
+/// Describes every entry of [`MACHINE_KEYS`] that differs between the runs, such as
+/// `cpu: AMD EPYC 9654 (2 runs), Apple M2 Pro (1 run)`, and whether the runs came from several
+/// hosts. Runs that did not record an entry are left out of its comparison.
+pub fn machine_mismatches(results: &[Results]) -> Vec<String> {
+	let mut mismatches = Vec::new();
+	let mut describe = |key: &str, values: Vec<&str>| {
+		let mut counts = BTreeMap::<&str, usize>::new();
+		for value in values {
+			*counts.entry(value).or_default() += 1;
+		}
+		if counts.len() > 1 {
+			let counts = counts
+				.into_iter()
+				.map(|(value, runs)| {
+					format!("{value} ({runs} run{})", if runs == 1 { "" } else { "s" })
+				})
+				.collect::<Vec<_>>();
+			mismatches.push(format!("{key}: {}", counts.join(", ")));
+		}
+	};
+	describe(
+		"machine",
+		results
+			.iter()
+			.map(|run| run.machine.as_str())
+			.filter(|machine| !machine.is_empty())
+			.collect(),
+	);
+	for key in MACHINE_KEYS {
+		describe(
+			key,
+			results
+				.iter()
+				.filter_map(|run| run.environment.get(*key).map(String::as_str))
+				.collect(),
+		);
+	}
+	mismatches
+}
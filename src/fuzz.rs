@@ -0,0 +1,35 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Seeded repetition mode for checking Fiat-Shamir wiring.
+//!
+//! When `SNARK_BENCH_FUZZ=<iterations>` is set, the sumcheck and PCS examples skip their
+//! benchmark sweep and instead run a small configuration once per seed, asserting that the prover
+//! and verifier transcripts agree. Failing runs print the seed, which can be replayed by setting
+//! `SNARK_BENCH_FUZZ_SEED` to it together with `SNARK_BENCH_FUZZ=1`.
+
+use std::env;
+
+/// Environment variable holding the number of seeded iterations to run.
+pub const FUZZ_ITERATIONS_VAR: &str = "SNARK_BENCH_FUZZ";
+
+/// Environment variable holding the first seed. A random seed is chosen when it is unset.
+pub const FUZZ_SEED_VAR: &str = "SNARK_BENCH_FUZZ_SEED";
+
+/// Returns the seeds to run in fuzz mode, or `None` if fuzz mode is not enabled.
+pub fn fuzz_seeds() -> Option<Vec<u64>> {
+	let iterations = env::var(FUZZ_ITERATIONS_VAR).ok()?;
+	let iterations = iterations.parse::<u64>().unwrap_or_else(|_| {
+		panic!("{FUZZ_ITERATIONS_VAR} must be a number of iterations, got {iterations:?}")
+	});
+	let first_seed = match env::var(FUZZ_SEED_VAR) {
+		Ok(seed) => seed
+			.parse::<u64>()
+			.unwrap_or_else(|_| panic!("{FUZZ_SEED_VAR} must be a u64, got {seed:?}")),
+		Err(_) => rand::random(),
+	};
+	Some(
+		(0..iterations)
+			.map(|i| first_seed.wrapping_add(i))
+			.collect(),
+	)
+}
@@ -0,0 +1,179 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Export of the results in the JSON format of Google Benchmark.
+//!
+//! Dashboards and comparison tools that ingest `--benchmark_format=json` output, such as Google
+//! Benchmark's own `compare.py`, read the `benchmarks` list of that format. Every phase of every
+//! configuration becomes a benchmark named `<backend>/<config>/<phase>/<n>`, with one entry per
+//! repetition of the phase and, for repeated phases, `mean`, `median`, and `stddev` aggregates as
+//! Google Benchmark reports them. The metrics of a configuration at a size, such as its proof size,
+//! are attached to each of its benchmarks as user counters. Only wall time is measured, so the CPU
+//! time equals the real time.
+
+use std::{collections::BTreeMap, io};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+	results::{Record, Results},
+	stability::median,
+};
+
+/// Civil date of a day count since the Unix epoch, by Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+	let z = days + 719468;
+	let era = z.div_euclid(146097);
+	let doe = z.rem_euclid(146097);
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+	let year = yoe + era * 400 + i64::from(month <= 2);
+	(year, month, day)
+}
+
+/// A Unix time as an ISO 8601 date and time in UTC, as in the `context.date` of Google Benchmark.
+pub(crate) fn iso8601(unix_time: u64) -> String {
+	let (days, seconds) = ((unix_time / 86400) as i64, unix_time % 86400);
+	let (year, month, day) = civil_from_days(days);
+	format!(
+		"{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}+00:00",
+		seconds / 3600,
+		seconds / 60 % 60,
+		seconds % 60
+	)
+}
+
+/// The name `<backend>/<config>/<phase>/<n>` of a benchmark, without an empty configuration.
+fn benchmark_name(backend: &str, config: &str, phase: &str, n: u64) -> String {
+	if config.is_empty() {
+		format!("{backend}/{phase}/{n}")
+	} else {
+		format!("{backend}/{config}/{phase}/{n}")
+	}
+}
+
+/// An entry of the `benchmarks` list, with the fields that differ between entry types and the
+/// user counters added to the common ones.
+fn entry(
+	name: &str,
+	run_name: &str,
+	family_index: usize,
+	nanoseconds: f64,
+	fields: Value,
+	counters: &Map<String, Value>,
+) -> Value {
+	let mut entry = json!({
+		"name": name,
+		"family_index": family_index,
+		"per_family_instance_index": 0,
+		"run_name": run_name,
+		"threads": 1,
+		"iterations": 1,
+		"real_time": nanoseconds,
+		"cpu_time": nanoseconds,
+		"time_unit": "ns",
+	});
+	let object = entry.as_object_mut().unwrap();
+	if let Value::Object(fields) = fields {
+		object.extend(fields);
+	}
+	object.extend(counters.clone());
+	entry
+}
+
+/// Writes the results as a Google Benchmark JSON document.
+pub fn write_google_benchmark(results: &[Results], mut out: impl io::Write) -> io::Result<()> {
+	// Benchmarks in the order they first ran, and the metrics of each configuration and size.
+	let mut names = Vec::new();
+	let mut benchmarks = BTreeMap::<String, Vec<f64>>::new();
+	let mut metrics = BTreeMap::<(String, String, u64), Map<String, Value>>::new();
+	for run in results {
+		for record in &run.records {
+			match record {
+				Record::Phase(record) => {
+					let name = benchmark_name(
+						&record.backend,
+						&record.config,
+						&record.phase,
+						record.n_elements,
+					);
+					if !benchmarks.contains_key(&name) {
+						names.push((
+							name.clone(),
+							(record.backend.clone(), record.config.clone(), record.n_elements),
+						));
+					}
+					benchmarks
+						.entry(name)
+						.or_default()
+						.push(record.seconds * 1e9);
+				}
+				Record::Metric(record) => {
+					if let Some(n) = record.n_elements {
+						metrics
+							.entry((record.backend.clone(), record.config.clone(), n))
+							.or_default()
+							.insert(record.metric.clone(), json!(record.value));
+					}
+				}
+			}
+		}
+	}
+
+	let no_counters = Map::new();
+	let mut entries = Vec::new();
+	for (family_index, (name, config)) in names.iter().enumerate() {
+		let times = &benchmarks[name];
+		let counters = metrics.get(config).unwrap_or(&no_counters);
+		let repetitions = times.len();
+		for (repetition_index, &nanoseconds) in times.iter().enumerate() {
+			let fields = json!({
+				"run_type": "iteration",
+				"repetitions": repetitions,
+				"repetition_index": repetition_index,
+			});
+			entries.push(entry(name, name, family_index, nanoseconds, fields, counters));
+		}
+		if repetitions < 2 {
+			continue;
+		}
+
+		let n = repetitions as f64;
+		let mean = times.iter().sum::<f64>() / n;
+		let mut sorted = times.clone();
+		sorted.sort_by(f64::total_cmp);
+		let variance = times.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0);
+		let aggregates = [
+			("mean", mean),
+			("median", median(&sorted)),
+			("stddev", variance.sqrt()),
+		];
+		for (aggregate, nanoseconds) in aggregates {
+			let fields = json!({
+				"run_type": "aggregate",
+				"repetitions": repetitions,
+				"aggregate_name": aggregate,
+				"aggregate_unit": "time",
+			});
+			let aggregate_name = format!("{name}_{aggregate}");
+			entries.push(entry(&aggregate_name, name, family_index, nanoseconds, fields, counters));
+		}
+	}
+
+	let examples = results
+		.iter()
+		.map(|run| run.example.as_str())
+		.collect::<Vec<_>>();
+	let context = json!({
+		"date": results.first().map(|run| iso8601(run.unix_time)).unwrap_or_default(),
+		"host_name": results.first().map(|run| run.machine.as_str()).unwrap_or_default(),
+		"executable": examples.join(","),
+		"library_build_type": "release",
+	});
+	let document = json!({"context": context, "benchmarks": entries});
+	serde_json::to_writer_pretty(&mut out, &document)?;
+	writeln!(out)?;
+	out.flush()
+}
@@ -0,0 +1,220 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Counting wrappers around the hash functions used for Merkle commitments.
+//!
+//! [`Counting`] forwards to the wrapped hasher or compression function and increments
+//! process-wide counters, so verifier hash budgets can be reported from the same runs as the
+//! timings. Digests count calls that hash leaf data, compressions count calls that combine child
+//! digests. Counts are per call, so a plonky3 packed hasher call that hashes one leaf per SIMD
+//! lane counts once.
+//!
+//! The counters are sharded across cache lines by thread so that counting does not serialize
+//! parallel tree building.
+
+use std::{
+	fmt,
+	marker::PhantomData,
+	mem::MaybeUninit,
+	sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+use p3_symmetric::{CompressionFunction, CryptographicHasher, PseudoCompressionFunction};
+use plonky2::{
+	hash::hash_types::RichField,
+	plonk::config::{GenericConfig, Hasher as Plonky2Hasher},
+};
+use serde::Serialize;
+
+use crate::cost_model::VerifierOps;
+
+const N_SHARDS: usize = 64;
+
+#[repr(align(128))]
+struct Shard {
+	digests: AtomicU64,
+	compressions: AtomicU64,
+}
+
+static SHARDS: [Shard; N_SHARDS] = [const {
+	Shard {
+		digests: AtomicU64::new(0),
+		compressions: AtomicU64::new(0),
+	}
+}; N_SHARDS];
+
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+	static THREAD_SHARD: &'static Shard =
+		&SHARDS[NEXT_SHARD.fetch_add(1, Ordering::Relaxed) % N_SHARDS];
+}
+
+fn record_digest() {
+	THREAD_SHARD.with(|shard| shard.digests.fetch_add(1, Ordering::Relaxed));
+}
+
+fn record_compression() {
+	THREAD_SHARD.with(|shard| shard.compressions.fetch_add(1, Ordering::Relaxed));
+}
+
+/// Hash invocation counts for the current process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HashCounts {
+	/// Calls hashing leaf data into a digest.
+	pub digests: u64,
+	/// Calls compressing child digests into a parent digest.
+	pub compressions: u64,
+}
+
+impl HashCounts {
+	/// Reads the counters, summed over all threads.
+	pub fn current() -> Self {
+		SHARDS.iter().fold(Self::default(), |counts, shard| Self {
+			digests: counts.digests + shard.digests.load(Ordering::Relaxed),
+			compressions: counts.compressions + shard.compressions.load(Ordering::Relaxed),
+		})
+	}
+
+	/// Returns the calls made since an earlier reading.
+	pub fn since(&self, earlier: &Self) -> Self {
+		Self {
+			digests: self.digests.saturating_sub(earlier.digests),
+			compressions: self.compressions.saturating_sub(earlier.compressions),
+		}
+	}
+}
+
+impl From<HashCounts> for VerifierOps {
+	fn from(counts: HashCounts) -> Self {
+		Self {
+			digests: Some(counts.digests),
+			compressions: Some(counts.compressions),
+			..Self::default()
+		}
+	}
+}
+
+impl fmt::Display for HashCounts {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "digests={}, compressions={}", self.digests, self.compressions)
+	}
+}
+
+/// A hasher or compression function that counts its invocations.
+///
+/// This implements the plonky3, plonky2, and binius hasher traits for whichever of them the
+/// wrapped type implements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counting<H>(pub H);
+
+impl<T: Clone, Out, H: CryptographicHasher<T, Out>> CryptographicHasher<T, Out> for Counting<H> {
+	fn hash_iter<I>(&self, input: I) -> Out
+	where
+		I: IntoIterator<Item = T>,
+	{
+		record_digest();
+		self.0.hash_iter(input)
+	}
+
+	fn hash_iter_slices<'a, I>(&self, input: I) -> Out
+	where
+		I: IntoIterator<Item = &'a [T]>,
+		T: 'a,
+	{
+		record_digest();
+		self.0.hash_iter_slices(input)
+	}
+
+	fn hash_slice(&self, input: &[T]) -> Out {
+		record_digest();
+		self.0.hash_slice(input)
+	}
+
+	fn hash_item(&self, input: T) -> Out {
+		record_digest();
+		self.0.hash_item(input)
+	}
+}
+
+impl<T, H: PseudoCompressionFunction<T, N>, const N: usize> PseudoCompressionFunction<T, N>
+	for Counting<H>
+{
+	fn compress(&self, input: [T; N]) -> T {
+		record_compression();
+		self.0.compress(input)
+	}
+}
+
+impl<T, H: CompressionFunction<T, N>, const N: usize> CompressionFunction<T, N> for Counting<H> {}
+
+impl<T, H: binius_hash::Hasher<T>> binius_hash::Hasher<T> for Counting<H> {
+	type Digest = H::Digest;
+
+	fn new() -> Self {
+		Self(H::new())
+	}
+
+	fn update(&mut self, data: impl AsRef<[T]>) {
+		self.0.update(data)
+	}
+
+	fn chain_update(self, data: impl AsRef<[T]>) -> Self {
+		Self(self.0.chain_update(data))
+	}
+
+	fn finalize(self) -> Self::Digest {
+		record_digest();
+		self.0.finalize()
+	}
+
+	fn finalize_into(self, out: &mut MaybeUninit<Self::Digest>) {
+		record_digest();
+		self.0.finalize_into(out)
+	}
+
+	fn finalize_reset(&mut self) -> Self::Digest {
+		record_digest();
+		self.0.finalize_reset()
+	}
+
+	fn finalize_into_reset(&mut self, out: &mut MaybeUninit<Self::Digest>) {
+		record_digest();
+		self.0.finalize_into_reset(out)
+	}
+
+	fn reset(&mut self) {
+		self.0.reset()
+	}
+}
+
+impl<F: RichField, H: Plonky2Hasher<F>> Plonky2Hasher<F> for Counting<H> {
+	const HASH_SIZE: usize = H::HASH_SIZE;
+	type Hash = H::Hash;
+	type Permutation = H::Permutation;
+
+	fn hash_no_pad(input: &[F]) -> Self::Hash {
+		record_digest();
+		H::hash_no_pad(input)
+	}
+
+	fn hash_pad(input: &[F]) -> Self::Hash {
+		record_digest();
+		H::hash_pad(input)
+	}
+
+	fn two_to_one(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+		record_compression();
+		H::two_to_one(left, right)
+	}
+}
+
+/// A plonky2 configuration whose Merkle hasher counts its invocations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CountingConfig<C>(PhantomData<C>);
+
+impl<C: GenericConfig<D>, const D: usize> GenericConfig<D> for CountingConfig<C> {
+	type F = C::F;
+	type FE = C::FE;
+	type Hasher = Counting<C::Hasher>;
+	type InnerHasher = C::InnerHasher;
+}
@@ -0,0 +1,173 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Peak heap measurement and allocation counting through a tracking global allocator.
+//!
+//! The `alloc_count` feature installs [`PeakAllocator`] as the global allocator of every target
+//! linking this crate, in front of the allocator chosen in [`crate::allocator`]. The examples then
+//! report [`AllocCounts`] and the [`HeapPeak`] of every phase, and the benches print the heap
+//! measured by [`peak_heap_during`] and [`retained_heap`]. Tracking costs atomic updates on every
+//! allocation, so without the feature the selected allocator is installed untouched, and the heap
+//! measurements read [`HeapBytes`] of `None`.
+
+use std::{
+	alloc::{GlobalAlloc, Layout},
+	fmt,
+	sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+use bytesize::ByteSize;
+
+use crate::allocator::SELECTED_ALLOCATOR;
+
+static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// The selected allocator, tracking live and peak heap bytes and counting allocations.
+///
+/// Every method forwards to the selected allocator, so that reallocations stay in place where it
+/// can grow them and zeroed allocations keep their lazily zeroed pages.
+pub struct PeakAllocator;
+
+impl PeakAllocator {
+	fn record_alloc(size: usize) {
+		let current = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+		PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+	}
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		let ptr = SELECTED_ALLOCATOR.alloc(layout);
+		if !ptr.is_null() {
+			Self::record_alloc(layout.size());
+		}
+		ptr
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		let ptr = SELECTED_ALLOCATOR.alloc_zeroed(layout);
+		if !ptr.is_null() {
+			Self::record_alloc(layout.size());
+		}
+		ptr
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		SELECTED_ALLOCATOR.dealloc(ptr, layout);
+		CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new_ptr = SELECTED_ALLOCATOR.realloc(ptr, layout, new_size);
+		if !new_ptr.is_null() {
+			// The block changes size by the difference and counts as an allocation of its new size.
+			CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+			Self::record_alloc(new_size);
+		}
+		new_ptr
+	}
+}
+
+/// Heap bytes measured by [`peak_heap_during`] or [`retained_heap`], or `None` without the
+/// `alloc_count` feature, which installs [`PeakAllocator`] to measure them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapBytes(pub Option<u64>);
+
+impl fmt::Display for HeapBytes {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.0 {
+			Some(bytes) => write!(f, "{}", ByteSize(bytes)),
+			None => f.write_str("not measured without the alloc_count feature"),
+		}
+	}
+}
+
+/// Runs `f` once and returns the peak heap usage above what was live when it started.
+///
+/// This is measured on the first call, so it includes any tables a library caches lazily. Without
+/// the `alloc_count` feature, `f` is not run.
+pub fn peak_heap_during<R>(f: impl FnOnce() -> R) -> HeapBytes {
+	if !cfg!(feature = "alloc_count") {
+		return HeapBytes(None);
+	}
+	let start = CURRENT_BYTES.load(Ordering::Relaxed);
+	PEAK_BYTES.store(start, Ordering::Relaxed);
+	drop(f());
+	HeapBytes(Some((PEAK_BYTES.load(Ordering::Relaxed) - start) as u64))
+}
+
+/// Runs `f` and returns its result along with the heap bytes that are still allocated when it
+/// returns, which is the memory the result holds on to.
+///
+/// Inputs moved into `f` and kept by the result are not counted, since they were live before it
+/// started. Like [`peak_heap_during`], this needs the `alloc_count` feature.
+pub fn retained_heap<R>(f: impl FnOnce() -> R) -> (R, HeapBytes) {
+	let start = CURRENT_BYTES.load(Ordering::Relaxed);
+	let result = f();
+	let retained = CURRENT_BYTES.load(Ordering::Relaxed).saturating_sub(start);
+	(result, HeapBytes(cfg!(feature = "alloc_count").then_some(retained as u64)))
+}
+
+/// Heap allocation counts for the current process, from [`PeakAllocator`].
+///
+/// Reallocations count as an allocation of the new size, as they do when vectors grow.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocCounts {
+	/// Number of allocations.
+	pub allocations: u64,
+	/// Total bytes allocated, regardless of whether they were freed since.
+	pub bytes: u64,
+}
+
+impl AllocCounts {
+	/// Reads the counters, if the `alloc_count` feature installed the counting allocator.
+	pub fn current() -> Option<Self> {
+		cfg!(feature = "alloc_count").then(|| Self {
+			allocations: ALLOCATIONS.load(Ordering::Relaxed),
+			bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+		})
+	}
+
+	/// Returns the allocations made since an earlier reading.
+	pub fn since(&self, earlier: &Self) -> Self {
+		Self {
+			allocations: self.allocations.saturating_sub(earlier.allocations),
+			bytes: self.bytes.saturating_sub(earlier.bytes),
+		}
+	}
+}
+
+impl fmt::Display for AllocCounts {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "count={}, bytes={}", self.allocations, ByteSize(self.bytes))
+	}
+}
+
+/// Tracks the largest heap size over a span, such as a phase, from [`PeakAllocator`].
+///
+/// Spans may nest: a span restores the peak of the span it is nested in when it finishes, so that
+/// both see the largest heap size over their own duration.
+#[derive(Debug)]
+pub struct HeapPeak {
+	/// The peak of the enclosing span when this one started.
+	outer_peak: usize,
+}
+
+impl HeapPeak {
+	/// Starts tracking, if the `alloc_count` feature installed the counting allocator.
+	pub fn start() -> Option<Self> {
+		cfg!(feature = "alloc_count").then(|| Self {
+			outer_peak: PEAK_BYTES.swap(CURRENT_BYTES.load(Ordering::Relaxed), Ordering::Relaxed),
+		})
+	}
+
+	/// Stops tracking and returns the largest number of heap bytes live during the span.
+	pub fn finish(self) -> u64 {
+		let peak = PEAK_BYTES.fetch_max(self.outer_peak, Ordering::Relaxed);
+		peak as u64
+	}
+}
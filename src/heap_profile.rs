@@ -0,0 +1,84 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Heap profiles of the examples under dhat.
+//!
+//! The per-phase allocation counts show how much a backend allocates, but not where. Passing
+//! `--profile heap` to an example built with the `dhat` feature runs it under the dhat-rs heap
+//! profiler, which records the call stack of every allocation, and writes the profile to
+//! `<directory>/<example>-<unix time>-heap.json`, with the directory taken from
+//! `SNARK_BENCH_PROFILE_DIR` or defaulting to `profiles`. The examples then stop after the first
+//! size of their sweep, as every allocation is slowed down by the stack capture. The profile can be
+//! opened in the dhat viewer (<https://nnethercote.github.io/dh_view/dh_view.html>).
+
+use std::{
+	env, fs,
+	path::Path,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::params::command_line;
+
+/// Environment variable holding the directory to write profiles to.
+pub const PROFILE_DIR_VAR: &str = "SNARK_BENCH_PROFILE_DIR";
+
+/// Directory profiles are written to when `SNARK_BENCH_PROFILE_DIR` is unset.
+pub const DEFAULT_PROFILE_DIR: &str = "profiles";
+
+/// Profilers selectable with `--profile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileKind {
+	/// Heap allocations under dhat.
+	Heap,
+}
+
+/// The profiler requested on the command line with `--profile <kind>` or `--profile=<kind>`.
+pub fn requested_profile() -> Option<ProfileKind> {
+	let mut args = command_line().into_iter();
+	while let Some(arg) = args.next() {
+		let kind = if arg == "--profile" {
+			args.next()
+		} else if let Some(kind) = arg.strip_prefix("--profile=") {
+			Some(kind.to_string())
+		} else {
+			continue;
+		};
+		return match kind.as_deref() {
+			Some("heap") => Some(ProfileKind::Heap),
+			kind => panic!("unknown profile {kind:?}, expected `--profile heap`"),
+		};
+	}
+	None
+}
+
+/// Records the heap profile until dropped, then writes it.
+pub struct HeapProfileGuard {
+	#[cfg(feature = "dhat")]
+	_profiler: dhat::Profiler,
+}
+
+/// Starts the heap profiler if `--profile heap` was passed.
+///
+/// The profile is only written once the returned guard is dropped, so it must be held until the
+/// end of `main`.
+pub fn init_heap_profile(name: &str) -> Option<HeapProfileGuard> {
+	if requested_profile()? != ProfileKind::Heap {
+		return None;
+	}
+	if !cfg!(feature = "dhat") {
+		panic!("`--profile heap` requires building with `--features dhat`");
+	}
+
+	let dir = env::var(PROFILE_DIR_VAR).unwrap_or_else(|_| DEFAULT_PROFILE_DIR.to_string());
+	fs::create_dir_all(&dir).unwrap_or_else(|err| panic!("failed to create {dir:?}: {err}"));
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap()
+		.as_secs();
+	let path = Path::new(&dir).join(format!("{name}-{timestamp}-heap.json"));
+	println!("Writing heap profile to {}", path.display());
+
+	Some(HeapProfileGuard {
+		#[cfg(feature = "dhat")]
+		_profiler: dhat::Profiler::builder().file_name(path).build(),
+	})
+}
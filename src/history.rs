@@ -0,0 +1,332 @@
+// Copyright 2024 Irreducible Inc.
+
+//! SQLite database of the results of all runs, for tracking backends over time.
+//!
+//! The results files of a run answer how backends compare today; whether an upstream release made
+//! a prover faster needs the same configurations across months of runs, machines, and dependency
+//! bumps. When `SNARK_BENCH_HISTORY` is set to a database file, every run appends its
+//! [`Results`] to it, keyed by run ID, with the machine and the locked git revisions of its
+//! dependencies. The `runs` table holds one row per run, `revisions` the dependency commits of each
+//! run, and `records` the values of each run in the long format of [`crate::report::rows`].
+//!
+//! [`query_history`] reads the values of one metric of one backend across runs, which
+//! `snark_bench history` prints. Runs designated as named baselines with [`set_baseline`] are what
+//! `snark_bench check` compares the latest run of the same example against.
+
+use std::{collections::BTreeMap, io, path::Path};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{
+	report::{rows, Row},
+	results::Results,
+	stability::median,
+};
+
+/// Environment variable holding the database file to append results to.
+pub const HISTORY_DB_VAR: &str = "SNARK_BENCH_HISTORY";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+	run_id TEXT PRIMARY KEY,
+	example TEXT NOT NULL,
+	unix_time INTEGER NOT NULL,
+	machine TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS revisions (
+	run_id TEXT NOT NULL REFERENCES runs (run_id),
+	dependency TEXT NOT NULL,
+	revision TEXT NOT NULL,
+	PRIMARY KEY (run_id, dependency)
+);
+CREATE TABLE IF NOT EXISTS records (
+	run_id TEXT NOT NULL REFERENCES runs (run_id),
+	backend TEXT NOT NULL,
+	config TEXT NOT NULL,
+	phase TEXT,
+	n_elements INTEGER,
+	metric TEXT NOT NULL,
+	value REAL NOT NULL,
+	unit TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS records_by_backend ON records (backend, metric);
+CREATE TABLE IF NOT EXISTS baselines (
+	name TEXT PRIMARY KEY,
+	run_id TEXT NOT NULL REFERENCES runs (run_id)
+);
+";
+
+/// Opens the database at `path`, creating it and its tables if needed.
+pub fn open_history(path: &Path) -> Result<Connection, String> {
+	let conn = Connection::open(path)
+		.map_err(|err| format!("failed to open {}: {err}", path.display()))?;
+	conn.execute_batch(SCHEMA)
+		.map_err(|err| format!("failed to create the tables of {}: {err}", path.display()))?;
+	Ok(conn)
+}
+
+/// Appends the results of a run to the database at `path`.
+pub fn append_history(path: &Path, results: &Results) -> Result<(), String> {
+	let mut conn = open_history(path)?;
+	let tx = conn.transaction().map_err(|err| err.to_string())?;
+	insert_run(&tx, results)
+		.map_err(|err| format!("failed to insert {}: {err}", results.run_id))?;
+	tx.commit().map_err(|err| err.to_string())
+}
+
+fn insert_run(conn: &Connection, results: &Results) -> rusqlite::Result<()> {
+	conn.execute(
+		"INSERT INTO runs (run_id, example, unix_time, machine) VALUES (?1, ?2, ?3, ?4)",
+		params![
+			results.run_id,
+			results.example,
+			results.unix_time,
+			results.machine
+		],
+	)?;
+	for (dependency, revision) in &results.revisions {
+		conn.execute(
+			"INSERT INTO revisions (run_id, dependency, revision) VALUES (?1, ?2, ?3)",
+			params![results.run_id, dependency, revision],
+		)?;
+	}
+	let mut insert = conn.prepare(
+		"INSERT INTO records (run_id, backend, config, phase, n_elements, metric, value, unit)
+		VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+	)?;
+	for row in rows(std::slice::from_ref(results)) {
+		insert.execute(params![
+			results.run_id,
+			row.backend,
+			row.config,
+			row.phase,
+			row.n_elements,
+			row.metric,
+			row.value,
+			row.unit,
+		])?;
+	}
+	Ok(())
+}
+
+/// Designates a run, or the latest run if `run_id` is `None`, as the baseline `name`, and returns
+/// its ID.
+pub fn set_baseline(conn: &Connection, name: &str, run_id: Option<&str>) -> Result<String, String> {
+	let run_id = match run_id {
+		Some(run_id) => conn
+			.query_row("SELECT run_id FROM runs WHERE run_id = ?1", [run_id], |row| row.get(0))
+			.optional()
+			.map_err(|err| err.to_string())?
+			.ok_or_else(|| format!("no run {run_id}"))?,
+		None => conn
+			.query_row("SELECT run_id FROM runs ORDER BY unix_time DESC, rowid DESC", [], |row| {
+				row.get(0)
+			})
+			.optional()
+			.map_err(|err| err.to_string())?
+			.ok_or("no runs to designate as a baseline")?,
+	};
+	conn.execute(
+		"INSERT OR REPLACE INTO baselines (name, run_id) VALUES (?1, ?2)",
+		params![name, run_id],
+	)
+	.map_err(|err| format!("failed to set the baseline {name}: {err}"))?;
+	Ok(run_id)
+}
+
+/// The run designated as the baseline `name`.
+pub fn baseline_run(conn: &Connection, name: &str) -> Result<String, String> {
+	conn.query_row("SELECT run_id FROM baselines WHERE name = ?1", [name], |row| row.get(0))
+		.optional()
+		.map_err(|err| err.to_string())?
+		.ok_or_else(|| format!("no baseline named {name}"))
+}
+
+/// The latest run of the same example as `run_id`, other than that run itself.
+pub fn latest_run_like(conn: &Connection, run_id: &str) -> Result<String, String> {
+	conn.query_row(
+		"SELECT runs.run_id FROM runs
+		JOIN runs AS baseline ON baseline.example = runs.example
+		WHERE baseline.run_id = ?1 AND runs.run_id != ?1
+		ORDER BY runs.unix_time DESC, runs.rowid DESC",
+		[run_id],
+		|row| row.get(0),
+	)
+	.optional()
+	.map_err(|err| err.to_string())?
+	.ok_or_else(|| format!("no other run of the same example as {run_id}"))
+}
+
+/// The locked commits of the dependencies of a run, by lowercase repository name.
+pub fn run_revisions(conn: &Connection, run_id: &str) -> Result<BTreeMap<String, String>, String> {
+	let query = || -> rusqlite::Result<BTreeMap<String, String>> {
+		let mut statement =
+			conn.prepare("SELECT dependency, revision FROM revisions WHERE run_id = ?1")?;
+		let revisions = statement.query_map([run_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+		revisions.collect()
+	};
+	query().map_err(|err| format!("failed to read the revisions of {run_id}: {err}"))
+}
+
+/// The values of a run in the long format of [`crate::report::rows`].
+pub fn run_rows(conn: &Connection, run_id: &str) -> Result<Vec<Row>, String> {
+	let query = || -> rusqlite::Result<Vec<Row>> {
+		let mut statement = conn.prepare(
+			"SELECT runs.example, runs.unix_time, records.backend, records.config, records.phase,
+				records.n_elements, records.metric, records.value, records.unit
+			FROM records
+			JOIN runs ON runs.run_id = records.run_id
+			WHERE records.run_id = ?1
+			ORDER BY records.rowid",
+		)?;
+		let rows = statement.query_map([run_id], |row| {
+			Ok(Row {
+				example: row.get(0)?,
+				unix_time: row.get(1)?,
+				backend: row.get(2)?,
+				config: row.get(3)?,
+				phase: row.get(4)?,
+				n_elements: row.get(5)?,
+				metric: row.get(6)?,
+				value: row.get(7)?,
+				unit: row.get(8)?,
+			})
+		})?;
+		rows.collect()
+	};
+	query().map_err(|err| format!("failed to read the run {run_id}: {err}"))
+}
+
+/// The value of a metric of one configuration in one run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryPoint {
+	/// Identifier of the run.
+	pub run_id: String,
+	/// Start of the run, in seconds since the Unix epoch.
+	pub unix_time: u64,
+	/// Host name of the machine the run ran on.
+	pub machine: String,
+	/// Locked commit of the backend's repository in the run, if it was recorded.
+	pub revision: Option<String>,
+	/// Parameters of the configuration other than the problem size.
+	pub config: String,
+	/// Phase the value was measured in, or `None` for metrics of a whole configuration.
+	pub phase: Option<String>,
+	/// Number of elements the phase processed, or the configuration ran with.
+	pub n_elements: Option<u64>,
+	/// Median of the values of the run, for phases repeated within it.
+	pub value: f64,
+	/// Unit of the value.
+	pub unit: String,
+}
+
+/// The values of `metric` of `backend` in every run, optionally only on `machine`, ordered by
+/// configuration, size, and time.
+///
+/// A metric named `<phase>_time`, such as `prove_time`, selects the wall time of that phase.
+pub fn query_history(
+	conn: &Connection,
+	backend: &str,
+	metric: &str,
+	machine: Option<&str>,
+) -> Result<Vec<HistoryPoint>, String> {
+	let (metric, phase) = match metric.strip_suffix("_time") {
+		Some(phase) => ("seconds", Some(phase)),
+		None => (metric, None),
+	};
+	let query = || -> rusqlite::Result<Vec<HistoryPoint>> {
+		let mut statement = conn.prepare(
+			"SELECT runs.run_id, runs.unix_time, runs.machine, revisions.revision, records.config,
+				records.phase, records.n_elements, records.value, records.unit
+			FROM records
+			JOIN runs ON runs.run_id = records.run_id
+			LEFT JOIN revisions
+				ON revisions.run_id = records.run_id AND revisions.dependency = records.backend
+			WHERE records.backend = ?1 AND records.metric = ?2
+				AND (?3 IS NULL OR records.phase = ?3) AND (?4 IS NULL OR runs.machine = ?4)
+			ORDER BY records.config, records.phase, records.n_elements, runs.unix_time, runs.run_id",
+		)?;
+		let points = statement.query_map(params![backend, metric, phase, machine], |row| {
+			Ok(HistoryPoint {
+				run_id: row.get(0)?,
+				unix_time: row.get(1)?,
+				machine: row.get(2)?,
+				revision: row.get(3)?,
+				config: row.get(4)?,
+				phase: row.get(5)?,
+				n_elements: row.get(6)?,
+				value: row.get(7)?,
+				unit: row.get(8)?,
+			})
+		})?;
+		points.collect()
+	};
+	let points = query().map_err(|err| format!("failed to query the history: {err}"))?;
+
+	// Phases repeated within a run have a value per repetition, reduced here to their median.
+	let mut history = Vec::<(HistoryPoint, Vec<f64>)>::new();
+	for point in points {
+		match history.last_mut() {
+			Some((last, values))
+				if last.run_id == point.run_id
+					&& last.config == point.config
+					&& last.phase == point.phase
+					&& last.n_elements == point.n_elements =>
+			{
+				values.push(point.value)
+			}
+			_ => {
+				let value = point.value;
+				history.push((point, vec![value]));
+			}
+		}
+	}
+	Ok(history
+		.into_iter()
+		.map(|(point, mut values)| {
+			values.sort_by(f64::total_cmp);
+			HistoryPoint {
+				value: median(&values),
+				..point
+			}
+		})
+		.collect())
+}
+
+/// Writes the history of each configuration as a table with one line per run.
+pub fn write_history(
+	backend: &str,
+	points: &[HistoryPoint],
+	mut out: impl io::Write,
+) -> io::Result<()> {
+	let mut last_key = None;
+	for point in points {
+		let key = (&point.config, &point.phase, point.n_elements);
+		if last_key != Some(key) {
+			let mut title = backend.to_string();
+			if !point.config.is_empty() {
+				title = format!("{title}, {}", point.config);
+			}
+			if let Some(phase) = &point.phase {
+				title = format!("{title}, {phase}");
+			}
+			if let Some(n) = point.n_elements {
+				title = format!("{title}, n={n}");
+			}
+			writeln!(out, "{title}:")?;
+			last_key = Some(key);
+		}
+		let revision = point.revision.as_deref().unwrap_or("-");
+		writeln!(
+			out,
+			"  {:<12} {:<24} {:<12} {:>14.6} {:<6} {}",
+			point.unix_time,
+			point.machine,
+			&revision[..revision.len().min(12)],
+			point.value,
+			point.unit,
+			point.run_id
+		)?;
+	}
+	out.flush()
+}
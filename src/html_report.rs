@@ -0,0 +1,436 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Interactive HTML report of the collected results, drawn with plotly.js.
+//!
+//! The report holds the charts people otherwise make by hand from the results. First, for every
+//! size and security level, each backend and configuration as a point of prover time against proof
+//! size, with the Pareto frontier of the configurations no other one beats in both highlighted;
+//! the prover time is that of the phases named `commit*` and `prove*`. Then the time of every phase
+//! against the problem size for each backend and configuration, on log-log axes; proof size against
+//! the code rate, for configurations that record both; for every backend, the proof size of each
+//! configuration stacked by the components [`crate::proof_size`] records, ordered by code rate; and
+//! for every size, the time of each configuration stacked by phase. Phases repeated in one or more
+//! runs are summarized by their median. The page loads plotly.js from its CDN, so it needs no build
+//! step but does need a network connection to render.
+
+use std::{collections::BTreeMap, io};
+
+use serde_json::{json, Value};
+
+use crate::{
+	proof_size::proof_size_component,
+	report::{label, median_of, PhaseTimings},
+	results::{Record, Results},
+};
+
+/// Location of the plotly.js bundle the report loads.
+const PLOTLY_URL: &str = "https://cdn.plot.ly/plotly-2.35.2.min.js";
+
+/// One plotly chart.
+struct Chart {
+	title: String,
+	traces: Vec<Value>,
+	layout: Value,
+}
+
+/// Whether a phase counts towards the prover time of a configuration.
+fn is_prover_phase(phase: &str) -> bool {
+	phase.starts_with("commit") || phase.starts_with("prove")
+}
+
+/// A configuration at one size, as a point of the Pareto charts.
+struct ParetoPoint {
+	series: String,
+	prover_seconds: f64,
+	proof_bytes: f64,
+}
+
+/// The points no other point beats in both prover time and proof size, by increasing time.
+fn pareto_frontier(points: &[ParetoPoint]) -> Vec<&ParetoPoint> {
+	let mut sorted = points.iter().collect::<Vec<_>>();
+	sorted.sort_by(|a, b| {
+		a.prover_seconds
+			.total_cmp(&b.prover_seconds)
+			.then(a.proof_bytes.total_cmp(&b.proof_bytes))
+	});
+	let mut frontier = Vec::<&ParetoPoint>::new();
+	for point in sorted {
+		if frontier
+			.last()
+			.is_none_or(|last| point.proof_bytes < last.proof_bytes)
+		{
+			frontier.push(point);
+		}
+	}
+	frontier
+}
+
+/// Prover time against proof size of every configuration, per size and security level rounded
+/// down to whole bits, for the configurations that record their proof size.
+fn pareto_charts(results: &[Results], timings: &PhaseTimings) -> Vec<Chart> {
+	let mut proof_bytes = BTreeMap::<(String, u64), Vec<f64>>::new();
+	let mut security_bits = BTreeMap::<(String, u64), f64>::new();
+	let records = results.iter().flat_map(|run| &run.records);
+	for record in records {
+		let Record::Metric(record) = record else {
+			continue;
+		};
+		let Some(n) = record.n_elements else {
+			continue;
+		};
+		let key = (label(&record.backend, &record.config), n);
+		match record.metric.as_str() {
+			"proof_size" => proof_bytes.entry(key).or_default().push(record.value),
+			"security_bits" => {
+				security_bits.insert(key, record.value);
+			}
+			_ => {}
+		}
+	}
+
+	let mut groups = BTreeMap::<(u64, Option<u64>), Vec<ParetoPoint>>::new();
+	for ((series, n), sizes) in proof_bytes {
+		let prover_seconds = timings
+			.phases
+			.iter()
+			.filter(|phase| is_prover_phase(phase))
+			.filter_map(|phase| timings.median(&series, phase, n))
+			.sum::<f64>();
+		if prover_seconds == 0.0 {
+			continue;
+		}
+		let bits = security_bits
+			.get(&(series.clone(), n))
+			.map(|bits| bits.floor() as u64);
+		groups.entry((n, bits)).or_default().push(ParetoPoint {
+			series,
+			prover_seconds,
+			proof_bytes: median_of(&sizes),
+		});
+	}
+
+	groups
+		.into_iter()
+		.map(|((n, bits), points)| {
+			let frontier = pareto_frontier(&points);
+			let traces = vec![
+				json!({
+					"type": "scatter",
+					"mode": "markers+text",
+					"name": "configurations",
+					"x": points.iter().map(|p| p.prover_seconds).collect::<Vec<_>>(),
+					"y": points.iter().map(|p| p.proof_bytes).collect::<Vec<_>>(),
+					"text": points.iter().map(|p| &p.series).collect::<Vec<_>>(),
+					"textposition": "top center",
+				}),
+				json!({
+					"type": "scatter",
+					"mode": "lines+markers",
+					"name": "Pareto frontier",
+					"line": {"shape": "hv"},
+					"marker": {"size": 12},
+					"x": frontier.iter().map(|p| p.prover_seconds).collect::<Vec<_>>(),
+					"y": frontier.iter().map(|p| p.proof_bytes).collect::<Vec<_>>(),
+					"text": frontier.iter().map(|p| &p.series).collect::<Vec<_>>(),
+				}),
+			];
+			let security = match bits {
+				Some(bits) => format!("{bits} bits of security"),
+				None => "unknown security".to_string(),
+			};
+			Chart {
+				title: format!("Prover time vs proof size at n={n}, {security}"),
+				traces,
+				layout: json!({
+					"xaxis": {"title": {"text": "prover seconds"}, "type": "log"},
+					"yaxis": {"title": {"text": "proof bytes"}, "type": "log"},
+				}),
+			}
+		})
+		.collect()
+}
+
+fn time_vs_size_charts(timings: &PhaseTimings) -> Vec<Chart> {
+	timings
+		.phases
+		.iter()
+		.map(|phase| Chart {
+			title: format!("{phase} time vs size"),
+			traces: timings
+				.series
+				.iter()
+				.filter_map(|series| {
+					let (sizes, seconds) = timings.curve(series, phase);
+					(!sizes.is_empty()).then(|| {
+						json!({
+							"type": "scatter",
+							"mode": "lines+markers",
+							"name": series,
+							"x": sizes,
+							"y": seconds,
+						})
+					})
+				})
+				.collect(),
+			layout: json!({
+				"xaxis": {"title": {"text": "elements"}, "type": "log"},
+				"yaxis": {"title": {"text": "seconds"}, "type": "log"},
+			}),
+		})
+		.collect()
+}
+
+/// Proof sizes against the code rate recorded before them in the same configuration.
+fn proof_size_vs_rate_chart(results: &[Results]) -> Option<Chart> {
+	let mut points = BTreeMap::<String, Vec<(f64, f64)>>::new();
+	for run in results {
+		let mut rates = BTreeMap::new();
+		for record in &run.records {
+			let Record::Metric(record) = record else {
+				continue;
+			};
+			let series = label(&record.backend, &record.config);
+			match record.metric.as_str() {
+				"log_inv_rate" => {
+					rates.insert(series, record.value);
+				}
+				"proof_size" => {
+					if let Some(&rate) = rates.get(&series) {
+						let key = match record.n_elements {
+							Some(n) => format!("{series}, n={n}"),
+							None => series,
+						};
+						points.entry(key).or_default().push((rate, record.value));
+					}
+				}
+				_ => {}
+			}
+		}
+	}
+	if points.is_empty() {
+		return None;
+	}
+
+	let traces = points
+		.into_iter()
+		.map(|(name, mut points)| {
+			points.sort_by(|a, b| a.0.total_cmp(&b.0));
+			let (rates, sizes): (Vec<_>, Vec<_>) = points.into_iter().unzip();
+			json!({
+				"type": "scatter",
+				"mode": "lines+markers",
+				"name": name,
+				"x": rates,
+				"y": sizes,
+			})
+		})
+		.collect();
+	Some(Chart {
+		title: "Proof size vs rate".to_string(),
+		traces,
+		layout: json!({
+			"xaxis": {"title": {"text": "log2 inverse rate"}, "dtick": 1},
+			"yaxis": {"title": {"text": "bytes"}},
+		}),
+	})
+}
+
+/// A bar of a proof size breakdown chart: a configuration at a size and code rate.
+struct ProofSizeBar {
+	name: String,
+	log_inv_rate: Option<f64>,
+	n_elements: Option<u64>,
+	components: BTreeMap<String, Vec<f64>>,
+}
+
+/// For every backend, the proof size of each configuration stacked by component, ordered by the
+/// code rate recorded before it in the same configuration.
+fn proof_size_breakdown_charts(results: &[Results]) -> Vec<Chart> {
+	// Bars by backend and name, and the components of each backend in the order they appear.
+	let mut bars = BTreeMap::<String, BTreeMap<String, ProofSizeBar>>::new();
+	let mut components = BTreeMap::<String, Vec<String>>::new();
+	for run in results {
+		let mut rates = BTreeMap::new();
+		for record in &run.records {
+			let Record::Metric(record) = record else {
+				continue;
+			};
+			let series = label(&record.backend, &record.config);
+			if record.metric == "log_inv_rate" {
+				rates.insert(series, record.value);
+				continue;
+			}
+			let Some(component) = proof_size_component(&record.metric) else {
+				continue;
+			};
+			let log_inv_rate = rates.get(&series).copied();
+			let mut name = if record.config.is_empty() {
+				record.backend.clone()
+			} else {
+				record.config.clone()
+			};
+			if let Some(n) = record.n_elements {
+				name = format!("{name}, n={n}");
+			}
+			if let Some(rate) = log_inv_rate {
+				name = format!("{name}, log2 1/rate={rate}");
+			}
+			let backend_components = components.entry(record.backend.clone()).or_default();
+			if !backend_components.iter().any(|c| c == component) {
+				backend_components.push(component.to_string());
+			}
+			bars.entry(record.backend.clone())
+				.or_default()
+				.entry(name.clone())
+				.or_insert_with(|| ProofSizeBar {
+					name,
+					log_inv_rate,
+					n_elements: record.n_elements,
+					components: BTreeMap::new(),
+				})
+				.components
+				.entry(component.to_string())
+				.or_default()
+				.push(record.value);
+		}
+	}
+
+	bars.into_iter()
+		.map(|(backend, bars)| {
+			let mut bars = bars.into_values().collect::<Vec<_>>();
+			bars.sort_by(|a, b| {
+				let rate = |bar: &ProofSizeBar| bar.log_inv_rate.unwrap_or(f64::NEG_INFINITY);
+				rate(a)
+					.total_cmp(&rate(b))
+					.then(a.n_elements.cmp(&b.n_elements))
+					.then_with(|| a.name.cmp(&b.name))
+			});
+			let names = bars.iter().map(|bar| bar.name.as_str()).collect::<Vec<_>>();
+			let traces = components[&backend]
+				.iter()
+				.map(|component| {
+					let bytes = bars
+						.iter()
+						.map(|bar| {
+							bar.components
+								.get(component)
+								.map(|values| median_of(values))
+						})
+						.collect::<Vec<_>>();
+					json!({
+						"type": "bar",
+						"name": component.replace('_', " "),
+						"x": names,
+						"y": bytes,
+					})
+				})
+				.collect();
+			Chart {
+				title: format!("Proof size breakdown of {backend}"),
+				traces,
+				layout: json!({
+					"barmode": "stack",
+					"yaxis": {"title": {"text": "bytes"}},
+				}),
+			}
+		})
+		.collect()
+}
+
+fn phase_breakdown_charts(timings: &PhaseTimings) -> Vec<Chart> {
+	timings
+		.sizes(None)
+		.into_iter()
+		.map(|n| {
+			let series = timings
+				.series
+				.iter()
+				.filter(|series| {
+					timings
+						.seconds
+						.keys()
+						.any(|(s, _, size)| s == *series && *size == n)
+				})
+				.collect::<Vec<_>>();
+			let traces = timings
+				.phases
+				.iter()
+				.map(|phase| {
+					let seconds = series
+						.iter()
+						.map(|series| timings.median(series, phase, n))
+						.collect::<Vec<_>>();
+					json!({
+						"type": "bar",
+						"name": phase,
+						"x": series,
+						"y": seconds,
+					})
+				})
+				.collect();
+			Chart {
+				title: format!("Phases at n={n}"),
+				traces,
+				layout: json!({
+					"barmode": "stack",
+					"yaxis": {"title": {"text": "seconds"}},
+				}),
+			}
+		})
+		.collect()
+}
+
+fn escape_html(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
+
+/// JSON for embedding in a script element, which must not contain a closing tag.
+fn script_json(value: &Value) -> String {
+	value.to_string().replace("</", "<\\/")
+}
+
+/// Writes the HTML report of the results.
+pub fn write_html(results: &[Results], mut out: impl io::Write) -> io::Result<()> {
+	let timings = PhaseTimings::new(results);
+	let mut charts = pareto_charts(results, &timings);
+	charts.extend(time_vs_size_charts(&timings));
+	charts.extend(proof_size_vs_rate_chart(results));
+	charts.extend(proof_size_breakdown_charts(results));
+	charts.extend(phase_breakdown_charts(&timings));
+
+	writeln!(out, "<!DOCTYPE html>")?;
+	writeln!(out, "<html>\n<head>\n<meta charset=\"utf-8\">")?;
+	writeln!(out, "<title>snark-bench report</title>")?;
+	writeln!(out, "<script src=\"{PLOTLY_URL}\"></script>")?;
+	writeln!(
+		out,
+		"<style>body {{ font-family: sans-serif; }} .chart {{ height: 480px; }}</style>"
+	)?;
+	writeln!(out, "</head>\n<body>\n<h1>snark-bench report</h1>\n<ul>")?;
+	for run in results {
+		writeln!(
+			out,
+			"<li>{}, started at Unix time {}, {} records</li>",
+			escape_html(&run.example),
+			run.unix_time,
+			run.records.len()
+		)?;
+	}
+	writeln!(out, "</ul>")?;
+	for (i, chart) in charts.iter().enumerate() {
+		let mut layout = chart.layout.clone();
+		layout["title"] = json!({"text": chart.title});
+		writeln!(out, "<div id=\"chart-{i}\" class=\"chart\"></div>")?;
+		writeln!(
+			out,
+			"<script>Plotly.newPlot(\"chart-{i}\", {}, {});</script>",
+			script_json(&Value::from(chart.traces.clone())),
+			script_json(&layout)
+		)?;
+	}
+	writeln!(out, "</body>\n</html>")?;
+	out.flush()
+}
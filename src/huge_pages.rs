@@ -0,0 +1,283 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Huge page backing for large allocations.
+//!
+//! NTT and Merkle passes over 2^26+ elements stride across far more memory than the TLB covers
+//! with 4 KiB pages, so their speed depends on whether the witness and codeword buffers are backed
+//! by huge pages. With the `huge_pages` feature, [`HugePageAllocator`] maps every allocation of at
+//! least [`HUGE_PAGE_BYTES`] directly, aligned to a huge page, and `SNARK_BENCH_HUGE_PAGES` picks
+//! their backing:
+//!
+//! - `off`: transparent huge pages are disabled for the mapping (`MADV_NOHUGEPAGE`).
+//! - `thp`: transparent huge pages are requested for the mapping (`MADV_HUGEPAGE`).
+//! - `explicit`: the mapping comes from the reserved hugetlb pool (`MAP_HUGETLB`), falling back to
+//!   `thp` when the pool is exhausted.
+//!
+//! When unset, the kernel's transparent huge page setting applies unchanged. Smaller allocations
+//! go to the allocator selected in [`crate::allocator`]. `scripts/huge_pages.sh` runs an example
+//! under each mode and reports the difference per phase. Huge pages are only supported on Linux;
+//! elsewhere every allocation goes to the selected allocator.
+
+use std::{
+	alloc::{GlobalAlloc, Layout},
+	ffi::CStr,
+	fmt, fs,
+	sync::atomic::{AtomicU64, AtomicU8, Ordering},
+};
+
+use crate::{allocator::BASE_ALLOCATOR, results::record_warning};
+
+/// Name of the environment variable that selects the huge page backing.
+pub const HUGE_PAGES_VAR: &str = "SNARK_BENCH_HUGE_PAGES";
+
+/// [`HUGE_PAGES_VAR`] for `getenv`, which the allocator reads without allocating.
+const HUGE_PAGES_VAR_C: &CStr = c"SNARK_BENCH_HUGE_PAGES";
+
+/// Size of the huge pages requested, and the smallest allocation mapped directly.
+pub const HUGE_PAGE_BYTES: usize = 2 << 20;
+
+/// Backing of the allocations mapped by [`HugePageAllocator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HugePageMode {
+	/// The kernel's transparent huge page setting.
+	Default,
+	/// Base pages only.
+	Off,
+	/// Transparent huge pages.
+	Transparent,
+	/// Pages from the hugetlb pool.
+	Explicit,
+}
+
+impl HugePageMode {
+	fn from_u8(value: u8) -> Self {
+		match value {
+			0 => Self::Default,
+			1 => Self::Off,
+			2 => Self::Transparent,
+			_ => Self::Explicit,
+		}
+	}
+
+	fn parse(value: &str) -> Option<Self> {
+		match value {
+			"off" => Some(Self::Off),
+			"thp" => Some(Self::Transparent),
+			"explicit" => Some(Self::Explicit),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for HugePageMode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Default => "default",
+			Self::Off => "off",
+			Self::Transparent => "thp",
+			Self::Explicit => "explicit",
+		})
+	}
+}
+
+const MODE_UNSET: u8 = u8::MAX;
+
+static MODE: AtomicU8 = AtomicU8::new(MODE_UNSET);
+static MAPPED_ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static EXPLICIT_FALLBACKS: AtomicU64 = AtomicU64::new(0);
+
+/// The huge page backing selected by `SNARK_BENCH_HUGE_PAGES`.
+///
+/// It is read once, on the first call, without allocating, so that the allocator can call it and
+/// every mapping of the process is backed the same way. Unrecognized values select the default.
+pub fn huge_page_mode() -> HugePageMode {
+	match MODE.load(Ordering::Relaxed) {
+		MODE_UNSET => {}
+		mode => return HugePageMode::from_u8(mode),
+	}
+	// SAFETY: the name is NUL-terminated and the value is only read before returning.
+	let value = unsafe {
+		let value = libc::getenv(HUGE_PAGES_VAR_C.as_ptr());
+		(!value.is_null()).then(|| CStr::from_ptr(value))
+	};
+	let mode = value
+		.and_then(|value| HugePageMode::parse(value.to_str().ok()?))
+		.unwrap_or(HugePageMode::Default);
+	MODE.store(mode as u8, Ordering::Relaxed);
+	mode
+}
+
+fn is_mapped(layout: Layout) -> bool {
+	cfg!(target_os = "linux")
+		&& layout.size() >= HUGE_PAGE_BYTES
+		&& layout.align() <= HUGE_PAGE_BYTES
+}
+
+fn mapped_len(size: usize) -> usize {
+	size.next_multiple_of(HUGE_PAGE_BYTES)
+}
+
+/// Maps `len` bytes aligned to a huge page, backed as `mode` selects.
+#[cfg(target_os = "linux")]
+unsafe fn map(len: usize, mode: HugePageMode) -> *mut u8 {
+	use libc::{
+		madvise, mmap, munmap, MADV_HUGEPAGE, MADV_NOHUGEPAGE, MAP_ANONYMOUS, MAP_FAILED,
+		MAP_HUGETLB, MAP_PRIVATE, PROT_READ, PROT_WRITE,
+	};
+
+	let prot = PROT_READ | PROT_WRITE;
+	let flags = MAP_PRIVATE | MAP_ANONYMOUS;
+	if mode == HugePageMode::Explicit {
+		let ptr = mmap(std::ptr::null_mut(), len, prot, flags | MAP_HUGETLB, -1, 0);
+		if ptr != MAP_FAILED {
+			return ptr.cast();
+		}
+		EXPLICIT_FALLBACKS.fetch_add(1, Ordering::Relaxed);
+	}
+
+	// Over-map by one huge page and trim both ends, leaving a mapping aligned to a huge page.
+	let ptr = mmap(std::ptr::null_mut(), len + HUGE_PAGE_BYTES, prot, flags, -1, 0);
+	if ptr == MAP_FAILED {
+		return std::ptr::null_mut();
+	}
+	let start = ptr as usize;
+	let aligned = start.next_multiple_of(HUGE_PAGE_BYTES);
+	if aligned > start {
+		munmap(ptr, aligned - start);
+	}
+	let tail = aligned + len;
+	let end = start + len + HUGE_PAGE_BYTES;
+	if end > tail {
+		munmap(tail as *mut libc::c_void, end - tail);
+	}
+
+	let advice = match mode {
+		HugePageMode::Default => None,
+		HugePageMode::Off => Some(MADV_NOHUGEPAGE),
+		HugePageMode::Transparent | HugePageMode::Explicit => Some(MADV_HUGEPAGE),
+	};
+	if let Some(advice) = advice {
+		madvise(aligned as *mut libc::c_void, len, advice);
+	}
+	aligned as *mut u8
+}
+
+#[cfg(not(target_os = "linux"))]
+unsafe fn map(_len: usize, _mode: HugePageMode) -> *mut u8 {
+	unreachable!("allocations are only mapped on Linux")
+}
+
+#[cfg(target_os = "linux")]
+unsafe fn unmap(ptr: *mut u8, len: usize) {
+	libc::munmap(ptr.cast(), len);
+}
+
+#[cfg(not(target_os = "linux"))]
+unsafe fn unmap(_ptr: *mut u8, _len: usize) {
+	unreachable!("allocations are only mapped on Linux")
+}
+
+/// The selected allocator, with allocations of at least [`HUGE_PAGE_BYTES`] mapped directly and
+/// backed as [`huge_page_mode`] selects.
+pub struct HugePageAllocator;
+
+unsafe impl GlobalAlloc for HugePageAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		if !is_mapped(layout) {
+			return BASE_ALLOCATOR.alloc(layout);
+		}
+		MAPPED_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		map(mapped_len(layout.size()), huge_page_mode())
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		if !is_mapped(layout) {
+			return BASE_ALLOCATOR.alloc_zeroed(layout);
+		}
+		// Anonymous mappings are zero-filled.
+		self.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		if is_mapped(layout) {
+			unmap(ptr, mapped_len(layout.size()));
+		} else {
+			BASE_ALLOCATOR.dealloc(ptr, layout);
+		}
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+		match (is_mapped(layout), is_mapped(new_layout)) {
+			(false, false) => BASE_ALLOCATOR.realloc(ptr, layout, new_size),
+			(true, true) if mapped_len(layout.size()) == mapped_len(new_size) => ptr,
+			_ => {
+				let new_ptr = self.alloc(new_layout);
+				if !new_ptr.is_null() {
+					std::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+					self.dealloc(ptr, layout);
+				}
+				new_ptr
+			}
+		}
+	}
+}
+
+/// Reads a field of `/proc/meminfo`, in the units the kernel reports it.
+fn meminfo(field: &str) -> Option<u64> {
+	let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+	meminfo
+		.lines()
+		.find_map(|line| line.strip_prefix(field)?.strip_prefix(':'))?
+		.split_whitespace()
+		.next()?
+		.parse()
+		.ok()
+}
+
+/// Prints the huge page mode, the kernel's transparent huge page setting, and the free pages in
+/// the hugetlb pool. Nothing is printed without the `huge_pages` feature.
+pub fn report_huge_pages() {
+	if !cfg!(feature = "huge_pages") {
+		return;
+	}
+	let thp = fs::read_to_string("/sys/kernel/mm/transparent_hugepage/enabled")
+		.ok()
+		.and_then(|enabled| {
+			let start = enabled.find('[')?;
+			let end = enabled.find(']')?;
+			Some(enabled[start + 1..end].to_string())
+		});
+	print!("Huge pages: mode={}", huge_page_mode());
+	if let Some(thp) = thp {
+		print!(", kernel THP={thp}");
+	}
+	if let (Some(free), Some(total)) = (meminfo("HugePages_Free"), meminfo("HugePages_Total")) {
+		print!(", hugetlb pool={free}/{total} free");
+	}
+	println!();
+	if huge_page_mode() == HugePageMode::Explicit && meminfo("HugePages_Free") == Some(0) {
+		record_warning(
+			"the hugetlb pool is empty, reserve pages in /proc/sys/vm/nr_hugepages or large \
+			 allocations fall back to transparent huge pages",
+		);
+	}
+}
+
+/// Prints how many allocations were mapped, how many of them fell back from the hugetlb pool, and
+/// the transparent huge pages in use. Nothing is printed without the `huge_pages` feature.
+pub fn report_huge_page_usage() {
+	if !cfg!(feature = "huge_pages") {
+		return;
+	}
+	print!(
+		"Huge page allocations: mapped={}, hugetlb fallbacks={}",
+		MAPPED_ALLOCATIONS.load(Ordering::Relaxed),
+		EXPLICIT_FALLBACKS.load(Ordering::Relaxed)
+	);
+	if let Some(anon_kib) = meminfo("AnonHugePages") {
+		print!(", system THP in use={}", bytesize::ByteSize(anon_kib << 10));
+	}
+	println!();
+}
@@ -0,0 +1,121 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Runs of the same benchmarks built for each instruction set level of the host, and the table of
+//! how much each backend gains from each level.
+//!
+//! binius and plonky3 choose their SIMD code paths at compile time, so measuring a backend at a
+//! lower level takes a rebuild with the features above it disabled, as `scripts/simd_levels.sh`
+//! does for the Criterion benches. [`run_at_level`] rebuilds and runs an example or bench with
+//! [`crate::variants::run_variant`] under the `RUSTFLAGS` of an [`IsaLevel`], in a target
+//! directory of its own so that the levels do not rebuild each other, and
+//! [`crate::variants::write_variant_table`] compares the levels against the lowest one.
+//!
+//! The levels on x86-64 are the x86-64 baseline without AVX, where plonky3 runs its scalar code,
+//! AVX2, AVX-512 without the GFNI and vector AES and carry-less multiplication extensions that
+//! binius uses for binary fields, and everything the CPU supports. On aarch64, NEON cannot be
+//! disabled, so the levels are NEON without and with the AES extension that the binius NEON code
+//! needs. Levels whose features the CPU lacks are skipped.
+
+use std::{
+	env,
+	path::{Path, PathBuf},
+};
+
+use crate::{
+	results::Results,
+	simd::detected_features,
+	variants::{run_variant, BenchTarget},
+};
+
+/// An instruction set level to build the benchmarks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IsaLevel {
+	/// Name of the level.
+	pub name: &'static str,
+	/// `RUSTFLAGS` of the builds for the level.
+	pub rustflags: &'static str,
+	/// CPU features the level needs, see [`detected_features`].
+	pub requires: &'static [&'static str],
+}
+
+/// The instruction set levels of this architecture, from the lowest.
+#[cfg(target_arch = "x86_64")]
+pub const ISA_LEVELS: &[IsaLevel] = &[
+	IsaLevel {
+		name: "baseline",
+		rustflags: "-Ctarget-cpu=x86-64",
+		requires: &[],
+	},
+	IsaLevel {
+		name: "avx2",
+		rustflags: "-Ctarget-cpu=native -Ctarget-feature=-avx512f,-gfni,-vpclmulqdq,-vaes",
+		requires: &["avx2"],
+	},
+	IsaLevel {
+		name: "avx512",
+		rustflags: "-Ctarget-cpu=native -Ctarget-feature=-gfni,-vpclmulqdq,-vaes",
+		requires: &["avx512f"],
+	},
+	IsaLevel {
+		name: "gfni",
+		rustflags: "-Ctarget-cpu=native",
+		requires: &["gfni"],
+	},
+];
+
+/// The instruction set levels of this architecture, from the lowest.
+#[cfg(target_arch = "aarch64")]
+pub const ISA_LEVELS: &[IsaLevel] = &[
+	IsaLevel {
+		name: "neon",
+		rustflags: "-Ctarget-cpu=native -Ctarget-feature=-aes",
+		requires: &["neon"],
+	},
+	IsaLevel {
+		name: "neon-aes",
+		rustflags: "-Ctarget-cpu=native",
+		requires: &["neon", "aes"],
+	},
+];
+
+/// The instruction set levels of this architecture, from the lowest.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub const ISA_LEVELS: &[IsaLevel] = &[IsaLevel {
+	name: "native",
+	rustflags: "-Ctarget-cpu=native",
+	requires: &[],
+}];
+
+/// The levels of [`ISA_LEVELS`] that this CPU supports.
+pub fn supported_levels() -> Vec<IsaLevel> {
+	let detected = detected_features();
+	ISA_LEVELS
+		.iter()
+		.filter(|level| {
+			level
+				.requires
+				.iter()
+				.all(|feature| detected.contains(feature))
+		})
+		.copied()
+		.collect()
+}
+
+/// Builds and runs `target` at `level` with the given Cargo features, writing its results to
+/// `dir`, and returns them.
+pub fn run_at_level(
+	level: &IsaLevel,
+	target: &BenchTarget,
+	features: Option<&str>,
+	dir: &Path,
+) -> Result<Vec<Results>, String> {
+	let target_dir = env::var_os("CARGO_TARGET_DIR")
+		.map_or_else(|| PathBuf::from("target"), PathBuf::from)
+		.join(format!("isa-{}", level.name));
+	let setting = format!("ISA level {} (RUSTFLAGS=\"{}\")", level.name, level.rustflags);
+	let envs = [
+		("RUSTFLAGS", level.rustflags.as_ref()),
+		("CARGO_TARGET_DIR", target_dir.as_os_str()),
+	];
+	run_variant(&setting, target, features, &envs, &[], dir)
+}
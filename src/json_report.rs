@@ -0,0 +1,97 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Export of the results as a flat JSON array of [`BenchResult`]s, one per timed phase.
+//!
+//! The results files keep phases and the metrics of their configurations as separate records, in
+//! the order they were taken, which suits appending but makes consumers join them. Here every
+//! phase carries its run, system, parameters, size, and wall time, and the proof size of its
+//! configuration at that size when the example recorded one, so the array loads straight into a
+//! data frame or a `jq` filter.
+
+use std::{collections::BTreeMap, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	proof_size::PROOF_SIZE_METRIC,
+	results::{Record, Results},
+};
+
+/// One timed phase of one run, with the proof size of its configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+	/// Example that ran the phase.
+	pub example: String,
+	/// Identifier of the run.
+	pub run_id: String,
+	/// Host name of the machine the run ran on.
+	pub machine: String,
+	/// Proving library the phase ran in.
+	pub system: String,
+	/// Parameters of the configuration other than the problem size.
+	pub params: String,
+	/// Name of the phase.
+	pub phase: String,
+	/// Number of elements the phase processed.
+	pub n_elements: u64,
+	/// Log2 of the number of elements, if it is a power of two.
+	pub n_vars: Option<u32>,
+	/// Device the phase ran on.
+	pub device: String,
+	/// Wall time in seconds.
+	pub wall_time: f64,
+	/// Peak resident set size during the phase, if the platform reports it.
+	pub peak_rss_bytes: Option<u64>,
+	/// Size of the proof of the configuration at this size in bytes, if the example records it.
+	pub proof_size: Option<u64>,
+}
+
+/// Flattens results into one entry per phase.
+pub fn bench_results(results: &[Results]) -> Vec<BenchResult> {
+	let mut bench_results = Vec::new();
+	for run in results {
+		let proof_sizes = run
+			.records
+			.iter()
+			.filter_map(|record| match record {
+				Record::Metric(metric) if metric.metric == PROOF_SIZE_METRIC => Some((
+					(&metric.backend, &metric.config, metric.n_elements?),
+					metric.value as u64,
+				)),
+				_ => None,
+			})
+			.collect::<BTreeMap<_, _>>();
+		for record in &run.records {
+			let Record::Phase(phase) = record else {
+				continue;
+			};
+			bench_results.push(BenchResult {
+				example: run.example.clone(),
+				run_id: run.run_id.clone(),
+				machine: run.machine.clone(),
+				system: phase.backend.clone(),
+				params: phase.config.clone(),
+				phase: phase.phase.clone(),
+				n_elements: phase.n_elements,
+				n_vars: phase
+					.n_elements
+					.is_power_of_two()
+					.then(|| phase.n_elements.trailing_zeros()),
+				device: phase.device.clone(),
+				wall_time: phase.seconds,
+				peak_rss_bytes: phase.peak_rss_bytes,
+				proof_size: proof_sizes
+					.get(&(&phase.backend, &phase.config, phase.n_elements))
+					.copied(),
+			});
+		}
+	}
+	bench_results
+}
+
+/// Writes the results as a JSON array of [`BenchResult`]s.
+pub fn write_json(results: &[Results], mut out: impl io::Write) -> io::Result<()> {
+	serde_json::to_writer_pretty(&mut out, &bench_results(results))?;
+	writeln!(out)?;
+	out.flush()
+}
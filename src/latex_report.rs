@@ -0,0 +1,268 @@
+// Copyright 2024 Irreducible Inc.
+
+//! LaTeX tables of the results for papers, in the style of the booktabs package.
+//!
+//! The table is the standard comparison of the backends: a row for every backend and
+//! configuration at every size, grouped by size, with a column for the median wall time of each
+//! phase and for the proof size. Other columns can be selected by the name of a phase or metric,
+//! such as `prove`, `verify`, `proof_size`, or `security_bits`. Each column is scaled to the unit
+//! that its largest value reaches, from seconds down to nanoseconds and from bytes up to GiB, and
+//! rounded to a fixed number of decimal places, so that the numbers can be pasted as they are.
+//! The output needs `\usepackage{booktabs}` in the preamble.
+
+use std::{collections::BTreeMap, io};
+
+use crate::{
+	proof_size::PROOF_SIZE_METRIC,
+	report::median_of,
+	results::{Record, Results},
+};
+
+/// Decimal places the values are rounded to by default.
+pub const DEFAULT_PRECISION: usize = 2;
+
+/// The scale of a unit for values up to `max`, and the name of the scaled unit.
+fn scaled_unit(unit: &str, max: f64) -> (f64, String) {
+	const TIME: [(f64, &str); 4] = [(1.0, "s"), (1e-3, "ms"), (1e-6, r"\textmu s"), (1e-9, "ns")];
+	const BYTES: [(f64, &str); 4] = [
+		(1024.0 * 1024.0 * 1024.0, "GiB"),
+		(1024.0 * 1024.0, "MiB"),
+		(1024.0, "KiB"),
+		(1.0, "B"),
+	];
+	let units = match unit {
+		"s" => &TIME,
+		"bytes" => &BYTES,
+		_ => return (1.0, escape_latex(unit)),
+	};
+	// The largest unit the values reach, or the smallest one.
+	let &(scale, name) = units
+		.iter()
+		.find(|(scale, _)| max >= *scale)
+		.unwrap_or(&units[units.len() - 1]);
+	(scale, name.to_string())
+}
+
+/// Escapes the characters LaTeX treats specially in text.
+fn escape_latex(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars() {
+		match c {
+			'&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+				escaped.push('\\');
+				escaped.push(c);
+			}
+			'~' => escaped.push_str(r"\textasciitilde{}"),
+			'^' => escaped.push_str(r"\textasciicircum{}"),
+			'\\' => escaped.push_str(r"\textbackslash{}"),
+			_ => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+/// A size, as a power of two when it is one.
+fn size_cell(n: u64) -> String {
+	if n.is_power_of_two() {
+		format!("$2^{{{}}}$", n.trailing_zeros())
+	} else {
+		n.to_string()
+	}
+}
+
+/// Values of the results by column, series, and size, with the series and columns in the order
+/// they first appear.
+struct Table {
+	series: Vec<(String, String)>,
+	phases: Vec<String>,
+	columns: Vec<String>,
+	units: BTreeMap<String, String>,
+	values: BTreeMap<(String, usize, u64), Vec<f64>>,
+}
+
+impl Table {
+	fn new(results: &[Results]) -> Self {
+		let mut table = Self {
+			series: Vec::new(),
+			phases: Vec::new(),
+			columns: Vec::new(),
+			units: BTreeMap::new(),
+			values: BTreeMap::new(),
+		};
+		let records = results.iter().flat_map(|run| &run.records);
+		for record in records {
+			let (backend, config, column, n, value, unit) = match record {
+				Record::Phase(record) => (
+					&record.backend,
+					&record.config,
+					&record.phase,
+					record.n_elements,
+					record.seconds,
+					"s",
+				),
+				Record::Metric(record) => {
+					let Some(n) = record.n_elements else {
+						continue;
+					};
+					(
+						&record.backend,
+						&record.config,
+						&record.metric,
+						n,
+						record.value,
+						record.unit.as_str(),
+					)
+				}
+			};
+			let series = (backend.clone(), config.clone());
+			let index = match table.series.iter().position(|s| *s == series) {
+				Some(index) => index,
+				None => {
+					table.series.push(series);
+					table.series.len() - 1
+				}
+			};
+			if let Record::Phase(_) = record {
+				if !table.phases.contains(column) {
+					table.phases.push(column.clone());
+				}
+			}
+			if !table.columns.contains(column) {
+				table.columns.push(column.clone());
+				table.units.insert(column.clone(), unit.to_string());
+			}
+			table
+				.values
+				.entry((column.clone(), index, n))
+				.or_default()
+				.push(value);
+		}
+		table
+	}
+
+	/// The columns of the standard comparison: every phase, then the proof size.
+	fn default_columns(&self) -> Vec<String> {
+		let mut columns = self.phases.clone();
+		if self.units.contains_key(PROOF_SIZE_METRIC) {
+			columns.push(PROOF_SIZE_METRIC.to_string());
+		}
+		columns
+	}
+
+	fn median(&self, column: &str, series: usize, n: u64) -> Option<f64> {
+		self.values
+			.get(&(column.to_string(), series, n))
+			.map(|values| median_of(values))
+	}
+}
+
+/// Writes a booktabs table of the results with the selected phases and metrics as columns, or
+/// those of the standard comparison by default, rounded to `precision` decimal places.
+pub fn write_latex(
+	results: &[Results],
+	columns: Option<&[String]>,
+	precision: usize,
+	mut out: impl io::Write,
+) -> Result<(), String> {
+	let table = Table::new(results);
+	let columns = match columns {
+		Some(columns) => {
+			for column in columns {
+				if !table.columns.contains(column) {
+					return Err(format!(
+						"no phase or metric named {column:?}, expected one of {:?}",
+						table.columns
+					));
+				}
+			}
+			columns.to_vec()
+		}
+		None => table.default_columns(),
+	};
+	if columns.is_empty() {
+		return Err("no phases or metrics to tabulate".to_string());
+	}
+
+	let mut sizes = table
+		.values
+		.keys()
+		.filter(|(column, _, _)| columns.contains(column))
+		.map(|&(_, _, n)| n)
+		.collect::<Vec<_>>();
+	sizes.sort_unstable();
+	sizes.dedup();
+	let units = columns
+		.iter()
+		.map(|column| {
+			let max = table
+				.values
+				.iter()
+				.filter(|((c, _, _), _)| c == column)
+				.map(|(_, values)| median_of(values).abs())
+				.fold(0.0, f64::max);
+			scaled_unit(&table.units[column], max)
+		})
+		.collect::<Vec<_>>();
+	let with_config = table.series.iter().any(|(_, config)| !config.is_empty());
+
+	let mut write = || -> io::Result<()> {
+		writeln!(out, "% Requires \\usepackage{{booktabs}}.")?;
+		let label_columns = if with_config { "ll" } else { "l" };
+		writeln!(out, "\\begin{{tabular}}{{{label_columns}r{}}}", "r".repeat(columns.len()))?;
+		writeln!(out, "\\toprule")?;
+		let mut header = vec!["Backend".to_string()];
+		if with_config {
+			header.push("Configuration".to_string());
+		}
+		header.push("$n$".to_string());
+		for (column, (_, unit)) in columns.iter().zip(&units) {
+			let name = escape_latex(&column.replace('_', " "));
+			header.push(match unit.as_str() {
+				"" | "count" => name,
+				unit => format!("{name} ({unit})"),
+			});
+		}
+		writeln!(out, "{} \\\\", header.join(" & "))?;
+
+		for (i, &n) in sizes.iter().enumerate() {
+			writeln!(
+				out,
+				"{}",
+				if i == 0 {
+					"\\midrule"
+				} else {
+					"\\addlinespace"
+				}
+			)?;
+			for (index, (backend, config)) in table.series.iter().enumerate() {
+				let values = columns
+					.iter()
+					.zip(&units)
+					.map(|(column, (scale, _))| {
+						table
+							.median(column, index, n)
+							.map(|value| format!("{:.precision$}", value / scale))
+					})
+					.collect::<Vec<_>>();
+				if values.iter().all(Option::is_none) {
+					continue;
+				}
+				let mut cells = vec![escape_latex(backend)];
+				if with_config {
+					cells.push(escape_latex(config));
+				}
+				cells.push(size_cell(n));
+				cells.extend(
+					values
+						.into_iter()
+						.map(|value| value.unwrap_or_else(|| "--".to_string())),
+				);
+				writeln!(out, "{} \\\\", cells.join(" & "))?;
+			}
+		}
+		writeln!(out, "\\bottomrule")?;
+		writeln!(out, "\\end{{tabular}}")?;
+		out.flush()
+	};
+	write().map_err(|err| format!("failed to write the report: {err}"))
+}
@@ -1 +1,72 @@
+// Copyright 2024 Irreducible Inc.
 
+//! Shared harness code for the benchmark examples and microbenchmarks.
+
+pub mod advisor;
+pub mod allocation_report;
+pub mod allocator;
+pub mod bandwidth;
+pub mod bencher;
+pub mod cache;
+pub mod core_efficiency;
+pub mod cost_model;
+pub mod cpu_state;
+pub mod criterion_import;
+pub mod cycles;
+pub mod environment;
+pub mod fuzz;
+pub mod google_benchmark;
+pub mod hash_count;
+pub mod heap;
+pub mod heap_profile;
+pub mod history;
+pub mod html_report;
+pub mod huge_pages;
+pub mod isa;
+pub mod json_report;
+pub mod latex_report;
+pub mod measurement;
+pub mod memory_cap;
+pub mod notify;
+pub mod numa;
+pub mod output;
+pub mod params;
+pub mod perf;
+pub mod phase;
+pub mod proof_size;
+pub mod regression;
+pub mod report;
+pub mod results;
+pub mod revisions;
+pub mod rss;
+pub mod rusage;
+pub mod scaling;
+pub mod scaling_plot;
+pub mod security;
+pub mod significance;
+pub mod simd;
+pub mod smt;
+pub mod stability;
+pub mod suite;
+pub mod summary;
+pub mod systems;
+pub mod threads;
+pub mod throughput;
+pub mod timeline;
+pub mod trace;
+pub mod variants;
+pub mod verifier_profile;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(feature = "dhat")]
+#[global_allocator]
+static ALLOCATOR: dhat::Alloc = dhat::Alloc;
+
+#[cfg(feature = "alloc_count")]
+#[global_allocator]
+static ALLOCATOR: heap::PeakAllocator = heap::PeakAllocator;
+
+#[cfg(not(any(feature = "dhat", feature = "alloc_count")))]
+#[global_allocator]
+static ALLOCATOR: allocator::SelectedAllocator = allocator::SELECTED_ALLOCATOR;
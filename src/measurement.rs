@@ -0,0 +1,168 @@
+// Copyright 2024 Irreducible Inc.
+
+//! A Criterion measurement reporting throughput as both field elements and bytes per second.
+//!
+//! The field groups configure their throughput in packed elements and the hashing groups in bytes,
+//! so comparing a multiplication against a hash, or two fields of different widths, otherwise means
+//! converting by hand. [`FieldThroughput`] measures wall time like Criterion's default, and prints
+//! every throughput in both units, converting with the element size most recently passed to
+//! [`set_element_bytes`]. Once the peak memory bandwidth has been measured, byte throughputs also
+//! show the fraction of it they reach.
+
+use std::{
+	sync::atomic::{AtomicU64, Ordering},
+	time::{Duration, Instant},
+};
+
+use criterion::{
+	measurement::{Measurement, ValueFormatter, WallTime},
+	Throughput,
+};
+
+use crate::bandwidth::bandwidth_fraction;
+
+/// Size of the field elements being benchmarked, or zero if unknown.
+static ELEMENT_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Sets the size in bytes of the elements benchmarked next, used to convert between elements and
+/// bytes. Criterion formats results right after each benchmark, so this must be called before
+/// every benchmark whose element size differs from the previous one.
+pub fn set_element_bytes(bytes: u64) {
+	ELEMENT_BYTES.store(bytes, Ordering::Relaxed);
+}
+
+/// Elements and bytes processed per iteration, where the element size is known.
+fn per_iteration(throughput: &Throughput) -> (Option<f64>, Option<f64>) {
+	let element_bytes = ELEMENT_BYTES.load(Ordering::Relaxed) as f64;
+	let known = element_bytes > 0.0;
+	match *throughput {
+		Throughput::Elements(elements) => {
+			let elements = elements as f64;
+			(Some(elements), known.then_some(elements * element_bytes))
+		}
+		Throughput::Bytes(bytes) | Throughput::BytesDecimal(bytes) => {
+			let bytes = bytes as f64;
+			(known.then_some(bytes / element_bytes), Some(bytes))
+		}
+	}
+}
+
+const ELEMENT_UNITS: [&str; 4] = ["elem/s", "Kelem/s", "Melem/s", "Gelem/s"];
+const BYTE_UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+
+/// Converts times per iteration in nanoseconds to rates of `per_iteration` per second, scaled by a
+/// power of 1000 chosen from `typical`, and returns the unit out of `units`.
+fn scale_rate(
+	per_iteration: f64,
+	typical: f64,
+	values: &mut [f64],
+	units: [&'static str; 4],
+) -> &'static str {
+	let typical_rate = per_iteration * 1e9 / typical;
+	let power = (0..3)
+		.take_while(|&i| typical_rate >= 1e3f64.powi(i + 1))
+		.count();
+	let factor = 1e3f64.powi(power as i32);
+	for value in values {
+		*value = per_iteration * 1e9 / *value / factor;
+	}
+	units[power]
+}
+
+fn format_rate(per_iteration: f64, ns: f64, units: [&'static str; 4]) -> String {
+	let mut values = [ns];
+	let unit = scale_rate(per_iteration, ns, &mut values, units);
+	format!("{:.4} {unit}", values[0])
+}
+
+/// Wall time measurement whose throughput is reported in both elements and bytes per second.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FieldThroughput;
+
+impl Measurement for FieldThroughput {
+	type Intermediate = Instant;
+	type Value = Duration;
+
+	fn start(&self) -> Self::Intermediate {
+		Instant::now()
+	}
+
+	fn end(&self, i: Self::Intermediate) -> Self::Value {
+		i.elapsed()
+	}
+
+	fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+		*v1 + *v2
+	}
+
+	fn zero(&self) -> Self::Value {
+		Duration::ZERO
+	}
+
+	fn to_f64(&self, value: &Self::Value) -> f64 {
+		value.as_nanos() as f64
+	}
+
+	fn formatter(&self) -> &dyn ValueFormatter {
+		self
+	}
+}
+
+impl ValueFormatter for FieldThroughput {
+	fn format_throughput(&self, throughput: &Throughput, value: f64) -> String {
+		let (elements, bytes) = per_iteration(throughput);
+		let elements = elements.map(|elements| format_rate(elements, value, ELEMENT_UNITS));
+		let bytes = bytes.map(|bytes| {
+			let rate = format_rate(bytes, value, BYTE_UNITS);
+			match bandwidth_fraction(bytes, value * 1e-9) {
+				Some(fraction) => format!("{rate}, {:.0}% of peak bandwidth", fraction * 100.0),
+				None => rate,
+			}
+		});
+		match (elements, bytes) {
+			(Some(elements), Some(bytes)) => format!("{elements} ({bytes})"),
+			(elements, bytes) => elements.or(bytes).unwrap_or_default(),
+		}
+	}
+
+	fn scale_values(&self, typical_value: f64, values: &mut [f64]) -> &'static str {
+		WallTime.formatter().scale_values(typical_value, values)
+	}
+
+	fn scale_throughputs(
+		&self,
+		typical_value: f64,
+		throughput: &Throughput,
+		values: &mut [f64],
+	) -> &'static str {
+		match per_iteration(throughput) {
+			(Some(elements), _) => scale_rate(elements, typical_value, values, ELEMENT_UNITS),
+			(None, Some(bytes)) => scale_rate(bytes, typical_value, values, BYTE_UNITS),
+			(None, None) => unreachable!("every throughput is in elements or bytes"),
+		}
+	}
+
+	fn scale_for_machines(&self, values: &mut [f64]) -> &'static str {
+		WallTime.formatter().scale_for_machines(values)
+	}
+}
+
+/// Measurement of the `field_ops` and `hashing` benchmarks: the hardware event selected with
+/// [`crate::perf::PERF_EVENT_VAR`] with the `perf_counters` feature on Linux, and wall time
+/// otherwise.
+#[cfg(all(feature = "perf_counters", target_os = "linux"))]
+pub type PerfMeasurement = crate::perf::HardwareCounter;
+#[cfg(not(all(feature = "perf_counters", target_os = "linux")))]
+pub type PerfMeasurement = FieldThroughput;
+
+/// Creates the [`PerfMeasurement`], panicking if its event cannot be counted.
+pub fn perf_measurement() -> PerfMeasurement {
+	#[cfg(all(feature = "perf_counters", target_os = "linux"))]
+	{
+		crate::perf::HardwareCounter::from_env()
+	}
+	#[cfg(not(all(feature = "perf_counters", target_os = "linux")))]
+	{
+		FieldThroughput
+	}
+}
@@ -0,0 +1,423 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Runs of the examples under a memory cap, emulating cloud instances of a given size, and the
+//! table of the smallest instance each configuration fits in.
+//!
+//! Whether a prover fits on a 16, 32, or 64 GiB instance decides what it costs to run, and the
+//! peak resident set of a run on a larger machine does not answer it: the page cache, allocator
+//! slack, and the memory the kernel reclaims under pressure all move the line. [`run_capped`] runs
+//! an example under a cap and records, for every configuration and trace size, whether it
+//! completed, completed while paging, or ran out of memory, and [`write_instance_table`] lists
+//! the outcomes per cap with the smallest cap each configuration completes under.
+//!
+//! The cap is applied in one of two ways, see [`CapMethod`]:
+//!
+//! - in a cgroup v2 with `memory.max` set to the cap, which counts resident memory and the page
+//!   cache as an instance would, and swaps out the rest if the machine has swap. The cgroups are
+//!   created under a directory the user can write to and whose `cgroup.subtree_control` enables
+//!   the `memory` controller, such as a scope from `systemd-run --user --scope -p Delegate=yes`;
+//! - otherwise with a `RLIMIT_DATA` resource limit, as `ulimit -d` sets, which caps the writable
+//!   private memory the process maps rather than what is resident. Allocations beyond it fail
+//!   instead of swapping, so this method never reports paging, and any failure of the run is
+//!   taken as running out of memory.
+//!
+//! The example is built before the cap is applied, so that the build does not count against it,
+//! and runs with `--output ndjson` so that the phases it finished survive it being killed. A
+//! configuration swapped if its phases took at least [`SWAP_FAULTS`] major page faults.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	env, fmt,
+	fs::{self, File},
+	io,
+	path::{Path, PathBuf},
+	process::{self, Command},
+};
+
+use bytesize::ByteSize;
+
+use crate::{
+	report::label,
+	results::{PhaseRecord, Record},
+	summary::size_cell,
+	variants::{variant_command, BenchTarget},
+};
+
+/// Memory sizes of common cloud instances, in GiB, the default caps.
+pub const INSTANCE_SIZES_GIB: [u64; 3] = [16, 32, 64];
+
+/// Major page faults over the phases of a configuration above which it counts as swapping.
+pub const SWAP_FAULTS: u64 = 256;
+
+/// Name of the file the records of a capped run are written to, in its directory.
+const RECORDS_FILE: &str = "records.ndjson";
+
+const GIB: u64 = 1 << 30;
+
+/// Parses a memory cap, a number of GiB or a size with a unit such as `512MiB`.
+pub fn parse_cap(text: &str) -> Result<u64, String> {
+	if let Ok(gib) = text.parse::<u64>() {
+		return Ok(gib * GIB);
+	}
+	text.parse::<ByteSize>()
+		.map(|size| size.as_u64())
+		.map_err(|err| format!("invalid memory cap {text:?}: {err}"))
+}
+
+/// A memory cap for display, in whole GiB when it is a multiple of one.
+pub fn cap_name(cap: u64) -> String {
+	match (cap / GIB, cap % GIB) {
+		(gib, 0) => format!("{gib} GiB"),
+		_ => ByteSize(cap).to_string_as(true),
+	}
+}
+
+/// The physical memory of the machine, if the platform reports it.
+pub fn physical_memory() -> Option<u64> {
+	#[cfg(unix)]
+	{
+		let pages = unsafe { libc::sysconf(libc::_SC_PHYS_PAGES) };
+		let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+		(pages > 0 && page_size > 0).then(|| pages as u64 * page_size as u64)
+	}
+	#[cfg(windows)]
+	{
+		crate::windows::physical_memory()
+	}
+	#[cfg(not(any(unix, windows)))]
+	{
+		None
+	}
+}
+
+/// How a memory cap is applied to a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapMethod {
+	/// A cgroup v2 created under the given directory, with `memory.max` set to the cap.
+	Cgroup(PathBuf),
+	/// A `RLIMIT_DATA` resource limit.
+	DataLimit,
+}
+
+impl fmt::Display for CapMethod {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Cgroup(parent) => write!(f, "cgroups under {}", parent.display()),
+			Self::DataLimit => write!(f, "RLIMIT_DATA"),
+		}
+	}
+}
+
+/// Creates a cgroup under `parent` whose memory is capped at `cap` bytes.
+fn create_cgroup(parent: &Path, cap: u64) -> Result<PathBuf, String> {
+	let cgroup = parent.join(format!("snark-bench-{}-{cap}", process::id()));
+	fs::create_dir(&cgroup)
+		.map_err(|err| format!("failed to create the cgroup {}: {err}", cgroup.display()))?;
+	if let Err(err) = fs::write(cgroup.join("memory.max"), cap.to_string()) {
+		let _ = fs::remove_dir(&cgroup);
+		return Err(format!(
+			"failed to set memory.max of {}, is the memory controller enabled in {}? {err}",
+			cgroup.display(),
+			parent.join("cgroup.subtree_control").display()
+		));
+	}
+	Ok(cgroup)
+}
+
+/// Number of processes of `cgroup` the kernel killed for running out of memory.
+fn oom_kills(cgroup: &Path) -> u64 {
+	fs::read_to_string(cgroup.join("memory.events"))
+		.ok()
+		.and_then(|events| {
+			let line = events.lines().find(|line| line.starts_with("oom_kill "))?;
+			line["oom_kill ".len()..].trim().parse().ok()
+		})
+		.unwrap_or(0)
+}
+
+/// Confines `command` before it execs: to `cgroup` if given, otherwise to a `RLIMIT_DATA` of
+/// `cap` bytes. The forked child applies the cap to itself, so that cargo and every process it
+/// starts inherit it, rather than being confined once already running.
+fn confine(command: &mut Command, cgroup: Option<&Path>, cap: u64) -> Result<(), String> {
+	#[cfg(target_os = "linux")]
+	{
+		use std::{
+			ffi::CString,
+			os::unix::{ffi::OsStrExt, process::CommandExt},
+		};
+
+		let procs = cgroup
+			.map(|cgroup| CString::new(cgroup.join("cgroup.procs").as_os_str().as_bytes()))
+			.transpose()
+			.map_err(|err| format!("invalid cgroup path: {err}"))?;
+		let limit = libc::rlimit {
+			rlim_cur: cap,
+			rlim_max: cap,
+		};
+		// SAFETY: the closure runs between fork and exec, and only makes async-signal-safe system
+		// calls on data prepared before the fork, without allocating.
+		unsafe {
+			command.pre_exec(move || {
+				match &procs {
+					// Writing 0 to `cgroup.procs` moves the writing process into the cgroup.
+					Some(procs) => {
+						let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+						if fd < 0 {
+							return Err(io::Error::last_os_error());
+						}
+						let written = libc::write(fd, b"0".as_ptr().cast(), 1);
+						let err = io::Error::last_os_error();
+						libc::close(fd);
+						if written != 1 {
+							return Err(err);
+						}
+					}
+					None => {
+						if libc::setrlimit(libc::RLIMIT_DATA, &limit) != 0 {
+							return Err(io::Error::last_os_error());
+						}
+					}
+				}
+				Ok(())
+			});
+		}
+		Ok(())
+	}
+	#[cfg(not(target_os = "linux"))]
+	{
+		let _ = (command, cgroup, cap);
+		Err("memory caps are only supported on Linux".to_string())
+	}
+}
+
+/// A run of an example under a memory cap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CappedRun {
+	/// The cap, in bytes.
+	pub cap: u64,
+	/// Whether the run ran out of memory before finishing.
+	pub out_of_memory: bool,
+	/// The phases the run finished, in order.
+	pub phases: Vec<PhaseRecord>,
+}
+
+/// Builds `example` with the given Cargo features, then runs it with its memory capped at `cap`
+/// bytes by `method`, writing its results to `dir`.
+pub fn run_capped(
+	example: &str,
+	features: Option<&str>,
+	cap: u64,
+	method: &CapMethod,
+	dir: &Path,
+) -> Result<CappedRun, String> {
+	let mut build = Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+	build.args(["build", "--release", "--example", example]);
+	if let Some(features) = features {
+		build.args(["--features", features]);
+	}
+	let status = build
+		.status()
+		.map_err(|err| format!("failed to run cargo: {err}"))?;
+	if !status.success() {
+		return Err(format!("failed to build {example}: {status}"));
+	}
+
+	let target = BenchTarget::Example(example.to_string());
+	let mut cargo = variant_command(&target, features, &[], dir)?;
+	let records_path = dir.join(RECORDS_FILE);
+	let records = File::create(&records_path)
+		.map_err(|err| format!("failed to create {}: {err}", records_path.display()))?;
+	cargo.args(["--", "--output", "ndjson"]).stdout(records);
+
+	let cgroup = match method {
+		CapMethod::Cgroup(parent) => Some(create_cgroup(parent, cap)?),
+		CapMethod::DataLimit => None,
+	};
+	println!("== Memory cap {} ({method}): {example}", cap_name(cap));
+	let status = confine(&mut cargo, cgroup.as_deref(), cap).and_then(|()| {
+		cargo
+			.status()
+			.map_err(|err| format!("failed to run cargo under the cap: {err}"))
+	});
+	let oom_killed = cgroup.as_deref().map(oom_kills);
+	if let Some(cgroup) = &cgroup {
+		let _ = fs::remove_dir(cgroup);
+	}
+	let status = status?;
+
+	let out_of_memory = !status.success() && oom_killed.is_none_or(|kills| kills > 0);
+	if !status.success() && !out_of_memory {
+		return Err(format!("{example} failed under a memory cap of {}: {status}", cap_name(cap)));
+	}
+
+	// A run killed while writing a record leaves its last line incomplete.
+	let phases = fs::read_to_string(&records_path)
+		.map_err(|err| format!("failed to read {}: {err}", records_path.display()))?
+		.lines()
+		.filter_map(|line| match serde_json::from_str(line) {
+			Ok(Record::Phase(phase)) => Some(phase),
+			_ => None,
+		})
+		.collect();
+	Ok(CappedRun {
+		cap,
+		out_of_memory,
+		phases,
+	})
+}
+
+/// What happened to a configuration at one trace size under one cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+	/// Every phase finished without paging.
+	Completed,
+	/// Every phase finished, with at least [`SWAP_FAULTS`] major page faults.
+	Swapped,
+	/// The run ran out of memory during the configuration.
+	OutOfMemory,
+	/// The run ran out of memory before it reached the configuration.
+	NotReached,
+}
+
+impl fmt::Display for Outcome {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.pad(match self {
+			Self::Completed => "ok",
+			Self::Swapped => "swap",
+			Self::OutOfMemory => "OOM",
+			Self::NotReached => "-",
+		})
+	}
+}
+
+/// A configuration at one trace size: its series label and number of elements.
+type Configuration = (String, u64);
+
+/// Writes, for every configuration and trace size of the runs of `example`, its outcome under
+/// every cap, the peak resident set of its phases, and the smallest cap it completed under.
+pub fn write_instance_table(
+	example: &str,
+	runs: &[CappedRun],
+	mut out: impl io::Write,
+) -> Result<(), String> {
+	let configuration =
+		|phase: &PhaseRecord| (label(&phase.backend, &phase.config), phase.n_elements);
+
+	// The configurations in the order they ran, taken first from the runs that got furthest, and
+	// the phases every configuration of a series runs.
+	let mut by_progress = runs.iter().collect::<Vec<_>>();
+	by_progress.sort_by_key(|run| std::cmp::Reverse(run.phases.len()));
+	let mut order = Vec::<Configuration>::new();
+	let mut series_phases = BTreeMap::<String, BTreeSet<&str>>::new();
+	for phase in by_progress.iter().flat_map(|run| &run.phases) {
+		let configuration = configuration(phase);
+		series_phases
+			.entry(configuration.0.clone())
+			.or_default()
+			.insert(&phase.phase);
+		if !order.contains(&configuration) {
+			order.push(configuration);
+		}
+	}
+	if order.is_empty() {
+		return writeln!(out, "{example} finished no phases under any memory cap")
+			.map_err(|err| format!("failed to write the memory cap table: {err}"));
+	}
+
+	let outcomes = runs
+		.iter()
+		.map(|run| {
+			let mut finished = BTreeMap::<&Configuration, (BTreeSet<&str>, u64)>::new();
+			for phase in &run.phases {
+				let configuration = order.iter().find(|&c| *c == configuration(phase)).unwrap();
+				let (phases, faults) = finished.entry(configuration).or_default();
+				phases.insert(&phase.phase);
+				*faults += phase.major_page_faults.unwrap_or(0);
+			}
+			let completed = |configuration: &Configuration| {
+				finished
+					.get(configuration)
+					.filter(|(phases, _)| *phases == series_phases[&configuration.0])
+					.map(|&(_, faults)| faults)
+			};
+			let killed = order
+				.iter()
+				.find(|configuration| completed(configuration).is_none());
+			order
+				.iter()
+				.map(|configuration| match completed(configuration) {
+					Some(faults) if faults >= SWAP_FAULTS => Outcome::Swapped,
+					Some(_) => Outcome::Completed,
+					None if run.out_of_memory && killed == Some(configuration) => {
+						Outcome::OutOfMemory
+					}
+					None => Outcome::NotReached,
+				})
+				.collect::<Vec<_>>()
+		})
+		.collect::<Vec<_>>();
+
+	let mut series = Vec::<&str>::new();
+	for (name, _) in &order {
+		if !series.contains(&name.as_str()) {
+			series.push(name);
+		}
+	}
+	let caps = runs.iter().map(|run| cap_name(run.cap)).collect::<Vec<_>>();
+	let largest = runs.iter().map(|run| run.cap).max().unwrap_or(0);
+
+	let mut write = || -> io::Result<()> {
+		writeln!(
+			out,
+			"Outcome of {example} under each memory cap (ok: completed, swap: completed with at least \
+			 {SWAP_FAULTS} major page faults, OOM: ran out of memory, -: not reached):"
+		)?;
+		for series in &series {
+			writeln!(out)?;
+			writeln!(out, "{series}")?;
+			write!(out, "  {:8}", "n")?;
+			for cap in &caps {
+				write!(out, " {cap:>8}")?;
+			}
+			writeln!(out, " {:>12} {:>14}", "peak RSS", "minimum")?;
+
+			let mut sizes = order
+				.iter()
+				.enumerate()
+				.filter(|(_, (name, _))| name == series)
+				.collect::<Vec<_>>();
+			sizes.sort_by_key(|(_, (_, n))| *n);
+			for (index, (_, n)) in sizes {
+				write!(out, "  {:8}", size_cell(*n))?;
+				for outcomes in &outcomes {
+					write!(out, " {:>8}", outcomes[index])?;
+				}
+				let peak_rss = runs
+					.iter()
+					.zip(&outcomes)
+					.filter(|(_, outcomes)| outcomes[index] == Outcome::Completed)
+					.flat_map(|(run, _)| &run.phases)
+					.filter(|phase| configuration(phase) == order[index])
+					.filter_map(|phase| phase.peak_rss_bytes)
+					.max();
+				let smallest = |outcome| {
+					runs.iter()
+						.zip(&outcomes)
+						.filter(|(_, outcomes)| outcomes[index] == outcome)
+						.map(|(run, _)| run.cap)
+						.min()
+				};
+				let minimum = match (smallest(Outcome::Completed), smallest(Outcome::Swapped)) {
+					(Some(cap), _) => cap_name(cap),
+					(None, Some(cap)) => format!("{} (swap)", cap_name(cap)),
+					(None, None) => format!("> {}", cap_name(largest)),
+				};
+				let peak_rss =
+					peak_rss.map_or("-".to_string(), |bytes| ByteSize(bytes).to_string());
+				writeln!(out, " {peak_rss:>12} {minimum:>14}")?;
+			}
+		}
+		out.flush()
+	};
+	write().map_err(|err| format!("failed to write the memory cap table: {err}"))
+}
@@ -0,0 +1,174 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Notification of the end of a run, for sweeps left running on remote machines.
+//!
+//! Matrix sweeps run for hours on machines that are only reachable over SSH, so finding out that
+//! one finished meant polling. When `SNARK_BENCH_NOTIFY` is set, [`notify`] sends a JSON
+//! [`Notification`] at the end of the run, with the headline counts, the warnings, the paths of
+//! the files written, and the Markdown summary of [`crate::summary`]:
+//!
+//! - to an `http://` or `https://` URL, it is POSTed with `curl`, which handles TLS and proxies
+//!   without adding an HTTP client to the build. The `text` field holds the summary, so Slack and
+//!   Mattermost incoming webhooks display it as they are;
+//! - to any other value, it is written as a completion marker file at that path, for scripts that
+//!   wait on the file over a shared filesystem or `ssh test -f`. The file is written under a
+//!   temporary name first and renamed, so it never appears half written.
+//!
+//! A failed notification is printed and otherwise ignored, so that it never loses the results.
+
+use std::{
+	env, fs,
+	io::Write,
+	path::Path,
+	process::{Command, Stdio},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+	results::{Record, Results},
+	summary::write_summary,
+};
+
+/// Environment variable holding the URL to POST to, or the path of the marker file to write, at
+/// the end of the run.
+pub const NOTIFY_VAR: &str = "SNARK_BENCH_NOTIFY";
+
+/// Seconds `curl` may take to deliver the notification.
+const POST_TIMEOUT_SECONDS: u32 = 30;
+
+/// What a run sends when it ends.
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+	/// The headline and Markdown summary of the run, for chat webhooks.
+	pub text: String,
+	/// Name of the example.
+	pub example: String,
+	/// Identifier of the run.
+	pub run_id: String,
+	/// Host name of the machine the run ran on.
+	pub machine: String,
+	/// Start of the run, in seconds since the Unix epoch.
+	pub started_unix_time: u64,
+	/// End of the run, in seconds since the Unix epoch.
+	pub finished_unix_time: u64,
+	/// Number of phases timed.
+	pub phases: usize,
+	/// Warnings about conditions that skew the results.
+	pub warnings: Vec<String>,
+	/// Path of the results file, if one was written.
+	pub results_file: Option<String>,
+	/// Path of the summary file, if one was written.
+	pub summary_file: Option<String>,
+}
+
+impl Notification {
+	/// The notification of the end of the run of `results`.
+	pub fn new(
+		results: &Results,
+		results_file: Option<&Path>,
+		summary_file: Option<&Path>,
+	) -> Self {
+		let finished_unix_time = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap()
+			.as_secs();
+		let phases = results
+			.records
+			.iter()
+			.filter(|record| matches!(record, Record::Phase(_)))
+			.count();
+		let mut text = format!(
+			"{} finished on {} after {} s: {phases} phases, {} warnings\n\n",
+			results.example,
+			results.machine,
+			finished_unix_time.saturating_sub(results.unix_time),
+			results.warnings.len()
+		)
+		.into_bytes();
+		// Writing to a vector does not fail.
+		let _ = write_summary(std::slice::from_ref(results), &mut text);
+		Self {
+			text: String::from_utf8_lossy(&text).into_owned(),
+			example: results.example.clone(),
+			run_id: results.run_id.clone(),
+			machine: results.machine.clone(),
+			started_unix_time: results.unix_time,
+			finished_unix_time,
+			phases,
+			warnings: results.warnings.clone(),
+			results_file: results_file.map(|path| path.display().to_string()),
+			summary_file: summary_file.map(|path| path.display().to_string()),
+		}
+	}
+}
+
+/// POSTs a JSON body to `url` with `curl`.
+fn post(url: &str, body: &[u8]) -> Result<(), String> {
+	let mut curl = Command::new("curl")
+		.args(["--silent", "--show-error", "--fail", "--max-time"])
+		.arg(POST_TIMEOUT_SECONDS.to_string())
+		.args([
+			"--header",
+			"Content-Type: application/json",
+			"--data-binary",
+			"@-",
+		])
+		.arg(url)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::null())
+		.spawn()
+		.map_err(|err| format!("failed to run curl: {err}"))?;
+	let written = curl.stdin.take().unwrap().write_all(body);
+	let status = curl
+		.wait()
+		.map_err(|err| format!("failed to run curl: {err}"))?;
+	written.map_err(|err| format!("failed to send the notification to curl: {err}"))?;
+	if !status.success() {
+		return Err(format!("curl exited with {status}"));
+	}
+	Ok(())
+}
+
+/// Writes `body` to the marker file at `path`, through a temporary file renamed into place.
+fn write_marker(path: &Path, body: &[u8]) -> Result<(), String> {
+	if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+		fs::create_dir_all(dir)
+			.map_err(|err| format!("failed to create {}: {err}", dir.display()))?;
+	}
+	let mut temporary = path.as_os_str().to_owned();
+	temporary.push(".tmp");
+	fs::write(&temporary, body)
+		.and_then(|()| fs::rename(&temporary, path))
+		.map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+/// Sends the notification of the end of the run of `results` to the target in
+/// `SNARK_BENCH_NOTIFY`, if it is set.
+pub fn notify(results: &Results, results_file: Option<&Path>, summary_file: Option<&Path>) {
+	let Ok(target) = env::var(NOTIFY_VAR) else {
+		return;
+	};
+	let notification = Notification::new(results, results_file, summary_file);
+	let body = match serde_json::to_vec_pretty(&notification) {
+		Ok(body) => body,
+		Err(err) => {
+			println!("Failed to serialize the notification: {err}");
+			return;
+		}
+	};
+	if target.starts_with("http://") || target.starts_with("https://") {
+		match post(&target, &body) {
+			// The URL of a webhook is its credential, so it is kept out of the logs.
+			Ok(()) => println!("Sent the run notification"),
+			Err(err) => println!("Failed to send the run notification: {err}"),
+		}
+	} else {
+		let path = Path::new(&target);
+		match write_marker(path, &body) {
+			Ok(()) => println!("Wrote the completion marker to {}", path.display()),
+			Err(err) => println!("Failed to write the completion marker: {err}"),
+		}
+	}
+}
@@ -0,0 +1,248 @@
+// Copyright 2024 Irreducible Inc.
+
+//! NUMA topology reporting and placement, on Linux.
+//!
+//! On multi-socket machines, every memory access from a thread on one node to pages on another
+//! crosses the interconnect, so the same prover can run at very different speeds depending on
+//! where the scheduler and the kernel happened to put it. Setting `SNARK_BENCH_NUMA` to a node
+//! index runs the examples on that node's CPUs with their memory bound to it, and setting it to
+//! `interleave` spreads pages across all nodes instead. The policy is applied to the main thread
+//! before any worker threads start, and the threads it spawns inherit it.
+//!
+//! A [`NumaStudy`] lists the two placements of the `numa` command of `snark_bench`, which runs the
+//! large PCS configurations on one socket and then on two with interleaved memory. rayon sizes its
+//! pool from the CPUs the process may run on, so the run bound to a node uses only that node's
+//! CPUs, and the ratio of the two runs is the scaling of each backend across sockets.
+
+use std::{env, fmt, fs, path::Path};
+
+use bytesize::ByteSize;
+
+use crate::results::record_warning;
+
+/// Environment variable selecting a node to run on, or `interleave`.
+pub const NUMA_VAR: &str = "SNARK_BENCH_NUMA";
+
+const NODE_DIR: &str = "/sys/devices/system/node";
+
+/// A NUMA node, with its CPUs and memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumaNode {
+	pub id: usize,
+	pub cpus: Vec<usize>,
+	pub memory_bytes: u64,
+}
+
+impl fmt::Display for NumaNode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "node{} ({} CPUs, {})", self.id, self.cpus.len(), ByteSize(self.memory_bytes))
+	}
+}
+
+/// Parses a kernel CPU list such as `0-15,32-47`.
+pub(crate) fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+	let mut cpus = Vec::new();
+	for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+		match range.split_once('-') {
+			Some((start, end)) => cpus.extend(start.parse::<usize>().ok()?..=end.parse().ok()?),
+			None => cpus.push(range.parse().ok()?),
+		}
+	}
+	Some(cpus)
+}
+
+/// Lists the NUMA nodes with memory or CPUs attached, in order of id. Returns an empty list on
+/// platforms without NUMA reporting.
+pub fn numa_nodes() -> Vec<NumaNode> {
+	let Ok(entries) = fs::read_dir(NODE_DIR) else {
+		return Vec::new();
+	};
+	let mut nodes = entries
+		.filter_map(|entry| {
+			let entry = entry.ok()?;
+			let id = entry
+				.file_name()
+				.to_str()?
+				.strip_prefix("node")?
+				.parse()
+				.ok()?;
+			let cpus = parse_cpu_list(&fs::read_to_string(entry.path().join("cpulist")).ok()?)?;
+			let memory_bytes = fs::read_to_string(entry.path().join("meminfo"))
+				.ok()
+				.and_then(|meminfo| {
+					let line = meminfo.lines().find(|line| line.contains("MemTotal:"))?;
+					let kib = line.split_whitespace().rev().nth(1)?.parse::<u64>().ok()?;
+					Some(kib << 10)
+				})
+				.unwrap_or(0);
+			Some(NumaNode {
+				id,
+				cpus,
+				memory_bytes,
+			})
+		})
+		.filter(|node| !node.cpus.is_empty() || node.memory_bytes > 0)
+		.collect::<Vec<_>>();
+	nodes.sort_by_key(|node| node.id);
+	nodes
+}
+
+/// The placements of a scaling study from one NUMA node to all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumaStudy {
+	/// The node of the single-node placement.
+	pub node: usize,
+	/// Number of CPUs of that node.
+	pub node_cpus: usize,
+	/// Number of CPUs of all nodes.
+	pub total_cpus: usize,
+}
+
+impl NumaStudy {
+	/// The study from `node` to all of `nodes`, which needs at least two nodes with CPUs.
+	pub fn new(nodes: &[NumaNode], node: usize) -> Result<Self, String> {
+		let with_cpus = nodes.iter().filter(|node| !node.cpus.is_empty()).count();
+		if with_cpus < 2 {
+			return Err(format!(
+				"a NUMA scaling study needs at least two nodes with CPUs, this machine has {with_cpus}"
+			));
+		}
+		let node_cpus = nodes
+			.iter()
+			.find(|candidate| candidate.id == node)
+			.map(|node| node.cpus.len())
+			.filter(|&cpus| cpus > 0)
+			.ok_or_else(|| format!("node{node} does not exist or has no CPUs"))?;
+		Ok(Self {
+			node,
+			node_cpus,
+			total_cpus: nodes.iter().map(|node| node.cpus.len()).sum(),
+		})
+	}
+
+	/// The names of the placements, and the values of [`NUMA_VAR`] that select them.
+	pub fn placements(&self) -> Vec<(String, String)> {
+		vec![
+			(format!("node{}", self.node), self.node.to_string()),
+			("interleave".to_string(), "interleave".to_string()),
+		]
+	}
+
+	/// The speedup of the interleaved placement if the backends scaled with the number of CPUs.
+	pub fn ideal_speedup(&self) -> f64 {
+		self.total_cpus as f64 / self.node_cpus as f64
+	}
+}
+
+/// Where the memory of a thread is placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryPolicy {
+	/// Allocate only on the given node.
+	Bind(usize),
+	/// Spread pages round-robin over all nodes.
+	Interleave,
+}
+
+impl fmt::Display for MemoryPolicy {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Bind(node) => write!(f, "bound to node{node}"),
+			Self::Interleave => write!(f, "interleaved"),
+		}
+	}
+}
+
+/// Restricts the calling thread to the CPUs of `node`, returning whether the platform allows it.
+/// Threads spawned afterwards inherit the restriction.
+pub fn pin_to_node(node: usize) -> bool {
+	#[cfg(target_os = "linux")]
+	{
+		let Some(cpus) =
+			fs::read_to_string(Path::new(NODE_DIR).join(format!("node{node}/cpulist")))
+				.ok()
+				.and_then(|list| parse_cpu_list(&list))
+		else {
+			return false;
+		};
+		let mut set = unsafe { std::mem::zeroed::<libc::cpu_set_t>() };
+		for cpu in cpus {
+			unsafe { libc::CPU_SET(cpu, &mut set) };
+		}
+		unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0 }
+	}
+	#[cfg(not(target_os = "linux"))]
+	{
+		let _ = node;
+		false
+	}
+}
+
+/// Sets the memory policy of the calling thread for pages it touches from now on, returning
+/// whether the platform allows it. Threads spawned afterwards inherit the policy.
+pub fn set_memory_policy(policy: MemoryPolicy) -> bool {
+	#[cfg(target_os = "linux")]
+	{
+		const MPOL_BIND: libc::c_int = 2;
+		const MPOL_INTERLEAVE: libc::c_int = 3;
+
+		let (mode, mask) = match policy {
+			MemoryPolicy::Bind(node) if node < 64 => (MPOL_BIND, 1u64 << node),
+			MemoryPolicy::Bind(_) => return false,
+			MemoryPolicy::Interleave => (
+				MPOL_INTERLEAVE,
+				numa_nodes()
+					.iter()
+					.filter(|node| node.id < 64)
+					.fold(0u64, |mask, node| mask | 1 << node.id),
+			),
+		};
+		// The kernel reads one bit fewer than `maxnode`.
+		unsafe { libc::syscall(libc::SYS_set_mempolicy, mode, &mask as *const u64, 65) == 0 }
+	}
+	#[cfg(not(target_os = "linux"))]
+	{
+		let _ = policy;
+		false
+	}
+}
+
+/// Reads the placement requested through [`NUMA_VAR`], if any.
+pub fn numa_policy_from_env() -> Option<MemoryPolicy> {
+	let value = env::var(NUMA_VAR).ok()?;
+	if value == "interleave" {
+		return Some(MemoryPolicy::Interleave);
+	}
+	let node = value.parse::<usize>().unwrap_or_else(|_| {
+		panic!("{NUMA_VAR} must be a node index or `interleave`, got {value:?}")
+	});
+	Some(MemoryPolicy::Bind(node))
+}
+
+/// Prints the NUMA topology and applies the placement requested through [`NUMA_VAR`].
+///
+/// Must be called before the rayon pool or any other threads start, so that they inherit the
+/// placement.
+pub fn configure_numa() {
+	let nodes = numa_nodes();
+	if !nodes.is_empty() {
+		let nodes = nodes.iter().map(ToString::to_string).collect::<Vec<_>>();
+		println!("NUMA nodes: {}", nodes.join(", "));
+	}
+	let Some(policy) = numa_policy_from_env() else {
+		return;
+	};
+	let applied = match policy {
+		MemoryPolicy::Bind(node) => pin_to_node(node) && set_memory_policy(policy),
+		MemoryPolicy::Interleave => set_memory_policy(policy),
+	};
+	if applied {
+		match policy {
+			MemoryPolicy::Bind(node) => {
+				println!("NUMA placement: CPUs on node{node}, memory {policy}")
+			}
+			MemoryPolicy::Interleave => println!("NUMA placement: memory {policy}"),
+		}
+	} else {
+		record_warning(format!("could not apply NUMA placement from {NUMA_VAR}"));
+	}
+}
@@ -0,0 +1,185 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Machine-readable output of the examples as newline-delimited JSON, and as rows appended to a
+//! CSV file.
+//!
+//! Passing `--output ndjson` to an example prints every result record, each completed phase and
+//! each metric such as a proof size, to standard output as one JSON line in the schema of
+//! [`crate::results::Record`], as soon as it is taken. Everything else the example prints,
+//! including the output of the backends themselves, goes to standard error instead, so the output
+//! pipes straight into `jq` or a CI step. This works by pointing the standard output file
+//! descriptor at standard error when [`init_output`] is called and keeping a copy of the original
+//! for the records, so it is only available on Unix, and it lasts for the rest of a process that
+//! runs several examples, such as `snark_bench`.
+//!
+//! Passing `--csv <path>` appends one row per completed phase to the CSV file at `path`, with the
+//! example, the start of the run, the system and parameters of the configuration, the phase, its
+//! size, time, and memory. The header is written when the file is new, so the rows of many runs
+//! accumulate in one file that opens directly in a spreadsheet.
+
+use std::{
+	fs::{File, OpenOptions},
+	io::Write,
+	path::{Path, PathBuf},
+	sync::{Mutex, OnceLock},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+	params::command_line,
+	report::CsvField,
+	results::{PhaseRecord, Record},
+};
+
+/// Header of the CSV file written with `--csv`.
+pub const CSV_HEADER: &str = "example,unix_time,system,params,phase,n_elements,seconds,\
+	peak_rss_bytes,average_rss_bytes,allocations,allocated_bytes,peak_heap_bytes,major_page_faults";
+
+/// Output formats selectable with `--output`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+	/// Human-readable text on standard output.
+	Human,
+	/// One JSON record per line on standard output, and human-readable text on standard error.
+	Ndjson,
+}
+
+/// The output format requested on the command line with `--output <format>` or
+/// `--output=<format>`.
+pub fn requested_output() -> OutputFormat {
+	let mut args = command_line().into_iter();
+	while let Some(arg) = args.next() {
+		let format = if arg == "--output" {
+			args.next()
+		} else if let Some(format) = arg.strip_prefix("--output=") {
+			Some(format.to_string())
+		} else {
+			continue;
+		};
+		return match format.as_deref() {
+			Some("human") => OutputFormat::Human,
+			Some("ndjson") => OutputFormat::Ndjson,
+			format => panic!("unknown output format {format:?}, expected `--output ndjson`"),
+		};
+	}
+	OutputFormat::Human
+}
+
+/// The CSV file requested on the command line with `--csv <path>` or `--csv=<path>`.
+pub fn requested_csv() -> Option<PathBuf> {
+	let mut args = command_line().into_iter();
+	while let Some(arg) = args.next() {
+		if arg == "--csv" {
+			return Some(PathBuf::from(args.next().expect("missing path of `--csv <path>`")));
+		}
+		if let Some(path) = arg.strip_prefix("--csv=") {
+			return Some(PathBuf::from(path));
+		}
+	}
+	None
+}
+
+/// The original standard output, when records are printed to it as NDJSON.
+static RECORD_OUTPUT: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// The CSV file phases are appended to, with the example name and start of the run.
+static CSV_OUTPUT: Mutex<Option<(File, String, u64)>> = Mutex::new(None);
+
+/// Opens the CSV file at `path` for appending, writing the header if it is empty.
+fn open_csv(path: &Path) -> File {
+	let mut file = OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(path)
+		.unwrap_or_else(|err| panic!("failed to open {}: {err}", path.display()));
+	let is_empty = file.metadata().is_ok_and(|metadata| metadata.len() == 0);
+	if is_empty {
+		writeln!(file, "{CSV_HEADER}")
+			.unwrap_or_else(|err| panic!("failed to write {}: {err}", path.display()));
+	}
+	file
+}
+
+/// Moves human-readable output to standard error if `--output ndjson` was passed, and opens the
+/// CSV file that the phases of the example `name` are appended to if `--csv` was.
+///
+/// Must be called first in `main`, before anything is printed.
+pub fn init_output(name: &str) {
+	*CSV_OUTPUT.lock().unwrap() = requested_csv().map(|path| {
+		let unix_time = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap()
+			.as_secs();
+		(open_csv(&path), name.to_string(), unix_time)
+	});
+
+	if requested_output() != OutputFormat::Ndjson || RECORD_OUTPUT.get().is_some() {
+		return;
+	}
+
+	#[cfg(unix)]
+	{
+		use std::os::fd::FromRawFd;
+
+		// SAFETY: duplicating and replacing the standard descriptors has no memory effects, and the
+		// duplicate is owned by the returned file alone.
+		let file = unsafe {
+			let fd = libc::dup(libc::STDOUT_FILENO);
+			assert!(fd >= 0, "failed to duplicate standard output");
+			assert!(
+				libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) >= 0,
+				"failed to redirect standard output to standard error"
+			);
+			File::from_raw_fd(fd)
+		};
+		RECORD_OUTPUT
+			.set(Mutex::new(file))
+			.expect("output initialized twice");
+	}
+	#[cfg(not(unix))]
+	panic!("`--output ndjson` is only supported on Unix");
+}
+
+/// Appends a phase to the CSV file as one row.
+fn append_csv_row(phase: &PhaseRecord) {
+	let mut output = CSV_OUTPUT.lock().unwrap();
+	let Some((file, example, unix_time)) = &mut *output else {
+		return;
+	};
+	let optional = |value: Option<u64>| value.map_or(String::new(), |value| value.to_string());
+	writeln!(
+		file,
+		"{},{unix_time},{},{},{},{},{},{},{},{},{},{},{}",
+		CsvField(example),
+		CsvField(&phase.backend),
+		CsvField(&phase.config),
+		CsvField(&phase.phase),
+		phase.n_elements,
+		phase.seconds,
+		optional(phase.peak_rss_bytes),
+		optional(phase.average_rss_bytes),
+		optional(phase.allocations),
+		optional(phase.allocated_bytes),
+		optional(phase.peak_heap_bytes),
+		optional(phase.major_page_faults),
+	)
+	.expect("failed to append a row to the CSV file");
+}
+
+/// Prints a record as a JSON line if `--output ndjson` was passed, and appends a phase to the CSV
+/// file if `--csv` was.
+pub fn emit_record(record: &Record) {
+	if let Record::Phase(phase) = record {
+		append_csv_row(phase);
+	}
+	let Some(output) = RECORD_OUTPUT.get() else {
+		return;
+	};
+	let mut line = serde_json::to_string(record).expect("records serialize to JSON");
+	line.push('\n');
+	output
+		.lock()
+		.unwrap()
+		.write_all(line.as_bytes())
+		.expect("failed to write a record to standard output");
+}
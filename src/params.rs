@@ -0,0 +1,184 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Parameters of the example sweeps, overridden on the command line.
+//!
+//! Every example sweeps a fixed list of problem sizes under fixed parameters. These options replace
+//! them for one run, and `snark_bench` passes them to the examples a subcommand runs, see
+//! [`with_command_line`]:
+//!
+//! - `--n-vars <list>`: log2 of the problem sizes, the number of variables of the multilinears, or
+//!   log2 of the degree of the polynomials or of the transform length.
+//! - `--log-inv-rate <n>`: log2 of the inverse code rate of the PCS examples.
+//! - `--degree <list>`: degrees of the sumcheck compositions.
+//! - `--n-bits <list>`: bit widths of the committed values.
+//! - `--field <names>`: the fields to run, by their names in the output, such as
+//!   `AESTowerField8b`, ignoring case.
+//! - `--hash <names>`: the hash functions to run, such as `keccak`, ignoring case.
+//! - `--security-bits <n>`: bits of security the FRI configurations target, see
+//!   [`crate::security::SecurityTarget`].
+//! - `--pow-bits <n>`: bits of that security the FRI configurations reach by grinding.
+//! - `--soundness <bound>`: `proven` or `conjectured`, the bound on the soundness of FRI queries
+//!   that their number is derived under, see [`crate::security::Soundness`].
+//! - `--repeats <n>`: run every configuration exactly `n` times and print the statistics of its
+//!   phases, see [`crate::stability`].
+//! - `--min-runs <n>`: run every configuration at least `n` times, as `SNARK_BENCH_RUNS` does.
+//! - `--results <dir>`: write the results to `dir` only, rather than to `SNARK_BENCH_RESULTS` and
+//!   the history database, see [`crate::results::init_results`].
+//!
+//! Lists are separated by commas. An example that does not sweep a parameter ignores it, and one
+//! whose fields or hash functions match none of the names runs nothing.
+
+use std::{env, path::PathBuf, sync::Mutex};
+
+use crate::security::Soundness;
+
+/// The command line of the example running in this process, if it is not that of the process.
+static COMMAND_LINE: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+/// The command-line arguments of the running example, without the program name: those of the
+/// process, or those of [`with_command_line`] while it runs.
+pub fn command_line() -> Vec<String> {
+	COMMAND_LINE
+		.lock()
+		.unwrap()
+		.clone()
+		.unwrap_or_else(|| env::args().skip(1).collect())
+}
+
+/// Restores the command line replaced by [`with_command_line`], even if the example panics.
+struct RestoreCommandLine(Option<Vec<String>>);
+
+impl Drop for RestoreCommandLine {
+	fn drop(&mut self) {
+		*COMMAND_LINE.lock().unwrap() = self.0.take();
+	}
+}
+
+/// Runs `f`, an example run in this process, with `args` as its command line.
+pub fn with_command_line<R>(args: &[String], f: impl FnOnce() -> R) -> R {
+	let previous = COMMAND_LINE.lock().unwrap().replace(args.to_vec());
+	let _restore = RestoreCommandLine(previous);
+	f()
+}
+
+/// The value of `--<name> <value>` or `--<name>=<value>` on the command line.
+fn arg_value(name: &str) -> Option<String> {
+	let flag = format!("--{name}");
+	let mut args = command_line().into_iter();
+	while let Some(arg) = args.next() {
+		if arg == flag {
+			return Some(
+				args.next()
+					.unwrap_or_else(|| panic!("missing value of {flag}")),
+			);
+		}
+		if let Some(value) = arg
+			.strip_prefix(&flag)
+			.and_then(|rest| rest.strip_prefix('='))
+		{
+			return Some(value.to_string());
+		}
+	}
+	None
+}
+
+/// The comma-separated numbers of `--<name>`, or `default` without it.
+fn numbers(name: &str, default: &[usize]) -> Vec<usize> {
+	let Some(value) = arg_value(name) else {
+		return default.to_vec();
+	};
+	value
+		.split(',')
+		.map(|number| {
+			number.trim().parse().unwrap_or_else(|_| {
+				panic!("--{name} must be a list of numbers separated by commas, got {value:?}")
+			})
+		})
+		.collect()
+}
+
+/// Whether `--<name>` is absent or lists `choice`, ignoring case.
+fn selected(name: &str, choice: &str) -> bool {
+	arg_value(name).is_none_or(|value| {
+		value
+			.split(',')
+			.any(|listed| listed.trim().eq_ignore_ascii_case(choice))
+	})
+}
+
+/// The single number of `--<name>`, or `default` without it.
+fn number(name: &str, default: usize) -> usize {
+	match numbers(name, &[default])[..] {
+		[number] => number,
+		_ => panic!("--{name} takes a single number"),
+	}
+}
+
+/// The problem sizes requested with `--n-vars`, or `default`.
+pub fn requested_n_vars(default: &[usize]) -> Vec<usize> {
+	numbers("n-vars", default)
+}
+
+/// The log2 inverse code rate requested with `--log-inv-rate`, or `default`.
+pub fn requested_log_inv_rate(default: usize) -> usize {
+	number("log-inv-rate", default)
+}
+
+/// The composition degrees requested with `--degree`, or `default`.
+pub fn requested_degrees(default: &[usize]) -> Vec<usize> {
+	numbers("degree", default)
+}
+
+/// The bit widths requested with `--n-bits`, or `default`.
+pub fn requested_n_bits(default: &[usize]) -> Vec<usize> {
+	numbers("n-bits", default)
+}
+
+/// The bits of security requested with `--security-bits`, or `default`.
+pub fn requested_security_bits(default: usize) -> usize {
+	number("security-bits", default)
+}
+
+/// The bits of proof-of-work grinding requested with `--pow-bits`, or `default`.
+pub fn requested_pow_bits(default: usize) -> usize {
+	number("pow-bits", default)
+}
+
+/// The soundness bound requested with `--soundness`, or the proven one.
+pub fn requested_soundness() -> Soundness {
+	arg_value("soundness").map_or(Soundness::Proven, |name| {
+		name.parse()
+			.unwrap_or_else(|err| panic!("--soundness: {err}"))
+	})
+}
+
+/// The number of runs of every configuration requested with `--repeats`, if any.
+pub fn requested_repeats() -> Option<usize> {
+	arg_value("repeats")?;
+	let repeats = number("repeats", 1);
+	assert!(repeats > 0, "--repeats must be a positive number of runs");
+	Some(repeats)
+}
+
+/// The minimum number of runs of every configuration requested with `--min-runs`, if any.
+pub fn requested_min_runs() -> Option<usize> {
+	arg_value("min-runs")?;
+	let runs = number("min-runs", 1);
+	assert!(runs > 0, "--min-runs must be a positive number of runs");
+	Some(runs)
+}
+
+/// The directory requested with `--results` to write the results to, if any.
+pub fn requested_results_dir() -> Option<PathBuf> {
+	arg_value("results").map(PathBuf::from)
+}
+
+/// Whether the field named `field` should run, which it does unless `--field` lists others.
+pub fn field_selected(field: &str) -> bool {
+	selected("field", field)
+}
+
+/// Whether the hash function named `hash` should run, which it does unless `--hash` lists others.
+pub fn hash_selected(hash: &str) -> bool {
+	selected("hash", hash)
+}
@@ -0,0 +1,434 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Hardware performance counters from `perf_event_open`, on Linux.
+//!
+//! Instruction counts and miss rates tell apart provers that are compute-bound from ones that
+//! stall on memory, which wall time alone cannot. Counters are opened on every thread of the
+//! process when counting starts, so threads spawned during a phase are missed; [`PerfCounters`]
+//! starts rayon's global pool first so that its workers are included. Counting needs
+//! `/proc/sys/kernel/perf_event_paranoid` at 2 or lower, and user-space events only are counted.
+//!
+//! With the `perf_counters` feature, the `field_ops` and `hashing` benchmarks measure one of these
+//! events per iteration instead of wall time, through the Criterion measurement
+//! [`HardwareCounter`], selecting the event with [`PERF_EVENT_VAR`].
+
+use std::{env, fmt};
+#[cfg(target_os = "linux")]
+use std::{io, sync::Mutex};
+
+#[cfg(target_os = "linux")]
+use criterion::{
+	measurement::{Measurement, ValueFormatter},
+	Throughput,
+};
+use serde::{Deserialize, Serialize};
+
+/// Environment variable selecting the event the [`HardwareCounter`] measurement counts, one of
+/// `cycles` (the default), `instructions`, `l1d-misses`, `llc-misses`, and `branch-misses`.
+pub const PERF_EVENT_VAR: &str = "SNARK_BENCH_PERF_EVENT";
+
+/// Hardware event counts, summed over threads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerfCounts {
+	/// Instructions retired.
+	pub instructions: u64,
+	/// CPU cycles.
+	pub cycles: u64,
+	/// Last-level cache references.
+	pub cache_references: u64,
+	/// Last-level cache misses.
+	pub cache_misses: u64,
+	/// Branch instructions retired.
+	pub branches: u64,
+	/// Mispredicted branches.
+	pub branch_misses: u64,
+	/// Level 1 data cache read misses, if the CPU exposes the event.
+	#[serde(default)]
+	pub l1d_misses: Option<u64>,
+}
+
+impl PerfCounts {
+	/// Instructions per cycle.
+	pub fn ipc(&self) -> f64 {
+		self.instructions as f64 / self.cycles as f64
+	}
+
+	/// Fraction of last-level cache references that missed.
+	pub fn llc_miss_rate(&self) -> f64 {
+		self.cache_misses as f64 / self.cache_references as f64
+	}
+
+	/// Fraction of branches that were mispredicted.
+	pub fn branch_miss_rate(&self) -> f64 {
+		self.branch_misses as f64 / self.branches as f64
+	}
+
+	/// Level 1 data cache read misses per thousand instructions.
+	pub fn l1d_mpki(&self) -> Option<f64> {
+		self.l1d_misses
+			.map(|misses| misses as f64 * 1e3 / self.instructions as f64)
+	}
+
+	/// Instructions per element of the problem, for comparing backends at the same size.
+	pub fn instructions_per_element(&self, n_elements: u64) -> f64 {
+		self.instructions as f64 / n_elements as f64
+	}
+}
+
+impl fmt::Display for PerfCounts {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"instructions={}, IPC={:.2}, LLC miss rate={:.1}%, branch miss rate={:.2}%",
+			self.instructions,
+			self.ipc(),
+			self.llc_miss_rate() * 100.0,
+			self.branch_miss_rate() * 100.0
+		)?;
+		if let Some(mpki) = self.l1d_mpki() {
+			write!(f, ", L1D MPKI={mpki:.1}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Performance counters running on the threads of the current process.
+pub struct PerfCounters {
+	#[cfg(target_os = "linux")]
+	threads: Vec<linux::ThreadCounters>,
+}
+
+impl PerfCounters {
+	/// Opens and enables counters on every current thread, or returns `None` if the platform or
+	/// its permissions do not allow counting any of them.
+	pub fn start() -> Option<Self> {
+		#[cfg(target_os = "linux")]
+		{
+			rayon::current_num_threads();
+			let mut threads = std::fs::read_dir("/proc/self/task")
+				.ok()?
+				.filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+				.filter_map(|tid| linux::ThreadCounters::open(tid).ok())
+				.collect::<Vec<_>>();
+			if threads.is_empty() {
+				return None;
+			}
+			for thread in &mut threads {
+				thread.enable().ok()?;
+			}
+			Some(Self { threads })
+		}
+		#[cfg(not(target_os = "linux"))]
+		{
+			None
+		}
+	}
+
+	/// Stops counting and returns the counts, scaled up for any time the kernel multiplexed the
+	/// counters off the hardware.
+	pub fn finish(self) -> Option<PerfCounts> {
+		#[cfg(target_os = "linux")]
+		{
+			self.threads.into_iter().try_fold(
+				PerfCounts {
+					l1d_misses: Some(0),
+					..PerfCounts::default()
+				},
+				|total, mut thread| {
+					let counts = thread.finish().ok()?;
+					Some(PerfCounts {
+						instructions: total.instructions + counts.instructions,
+						cycles: total.cycles + counts.cycles,
+						cache_references: total.cache_references + counts.cache_references,
+						cache_misses: total.cache_misses + counts.cache_misses,
+						branches: total.branches + counts.branches,
+						branch_misses: total.branch_misses + counts.branch_misses,
+						l1d_misses: total.l1d_misses.zip(counts.l1d_misses).map(|(a, b)| a + b),
+					})
+				},
+			)
+		}
+		#[cfg(not(target_os = "linux"))]
+		{
+			None
+		}
+	}
+}
+
+/// A hardware event counted by [`HardwareCounter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerfEvent {
+	/// CPU cycles.
+	Cycles,
+	/// Instructions retired.
+	Instructions,
+	/// Level 1 data cache read misses.
+	L1dMisses,
+	/// Last-level cache misses.
+	LlcMisses,
+	/// Mispredicted branches.
+	BranchMisses,
+}
+
+impl PerfEvent {
+	const ALL: [Self; 5] = [
+		Self::Cycles,
+		Self::Instructions,
+		Self::L1dMisses,
+		Self::LlcMisses,
+		Self::BranchMisses,
+	];
+
+	/// Name of the event in [`PERF_EVENT_VAR`].
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Cycles => "cycles",
+			Self::Instructions => "instructions",
+			Self::L1dMisses => "l1d-misses",
+			Self::LlcMisses => "llc-misses",
+			Self::BranchMisses => "branch-misses",
+		}
+	}
+
+	/// The event selected with [`PERF_EVENT_VAR`], or cycles if it is unset.
+	pub fn requested() -> Result<Self, String> {
+		let Ok(name) = env::var(PERF_EVENT_VAR) else {
+			return Ok(Self::Cycles);
+		};
+		Self::ALL
+			.into_iter()
+			.find(|event| event.name().eq_ignore_ascii_case(name.trim()))
+			.ok_or_else(|| {
+				let names = Self::ALL.map(Self::name).join(", ");
+				format!("unknown {PERF_EVENT_VAR} {name:?}, expected one of {names}")
+			})
+	}
+
+	/// Units of a count of the event, of a count per element, and of a count per byte.
+	#[cfg(target_os = "linux")]
+	fn units(self) -> [&'static str; 3] {
+		match self {
+			Self::Cycles => ["cycles", "cycles/elem", "cycles/B"],
+			Self::Instructions => ["instructions", "instructions/elem", "instructions/B"],
+			Self::L1dMisses => ["L1D misses", "L1D misses/elem", "L1D misses/B"],
+			Self::LlcMisses => ["LLC misses", "LLC misses/elem", "LLC misses/B"],
+			Self::BranchMisses => ["branch misses", "branch misses/elem", "branch misses/B"],
+		}
+	}
+}
+
+/// Criterion measurement counting a hardware event on the benchmarking thread instead of timing
+/// it, so that results are in events per iteration, and in events per element or byte for groups
+/// that set a throughput. Work a benchmark hands to other threads is not counted.
+#[cfg(target_os = "linux")]
+pub struct HardwareCounter {
+	event: PerfEvent,
+	counter: Mutex<perf_event::Counter>,
+}
+
+#[cfg(target_os = "linux")]
+impl HardwareCounter {
+	/// Opens and enables a counter of `event` on the calling thread.
+	pub fn new(event: PerfEvent) -> io::Result<Self> {
+		let mut counter = perf_event::Builder::new().kind(event).build()?;
+		counter.enable()?;
+		Ok(Self {
+			event,
+			counter: Mutex::new(counter),
+		})
+	}
+
+	/// Counts the event selected with [`PERF_EVENT_VAR`], panicking if it cannot be counted.
+	pub fn from_env() -> Self {
+		let event = PerfEvent::requested().unwrap_or_else(|err| panic!("{err}"));
+		Self::new(event).unwrap_or_else(|err| {
+			panic!(
+				"failed to count {}, is /proc/sys/kernel/perf_event_paranoid above 2? {err}",
+				event.name()
+			)
+		})
+	}
+
+	fn read(&self) -> u64 {
+		self.counter
+			.lock()
+			.unwrap()
+			.read()
+			.expect("failed to read the hardware counter")
+	}
+}
+
+#[cfg(target_os = "linux")]
+impl Measurement for HardwareCounter {
+	type Intermediate = u64;
+	type Value = u64;
+
+	fn start(&self) -> Self::Intermediate {
+		self.read()
+	}
+
+	fn end(&self, i: Self::Intermediate) -> Self::Value {
+		self.read() - i
+	}
+
+	fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+		v1 + v2
+	}
+
+	fn zero(&self) -> Self::Value {
+		0
+	}
+
+	fn to_f64(&self, value: &Self::Value) -> f64 {
+		*value as f64
+	}
+
+	fn formatter(&self) -> &dyn ValueFormatter {
+		self
+	}
+}
+
+#[cfg(target_os = "linux")]
+impl ValueFormatter for HardwareCounter {
+	fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+		self.event.units()[0]
+	}
+
+	fn scale_throughputs(
+		&self,
+		_typical_value: f64,
+		throughput: &Throughput,
+		values: &mut [f64],
+	) -> &'static str {
+		let [_, per_element, per_byte] = self.event.units();
+		let (per_iteration, unit) = match *throughput {
+			Throughput::Elements(elements) => (elements, per_element),
+			Throughput::Bytes(bytes) | Throughput::BytesDecimal(bytes) => (bytes, per_byte),
+		};
+		for value in values {
+			*value /= per_iteration as f64;
+		}
+		unit
+	}
+
+	fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+		self.event.units()[0]
+	}
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+	use std::io;
+
+	use perf_event::{
+		events::{Cache, CacheOp, CacheResult, Event, Hardware, WhichCache},
+		Builder, Counter, Group,
+	};
+
+	use super::{PerfCounts, PerfEvent};
+
+	/// Level 1 data cache read misses.
+	const L1D_READ_MISSES: Cache = Cache {
+		which: WhichCache::L1D,
+		operation: CacheOp::READ,
+		result: CacheResult::MISS,
+	};
+
+	impl From<PerfEvent> for Event {
+		fn from(event: PerfEvent) -> Self {
+			match event {
+				PerfEvent::Cycles => Hardware::CPU_CYCLES.into(),
+				PerfEvent::Instructions => Hardware::INSTRUCTIONS.into(),
+				PerfEvent::L1dMisses => L1D_READ_MISSES.into(),
+				PerfEvent::LlcMisses => Hardware::CACHE_MISSES.into(),
+				PerfEvent::BranchMisses => Hardware::BRANCH_MISSES.into(),
+			}
+		}
+	}
+
+	/// One counter group per thread, so that the events are scheduled together. The L1D misses
+	/// are counted outside the group, as the group already takes the programmable counters of most
+	/// CPUs, and not at all on CPUs that do not expose the event.
+	pub struct ThreadCounters {
+		group: Group,
+		instructions: Counter,
+		cycles: Counter,
+		cache_references: Counter,
+		cache_misses: Counter,
+		branches: Counter,
+		branch_misses: Counter,
+		l1d_misses: Option<Counter>,
+	}
+
+	impl ThreadCounters {
+		pub fn open(tid: libc::pid_t) -> io::Result<Self> {
+			let mut group = Builder::new().observe_pid(tid).build_group()?;
+			let mut counter = |event: Hardware| {
+				Builder::new()
+					.group(&mut group)
+					.observe_pid(tid)
+					.kind(event)
+					.build()
+			};
+			let instructions = counter(Hardware::INSTRUCTIONS)?;
+			let cycles = counter(Hardware::CPU_CYCLES)?;
+			let cache_references = counter(Hardware::CACHE_REFERENCES)?;
+			let cache_misses = counter(Hardware::CACHE_MISSES)?;
+			let branches = counter(Hardware::BRANCH_INSTRUCTIONS)?;
+			let branch_misses = counter(Hardware::BRANCH_MISSES)?;
+			let l1d_misses = Builder::new()
+				.observe_pid(tid)
+				.kind(L1D_READ_MISSES)
+				.build()
+				.ok();
+			Ok(Self {
+				group,
+				instructions,
+				cycles,
+				cache_references,
+				cache_misses,
+				branches,
+				branch_misses,
+				l1d_misses,
+			})
+		}
+
+		pub fn enable(&mut self) -> io::Result<()> {
+			if let Some(l1d_misses) = &mut self.l1d_misses {
+				l1d_misses.enable()?;
+			}
+			self.group.enable()
+		}
+
+		pub fn finish(&mut self) -> io::Result<PerfCounts> {
+			self.group.disable()?;
+			let l1d_misses = match &mut self.l1d_misses {
+				Some(counter) => {
+					counter.disable()?;
+					let counts = counter.read_count_and_time()?;
+					let scale = match counts.time_running {
+						0 => 1.0,
+						running => counts.time_enabled as f64 / running as f64,
+					};
+					Some((counts.count as f64 * scale) as u64)
+				}
+				None => None,
+			};
+			let counts = self.group.read()?;
+			let scale = match (counts.time_enabled(), counts.time_running()) {
+				(Some(enabled), Some(running)) if running > 0 => enabled as f64 / running as f64,
+				_ => 1.0,
+			};
+			let scaled = |counter: &Counter| (counts[counter] as f64 * scale) as u64;
+			Ok(PerfCounts {
+				instructions: scaled(&self.instructions),
+				cycles: scaled(&self.cycles),
+				cache_references: scaled(&self.cache_references),
+				cache_misses: scaled(&self.cache_misses),
+				branches: scaled(&self.branches),
+				branch_misses: scaled(&self.branch_misses),
+				l1d_misses,
+			})
+		}
+	}
+}
@@ -0,0 +1,156 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Resource reporting around the timed phases of the examples.
+//!
+//! A [`Phase`] collects every resource measurement the platform and enabled features provide:
+//! resident memory, allocations with the `alloc_count` feature, page faults, disk I/O, and CPU
+//! time, and hardware counters on Linux.
+//! Phases during which the CPU was thermally throttled are flagged. While a CPU timeline is being
+//! recorded, each phase also reports how many threads it kept busy. Phase times are recorded for
+//! fitting scaling laws across size sweeps with [`crate::scaling`], and every phase is recorded in
+//! the structured results of [`crate::results`]. Each phase is also a `phase` tracing span, whose
+//! arguments name the phase and its series, so that the phases of a sweep can be told apart in a
+//! Chrome trace from [`crate::trace`].
+
+use std::time::{Duration, Instant};
+
+use bytesize::ByteSize;
+use tracing::span::EnteredSpan;
+
+use crate::{
+	cpu_state::throttle_count,
+	heap::{AllocCounts, HeapPeak},
+	perf::PerfCounters,
+	results::{current_device, record_result, record_warning, PhaseRecord, Record},
+	rss::RssSampler,
+	rusage::{CpuTime, DiskIo, PageFaults},
+	scaling::{current_series, record_phase},
+	timeline::{timeline_position, utilization_since},
+};
+
+/// Resource measurements running for the duration of a phase.
+pub struct Phase {
+	name: String,
+	start: Instant,
+	rss: RssSampler,
+	allocs: Option<AllocCounts>,
+	heap_peak: Option<HeapPeak>,
+	perf: Option<PerfCounters>,
+	throttles: Option<u64>,
+	faults: Option<PageFaults>,
+	io: Option<DiskIo>,
+	cpu_time: Option<CpuTime>,
+	timeline: Option<Duration>,
+	span: EnteredSpan,
+}
+
+impl Phase {
+	/// Starts measuring the phase `name`. The RSS sampler thread is started first, so that the
+	/// hardware counters do not observe it.
+	pub fn start(name: &str) -> Self {
+		let rss = RssSampler::start();
+		let allocs = AllocCounts::current();
+		let heap_peak = HeapPeak::start();
+		let perf = PerfCounters::start();
+		let series = current_series();
+		let span = tracing::debug_span!(
+			"phase",
+			phase = name,
+			backend = series.backend,
+			config = series.config
+		)
+		.entered();
+		Self {
+			name: name.to_string(),
+			start: Instant::now(),
+			rss,
+			allocs,
+			heap_peak,
+			perf,
+			throttles: throttle_count(),
+			faults: PageFaults::current(),
+			io: DiskIo::current(),
+			cpu_time: CpuTime::current(),
+			timeline: timeline_position(),
+			span,
+		}
+	}
+
+	/// Stops measuring and prints the results, normalizing instruction counts by the number of
+	/// elements the phase processed. Returns the wall time of the phase in seconds.
+	pub fn finish(self, n_elements: u64) -> f64 {
+		let seconds = self.start.elapsed().as_secs_f64();
+		drop(self.span);
+		let perf = self.perf.and_then(PerfCounters::finish);
+		let allocs = self.allocs.zip(AllocCounts::current());
+		let heap_peak = self.heap_peak.map(HeapPeak::finish);
+		let rss = self.rss.finish();
+		let throttles = self.throttles.zip(throttle_count());
+		let faults = self.faults.zip(PageFaults::current());
+		let io = self.io.zip(DiskIo::current());
+		let cpu_time = self.cpu_time.zip(CpuTime::current());
+		let utilization = self.timeline.and_then(utilization_since);
+		record_phase(&self.name, n_elements, seconds);
+		let series = current_series();
+		let described = if series.backend.is_empty() {
+			format!("{} at n={n_elements}", self.name)
+		} else {
+			format!("{series}, {} at n={n_elements}", self.name)
+		};
+		let allocs = allocs.map(|(before, after)| after.since(&before));
+		let faults = faults.map(|(before, after)| after.since(&before));
+		record_result(Record::Phase(PhaseRecord {
+			backend: series.backend,
+			config: series.config,
+			phase: self.name,
+			n_elements,
+			device: current_device(),
+			seconds,
+			peak_rss_bytes: rss.map(|rss| rss.peak),
+			average_rss_bytes: rss.map(|rss| rss.average),
+			allocations: allocs.map(|allocs| allocs.allocations),
+			allocated_bytes: allocs.map(|allocs| allocs.bytes),
+			peak_heap_bytes: heap_peak,
+			major_page_faults: faults.map(|faults| faults.major),
+			perf,
+		}));
+
+		if let Some(rss) = rss {
+			println!("RSS: {rss}");
+		}
+		if let Some(allocs) = allocs {
+			match heap_peak {
+				Some(peak) => println!("Allocations: {allocs}, peak heap={}", ByteSize(peak)),
+				None => println!("Allocations: {allocs}"),
+			}
+		}
+		if let Some(faults) = faults {
+			println!("Page faults: {faults}");
+		}
+		if let Some((before, after)) = io {
+			println!("Disk I/O: {}", after.since(&before));
+		}
+		if let Some((before, after)) = cpu_time {
+			let cpu_time = after.since(&before);
+			println!("CPU time: {cpu_time}, busy cores={:.1}", cpu_time.busy_cores(seconds));
+		}
+		if let Some(perf) = perf {
+			println!(
+				"Perf: {perf}, instructions/element={:.1}",
+				perf.instructions_per_element(n_elements)
+			);
+		}
+		if let Some(utilization) = utilization {
+			println!("CPU timeline: {utilization}");
+		}
+		if let Some((before, after)) = throttles {
+			if after > before {
+				record_warning(format!(
+					"{} thermal throttling events during {described}, timings are skewed",
+					after - before
+				));
+			}
+		}
+		seconds
+	}
+}
@@ -0,0 +1,69 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Proof sizes broken down into the parts of a proof.
+//!
+//! Total proof sizes say which backend has the smaller proofs but not why: FRI proofs are mostly
+//! Merkle paths, whose length grows with the number of queries and so shrinks at lower rates,
+//! while sumcheck proofs are round polynomials whose size depends on the degree and field.
+//! [`record_proof_size`] records the total as the `proof_size` metric and each component as a
+//! `proof_size.<component>` metric, with whatever the components leave out, such as length
+//! prefixes of the serialization, as `proof_size.other`. The names in [`components`] are shared
+//! across backends so that the HTML report can stack them side by side.
+
+use bytesize::ByteSize;
+
+use crate::results::record_metric;
+
+/// Metric of the total proof size.
+pub const PROOF_SIZE_METRIC: &str = "proof_size";
+
+/// Names of the proof components shared across backends.
+pub mod components {
+	/// Commitments to the folded codewords of the FRI commit phase.
+	pub const FRI_COMMITMENTS: &str = "fri_commitments";
+	/// Merkle authentication paths of the queried codeword positions.
+	pub const MERKLE_PATHS: &str = "merkle_paths";
+	/// Codeword values opened at the queried positions.
+	pub const QUERY_VALUES: &str = "query_values";
+	/// Coefficients or evaluations of the final FRI polynomial.
+	pub const FINAL_POLYNOMIAL: &str = "final_polynomial";
+	/// Proof-of-work witness.
+	pub const PROOF_OF_WORK: &str = "proof_of_work";
+	/// Round polynomials of sumcheck.
+	pub const SUMCHECK_MESSAGES: &str = "sumcheck_messages";
+	/// Evaluations of the multilinears at the sumcheck challenge point.
+	pub const FINAL_EVALUATIONS: &str = "final_evaluations";
+	/// Bytes not attributed to any other component.
+	pub const OTHER: &str = "other";
+}
+
+/// The component a metric measures, if it is a component of the proof size.
+pub fn proof_size_component(metric: &str) -> Option<&str> {
+	metric.strip_prefix(PROOF_SIZE_METRIC)?.strip_prefix('.')
+}
+
+/// Prints a proof size with its components and records them in the results.
+pub fn record_proof_size(total: u64, parts: &[(&str, u64)]) {
+	let attributed = parts.iter().map(|&(_, bytes)| bytes).sum::<u64>();
+	let other = total.saturating_sub(attributed);
+	let parts = parts
+		.iter()
+		.copied()
+		.chain((other > 0 && !parts.is_empty()).then_some((components::OTHER, other)))
+		.collect::<Vec<_>>();
+
+	let breakdown = parts
+		.iter()
+		.map(|(component, bytes)| format!("{} = {}", component.replace('_', " "), ByteSize(*bytes)))
+		.collect::<Vec<_>>();
+	if breakdown.is_empty() {
+		println!("Proof size = {}", ByteSize(total));
+	} else {
+		println!("Proof size = {} ({})", ByteSize(total), breakdown.join(", "));
+	}
+
+	record_metric(PROOF_SIZE_METRIC, total as f64, "bytes");
+	for (component, bytes) in parts {
+		record_metric(&format!("{PROOF_SIZE_METRIC}.{component}"), bytes as f64, "bytes");
+	}
+}
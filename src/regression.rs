@@ -0,0 +1,284 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Regression detection between two runs, for gating upgrades of the proving libraries.
+//!
+//! [`compare_phases`] matches the phases of a candidate run to those of a baseline run by backend,
+//! configuration, phase, and size, and compares their median wall times. A phase regressed when it
+//! got slower by more than a threshold and, if both runs repeated it with `SNARK_BENCH_RUNS` or
+//! `--repeats` often enough for the test to reach [`SIGNIFICANCE_LEVEL`], the difference is
+//! significant by [`crate::significance::mann_whitney_p`], so that noise alone does not fail a
+//! check. Runs repeated fewer times, such as three against three, are judged by the threshold
+//! alone, since no difference between them can be significant, see [`min_p_value`].
+//! [`compare_proof_sizes`] likewise matches the proof sizes of every configuration and size, which
+//! are deterministic, so any growth beyond the threshold counts.
+//!
+//! [`backend_changes`] groups the phase changes by backend alongside the change of each backend's
+//! locked revision, so that a regression can be traced to the upstream bump that came with it.
+
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+	proof_size::PROOF_SIZE_METRIC,
+	report::Row,
+	revisions::RevisionChange,
+	significance::{mann_whitney_p, min_p_value, SIGNIFICANCE_LEVEL},
+	stability::median,
+};
+
+/// A phase of one configuration.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PhaseKey {
+	/// Proving library.
+	pub backend: String,
+	/// Parameters of the configuration other than the problem size.
+	pub config: String,
+	/// Name of the phase.
+	pub phase: String,
+	/// Number of elements the phase processed.
+	pub n_elements: Option<u64>,
+}
+
+impl fmt::Display for PhaseKey {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.backend)?;
+		if !self.config.is_empty() {
+			write!(f, ", {}", self.config)?;
+		}
+		write!(f, ", {}", self.phase)?;
+		if let Some(n) = self.n_elements {
+			write!(f, ", n={n}")?;
+		}
+		Ok(())
+	}
+}
+
+/// The change in wall time of a phase from a baseline run to a candidate run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseChange {
+	/// The phase.
+	pub key: PhaseKey,
+	/// Median wall time in the baseline run, in seconds.
+	pub baseline_seconds: f64,
+	/// Median wall time in the candidate run, in seconds.
+	pub seconds: f64,
+	/// p-value of the difference, if both runs timed the phase often enough for it to be
+	/// significant.
+	pub p_value: Option<f64>,
+}
+
+impl PhaseChange {
+	/// Candidate time over baseline time.
+	pub fn ratio(&self) -> f64 {
+		self.seconds / self.baseline_seconds
+	}
+
+	/// Whether the phase got slower by more than `threshold`, a fraction of the baseline time, with
+	/// a significant difference if it can be tested.
+	pub fn is_regression(&self, threshold: f64) -> bool {
+		self.ratio() > 1.0 + threshold && self.p_value.is_none_or(|p| p < SIGNIFICANCE_LEVEL)
+	}
+}
+
+impl fmt::Display for PhaseChange {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{}: {:.6}s -> {:.6}s, {:.3}x",
+			self.key,
+			self.baseline_seconds,
+			self.seconds,
+			self.ratio()
+		)?;
+		if let Some(p_value) = self.p_value {
+			write!(f, " (p={p_value:.3})")?;
+		}
+		Ok(())
+	}
+}
+
+/// The wall times of every phase of a run.
+fn phase_seconds(rows: &[Row]) -> BTreeMap<PhaseKey, Vec<f64>> {
+	let mut seconds = BTreeMap::<_, Vec<_>>::new();
+	for row in rows {
+		let Some(phase) = &row.phase else {
+			continue;
+		};
+		if row.metric != "seconds" {
+			continue;
+		}
+		let key = PhaseKey {
+			backend: row.backend.clone(),
+			config: row.config.clone(),
+			phase: phase.clone(),
+			n_elements: row.n_elements,
+		};
+		seconds.entry(key).or_default().push(row.value);
+	}
+	seconds
+}
+
+fn median_of(seconds: &[f64]) -> f64 {
+	let mut sorted = seconds.to_vec();
+	sorted.sort_by(f64::total_cmp);
+	median(&sorted)
+}
+
+/// Compares every phase of the candidate run that the baseline run also timed.
+pub fn compare_phases(baseline: &[Row], candidate: &[Row]) -> Vec<PhaseChange> {
+	let baseline = phase_seconds(baseline);
+	phase_seconds(candidate)
+		.into_iter()
+		.filter_map(|(key, seconds)| {
+			let baseline_seconds = baseline.get(&key)?;
+			let testable = baseline_seconds.len() >= 2
+				&& seconds.len() >= 2
+				&& min_p_value(baseline_seconds.len(), seconds.len()) < SIGNIFICANCE_LEVEL;
+			let p_value = testable.then(|| mann_whitney_p(baseline_seconds, &seconds));
+			Some(PhaseChange {
+				baseline_seconds: median_of(baseline_seconds),
+				seconds: median_of(&seconds),
+				p_value,
+				key,
+			})
+		})
+		.collect()
+}
+
+/// The change in proof size of a configuration from a baseline run to a candidate run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofSizeChange {
+	/// Proving library.
+	pub backend: String,
+	/// Parameters of the configuration other than the problem size.
+	pub config: String,
+	/// Problem size of the configuration.
+	pub n_elements: Option<u64>,
+	/// Proof size in the baseline run, in bytes.
+	pub baseline_bytes: f64,
+	/// Proof size in the candidate run, in bytes.
+	pub bytes: f64,
+}
+
+impl ProofSizeChange {
+	/// Candidate size over baseline size.
+	pub fn ratio(&self) -> f64 {
+		self.bytes / self.baseline_bytes
+	}
+
+	/// Whether the proof grew by more than `threshold`, a fraction of the baseline size.
+	pub fn is_regression(&self, threshold: f64) -> bool {
+		self.ratio() > 1.0 + threshold
+	}
+}
+
+impl fmt::Display for ProofSizeChange {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.backend)?;
+		if !self.config.is_empty() {
+			write!(f, ", {}", self.config)?;
+		}
+		if let Some(n) = self.n_elements {
+			write!(f, ", n={n}")?;
+		}
+		write!(
+			f,
+			": proof size {} B -> {} B, {:.3}x",
+			self.baseline_bytes,
+			self.bytes,
+			self.ratio()
+		)
+	}
+}
+
+/// The proof sizes of every configuration and size of a run, the median if it repeated.
+fn proof_sizes(rows: &[Row]) -> BTreeMap<(String, String, Option<u64>), f64> {
+	let mut sizes = BTreeMap::<_, Vec<_>>::new();
+	for row in rows {
+		if row.phase.is_none() && row.metric == PROOF_SIZE_METRIC {
+			sizes
+				.entry((row.backend.clone(), row.config.clone(), row.n_elements))
+				.or_default()
+				.push(row.value);
+		}
+	}
+	sizes
+		.into_iter()
+		.map(|(key, bytes)| (key, median_of(&bytes)))
+		.collect()
+}
+
+/// Compares the proof size of every configuration and size of the candidate run that the baseline
+/// run also recorded.
+pub fn compare_proof_sizes(baseline: &[Row], candidate: &[Row]) -> Vec<ProofSizeChange> {
+	let baseline = proof_sizes(baseline);
+	proof_sizes(candidate)
+		.into_iter()
+		.filter_map(|(key, bytes)| {
+			let baseline_bytes = *baseline.get(&key)?;
+			let (backend, config, n_elements) = key;
+			Some(ProofSizeChange {
+				backend,
+				config,
+				n_elements,
+				baseline_bytes,
+				bytes,
+			})
+		})
+		.collect()
+}
+
+/// The performance changes of one backend between two runs, with the change of its revision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendChange {
+	/// Proving library.
+	pub backend: String,
+	/// The change of the locked commit of the backend's repository, if it changed.
+	pub revision: Option<RevisionChange>,
+	/// The changes of the phases of the backend.
+	pub phases: Vec<PhaseChange>,
+}
+
+impl BackendChange {
+	/// Geometric mean of the time ratios of the phases, a size-independent summary of the change.
+	pub fn mean_ratio(&self) -> f64 {
+		let log_sum = self
+			.phases
+			.iter()
+			.map(|change| change.ratio().ln())
+			.sum::<f64>();
+		(log_sum / self.phases.len() as f64).exp()
+	}
+
+	/// The phases that got slower by more than `threshold`, see [`PhaseChange::is_regression`].
+	pub fn regressions(&self, threshold: f64) -> impl Iterator<Item = &PhaseChange> {
+		self.phases
+			.iter()
+			.filter(move |change| change.is_regression(threshold))
+	}
+}
+
+/// Groups the phase changes between two runs by backend and matches each backend to the change of
+/// the revision of its repository, which has the backend's name.
+pub fn backend_changes(
+	changes: Vec<PhaseChange>,
+	revisions: &[RevisionChange],
+) -> Vec<BackendChange> {
+	let mut backends = BTreeMap::<String, Vec<PhaseChange>>::new();
+	for change in changes {
+		backends
+			.entry(change.key.backend.clone())
+			.or_default()
+			.push(change);
+	}
+	backends
+		.into_iter()
+		.map(|(backend, phases)| BackendChange {
+			revision: revisions
+				.iter()
+				.find(|revision| revision.dependency == backend.to_lowercase())
+				.cloned(),
+			backend,
+			phases,
+		})
+		.collect()
+}
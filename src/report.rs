@@ -0,0 +1,437 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Reports over the collected results files of the examples.
+//!
+//! The `snark_bench report` command reads the results files written under `SNARK_BENCH_RESULTS`,
+//! flattens them into [`Row`]s with one value per backend, configuration, phase, and metric, and
+//! writes them in a [`ReportFormat`]. The long format leaves the choice of pivot to the reader, so
+//! the same rows load into pandas, R, or a spreadsheet pivot table.
+
+use std::{collections::BTreeMap, fmt, io, str::FromStr};
+
+use crate::{
+	allocation_report::write_allocations,
+	bencher::write_bencher,
+	core_efficiency::{write_core_efficiency, DEFAULT_MIN_EFFICIENCY},
+	google_benchmark::write_google_benchmark,
+	html_report::write_html,
+	json_report::write_json,
+	latex_report::{write_latex, DEFAULT_PRECISION},
+	results::{Record, Results},
+	scaling::Series,
+	stability::median,
+	summary::write_summary,
+	throughput::write_throughput,
+};
+
+/// Output formats of the `report` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+	/// Long-format CSV with one row per value.
+	Csv,
+	/// A JSON array with one entry per phase, see [`crate::json_report`].
+	Json,
+	/// Interactive HTML page of charts, see [`crate::html_report`].
+	Html,
+	/// Text tables of the speedup of every series over a baseline series, per phase and size.
+	Speedup,
+	/// JSON in the format of Google Benchmark, see [`crate::google_benchmark`].
+	GoogleBenchmark,
+	/// JSON in the Bencher Metric Format, see [`crate::bencher`].
+	Bencher,
+	/// A booktabs LaTeX table of the standard comparison, see [`crate::latex_report`].
+	Latex,
+	/// Text table of the throughput of every phase in standard units, see [`crate::throughput`].
+	Throughput,
+	/// Markdown summary of every run, see [`crate::summary`].
+	Summary,
+	/// Text tables of the most cost-efficient core count of every phase that ran at several thread
+	/// counts, see [`crate::core_efficiency`].
+	Efficiency,
+	/// Text tables of the heap allocations of every backend per phase and size, see
+	/// [`crate::allocation_report`].
+	Allocations,
+}
+
+impl ReportFormat {
+	/// Name of the file the report is written to in the results directory, for formats that are
+	/// not meant to be read on the console.
+	pub fn default_file_name(self) -> Option<&'static str> {
+		match self {
+			Self::Csv
+			| Self::Json
+			| Self::Speedup
+			| Self::GoogleBenchmark
+			| Self::Bencher
+			| Self::Latex
+			| Self::Throughput
+			| Self::Efficiency
+			| Self::Allocations => None,
+			Self::Html => Some("report.html"),
+			Self::Summary => Some("SUMMARY.md"),
+		}
+	}
+}
+
+impl FromStr for ReportFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"csv" => Ok(Self::Csv),
+			"json" => Ok(Self::Json),
+			"html" => Ok(Self::Html),
+			"speedup" => Ok(Self::Speedup),
+			"google-benchmark" => Ok(Self::GoogleBenchmark),
+			"bencher" => Ok(Self::Bencher),
+			"latex" => Ok(Self::Latex),
+			"throughput" => Ok(Self::Throughput),
+			"summary" => Ok(Self::Summary),
+			"efficiency" => Ok(Self::Efficiency),
+			"allocations" => Ok(Self::Allocations),
+			_ => Err(format!(
+				"unknown report format {s:?}, expected csv, json, html, speedup, google-benchmark, \
+				 bencher, latex, throughput, summary, efficiency, or allocations"
+			)),
+		}
+	}
+}
+
+/// One value of one run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+	/// Example that produced the value.
+	pub example: String,
+	/// Start of the run, in seconds since the Unix epoch.
+	pub unix_time: u64,
+	/// Proving library.
+	pub backend: String,
+	/// Parameters of the configuration other than the problem size.
+	pub config: String,
+	/// Phase the value was measured in, or `None` for metrics of a whole configuration.
+	pub phase: Option<String>,
+	/// Number of elements the phase processed, or the configuration ran with.
+	pub n_elements: Option<u64>,
+	/// Name of the metric.
+	pub metric: String,
+	/// Value of the metric.
+	pub value: f64,
+	/// Unit of the value.
+	pub unit: String,
+}
+
+/// Flattens results into one row per value, leaving out values that were not measured.
+pub fn rows(results: &[Results]) -> Vec<Row> {
+	let mut rows = Vec::new();
+	for run in results {
+		let row = |backend: &str, config: &str| Row {
+			example: run.example.clone(),
+			unix_time: run.unix_time,
+			backend: backend.to_string(),
+			config: config.to_string(),
+			phase: None,
+			n_elements: None,
+			metric: String::new(),
+			value: 0.0,
+			unit: String::new(),
+		};
+		for record in &run.records {
+			match record {
+				Record::Phase(record) => {
+					let perf = record.perf;
+					let values = [
+						("seconds", Some(record.seconds), "s"),
+						("peak_rss", record.peak_rss_bytes.map(|b| b as f64), "bytes"),
+						("average_rss", record.average_rss_bytes.map(|b| b as f64), "bytes"),
+						("allocations", record.allocations.map(|n| n as f64), "count"),
+						("allocated", record.allocated_bytes.map(|b| b as f64), "bytes"),
+						("peak_heap", record.peak_heap_bytes.map(|b| b as f64), "bytes"),
+						("cycles", perf.map(|perf| perf.cycles as f64), "count"),
+						("instructions", perf.map(|perf| perf.instructions as f64), "count"),
+						(
+							"l1d_misses",
+							perf.and_then(|perf| perf.l1d_misses).map(|n| n as f64),
+							"count",
+						),
+						("llc_misses", perf.map(|perf| perf.cache_misses as f64), "count"),
+						("branch_misses", perf.map(|perf| perf.branch_misses as f64), "count"),
+					];
+					for (metric, value, unit) in values {
+						let Some(value) = value else {
+							continue;
+						};
+						rows.push(Row {
+							phase: Some(record.phase.clone()),
+							n_elements: Some(record.n_elements),
+							metric: metric.to_string(),
+							value,
+							unit: unit.to_string(),
+							..row(&record.backend, &record.config)
+						});
+					}
+				}
+				Record::Metric(record) => rows.push(Row {
+					n_elements: record.n_elements,
+					metric: record.metric.clone(),
+					value: record.value,
+					unit: record.unit.clone(),
+					..row(&record.backend, &record.config)
+				}),
+			}
+		}
+	}
+	rows
+}
+
+/// The label of the series of a backend and configuration.
+pub(crate) fn label(backend: &str, config: &str) -> String {
+	Series {
+		backend: backend.to_string(),
+		config: config.to_string(),
+	}
+	.to_string()
+}
+
+pub(crate) fn median_of(seconds: &[f64]) -> f64 {
+	let mut sorted = seconds.to_vec();
+	sorted.sort_by(f64::total_cmp);
+	median(&sorted)
+}
+
+/// Timings of every phase of every series, by size, in the order they first appear.
+pub(crate) struct PhaseTimings {
+	pub series: Vec<String>,
+	pub phases: Vec<String>,
+	pub seconds: BTreeMap<(String, String, u64), Vec<f64>>,
+}
+
+impl PhaseTimings {
+	pub fn new(results: &[Results]) -> Self {
+		let mut timings = Self {
+			series: Vec::new(),
+			phases: Vec::new(),
+			seconds: BTreeMap::new(),
+		};
+		let records = results.iter().flat_map(|run| &run.records);
+		for record in records {
+			let Record::Phase(record) = record else {
+				continue;
+			};
+			let series = label(&record.backend, &record.config);
+			if !timings.series.contains(&series) {
+				timings.series.push(series.clone());
+			}
+			if !timings.phases.contains(&record.phase) {
+				timings.phases.push(record.phase.clone());
+			}
+			timings
+				.seconds
+				.entry((series, record.phase.clone(), record.n_elements))
+				.or_default()
+				.push(record.seconds);
+		}
+		timings
+	}
+
+	/// The sizes and median times of one phase of one series.
+	pub fn curve(&self, series: &str, phase: &str) -> (Vec<u64>, Vec<f64>) {
+		self.seconds
+			.iter()
+			.filter(|((s, p, _), _)| s == series && p == phase)
+			.map(|((_, _, n), seconds)| (*n, median_of(seconds)))
+			.unzip()
+	}
+
+	/// The median time of one phase of one series at one size, if it ran.
+	pub fn median(&self, series: &str, phase: &str, n: u64) -> Option<f64> {
+		self.seconds
+			.get(&(series.to_string(), phase.to_string(), n))
+			.map(|seconds| median_of(seconds))
+	}
+
+	/// The sizes of one phase across series, or of all phases if `phase` is `None`.
+	pub fn sizes(&self, phase: Option<&str>) -> Vec<u64> {
+		let mut sizes = self
+			.seconds
+			.keys()
+			.filter(|(_, p, _)| phase.is_none_or(|phase| p == phase))
+			.map(|&(_, _, n)| n)
+			.collect::<Vec<_>>();
+		sizes.sort_unstable();
+		sizes.dedup();
+		sizes
+	}
+}
+
+/// Quotes a CSV field if it contains a separator, quote, or line break.
+pub(crate) struct CsvField<'a>(pub(crate) &'a str);
+
+impl fmt::Display for CsvField<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.0.contains([',', '"', '\n', '\r']) {
+			write!(f, "\"{}\"", self.0.replace('"', "\"\""))
+		} else {
+			write!(f, "{}", self.0)
+		}
+	}
+}
+
+/// Writes rows as long-format CSV with a header.
+pub fn write_csv(rows: &[Row], mut out: impl io::Write) -> io::Result<()> {
+	writeln!(out, "example,unix_time,backend,config,phase,n_elements,metric,value,unit")?;
+	for row in rows {
+		writeln!(
+			out,
+			"{},{},{},{},{},{},{},{},{}",
+			CsvField(&row.example),
+			row.unix_time,
+			CsvField(&row.backend),
+			CsvField(&row.config),
+			CsvField(row.phase.as_deref().unwrap_or("")),
+			row.n_elements.map_or(String::new(), |n| n.to_string()),
+			CsvField(&row.metric),
+			row.value,
+			CsvField(&row.unit),
+		)?;
+	}
+	out.flush()
+}
+
+/// Finds the series a baseline selector names: the series labeled exactly `selector`, or else the
+/// only series with a label word starting with each word of it, with words split at commas,
+/// dashes, and spaces, so that `plonky3-keccak` selects `plonky3, keccak merkle`. Without a
+/// selector, the first series that ran is the baseline.
+fn select_baseline<'a>(series: &'a [String], selector: Option<&str>) -> Result<&'a str, String> {
+	let Some(selector) = selector else {
+		return series
+			.first()
+			.map(String::as_str)
+			.ok_or_else(|| "no phases to compare".to_string());
+	};
+	if let Some(exact) = series.iter().find(|label| *label == selector) {
+		return Ok(exact);
+	}
+
+	let words = |text: &str| {
+		text.to_lowercase()
+			.split([',', '-', ' '])
+			.filter(|word| !word.is_empty())
+			.map(str::to_string)
+			.collect::<Vec<_>>()
+	};
+	let selector_words = words(selector);
+	let matches = series
+		.iter()
+		.filter(|label| {
+			let label_words = words(label);
+			selector_words.iter().all(|word| {
+				label_words
+					.iter()
+					.any(|label_word| label_word.starts_with(word))
+			})
+		})
+		.collect::<Vec<_>>();
+	match matches[..] {
+		[baseline] => Ok(baseline),
+		[] => Err(format!("no series matches {selector:?}, expected one of {series:?}")),
+		_ => Err(format!("{selector:?} matches several series: {matches:?}")),
+	}
+}
+
+/// Writes, for every phase, a table of the speedup of every series over the baseline series at
+/// each size, as the time of the baseline over the time of the series.
+pub fn write_speedup(
+	results: &[Results],
+	baseline: Option<&str>,
+	mut out: impl io::Write,
+) -> Result<(), String> {
+	let timings = PhaseTimings::new(results);
+	let baseline = select_baseline(&timings.series, baseline)?;
+	let width = timings.series.iter().map(String::len).max().unwrap_or(0);
+
+	let mut write = || -> io::Result<()> {
+		writeln!(out, "Speedup over {baseline}, as its time over the time of each series:")?;
+		for phase in &timings.phases {
+			let sizes = timings
+				.sizes(Some(phase))
+				.into_iter()
+				.filter(|&n| timings.median(baseline, phase, n).is_some())
+				.collect::<Vec<_>>();
+			if sizes.is_empty() {
+				continue;
+			}
+
+			writeln!(out, "\n{phase}:")?;
+			write!(out, "  {:width$}", "")?;
+			for n in &sizes {
+				write!(out, " {:>10}", format!("n={n}"))?;
+			}
+			writeln!(out)?;
+			for series in &timings.series {
+				if sizes
+					.iter()
+					.all(|&n| timings.median(series, phase, n).is_none())
+				{
+					continue;
+				}
+				write!(out, "  {series:width$}")?;
+				for &n in &sizes {
+					let speedup = timings
+						.median(baseline, phase, n)
+						.zip(timings.median(series, phase, n))
+						.map(|(baseline, seconds)| baseline / seconds);
+					match speedup {
+						Some(speedup) => write!(out, " {:>10}", format!("{speedup:.2}x"))?,
+						None => write!(out, " {:>10}", "-")?,
+					}
+				}
+				writeln!(out)?;
+			}
+		}
+		out.flush()
+	};
+	write().map_err(|err| format!("failed to write the report: {err}"))
+}
+
+/// Options of the report formats that need more than the results.
+#[derive(Debug, Clone, Default)]
+pub struct ReportOptions {
+	/// Selector of the baseline series of [`ReportFormat::Speedup`], see [`write_speedup`].
+	pub baseline: Option<String>,
+	/// Phases and metrics tabulated by [`ReportFormat::Latex`], instead of the standard comparison.
+	pub columns: Option<Vec<String>>,
+	/// Decimal places of the values of [`ReportFormat::Latex`], [`DEFAULT_PRECISION`] by default.
+	pub precision: Option<usize>,
+	/// Marginal efficiency below which [`ReportFormat::Efficiency`] stops adding cores,
+	/// [`DEFAULT_MIN_EFFICIENCY`] by default.
+	pub min_efficiency: Option<f64>,
+}
+
+/// Writes a report of the results in the given format.
+pub fn write_report(
+	results: &[Results],
+	format: ReportFormat,
+	options: &ReportOptions,
+	out: impl io::Write,
+) -> Result<(), String> {
+	let written = match format {
+		ReportFormat::Csv => write_csv(&rows(results), out),
+		ReportFormat::Json => write_json(results, out),
+		ReportFormat::Html => write_html(results, out),
+		ReportFormat::GoogleBenchmark => write_google_benchmark(results, out),
+		ReportFormat::Bencher => write_bencher(results, out),
+		ReportFormat::Throughput => write_throughput(results, out),
+		ReportFormat::Summary => return write_summary(results, out),
+		ReportFormat::Allocations => return write_allocations(results, out),
+		ReportFormat::Speedup => return write_speedup(results, options.baseline.as_deref(), out),
+		ReportFormat::Efficiency => {
+			let min_efficiency = options.min_efficiency.unwrap_or(DEFAULT_MIN_EFFICIENCY);
+			return write_core_efficiency(results, min_efficiency, out);
+		}
+		ReportFormat::Latex => {
+			let precision = options.precision.unwrap_or(DEFAULT_PRECISION);
+			return write_latex(results, options.columns.as_deref(), precision, out);
+		}
+	};
+	written.map_err(|err| format!("failed to write the report: {err}"))
+}
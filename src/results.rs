@@ -0,0 +1,377 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Structured results of the examples in a versioned JSON schema.
+//!
+//! The console output of the examples is for people, and its format changes whenever a new
+//! measurement is added. Every [`crate::phase::Phase`] also records a [`PhaseRecord`] with its
+//! backend, configuration, size, time, and memory, and the examples record metrics of whole
+//! configurations, such as proof sizes, with [`record_metric`]. When `SNARK_BENCH_RESULTS` is set
+//! to a directory, [`init_results`] writes all records of the run to
+//! `<directory>/<example>-<unix time>.json` at the end, as a [`Results`] document.
+//!
+//! [`SCHEMA_VERSION`] is bumped whenever a field is removed or changes meaning, so that tooling can
+//! reject files it does not understand. Adding fields does not change the version, and readers
+//! should ignore fields they do not know. The directories of results files are what the reports
+//! of [`crate::report`] are built from, read with [`load_results`], and runs are also appended to
+//! the database of [`crate::history`] for tracking backends across dependency bumps.
+
+use std::{
+	collections::BTreeMap,
+	env,
+	fs::{self, File},
+	io,
+	path::{Path, PathBuf},
+	sync::Mutex,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	environment::environment,
+	history::{append_history, HISTORY_DB_VAR},
+	notify::{notify, NOTIFY_VAR},
+	output::emit_record,
+	params::requested_results_dir,
+	perf::PerfCounts,
+	revisions::dependency_revisions,
+	scaling::{current_series, last_phase_size},
+	scaling_plot::write_scaling_plots,
+	summary::write_summary,
+};
+
+/// Environment variable holding the directory to write results to.
+pub const RESULTS_DIR_VAR: &str = "SNARK_BENCH_RESULTS";
+
+/// Version of the results schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Device phases run on unless [`set_device`] says otherwise, the host CPU.
+pub const CPU_DEVICE: &str = "cpu";
+
+fn cpu_device() -> String {
+	CPU_DEVICE.to_string()
+}
+
+/// One timed phase of one configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseRecord {
+	/// Proving library the phase ran in.
+	pub backend: String,
+	/// Parameters of the configuration other than the problem size.
+	pub config: String,
+	/// Name of the phase.
+	pub phase: String,
+	/// Number of elements the phase processed.
+	pub n_elements: u64,
+	/// Device the phase ran on, [`CPU_DEVICE`] or a GPU such as `cuda:0`.
+	#[serde(default = "cpu_device")]
+	pub device: String,
+	/// Wall time in seconds.
+	pub seconds: f64,
+	/// Peak resident set size during the phase, if the platform reports it.
+	pub peak_rss_bytes: Option<u64>,
+	/// Mean resident set size during the phase, if the platform reports it.
+	pub average_rss_bytes: Option<u64>,
+	/// Heap allocations during the phase, with the `alloc_count` feature.
+	pub allocations: Option<u64>,
+	/// Bytes allocated on the heap during the phase, with the `alloc_count` feature.
+	pub allocated_bytes: Option<u64>,
+	/// Largest number of bytes live on the heap during the phase, with the `alloc_count` feature.
+	#[serde(default)]
+	pub peak_heap_bytes: Option<u64>,
+	/// Page faults during the phase that read the page from disk, if the platform reports them.
+	#[serde(default)]
+	pub major_page_faults: Option<u64>,
+	/// Hardware event counts during the phase, where the platform allows counting them.
+	#[serde(default)]
+	pub perf: Option<PerfCounts>,
+}
+
+/// A measurement of a whole configuration rather than of one phase, such as its proof size.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricRecord {
+	/// Proving library the configuration ran in.
+	pub backend: String,
+	/// Parameters of the configuration other than the problem size.
+	pub config: String,
+	/// Problem size of the configuration, if it ran any phases before the metric was recorded.
+	pub n_elements: Option<u64>,
+	/// Name of the metric.
+	pub metric: String,
+	/// Value of the metric.
+	pub value: f64,
+	/// Unit of the value.
+	pub unit: String,
+}
+
+/// One record of a results file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Record {
+	/// A timed phase.
+	Phase(PhaseRecord),
+	/// A metric of a configuration.
+	Metric(MetricRecord),
+}
+
+/// The results of one run of an example.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Results {
+	/// Version of the schema the file conforms to.
+	pub schema_version: u32,
+	/// Name of the example.
+	pub example: String,
+	/// Start of the run, in seconds since the Unix epoch.
+	pub unix_time: u64,
+	/// Identifier of the run, unique across machines in practice.
+	#[serde(default)]
+	pub run_id: String,
+	/// Host name of the machine the run ran on.
+	#[serde(default)]
+	pub machine: String,
+	/// Locked commits of the git dependencies of the build, by repository name.
+	#[serde(default)]
+	pub revisions: BTreeMap<String, String>,
+	/// Description of the machine and build, see [`crate::environment`].
+	#[serde(default)]
+	pub environment: BTreeMap<String, String>,
+	/// Warnings about conditions that skew the results, such as thermal throttling.
+	#[serde(default)]
+	pub warnings: Vec<String>,
+	/// Records in the order they were taken.
+	pub records: Vec<Record>,
+}
+
+static RECORDS: Mutex<Vec<Record>> = Mutex::new(Vec::new());
+
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+static DEVICE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Sets the device that later phases run on, such as `cuda:0`, until the next call.
+pub fn set_device(device: impl Into<String>) {
+	*DEVICE.lock().unwrap() = Some(device.into());
+}
+
+/// The device phases are currently recorded as running on.
+pub fn current_device() -> String {
+	DEVICE.lock().unwrap().clone().unwrap_or_else(cpu_device)
+}
+
+/// Records a result of the run, printing it right away with `--output ndjson`.
+pub fn record_result(record: Record) {
+	emit_record(&record);
+	RECORDS.lock().unwrap().push(record);
+}
+
+/// Records a metric of the configuration of the current series, at the size of its last phase.
+pub fn record_metric(metric: &str, value: f64, unit: &str) {
+	let series = current_series();
+	record_result(Record::Metric(MetricRecord {
+		backend: series.backend,
+		config: series.config,
+		n_elements: last_phase_size(),
+		metric: metric.to_string(),
+		value,
+		unit: unit.to_string(),
+	}));
+}
+
+/// Forgets the results, warnings, and device recorded by an earlier example run in this process.
+pub(crate) fn clear_results() {
+	RECORDS.lock().unwrap().clear();
+	WARNINGS.lock().unwrap().clear();
+	*DEVICE.lock().unwrap() = None;
+}
+
+/// The results recorded so far.
+pub fn recorded_results() -> Vec<Record> {
+	RECORDS.lock().unwrap().clone()
+}
+
+/// Prints a warning about a condition that skews the results and records it for the summary of
+/// the run.
+pub fn record_warning(message: impl Into<String>) {
+	let message = message.into();
+	println!("Warning: {message}");
+	WARNINGS.lock().unwrap().push(message);
+}
+
+/// The warnings recorded so far.
+pub fn recorded_warnings() -> Vec<String> {
+	WARNINGS.lock().unwrap().clone()
+}
+
+impl Results {
+	/// Empty results of a run of `example` on this machine, started at `unix_time`.
+	pub fn new(example: &str, unix_time: u64) -> Self {
+		let machine = hostname();
+		Self {
+			schema_version: SCHEMA_VERSION,
+			example: example.to_string(),
+			unix_time,
+			run_id: format!("{machine}-{example}-{unix_time}-{}", std::process::id()),
+			machine,
+			revisions: dependency_revisions(),
+			environment: BTreeMap::new(),
+			warnings: Vec::new(),
+			records: Vec::new(),
+		}
+	}
+
+	/// Writes the results to `<dir>/<example>-<unix time>.json` and returns the path.
+	pub fn write_to_dir(&self, dir: &Path) -> Result<PathBuf, String> {
+		fs::create_dir_all(dir)
+			.map_err(|err| format!("failed to create {}: {err}", dir.display()))?;
+		let path = dir.join(format!("{}-{}.json", self.example, self.unix_time));
+		serde_json::to_string_pretty(self)
+			.map_err(io::Error::from)
+			.and_then(|json| fs::write(&path, json))
+			.map_err(|err| format!("failed to write results to {}: {err}", path.display()))?;
+		Ok(path)
+	}
+
+	/// Reads a results file, rejecting files of other schema versions.
+	pub fn read(path: &Path) -> Result<Self, String> {
+		let contents = fs::read_to_string(path)
+			.map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+		let results = serde_json::from_str::<Self>(&contents)
+			.map_err(|err| format!("invalid results file {}: {err}", path.display()))?;
+		if results.schema_version != SCHEMA_VERSION {
+			return Err(format!(
+				"{} has schema version {}, expected {SCHEMA_VERSION}",
+				path.display(),
+				results.schema_version
+			));
+		}
+		Ok(results)
+	}
+}
+
+/// Reads the results files at `paths`, each either a file or a directory whose `.json` files are
+/// read in name order.
+pub fn load_results(paths: &[PathBuf]) -> Result<Vec<Results>, String> {
+	let mut files = Vec::new();
+	for path in paths {
+		if path.is_dir() {
+			let entries = fs::read_dir(path)
+				.map_err(|err| format!("failed to list {}: {err}", path.display()))?;
+			let mut json_files = entries
+				.filter_map(|entry| Some(entry.ok()?.path()))
+				.filter(|file| file.extension().is_some_and(|ext| ext == "json"))
+				.collect::<Vec<_>>();
+			json_files.sort();
+			files.extend(json_files);
+		} else {
+			files.push(path.clone());
+		}
+	}
+	files.iter().map(|file| Results::read(file)).collect()
+}
+
+/// Host name of the machine, or an empty string if it is unknown.
+fn hostname() -> String {
+	#[cfg(unix)]
+	{
+		let mut name = [0u8; 256];
+		// SAFETY: the buffer is valid for writes of its length.
+		if unsafe { libc::gethostname(name.as_mut_ptr().cast(), name.len()) } != 0 {
+			return String::new();
+		}
+		let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+		String::from_utf8_lossy(&name[..len]).into_owned()
+	}
+	#[cfg(windows)]
+	{
+		crate::windows::host_name().unwrap_or_default()
+	}
+	#[cfg(not(any(unix, windows)))]
+	{
+		String::new()
+	}
+}
+
+/// Writes the results of the run when dropped.
+pub struct ResultsGuard {
+	dir: Option<PathBuf>,
+	history: Option<PathBuf>,
+	results: Results,
+}
+
+/// Starts collecting the results of the run if enabled, into a file under `SNARK_BENCH_RESULTS`,
+/// the history database in `SNARK_BENCH_HISTORY`, or both, and to send a notification of its end
+/// to `SNARK_BENCH_NOTIFY`, see [`crate::notify`]. `--results <dir>` on the command line writes
+/// them to that directory only, keeping runs under a changed setting out of the history.
+///
+/// The results are only written once the returned guard is dropped, so it must be held until the
+/// end of `main`.
+pub fn init_results(name: &str) -> Option<ResultsGuard> {
+	let (dir, history) = match requested_results_dir() {
+		Some(dir) => (Some(dir), None),
+		None => (
+			env::var(RESULTS_DIR_VAR).ok().map(PathBuf::from),
+			env::var(HISTORY_DB_VAR).ok().map(PathBuf::from),
+		),
+	};
+	if dir.is_none() && history.is_none() && env::var_os(NOTIFY_VAR).is_none() {
+		return None;
+	}
+
+	let unix_time = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap()
+		.as_secs();
+	Some(ResultsGuard {
+		dir,
+		history,
+		results: Results {
+			environment: environment(),
+			..Results::new(name, unix_time)
+		},
+	})
+}
+
+impl Drop for ResultsGuard {
+	fn drop(&mut self) {
+		self.results.records = recorded_results();
+		self.results.warnings = recorded_warnings();
+		let (mut results_file, mut summary_file) = (None, None);
+		if let Some(dir) = &self.dir {
+			match self.results.write_to_dir(dir) {
+				Ok(path) => {
+					println!("Wrote results to {}", path.display());
+					results_file = Some(path);
+				}
+				Err(err) => println!("Failed to write results: {err}"),
+			}
+			match write_scaling_plots(&self.results, dir) {
+				Ok(paths) => {
+					for path in paths {
+						println!("Wrote a scaling plot to {}", path.display());
+					}
+				}
+				Err(err) => println!("Failed to write scaling plots: {err}"),
+			}
+			let summary =
+				dir.join(format!("{}-{}-SUMMARY.md", self.results.example, self.results.unix_time));
+			let written = File::create(&summary)
+				.map_err(|err| err.to_string())
+				.and_then(|file| write_summary(std::slice::from_ref(&self.results), file));
+			match written {
+				Ok(()) => {
+					println!("Wrote the run summary to {}", summary.display());
+					summary_file = Some(summary);
+				}
+				Err(err) => println!("Failed to write {}: {err}", summary.display()),
+			}
+		}
+		if let Some(path) = &self.history {
+			match append_history(path, &self.results) {
+				Ok(()) => println!("Appended results to {}", path.display()),
+				Err(err) => println!("Failed to append results to {}: {err}", path.display()),
+			}
+		}
+		notify(&self.results, results_file.as_deref(), summary_file.as_deref());
+	}
+}
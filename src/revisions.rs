@@ -0,0 +1,88 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Git revisions of the proving libraries the benchmarks were built against.
+//!
+//! The backends are git dependencies pinned by revision, tag, or branch in `Cargo.toml`, and
+//! `Cargo.lock` resolves each of them to a commit. A branch moves between builds, so only the
+//! locked commit identifies the code that was measured, and results record it to attribute changes
+//! in performance to upstream bumps. [`diff_revisions`] lists the dependencies whose commits
+//! differ between two runs, which `snark_bench diff` sets against the performance changes of the
+//! backends built from them.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt, fs,
+	path::Path,
+};
+
+/// Parses the repository name and commit from the `source` of a locked git package, such as
+/// `git+https://github.com/IrreducibleOSS/binius?rev=7be2ef5b#7be2ef5b...`.
+fn parse_git_source(source: &str) -> Option<(String, String)> {
+	let (url, commit) = source.strip_prefix("git+")?.split_once('#')?;
+	let path = url.split(['?', '#']).next()?;
+	let repo = path.trim_end_matches('/').rsplit('/').next()?;
+	let repo = repo.strip_suffix(".git").unwrap_or(repo);
+	Some((repo.to_lowercase(), commit.to_string()))
+}
+
+/// The locked commit of every git dependency in `lock`, the contents of a `Cargo.lock`, by
+/// lowercase repository name.
+pub fn parse_lock_file(lock: &str) -> BTreeMap<String, String> {
+	lock.lines()
+		.filter_map(|line| {
+			let source = line.strip_prefix("source = \"")?.strip_suffix('"')?;
+			parse_git_source(source)
+		})
+		.collect()
+}
+
+/// The locked commit of every git dependency of the build, by lowercase repository name, or
+/// nothing if the lock file of the crate is not found.
+///
+/// The repository names of the backends, such as `binius`, `plonky3`, and `jolt`, match their
+/// backend names in the results.
+pub fn dependency_revisions() -> BTreeMap<String, String> {
+	let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.lock");
+	fs::read_to_string(path)
+		.map(|lock| parse_lock_file(&lock))
+		.unwrap_or_default()
+}
+
+/// A dependency whose locked commit differs between two runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevisionChange {
+	/// Lowercase repository name of the dependency.
+	pub dependency: String,
+	/// Commit in the earlier run, if it depended on the repository.
+	pub before: Option<String>,
+	/// Commit in the later run, if it depends on the repository.
+	pub after: Option<String>,
+}
+
+impl fmt::Display for RevisionChange {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		// Abbreviated like git, which is enough to tell commits of one repository apart.
+		let short = |commit: &Option<String>| match commit {
+			Some(commit) => commit.chars().take(10).collect(),
+			None => "none".to_string(),
+		};
+		write!(f, "{} -> {}", short(&self.before), short(&self.after))
+	}
+}
+
+/// The dependencies whose locked commits differ between the revisions of two runs.
+pub fn diff_revisions(
+	before: &BTreeMap<String, String>,
+	after: &BTreeMap<String, String>,
+) -> Vec<RevisionChange> {
+	let dependencies = before.keys().chain(after.keys()).collect::<BTreeSet<_>>();
+	dependencies
+		.into_iter()
+		.filter(|dependency| before.get(*dependency) != after.get(*dependency))
+		.map(|dependency| RevisionChange {
+			dependency: dependency.clone(),
+			before: before.get(dependency).cloned(),
+			after: after.get(dependency).cloned(),
+		})
+		.collect()
+}
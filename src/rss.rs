@@ -0,0 +1,183 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Resident set size sampling during timed phases.
+//!
+//! Heap tracking through [`crate::heap::PeakAllocator`] only sees Rust allocations, while the
+//! resident set also covers memory-mapped buffers, allocator fragmentation, and thread stacks, so
+//! it is what decides whether a prover fits in RAM. [`RssSampler`] polls it from a background
+//! thread for the duration of a phase. The resident set is read from `/proc` on Linux, from the
+//! task info of the process on macOS, and as the working set of the process on Windows.
+//!
+//! Sampling misses spikes shorter than [`SAMPLE_INTERVAL`], such as a buffer allocated, filled, and
+//! freed within a millisecond. On Linux, the sampler also resets the kernel's high-water mark of
+//! the resident set when it starts and reads it when it finishes, which catches every spike. A
+//! phase nested in another resets the mark of the outer phase too, which then falls back to its
+//! samples, so the peak is never lower than the largest sample.
+
+use std::{
+	fmt,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+	thread::{self, JoinHandle},
+	time::Duration,
+};
+
+use bytesize::ByteSize;
+
+/// Time between samples. Phases shorter than this get a sample at each end only.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Reads the resident set size of the current process, if the platform provides it.
+pub fn current_rss() -> Option<u64> {
+	#[cfg(target_os = "linux")]
+	{
+		let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+		let resident_pages = statm.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+		let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+		Some(resident_pages * page_size as u64)
+	}
+	#[cfg(target_os = "macos")]
+	{
+		use std::mem::{size_of, zeroed};
+
+		let mut info = unsafe { zeroed::<libc::proc_taskinfo>() };
+		let size = size_of::<libc::proc_taskinfo>() as libc::c_int;
+		// SAFETY: the buffer is a `proc_taskinfo` of the size passed.
+		let written = unsafe {
+			libc::proc_pidinfo(
+				libc::getpid(),
+				libc::PROC_PIDTASKINFO,
+				0,
+				(&mut info as *mut libc::proc_taskinfo).cast(),
+				size,
+			)
+		};
+		(written == size).then_some(info.pti_resident_size)
+	}
+	#[cfg(windows)]
+	{
+		crate::windows::memory_counters().map(|counters| counters.WorkingSetSize as u64)
+	}
+	#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+	{
+		None
+	}
+}
+
+/// Resets the high-water mark of the resident set size of the current process to its current size,
+/// returning whether the platform supports it.
+fn reset_peak_rss() -> bool {
+	#[cfg(target_os = "linux")]
+	{
+		// Writing 5 to `clear_refs` resets `VmHWM`, since Linux 4.0.
+		std::fs::write("/proc/self/clear_refs", "5").is_ok()
+	}
+	#[cfg(not(target_os = "linux"))]
+	{
+		false
+	}
+}
+
+/// Reads the high-water mark of the resident set size of the current process, on Linux.
+fn peak_rss() -> Option<u64> {
+	#[cfg(target_os = "linux")]
+	{
+		let status = std::fs::read_to_string("/proc/self/status").ok()?;
+		let kib = status
+			.lines()
+			.find_map(|line| line.strip_prefix("VmHWM:"))?
+			.trim()
+			.strip_suffix("kB")?
+			.trim()
+			.parse::<u64>()
+			.ok()?;
+		Some(kib * 1024)
+	}
+	#[cfg(not(target_os = "linux"))]
+	{
+		None
+	}
+}
+
+/// Resident set size statistics over a phase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RssStats {
+	/// Largest resident set size, from the high-water mark of the phase where the platform
+	/// provides one, or else the largest sample, in bytes.
+	pub peak: u64,
+	/// Mean of the samples, in bytes.
+	pub average: u64,
+	/// Number of samples taken.
+	pub samples: u64,
+}
+
+impl fmt::Display for RssStats {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "peak={}, average={}", ByteSize(self.peak), ByteSize(self.average))
+	}
+}
+
+/// Samples the resident set size from a background thread until finished.
+pub struct RssSampler {
+	stop: Arc<AtomicBool>,
+	handle: JoinHandle<Option<RssStats>>,
+	/// Whether the high-water mark was reset at the start, so that it covers the phase alone.
+	tracks_peak: bool,
+}
+
+impl RssSampler {
+	/// Starts sampling, taking the first sample immediately.
+	pub fn start() -> Self {
+		let tracks_peak = reset_peak_rss();
+		let stop = Arc::new(AtomicBool::new(false));
+		let handle = thread::spawn({
+			let stop = stop.clone();
+			move || {
+				let mut peak = 0;
+				let mut total = 0;
+				let mut samples = 0;
+				loop {
+					// Read the flag before sampling, so the last sample is taken after the phase.
+					let stopped = stop.load(Ordering::Acquire);
+					let rss = current_rss()?;
+					peak = peak.max(rss);
+					total += rss;
+					samples += 1;
+					if stopped {
+						break;
+					}
+					thread::sleep(SAMPLE_INTERVAL);
+				}
+				Some(RssStats {
+					peak,
+					average: total / samples,
+					samples,
+				})
+			}
+		});
+		Self {
+			stop,
+			handle,
+			tracks_peak,
+		}
+	}
+
+	/// Stops sampling and returns the statistics, or `None` if the platform does not report the
+	/// resident set size.
+	pub fn finish(self) -> Option<RssStats> {
+		let peak = if self.tracks_peak { peak_rss() } else { None };
+		self.stop.store(true, Ordering::Release);
+		let mut stats = self.handle.join().unwrap()?;
+		stats.peak = stats.peak.max(peak.unwrap_or(0));
+		Some(stats)
+	}
+}
+
+/// Runs `f` while sampling the resident set size.
+pub fn measure_rss<R>(f: impl FnOnce() -> R) -> (R, Option<RssStats>) {
+	let sampler = RssSampler::start();
+	let result = f();
+	(result, sampler.finish())
+}
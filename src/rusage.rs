@@ -0,0 +1,179 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Process resource usage counters from `getrusage`, or the Win32 process counters on Windows.
+//!
+//! Page faults and block I/O explain anomalies that timings alone do not: major faults and disk
+//! reads appear once the witness approaches the size of RAM and pages are swapped, and will be the
+//! main cost of out-of-core provers. The CPU time of a phase over its wall time is the mean number
+//! of cores it kept busy, on every platform.
+//!
+//! Windows does not tell faults that read from disk apart from the others, so it counts every page
+//! fault as minor, and its I/O counters include reads and writes served by the file system cache.
+
+use std::{fmt, time::Duration};
+
+use bytesize::ByteSize;
+
+/// Size of the blocks `getrusage` counts I/O in.
+#[cfg(unix)]
+const BLOCK_BYTES: u64 = 512;
+
+#[cfg(unix)]
+fn rusage() -> Option<libc::rusage> {
+	let mut usage = unsafe { std::mem::zeroed::<libc::rusage>() };
+	if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+		return None;
+	}
+	Some(usage)
+}
+
+/// Page fault counts for the current process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageFaults {
+	/// Faults serviced without I/O, e.g. first touch of freshly allocated memory.
+	pub minor: u64,
+	/// Faults that required reading a page from disk.
+	pub major: u64,
+}
+
+impl PageFaults {
+	/// Reads the page fault counters of the current process, if the platform provides them.
+	pub fn current() -> Option<Self> {
+		#[cfg(unix)]
+		{
+			let usage = rusage()?;
+			Some(Self {
+				minor: usage.ru_minflt as u64,
+				major: usage.ru_majflt as u64,
+			})
+		}
+		#[cfg(windows)]
+		{
+			let counters = crate::windows::memory_counters()?;
+			Some(Self {
+				minor: counters.PageFaultCount as u64,
+				major: 0,
+			})
+		}
+		#[cfg(not(any(unix, windows)))]
+		{
+			None
+		}
+	}
+
+	/// Returns the faults incurred since an earlier reading.
+	pub fn since(&self, earlier: &Self) -> Self {
+		Self {
+			minor: self.minor.saturating_sub(earlier.minor),
+			major: self.major.saturating_sub(earlier.major),
+		}
+	}
+}
+
+impl fmt::Display for PageFaults {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "minor={}, major={}", self.minor, self.major)
+	}
+}
+
+/// Bytes the current process read from and wrote to block devices, excluding the page cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskIo {
+	/// Bytes read, including pages faulted back in from swap or mapped files.
+	pub read_bytes: u64,
+	/// Bytes written.
+	pub write_bytes: u64,
+}
+
+impl DiskIo {
+	/// Reads the block I/O counters of the current process, if the platform provides them.
+	pub fn current() -> Option<Self> {
+		#[cfg(unix)]
+		{
+			let usage = rusage()?;
+			Some(Self {
+				read_bytes: usage.ru_inblock as u64 * BLOCK_BYTES,
+				write_bytes: usage.ru_oublock as u64 * BLOCK_BYTES,
+			})
+		}
+		#[cfg(windows)]
+		{
+			let counters = crate::windows::io_counters()?;
+			Some(Self {
+				read_bytes: counters.ReadTransferCount,
+				write_bytes: counters.WriteTransferCount,
+			})
+		}
+		#[cfg(not(any(unix, windows)))]
+		{
+			None
+		}
+	}
+
+	/// Returns the I/O performed since an earlier reading.
+	pub fn since(&self, earlier: &Self) -> Self {
+		Self {
+			read_bytes: self.read_bytes.saturating_sub(earlier.read_bytes),
+			write_bytes: self.write_bytes.saturating_sub(earlier.write_bytes),
+		}
+	}
+}
+
+impl fmt::Display for DiskIo {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "read={}, written={}", ByteSize(self.read_bytes), ByteSize(self.write_bytes))
+	}
+}
+
+/// CPU time the current process spent, summed over its threads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuTime {
+	/// Time spent running the process's own code.
+	pub user: Duration,
+	/// Time spent in the kernel on behalf of the process.
+	pub system: Duration,
+}
+
+impl CpuTime {
+	/// Reads the CPU time of the current process, if the platform provides it.
+	pub fn current() -> Option<Self> {
+		#[cfg(unix)]
+		{
+			let usage = rusage()?;
+			let duration =
+				|time: libc::timeval| Duration::new(time.tv_sec as u64, time.tv_usec as u32 * 1000);
+			Some(Self {
+				user: duration(usage.ru_utime),
+				system: duration(usage.ru_stime),
+			})
+		}
+		#[cfg(windows)]
+		{
+			let (user, system) = crate::windows::process_times()?;
+			Some(Self { user, system })
+		}
+		#[cfg(not(any(unix, windows)))]
+		{
+			None
+		}
+	}
+
+	/// Returns the CPU time spent since an earlier reading.
+	pub fn since(&self, earlier: &Self) -> Self {
+		Self {
+			user: self.user.saturating_sub(earlier.user),
+			system: self.system.saturating_sub(earlier.system),
+		}
+	}
+
+	/// The mean number of cores busy over `wall_seconds` of wall time.
+	pub fn busy_cores(&self, wall_seconds: f64) -> f64 {
+		(self.user + self.system).as_secs_f64() / wall_seconds
+	}
+}
+
+impl fmt::Display for CpuTime {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "user={:.2?}, system={:.2?}", self.user, self.system)
+	}
+}
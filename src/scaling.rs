@@ -0,0 +1,206 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Fitting of scaling laws to the phase timings of a size sweep.
+//!
+//! Claims such as "linear-time prover" hide logarithmic factors and constants that only show
+//! across sizes. Every [`crate::phase::Phase`] records its time and problem size under the current
+//! [`Series`], set with [`begin_series`] for each backend and configuration, and [`report_scaling`]
+//! fits `time = c·n·log2(n)^k` to each phase of each series by least squares on
+//! `ln(time / n) = ln(c) + k·ln(log2(n))`. A linear-time phase fits `k ≈ 0` and an `n log n` one
+//! `k ≈ 1`. The plain power law `time = a·n^e` is fitted alongside, for phases that are not
+//! quasi-linear at all.
+
+use std::{fmt, sync::Mutex};
+
+/// One timed phase at one problem size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseTiming {
+	/// Series the phase ran in, as displayed.
+	pub series: String,
+	/// Name of the phase.
+	pub phase: String,
+	/// Number of elements the phase processed.
+	pub n: u64,
+	/// Wall time of the phase.
+	pub seconds: f64,
+}
+
+/// A backend and the configuration it runs in, such as plonky3 with a Poseidon2 Merkle tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Series {
+	/// Proving library the phases run in.
+	pub backend: String,
+	/// Parameters of the run other than the problem size.
+	pub config: String,
+}
+
+impl fmt::Display for Series {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.config.is_empty() {
+			write!(f, "{}", self.backend)
+		} else {
+			write!(f, "{}, {}", self.backend, self.config)
+		}
+	}
+}
+
+static SERIES: Mutex<Series> = Mutex::new(Series {
+	backend: String::new(),
+	config: String::new(),
+});
+static POINTS: Mutex<Vec<PhaseTiming>> = Mutex::new(Vec::new());
+
+/// Forgets the series and phases recorded by an earlier example run in this process.
+pub(crate) fn clear_phases() {
+	begin_series("", "");
+	POINTS.lock().unwrap().clear();
+}
+
+/// Starts a new series of measurements of `backend` in configuration `config`, which later phases
+/// are recorded under until the next call.
+pub fn begin_series(backend: &str, config: impl Into<String>) {
+	*SERIES.lock().unwrap() = Series {
+		backend: backend.to_string(),
+		config: config.into(),
+	};
+}
+
+/// The series phases are currently recorded under.
+pub fn current_series() -> Series {
+	SERIES.lock().unwrap().clone()
+}
+
+/// Records the time of a phase over `n` elements under the current series.
+pub fn record_phase(phase: &str, n: u64, seconds: f64) {
+	let series = current_series().to_string();
+	POINTS.lock().unwrap().push(PhaseTiming {
+		series,
+		phase: phase.to_string(),
+		n,
+		seconds,
+	});
+}
+
+/// The number of phase timings recorded so far.
+pub fn recorded_phases() -> usize {
+	POINTS.lock().unwrap().len()
+}
+
+/// The number of elements of the last phase recorded, if any.
+pub fn last_phase_size() -> Option<u64> {
+	POINTS.lock().unwrap().last().map(|point| point.n)
+}
+
+/// The phase timings recorded after the first `start`, in the order the phases finished.
+pub fn phase_timings_since(start: usize) -> Vec<PhaseTiming> {
+	POINTS.lock().unwrap()[start..].to_vec()
+}
+
+/// A fit of `time = c·n·log2(n)^k` and of `time = a·n^e` to the timings of one phase.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScalingFit {
+	/// Seconds per `n·log2(n)^k`.
+	pub c: f64,
+	/// Exponent of the logarithmic factor.
+	pub k: f64,
+	/// Exponent of the power law.
+	pub e: f64,
+	/// Number of timings fitted.
+	pub points: usize,
+}
+
+impl fmt::Display for ScalingFit {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"time = {:.3e} s · n · log2(n)^{:.2}, power law n^{:.3} ({} points)",
+			self.c, self.k, self.e, self.points
+		)
+	}
+}
+
+/// Least-squares slope and intercept of `y` against `x`, or `None` if `x` is constant.
+fn linear_fit(xs: &[f64], ys: &[f64]) -> Option<(f64, f64)> {
+	let n = xs.len() as f64;
+	let mean_x = xs.iter().sum::<f64>() / n;
+	let mean_y = ys.iter().sum::<f64>() / n;
+	let sxx = xs.iter().map(|x| (x - mean_x).powi(2)).sum::<f64>();
+	let sxy = xs
+		.iter()
+		.zip(ys)
+		.map(|(x, y)| (x - mean_x) * (y - mean_y))
+		.sum::<f64>();
+	if sxx <= f64::EPSILON {
+		return None;
+	}
+	let slope = sxy / sxx;
+	Some((slope, mean_y - slope * mean_x))
+}
+
+/// Fits the scaling laws to `(n, seconds)` timings, which need at least two distinct sizes above
+/// one.
+pub fn fit_scaling(timings: &[(u64, f64)]) -> Option<ScalingFit> {
+	let timings = timings
+		.iter()
+		.filter(|&&(n, seconds)| n > 1 && seconds > 0.0)
+		.collect::<Vec<_>>();
+	let ln_n = timings
+		.iter()
+		.map(|&&(n, _)| (n as f64).ln())
+		.collect::<Vec<_>>();
+	let ln_log_n = timings
+		.iter()
+		.map(|&&(n, _)| (n as f64).log2().ln())
+		.collect::<Vec<_>>();
+	let ln_t = timings
+		.iter()
+		.map(|&&(_, seconds)| seconds.ln())
+		.collect::<Vec<_>>();
+	let ln_t_per_n = ln_t
+		.iter()
+		.zip(&ln_n)
+		.map(|(t, n)| t - n)
+		.collect::<Vec<_>>();
+
+	let (k, ln_c) = linear_fit(&ln_log_n, &ln_t_per_n)?;
+	let (e, _) = linear_fit(&ln_n, &ln_t)?;
+	Some(ScalingFit {
+		c: ln_c.exp(),
+		k,
+		e,
+		points: timings.len(),
+	})
+}
+
+/// Prints the scaling fit of every phase of every series with timings at two or more sizes.
+pub fn report_scaling() {
+	let points = POINTS.lock().unwrap();
+	let mut keys = Vec::<(&str, &str)>::new();
+	for point in points.iter() {
+		let key = (point.series.as_str(), point.phase.as_str());
+		if !keys.contains(&key) {
+			keys.push(key);
+		}
+	}
+
+	let fits = keys
+		.into_iter()
+		.filter_map(|(series, phase)| {
+			let timings = points
+				.iter()
+				.filter(|point| point.series == series && point.phase == phase)
+				.map(|point| (point.n, point.seconds))
+				.collect::<Vec<_>>();
+			Some((series, phase, fit_scaling(&timings)?))
+		})
+		.collect::<Vec<_>>();
+	if fits.is_empty() {
+		return;
+	}
+
+	println!("Scaling fits:");
+	for (series, phase, fit) in fits {
+		let series = if series.is_empty() { "-" } else { series };
+		println!("  {series}, {phase}: {fit}");
+	}
+}
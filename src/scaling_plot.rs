@@ -0,0 +1,270 @@
+// Copyright 2024 Irreducible Inc.
+
+//! SVG plots of phase time against problem size, written next to the results of a sweep.
+//!
+//! [`crate::scaling::report_scaling`] prints one fitted exponent per phase, which hides how well
+//! the fit holds: a phase that is linear at large sizes but dominated by setup at small ones fits
+//! a misleading slope. After a run whose phases ran at two or more sizes, [`write_scaling_plots`]
+//! draws, for every backend, the median time of each phase of each configuration against its size
+//! on log-log axes, with the fitted power law `time = a·n^e` as a dashed line and its slope `e` in
+//! the legend. The plots are standalone SVG files, so they open in a browser or embed in a report
+//! without a network connection.
+
+use std::{
+	collections::BTreeMap,
+	fmt::Write as _,
+	fs,
+	path::{Path, PathBuf},
+};
+
+use crate::{
+	report::median_of,
+	results::{Record, Results},
+	scaling::fit_scaling,
+};
+
+/// Size of the plot, in pixels, which grows taller for legends that do not fit.
+const WIDTH: f64 = 960.0;
+const HEIGHT: f64 = 540.0;
+
+/// Height of one legend entry.
+const LEGEND_LINE: f64 = 20.0;
+
+/// The plot area, leaving room for the axis labels and, on the right, the legend.
+const LEFT: f64 = 80.0;
+const RIGHT: f64 = 600.0;
+const TOP: f64 = 50.0;
+const BOTTOM: f64 = 480.0;
+
+/// Colors of the curves, cycled through.
+const COLORS: [&str; 8] = [
+	"#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#17becf",
+];
+
+/// The median timings of one phase of one configuration, by size.
+struct Curve {
+	name: String,
+	points: Vec<(u64, f64)>,
+}
+
+fn escape_xml(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
+
+/// The curves of every backend with a phase that ran at two or more sizes.
+fn curves_by_backend(results: &Results) -> BTreeMap<String, Vec<Curve>> {
+	let mut seconds = BTreeMap::<(String, String), BTreeMap<u64, Vec<f64>>>::new();
+	let mut order = Vec::new();
+	for record in &results.records {
+		let Record::Phase(record) = record else {
+			continue;
+		};
+		let name = if record.config.is_empty() {
+			record.phase.clone()
+		} else {
+			format!("{}, {}", record.config, record.phase)
+		};
+		let key = (record.backend.clone(), name);
+		if !seconds.contains_key(&key) {
+			order.push(key.clone());
+		}
+		seconds
+			.entry(key)
+			.or_default()
+			.entry(record.n_elements)
+			.or_default()
+			.push(record.seconds);
+	}
+
+	let mut curves = BTreeMap::<String, Vec<Curve>>::new();
+	for key in order {
+		let points = seconds[&key]
+			.iter()
+			.filter(|&(&n, _)| n > 1)
+			.map(|(&n, seconds)| (n, median_of(seconds)))
+			.filter(|&(_, seconds)| seconds > 0.0)
+			.collect::<Vec<_>>();
+		if points.len() < 2 {
+			continue;
+		}
+		let (backend, name) = key;
+		curves
+			.entry(backend)
+			.or_default()
+			.push(Curve { name, points });
+	}
+	curves
+}
+
+/// Draws the curves of one backend as an SVG document.
+fn plot(backend: &str, curves: &[Curve]) -> String {
+	let points = curves.iter().flat_map(|curve| &curve.points);
+	let (mut min_x, mut max_x) = (f64::INFINITY, f64::NEG_INFINITY);
+	let (mut min_y, mut max_y) = (f64::INFINITY, f64::NEG_INFINITY);
+	for &(n, seconds) in points {
+		min_x = min_x.min((n as f64).log2());
+		max_x = max_x.max((n as f64).log2());
+		min_y = min_y.min(seconds.log10());
+		max_y = max_y.max(seconds.log10());
+	}
+	// Whole powers of two and ten at the ends, so that the axes end on ticks.
+	let (min_x, max_x) = (min_x.floor(), max_x.ceil().max(min_x.floor() + 1.0));
+	let (min_y, max_y) = (min_y.floor(), max_y.ceil().max(min_y.floor() + 1.0));
+	let x = |n: f64| LEFT + (n.log2() - min_x) / (max_x - min_x) * (RIGHT - LEFT);
+	let y = |seconds: f64| BOTTOM - (seconds.log10() - min_y) / (max_y - min_y) * (BOTTOM - TOP);
+
+	let height = HEIGHT.max(TOP + LEGEND_LINE * (curves.len() + 1) as f64);
+	let mut svg = String::new();
+	let _ = writeln!(
+		svg,
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{height}\" \
+		 font-family=\"sans-serif\" font-size=\"12\">"
+	);
+	let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>");
+	let _ = writeln!(
+		svg,
+		"<text x=\"{}\" y=\"25\" text-anchor=\"middle\" font-size=\"16\">{} phase time vs size</text>",
+		(LEFT + RIGHT) / 2.0,
+		escape_xml(backend)
+	);
+
+	// Grid lines and ticks at every power of two and ten.
+	let x_step = ((max_x - min_x) / 12.0).ceil().max(1.0);
+	let mut log_n = min_x;
+	while log_n <= max_x {
+		let px = x(log_n.exp2());
+		let _ = writeln!(
+			svg,
+			"<line x1=\"{px:.1}\" y1=\"{TOP}\" x2=\"{px:.1}\" y2=\"{BOTTOM}\" stroke=\"#ddd\"/>"
+		);
+		let _ = writeln!(
+			svg,
+			"<text x=\"{px:.1}\" y=\"{}\" text-anchor=\"middle\">2^{log_n}</text>",
+			BOTTOM + 18.0
+		);
+		log_n += x_step;
+	}
+	for log_seconds in min_y as i32..=max_y as i32 {
+		let py = y(10f64.powi(log_seconds));
+		let _ = writeln!(
+			svg,
+			"<line x1=\"{LEFT}\" y1=\"{py:.1}\" x2=\"{RIGHT}\" y2=\"{py:.1}\" stroke=\"#ddd\"/>"
+		);
+		let _ = writeln!(
+			svg,
+			"<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">1e{log_seconds} s</text>",
+			LEFT - 6.0,
+			py + 4.0
+		);
+	}
+	let _ = writeln!(
+		svg,
+		"<rect x=\"{LEFT}\" y=\"{TOP}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"black\"/>",
+		RIGHT - LEFT,
+		BOTTOM - TOP
+	);
+	let _ = writeln!(
+		svg,
+		"<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">n (elements)</text>",
+		(LEFT + RIGHT) / 2.0,
+		BOTTOM + 40.0
+	);
+
+	for (i, curve) in curves.iter().enumerate() {
+		let color = COLORS[i % COLORS.len()];
+		let path = curve
+			.points
+			.iter()
+			.map(|&(n, seconds)| format!("{:.1},{:.1}", x(n as f64), y(seconds)))
+			.collect::<Vec<_>>();
+		let _ = writeln!(
+			svg,
+			"<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"2\"/>",
+			path.join(" ")
+		);
+		for &(n, seconds) in &curve.points {
+			let _ = writeln!(
+				svg,
+				"<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{color}\"/>",
+				x(n as f64),
+				y(seconds)
+			);
+		}
+
+		let fit = fit_scaling(&curve.points);
+		if let Some(fit) = fit {
+			// The power law through the mean of the points in log-log space, where the least
+			// squares line passes.
+			let count = curve.points.len() as f64;
+			let mean_ln_n = curve
+				.points
+				.iter()
+				.map(|&(n, _)| (n as f64).ln())
+				.sum::<f64>()
+				/ count;
+			let mean_ln_t = curve
+				.points
+				.iter()
+				.map(|&(_, seconds)| seconds.ln())
+				.sum::<f64>()
+				/ count;
+			let fitted = |n: f64| (mean_ln_t + fit.e * (n.ln() - mean_ln_n)).exp();
+			let (first, last) =
+				(curve.points[0].0 as f64, curve.points[curve.points.len() - 1].0 as f64);
+			let _ = writeln!(
+				svg,
+				"<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{color}\" \
+				 stroke-dasharray=\"6 4\"/>",
+				x(first),
+				y(fitted(first)),
+				x(last),
+				y(fitted(last))
+			);
+		}
+
+		let legend_y = TOP + 10.0 + LEGEND_LINE * i as f64;
+		let label = match fit {
+			Some(fit) => format!("{} (slope {:.2})", curve.name, fit.e),
+			None => curve.name.clone(),
+		};
+		let _ = writeln!(
+			svg,
+			"<line x1=\"{}\" y1=\"{legend_y}\" x2=\"{}\" y2=\"{legend_y}\" stroke=\"{color}\" \
+			 stroke-width=\"2\"/>",
+			RIGHT + 20.0,
+			RIGHT + 40.0
+		);
+		let _ = writeln!(
+			svg,
+			"<text x=\"{}\" y=\"{}\">{}</text>",
+			RIGHT + 46.0,
+			legend_y + 4.0,
+			escape_xml(&label)
+		);
+	}
+	svg.push_str("</svg>\n");
+	svg
+}
+
+/// Writes a plot of phase time against size for every backend of the results that ran a phase at
+/// two or more sizes, to `<dir>/<example>-<unix time>-<backend>-scaling.svg`, and returns the
+/// paths written.
+pub fn write_scaling_plots(results: &Results, dir: &Path) -> Result<Vec<PathBuf>, String> {
+	let mut paths = Vec::new();
+	for (backend, curves) in curves_by_backend(results) {
+		let file_name = format!(
+			"{}-{}-{}-scaling.svg",
+			results.example,
+			results.unix_time,
+			backend.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+		);
+		let path = dir.join(file_name);
+		fs::write(&path, plot(&backend, &curves))
+			.map_err(|err| format!("failed to write {}: {err}", path.display()))?;
+		paths.push(path);
+	}
+	Ok(paths)
+}
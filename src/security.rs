@@ -0,0 +1,283 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Security levels of the FRI configurations, and prover time normalized by them.
+//!
+//! The backends are configured with different query counts and grinding, so their prover times
+//! buy different security levels and do not compare directly. [`FriSecurity`] computes the bits of
+//! security of the query phase under a [`Soundness`] bound: in the unique decoding regime, which is
+//! proven, each query lets a cheating prover through with probability at most `(1 + ρ) / 2` for
+//! code rate `ρ`, and under the conjectured proximity gaps up to capacity with probability `ρ`.
+//! Proof-of-work adds its bits on top. [`report_security_cost`] divides prover time by that level.
+//! The other soundness errors of each protocol, such as those from the size of the challenge field,
+//! are not included.
+//!
+//! So that the backends are compared at the same soundness, the PCS examples derive their query
+//! counts and grinding from one [`SecurityTarget`] rather than from the defaults of each library:
+//! [`SecurityTarget::fri`] gives the parameters of the query phase at any inverse rate. binius
+//! computes its query count from security bits itself, under the proven bound, and cannot grind, so
+//! [`SecurityTarget::binius_security_bits`] translates the target for it. Configurations under the
+//! conjectured bound are labeled so, see [`SecurityTarget::series_config`], so that reports show
+//! them next to the proven ones rather than mixing their timings.
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+	params::{requested_pow_bits, requested_security_bits, requested_soundness},
+	results::record_metric,
+};
+
+/// Bits of security the FRI configurations target unless `--security-bits` says otherwise.
+pub const DEFAULT_SECURITY_BITS: usize = 96;
+
+/// The bound on the soundness error of a FRI query that query counts are derived from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Soundness {
+	/// The proven bound of the unique decoding regime, `(1 + ρ) / 2` per query.
+	#[default]
+	Proven,
+	/// The conjectured bound of proximity gaps up to capacity, `ρ` per query.
+	Conjectured,
+}
+
+impl Soundness {
+	/// Name of the bound, as `--soundness` takes it.
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Proven => "proven",
+			Self::Conjectured => "conjectured",
+		}
+	}
+
+	/// Bits of security one query contributes at the given inverse rate.
+	fn bits_per_query(self, log_inv_rate: usize) -> f64 {
+		match self {
+			Self::Proven => -(0.5 * (1.0 + 2f64.powi(-(log_inv_rate as i32)))).log2(),
+			Self::Conjectured => log_inv_rate as f64,
+		}
+	}
+}
+
+impl FromStr for Soundness {
+	type Err = String;
+
+	fn from_str(name: &str) -> Result<Self, String> {
+		[Self::Proven, Self::Conjectured]
+			.into_iter()
+			.find(|soundness| soundness.name().eq_ignore_ascii_case(name.trim()))
+			.ok_or_else(|| format!("unknown soundness {name:?}, expected proven or conjectured"))
+	}
+}
+
+impl fmt::Display for Soundness {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+/// Parameters of the FRI query phase that determine its soundness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FriSecurity {
+	/// Log2 of the inverse rate of the Reed–Solomon code.
+	pub log_inv_rate: usize,
+	/// Number of queries.
+	pub n_queries: usize,
+	/// Bits of proof-of-work grinding before the queries are sampled.
+	pub pow_bits: usize,
+	/// The bound the security of the queries is computed under.
+	pub soundness: Soundness,
+}
+
+/// The number of queries that reach `security_bits` at the given inverse rate without grinding,
+/// under the given bound.
+pub fn n_queries_for(security_bits: usize, log_inv_rate: usize, soundness: Soundness) -> usize {
+	(security_bits as f64 / soundness.bits_per_query(log_inv_rate)).ceil() as usize
+}
+
+impl FriSecurity {
+	/// Bits of security of the query phase.
+	pub fn bits(&self) -> f64 {
+		self.n_queries as f64 * self.soundness.bits_per_query(self.log_inv_rate)
+			+ self.pow_bits as f64
+	}
+}
+
+/// The soundness every FRI configuration of a run is parameterized for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityTarget {
+	/// Bits of security of the query phase, including grinding.
+	pub bits: usize,
+	/// Bits of proof-of-work grinding, which replace as many bits of queries.
+	pub pow_bits: usize,
+	/// The bound the query counts are derived under.
+	pub soundness: Soundness,
+}
+
+impl Default for SecurityTarget {
+	/// [`DEFAULT_SECURITY_BITS`] without grinding under the proven bound, the target of the
+	/// benchmarks, whose command line belongs to criterion.
+	fn default() -> Self {
+		Self {
+			bits: DEFAULT_SECURITY_BITS,
+			pow_bits: 0,
+			soundness: Soundness::Proven,
+		}
+	}
+}
+
+impl SecurityTarget {
+	/// The target requested with `--security-bits`, `--pow-bits`, and `--soundness`, by default
+	/// [`DEFAULT_SECURITY_BITS`] without grinding under the proven bound.
+	pub fn requested() -> Self {
+		let bits = requested_security_bits(DEFAULT_SECURITY_BITS);
+		let pow_bits = requested_pow_bits(0);
+		assert!(pow_bits <= bits, "--pow-bits {pow_bits} exceeds --security-bits {bits}");
+		Self {
+			bits,
+			pow_bits,
+			soundness: requested_soundness(),
+		}
+	}
+
+	/// The fewest queries, with the grinding of the target, that reach it at the given inverse
+	/// rate.
+	pub fn fri(&self, log_inv_rate: usize) -> FriSecurity {
+		FriSecurity {
+			log_inv_rate,
+			n_queries: n_queries_for(self.bits - self.pow_bits, log_inv_rate, self.soundness),
+			pow_bits: self.pow_bits,
+			soundness: self.soundness,
+		}
+	}
+
+	/// The security bits to configure binius with at the given inverse rate. binius derives its
+	/// query count from them under the proven bound, so under the conjectured one these are the
+	/// bits the proven bound needs for at least the queries that reach the target, without
+	/// grinding, under the conjectured bound.
+	pub fn binius_security_bits(&self, log_inv_rate: usize) -> usize {
+		match self.soundness {
+			Soundness::Proven => self.bits,
+			soundness => {
+				let n_queries = n_queries_for(self.bits, log_inv_rate, soundness);
+				(n_queries as f64 * Soundness::Proven.bits_per_query(log_inv_rate)).ceil() as usize
+			}
+		}
+	}
+
+	/// The configuration label `config` of a series, marked as conjectured under that bound.
+	pub fn series_config(&self, config: &str) -> String {
+		match (self.soundness, config) {
+			(Soundness::Proven, _) => config.to_string(),
+			(soundness, "") => format!("soundness={soundness}"),
+			(soundness, config) => format!("{config}, soundness={soundness}"),
+		}
+	}
+}
+
+impl fmt::Display for FriSecurity {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{:.1} bits {} ({} queries at log_inv_rate={}, {} PoW bits)",
+			self.bits(),
+			self.soundness,
+			self.n_queries,
+			self.log_inv_rate,
+			self.pow_bits
+		)
+	}
+}
+
+/// Prints the security level of a configuration and the prover time per bit of security, and
+/// records its parameters and security level in the results.
+pub fn report_security_cost(security: &FriSecurity, prover_seconds: f64) {
+	println!(
+		"Security: {security}, prover time per bit = {:.3} ms",
+		prover_seconds * 1e3 / security.bits()
+	);
+	record_metric("log_inv_rate", security.log_inv_rate as f64, "bits");
+	record_metric("fri_queries", security.n_queries as f64, "count");
+	record_metric("pow_bits", security.pow_bits as f64, "bits");
+	record_metric("security_bits", security.bits(), "bits");
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn proven_query_counts() {
+		// At rate 1/2 a query lets a cheating prover through with probability 3/4, which is
+		// -log2(3/4) ≈ 0.415 bits.
+		assert_eq!(n_queries_for(96, 1, Soundness::Proven), 232);
+		assert_eq!(n_queries_for(96, 2, Soundness::Proven), 142);
+		assert_eq!(n_queries_for(96, 4, Soundness::Proven), 106);
+		assert_eq!(n_queries_for(80, 1, Soundness::Proven), 193);
+	}
+
+	#[test]
+	fn conjectured_query_counts() {
+		assert_eq!(n_queries_for(96, 1, Soundness::Conjectured), 96);
+		assert_eq!(n_queries_for(96, 3, Soundness::Conjectured), 32);
+		assert_eq!(n_queries_for(100, 3, Soundness::Conjectured), 34);
+	}
+
+	#[test]
+	fn fri_reaches_the_target() {
+		for soundness in [Soundness::Proven, Soundness::Conjectured] {
+			for pow_bits in [0, 16] {
+				let target = SecurityTarget {
+					bits: 96,
+					pow_bits,
+					soundness,
+				};
+				for log_inv_rate in 1..=4 {
+					let security = target.fri(log_inv_rate);
+					assert_eq!(security.pow_bits, pow_bits);
+					assert!(security.bits() >= 96.0, "{security}");
+				}
+			}
+		}
+		let grinding = SecurityTarget {
+			pow_bits: 16,
+			..Default::default()
+		};
+		assert_eq!(grinding.fri(1).n_queries, 193);
+	}
+
+	#[test]
+	fn binius_security_bits() {
+		let proven = SecurityTarget::default();
+		assert_eq!(proven.binius_security_bits(1), 96);
+		assert_eq!(proven.binius_security_bits(3), 96);
+
+		let conjectured = SecurityTarget {
+			soundness: Soundness::Conjectured,
+			..Default::default()
+		};
+		assert_eq!(conjectured.binius_security_bits(1), 40);
+		assert_eq!(conjectured.binius_security_bits(2), 33);
+		// binius derives at least the conjectured query count from these bits.
+		for log_inv_rate in 1..=4 {
+			let bits = conjectured.binius_security_bits(log_inv_rate);
+			assert!(
+				n_queries_for(bits, log_inv_rate, Soundness::Proven)
+					>= conjectured.fri(log_inv_rate).n_queries
+			);
+		}
+	}
+
+	#[test]
+	fn soundness_names() {
+		assert_eq!(" Conjectured".parse(), Ok(Soundness::Conjectured));
+		assert_eq!("proven".parse(), Ok(Soundness::Proven));
+		assert!("heuristic".parse::<Soundness>().is_err());
+
+		let conjectured = SecurityTarget {
+			soundness: Soundness::Conjectured,
+			..Default::default()
+		};
+		assert_eq!(SecurityTarget::default().series_config("arity=4"), "arity=4");
+		assert_eq!(conjectured.series_config(""), "soundness=conjectured");
+		assert_eq!(conjectured.series_config("arity=4"), "arity=4, soundness=conjectured");
+	}
+}
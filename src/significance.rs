@@ -0,0 +1,372 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Statistical significance of timing differences between backends and between runs.
+//!
+//! A 3% difference between two single timings says nothing when the same phase varies by 5% from
+//! run to run. When configurations are repeated with `SNARK_BENCH_RUNS`, every phase has a sample
+//! of timings, and [`compare_samples`] tests whether two samples come from the same distribution
+//! with the Mann–Whitney U test, which needs no normality assumption and tolerates outlying runs.
+//! The p-value is exact for small samples without ties, and from the normal approximation with a
+//! tie correction otherwise.
+//!
+//! [`report_comparisons`] compares, for every phase and size, each backend of an example against
+//! the first one that ran. Setting `SNARK_BENCH_SAMPLES=<file>` saves the samples of a run, and
+//! setting `SNARK_BENCH_BASELINE=<file>` to a file saved by an earlier run also compares every
+//! phase against it, so that runs across builds or machines are compared on the same footing.
+
+use std::{
+	env, fmt, fs,
+	io::{BufWriter, Write},
+	sync::Mutex,
+};
+
+use crate::{scaling::PhaseTiming, stability::median};
+
+/// Environment variable holding the file to save the timing samples of the run to.
+pub const SAMPLES_FILE_VAR: &str = "SNARK_BENCH_SAMPLES";
+
+/// Environment variable holding a samples file of an earlier run to compare against.
+pub const BASELINE_FILE_VAR: &str = "SNARK_BENCH_BASELINE";
+
+/// p-value below which a difference is reported as significant.
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Largest sample size for which the exact distribution of U is computed.
+const EXACT_LIMIT: usize = 25;
+
+/// The timings of one phase of one configuration over repeated runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseSamples {
+	/// Series the phase ran in.
+	pub series: String,
+	/// Name of the phase.
+	pub phase: String,
+	/// Number of elements the phase processed.
+	pub n: u64,
+	/// Wall time of each run in seconds.
+	pub seconds: Vec<f64>,
+}
+
+impl PhaseSamples {
+	fn same_phase(&self, other: &Self) -> bool {
+		self.phase == other.phase && self.n == other.n
+	}
+
+	/// Parses a line written by [`PhaseSamples::to_line`].
+	fn parse(line: &str) -> Result<Self, String> {
+		let fields = line.split('\t').collect::<Vec<_>>();
+		let [series, phase, n, seconds] = fields[..] else {
+			return Err(format!("expected 4 tab-separated fields in {line:?}"));
+		};
+		let n = n
+			.parse()
+			.map_err(|err| format!("invalid size in {line:?}: {err}"))?;
+		let seconds = seconds
+			.split(',')
+			.map(|s| s.parse::<f64>())
+			.collect::<Result<_, _>>()
+			.map_err(|err| format!("invalid timing in {line:?}: {err}"))?;
+		Ok(Self {
+			series: series.to_string(),
+			phase: phase.to_string(),
+			n,
+			seconds,
+		})
+	}
+
+	fn to_line(&self) -> String {
+		let seconds = self
+			.seconds
+			.iter()
+			.map(|s| format!("{s:.9}"))
+			.collect::<Vec<_>>()
+			.join(",");
+		format!("{}\t{}\t{}\t{seconds}", self.series, self.phase, self.n)
+	}
+}
+
+static SAMPLES: Mutex<Vec<PhaseSamples>> = Mutex::new(Vec::new());
+
+/// Forgets the samples recorded by an earlier example run in this process.
+pub(crate) fn clear_samples() {
+	SAMPLES.lock().unwrap().clear();
+}
+
+/// Records the timings of a phase over repeated runs of its configuration.
+pub fn record_samples(timing: &PhaseTiming, seconds: Vec<f64>) {
+	SAMPLES.lock().unwrap().push(PhaseSamples {
+		series: timing.series.clone(),
+		phase: timing.phase.clone(),
+		n: timing.n,
+		seconds,
+	});
+}
+
+/// The outcome of comparing a candidate sample of timings against a baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+	/// Median of the candidate over the median of the baseline.
+	pub ratio: f64,
+	/// Two-sided p-value of the Mann–Whitney U test.
+	pub p_value: f64,
+}
+
+impl Comparison {
+	/// Whether the samples differ at [`SIGNIFICANCE_LEVEL`].
+	pub fn is_significant(&self) -> bool {
+		self.p_value < SIGNIFICANCE_LEVEL
+	}
+}
+
+impl fmt::Display for Comparison {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{:.3}x (p={:.3}", self.ratio, self.p_value)?;
+		if !self.is_significant() {
+			write!(f, ", not significant")?;
+		}
+		write!(f, ")")
+	}
+}
+
+fn median_of(seconds: &[f64]) -> f64 {
+	let mut sorted = seconds.to_vec();
+	sorted.sort_by(f64::total_cmp);
+	median(&sorted)
+}
+
+/// The number of ways to reach each value of U for samples of `n` and `m` without ties, as the
+/// coefficients of the Gaussian binomial `[n + m choose n]` in `q`.
+fn u_distribution(n: usize, m: usize) -> Vec<f64> {
+	let mut counts = vec![0.0; n * m + n + m + 1];
+	counts[0] = 1.0;
+	for k in 1..=n {
+		// Multiply by 1 - q^(m + k), then divide by 1 - q^k, which leaves a polynomial.
+		for i in (m + k..counts.len()).rev() {
+			counts[i] -= counts[i - m - k];
+		}
+		for i in k..counts.len() {
+			counts[i] += counts[i - k];
+		}
+	}
+	counts.truncate(n * m + 1);
+	counts
+}
+
+/// The complementary error function, to within 1.2e-7 (Numerical Recipes, `erfcc`).
+fn erfc(x: f64) -> f64 {
+	let z = x.abs();
+	let t = 1.0 / (1.0 + 0.5 * z);
+	let poly = -z * z - 1.26551223
+		+ t * (1.00002368
+			+ t * (0.37409196
+				+ t * (0.09678418
+					+ t * (-0.18628806
+						+ t * (0.27886807
+							+ t * (-1.13520398
+								+ t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+	let erfc = t * poly.exp();
+	if x >= 0.0 {
+		erfc
+	} else {
+		2.0 - erfc
+	}
+}
+
+/// The smallest two-sided p-value the Mann–Whitney U test can give for samples of `n` and `m`
+/// timings, when one sample lies entirely below the other: `2 / (n + m choose n)`. With three runs
+/// against three it is 0.1, so such samples can never differ at [`SIGNIFICANCE_LEVEL`].
+pub fn min_p_value(n: usize, m: usize) -> f64 {
+	let arrangements = (1..=n).fold(1.0, |choose, k| choose * (m + k) as f64 / k as f64);
+	(2.0 / arrangements).min(1.0)
+}
+
+/// The two-sided p-value of the Mann–Whitney U test of two samples.
+pub fn mann_whitney_p(a: &[f64], b: &[f64]) -> f64 {
+	let (n, m) = (a.len(), b.len());
+	let mut u = 0.0;
+	let mut has_ties = false;
+	for x in a {
+		for y in b {
+			if x < y {
+				u += 1.0;
+			} else if x == y {
+				u += 0.5;
+				has_ties = true;
+			}
+		}
+	}
+
+	if !has_ties && n <= EXACT_LIMIT && m <= EXACT_LIMIT {
+		let counts = u_distribution(n, m);
+		let total = counts.iter().sum::<f64>();
+		let u = u as usize;
+		let lower = counts[..=u].iter().sum::<f64>() / total;
+		let upper = counts[u..].iter().sum::<f64>() / total;
+		return (2.0 * lower.min(upper)).min(1.0);
+	}
+
+	// Normal approximation, with the variance reduced by tied values across both samples.
+	let mut all = a.iter().chain(b).copied().collect::<Vec<_>>();
+	all.sort_by(f64::total_cmp);
+	let tie_term = all
+		.chunk_by(|x, y| x == y)
+		.map(|group| {
+			let t = group.len() as f64;
+			t * t * t - t
+		})
+		.sum::<f64>();
+	let (n, m) = (n as f64, m as f64);
+	let total = n + m;
+	let variance = n * m / 12.0 * ((total + 1.0) - tie_term / (total * (total - 1.0)));
+	if variance <= 0.0 {
+		return 1.0;
+	}
+	let z = ((u - n * m / 2.0).abs() - 0.5).max(0.0) / variance.sqrt();
+	erfc(z / std::f64::consts::SQRT_2).min(1.0)
+}
+
+/// Compares a candidate sample of timings against a baseline, or returns `None` if either has
+/// fewer than two timings.
+pub fn compare_samples(baseline: &[f64], candidate: &[f64]) -> Option<Comparison> {
+	if baseline.len() < 2 || candidate.len() < 2 {
+		return None;
+	}
+	Some(Comparison {
+		ratio: median_of(candidate) / median_of(baseline),
+		p_value: mann_whitney_p(baseline, candidate),
+	})
+}
+
+fn read_baseline(path: &str) -> Vec<PhaseSamples> {
+	let contents = fs::read_to_string(path)
+		.unwrap_or_else(|err| panic!("failed to read baseline samples from {path:?}: {err}"));
+	contents
+		.lines()
+		.filter(|line| !line.is_empty())
+		.map(|line| {
+			PhaseSamples::parse(line)
+				.unwrap_or_else(|err| panic!("invalid baseline samples in {path:?}: {err}"))
+		})
+		.collect()
+}
+
+fn write_samples(path: &str, samples: &[PhaseSamples]) -> std::io::Result<()> {
+	let mut file = BufWriter::new(fs::File::create(path)?);
+	for sample in samples {
+		writeln!(file, "{}", sample.to_line())?;
+	}
+	file.flush()
+}
+
+/// Compares every phase of every backend against the first backend with the same phase and size,
+/// and against the baseline run if `SNARK_BENCH_BASELINE` is set, then saves the samples if
+/// `SNARK_BENCH_SAMPLES` is set.
+pub fn report_comparisons() {
+	let samples = SAMPLES.lock().unwrap();
+	if samples.is_empty() {
+		return;
+	}
+
+	let backend_comparisons = samples
+		.iter()
+		.enumerate()
+		.filter_map(|(i, candidate)| {
+			let baseline = samples[..i]
+				.iter()
+				.find(|baseline| baseline.same_phase(candidate))?;
+			if baseline.series == candidate.series {
+				return None;
+			}
+			Some((baseline, candidate, compare_samples(&baseline.seconds, &candidate.seconds)?))
+		})
+		.collect::<Vec<_>>();
+	if !backend_comparisons.is_empty() {
+		println!("Backend comparisons:");
+		for (baseline, candidate, comparison) in backend_comparisons {
+			println!(
+				"  {}, n={}: {} vs {}: {comparison}",
+				candidate.phase, candidate.n, candidate.series, baseline.series
+			);
+		}
+	}
+
+	if let Ok(path) = env::var(BASELINE_FILE_VAR) {
+		let baseline = read_baseline(&path);
+		println!("Comparisons with the baseline run {path}:");
+		for candidate in samples.iter() {
+			let Some(comparison) = baseline
+				.iter()
+				.find(|baseline| {
+					baseline.series == candidate.series && baseline.same_phase(candidate)
+				})
+				.and_then(|baseline| compare_samples(&baseline.seconds, &candidate.seconds))
+			else {
+				continue;
+			};
+			println!(
+				"  {}, {}, n={}: {comparison}",
+				candidate.series, candidate.phase, candidate.n
+			);
+		}
+	}
+
+	if let Ok(path) = env::var(SAMPLES_FILE_VAR) {
+		match write_samples(&path, &samples) {
+			Ok(()) => println!("Wrote timing samples to {path}"),
+			Err(err) => println!("Failed to write timing samples to {path}: {err}"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_close(actual: f64, expected: f64) {
+		assert!((actual - expected).abs() < 1e-12, "{actual} != {expected}");
+	}
+
+	#[test]
+	fn u_distribution_of_three_against_three() {
+		assert_eq!(u_distribution(3, 3), [1.0, 1.0, 2.0, 3.0, 3.0, 3.0, 3.0, 2.0, 1.0, 1.0]);
+	}
+
+	#[test]
+	fn separated_samples_reach_the_smallest_p_value() {
+		assert_close(mann_whitney_p(&[1.0, 2.0], &[3.0, 4.0]), 1.0 / 3.0);
+		assert_close(mann_whitney_p(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]), 0.1);
+		assert_close(mann_whitney_p(&[5.0, 6.0, 7.0, 8.0], &[1.0, 2.0, 3.0, 4.0]), 2.0 / 70.0);
+		assert_close(mann_whitney_p(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0, 7.0, 8.0]), 2.0 / 56.0);
+	}
+
+	#[test]
+	fn interleaved_samples() {
+		// U = 6 of 9, and 7 of the 20 arrangements reach U >= 6.
+		assert_close(mann_whitney_p(&[1.0, 3.0, 5.0], &[2.0, 4.0, 6.0]), 0.7);
+		assert_close(mann_whitney_p(&[1.0, 4.0], &[2.0, 3.0]), 1.0);
+	}
+
+	#[test]
+	fn identical_samples_do_not_differ() {
+		assert_close(mann_whitney_p(&[1.0, 1.0, 1.0], &[1.0, 1.0, 1.0]), 1.0);
+	}
+
+	#[test]
+	fn smallest_p_value() {
+		assert_close(min_p_value(1, 1), 1.0);
+		assert_close(min_p_value(2, 2), 1.0 / 3.0);
+		assert_close(min_p_value(3, 3), 0.1);
+		assert_close(min_p_value(4, 4), 2.0 / 70.0);
+		assert_close(min_p_value(3, 5), min_p_value(5, 3));
+		assert!(min_p_value(3, 3) >= SIGNIFICANCE_LEVEL);
+		assert!(min_p_value(4, 4) < SIGNIFICANCE_LEVEL);
+	}
+
+	#[test]
+	fn comparison_needs_two_timings_on_each_side() {
+		assert_eq!(compare_samples(&[1.0], &[2.0, 3.0]), None);
+		let comparison = compare_samples(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]).unwrap();
+		assert_close(comparison.ratio, 2.5);
+		assert!(!comparison.is_significant());
+	}
+}
@@ -0,0 +1,144 @@
+// Copyright 2024 Irreducible Inc.
+
+//! SIMD capabilities of the host CPU and of the build, and the code paths the backends use.
+//!
+//! binius and plonky3 select their packed field implementations at compile time from the enabled
+//! target features, so a build without `-Ctarget-cpu=native` runs portable code on any CPU, and a
+//! build with features disabled runs the code for that lower level. [`report_simd`] prints what
+//! the CPU supports, what the build enables, and the packed types each backend resolved to, so
+//! every result records the code path it measured, and records a warning when a backend fell back
+//! to portable scalar code on a CPU with SIMD. `scripts/simd_levels.sh` rebuilds and runs the
+//! microbenchmarks once per SIMD level.
+//!
+//! On aarch64, plonky3 uses NEON, which every aarch64 target enables. binius also needs the AES
+//! extension for its NEON code, which the Apple targets enable by default but the generic
+//! `aarch64-unknown-linux-gnu` target does not, so Linux builds without `-Ctarget-cpu=native` run
+//! the portable binius code even on CPUs with AES.
+
+use std::any::type_name;
+
+use binius_field::arch::OptimalUnderlier;
+use p3_baby_bear::BabyBear;
+use p3_field::{Field, PackedValue};
+use p3_goldilocks::Goldilocks;
+use p3_mersenne_31::Mersenne31;
+
+use crate::results::record_warning;
+
+macro_rules! simd_features {
+	($detect:ident: $($feature:tt),*) => {
+		/// SIMD features that the CPU supports, out of those the backends use.
+		pub fn detected_features() -> Vec<&'static str> {
+			let mut features = Vec::new();
+			$(
+				if std::arch::$detect!($feature) {
+					features.push($feature);
+				}
+			)*
+			features
+		}
+
+		/// SIMD features that the build enables, out of those the backends use.
+		pub fn compiled_features() -> Vec<&'static str> {
+			let mut features = Vec::new();
+			$(
+				if cfg!(target_feature = $feature) {
+					features.push($feature);
+				}
+			)*
+			features
+		}
+	};
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+simd_features!(is_x86_feature_detected: "sse4.1", "avx2", "avx512f", "gfni", "vpclmulqdq", "vaes");
+
+#[cfg(target_arch = "aarch64")]
+simd_features!(is_aarch64_feature_detected: "neon", "aes");
+
+/// SIMD features that the CPU supports, out of those the backends use.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn detected_features() -> Vec<&'static str> {
+	Vec::new()
+}
+
+/// SIMD features that the build enables, out of those the backends use.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn compiled_features() -> Vec<&'static str> {
+	Vec::new()
+}
+
+/// The name of a type without its module path.
+fn short_type_name<T>() -> &'static str {
+	let name = type_name::<T>();
+	name.rsplit("::").next().unwrap_or(name)
+}
+
+/// The SIMD types each backend resolved to in this build, as `(backend, type)` pairs.
+pub fn backend_code_paths() -> Vec<(&'static str, &'static str)> {
+	vec![
+		("binius underlier", short_type_name::<OptimalUnderlier>()),
+		("plonky3 BB31", short_type_name::<<BabyBear as Field>::Packing>()),
+		("plonky3 M31", short_type_name::<<Mersenne31 as Field>::Packing>()),
+		("plonky3 GL64", short_type_name::<<Goldilocks as Field>::Packing>()),
+	]
+}
+
+/// The backends of [`backend_code_paths`] that resolved to their portable scalar code: binius
+/// when its underlier is a plain `u128`, and plonky3 when a field packs one element.
+pub fn scalar_code_paths() -> Vec<&'static str> {
+	let mut scalar = Vec::new();
+	if short_type_name::<OptimalUnderlier>() == "u128" {
+		scalar.push("binius underlier");
+	}
+	let plonky3_widths = [
+		("plonky3 BB31", <<BabyBear as Field>::Packing as PackedValue>::WIDTH),
+		("plonky3 M31", <<Mersenne31 as Field>::Packing as PackedValue>::WIDTH),
+		("plonky3 GL64", <<Goldilocks as Field>::Packing as PackedValue>::WIDTH),
+	];
+	for (backend, width) in plonky3_widths {
+		if width == 1 {
+			scalar.push(backend);
+		}
+	}
+	scalar
+}
+
+/// Prints the SIMD features of the CPU and the build, and the code path of each backend, with a
+/// note when the build leaves supported features unused.
+pub fn report_simd() {
+	let detected = detected_features();
+	let compiled = compiled_features();
+	println!(
+		"SIMD: CPU supports [{}], build enables [{}]",
+		detected.join(", "),
+		compiled.join(", ")
+	);
+	let paths = backend_code_paths()
+		.into_iter()
+		.map(|(backend, path)| format!("{backend}={path}"))
+		.collect::<Vec<_>>();
+	println!("SIMD code paths: {}", paths.join(", "));
+
+	let unused = detected
+		.iter()
+		.filter(|feature| !compiled.contains(feature))
+		.copied()
+		.collect::<Vec<_>>();
+	if !unused.is_empty() {
+		println!(
+			"Note: build does not use [{}], results reflect a lower SIMD level than this CPU supports",
+			unused.join(", ")
+		);
+	}
+	let scalar = scalar_code_paths();
+	if !scalar.is_empty() && !detected.is_empty() {
+		record_warning(format!(
+			"[{}] run portable scalar code although the CPU supports SIMD, build with \
+			 RUSTFLAGS=\"-Ctarget-cpu=native\"",
+			scalar.join(", ")
+		));
+	}
+	println!();
+}
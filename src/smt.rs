@@ -0,0 +1,143 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Simultaneous multithreading (hyperthreading) topology, and runs restricted to one hardware
+//! thread per physical core, on Linux.
+//!
+//! rayon starts one worker per logical CPU, so on a machine with SMT two workers share every core.
+//! That helps latency-bound work such as hashing, where one thread fills the pipeline while the
+//! other waits, and can hurt bandwidth-bound work such as NTTs, where the two threads evict each
+//! other's cache lines. An [`SmtStudy`] lists the two CPU sets of the `smt` command of
+//! `snark_bench`, the first hardware thread of every physical core and all logical CPUs. The
+//! command runs the benchmarks restricted to each set in turn with [`with_cpus`], and since rayon
+//! sizes its pool from the CPUs the process may run on, the ratio of the two runs is what SMT buys
+//! each backend and phase.
+
+use std::{fs, path::Path};
+
+use crate::{cpu_state::CPU_DIR, numa::parse_cpu_list};
+
+/// The CPUs the calling thread may run on, in order, or an empty list on platforms without CPU
+/// affinity.
+pub fn allowed_cpus() -> Vec<usize> {
+	#[cfg(target_os = "linux")]
+	{
+		let mut set = unsafe { std::mem::zeroed::<libc::cpu_set_t>() };
+		if unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) }
+			!= 0
+		{
+			return Vec::new();
+		}
+		(0..libc::CPU_SETSIZE as usize)
+			.filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+			.collect()
+	}
+	#[cfg(not(target_os = "linux"))]
+	{
+		Vec::new()
+	}
+}
+
+/// Restricts the calling thread to `cpus`, returning whether the platform allows it. Threads and
+/// processes it spawns afterwards inherit the restriction.
+pub fn restrict_to_cpus(cpus: &[usize]) -> bool {
+	#[cfg(target_os = "linux")]
+	{
+		let mut set = unsafe { std::mem::zeroed::<libc::cpu_set_t>() };
+		for &cpu in cpus {
+			unsafe { libc::CPU_SET(cpu, &mut set) };
+		}
+		unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0 }
+	}
+	#[cfg(not(target_os = "linux"))]
+	{
+		let _ = cpus;
+		false
+	}
+}
+
+/// Runs `f` with the calling thread restricted to `cpus`, so that the processes it spawns run on
+/// them too, then restores the CPUs it ran on before.
+pub fn with_cpus<R>(cpus: &[usize], f: impl FnOnce() -> R) -> Result<R, String> {
+	let previous = allowed_cpus();
+	if !restrict_to_cpus(cpus) {
+		return Err(format!("failed to restrict the CPUs to {cpus:?}"));
+	}
+	let result = f();
+	restrict_to_cpus(&previous);
+	Ok(result)
+}
+
+/// The hardware threads sharing a core with `cpu`, including itself.
+fn siblings(cpu: usize) -> Option<Vec<usize>> {
+	let topology = Path::new(CPU_DIR).join(format!("cpu{cpu}/topology"));
+	// Kernels before 5.7 only have the older name.
+	let list = fs::read_to_string(topology.join("core_cpus_list"))
+		.or_else(|_| fs::read_to_string(topology.join("thread_siblings_list")))
+		.ok()?;
+	parse_cpu_list(&list)
+}
+
+/// The number of physical cores among the CPUs the calling thread may run on, if the platform
+/// reports the SMT siblings of every CPU.
+pub fn physical_cores() -> Option<usize> {
+	let logical = allowed_cpus();
+	if logical.is_empty() {
+		return None;
+	}
+	let mut cores = logical
+		.iter()
+		.map(|&cpu| siblings(cpu))
+		.collect::<Option<Vec<_>>>()?;
+	cores.sort_unstable();
+	cores.dedup();
+	Some(cores.len())
+}
+
+/// The CPUs of a comparison of one hardware thread per physical core against all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmtStudy {
+	/// The lowest allowed hardware thread of every physical core.
+	pub physical: Vec<usize>,
+	/// Every allowed logical CPU.
+	pub logical: Vec<usize>,
+}
+
+impl SmtStudy {
+	/// The study over the CPUs this process may run on, which needs a core with at least two of
+	/// its hardware threads among them.
+	pub fn new() -> Result<Self, String> {
+		let logical = allowed_cpus();
+		if logical.is_empty() {
+			return Err("CPU affinity is only supported on Linux".to_string());
+		}
+		let mut physical = Vec::new();
+		for &cpu in &logical {
+			let siblings = siblings(cpu)
+				.ok_or_else(|| format!("failed to read the SMT siblings of CPU {cpu}"))?;
+			// The allowed CPUs are in order, so the first allowed sibling is met first.
+			let first = siblings
+				.into_iter()
+				.find(|sibling| logical.contains(sibling))
+				.unwrap_or(cpu);
+			if first == cpu {
+				physical.push(cpu);
+			}
+		}
+		if physical.len() == logical.len() {
+			return Err(format!(
+				"an SMT comparison needs a core with several hardware threads, but no two of the {} \
+				 CPUs this process may run on share a core",
+				logical.len()
+			));
+		}
+		Ok(Self { physical, logical })
+	}
+
+	/// The names of the two CPU sets, one thread per physical core first, and their CPUs.
+	pub fn cpu_sets(&self) -> Vec<(String, &[usize])> {
+		vec![
+			("physical".to_string(), &self.physical[..]),
+			("logical".to_string(), &self.logical[..]),
+		]
+	}
+}
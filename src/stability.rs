@@ -0,0 +1,207 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Repeated runs of example configurations until their phase timings are stable.
+//!
+//! The examples time each phase once, so a background process or frequency change publishes noise
+//! as data. When `SNARK_BENCH_RUNS=<runs>` or `--min-runs <runs>` is set, [`run_until_stable`] runs
+//! a configuration at least that many times, and keeps re-running it, up to [`MAX_RUN_FACTOR`]
+//! times as often, while any of its phases spreads more than [`SPREAD_THRESHOLD`] between runs. The
+//! spread is the median absolute deviation relative to the median, scaled to match the coefficient
+//! of variation of normally distributed timings, so that a single outlying run does not force
+//! re-runs on its own. Runs further than [`OUTLIER_DEVIATIONS`] deviations from the median are
+//! reported as outliers, and phases still above the threshold after the last run are marked
+//! unstable.
+//!
+//! Phases that take seconds or minutes are too long to re-run until they settle. `--repeats <n>`
+//! on the command line of an example instead runs every configuration exactly `n` times, taking
+//! precedence over `SNARK_BENCH_RUNS`. Either way, every phase is reported with the minimum,
+//! median, mean, and standard deviation of its timings next to its spread.
+
+use std::env;
+
+use crate::{
+	params::{requested_min_runs, requested_repeats},
+	results::record_warning,
+	scaling::{phase_timings_since, recorded_phases, PhaseTiming},
+	significance::record_samples,
+};
+
+/// Environment variable holding the minimum number of runs of each configuration.
+pub const RUNS_VAR: &str = "SNARK_BENCH_RUNS";
+
+/// Relative spread of a phase's timings above which it is re-run.
+pub const SPREAD_THRESHOLD: f64 = 0.05;
+
+/// Maximum number of runs, as a multiple of the minimum.
+pub const MAX_RUN_FACTOR: usize = 3;
+
+/// Deviations from the median beyond which a run is an outlier.
+pub const OUTLIER_DEVIATIONS: f64 = 3.0;
+
+/// Ratio of the standard deviation to the median absolute deviation for normal distributions.
+const MAD_TO_STD: f64 = 1.4826;
+
+/// The minimum number of runs set by `--min-runs` or `SNARK_BENCH_RUNS`, or one.
+pub fn min_runs() -> usize {
+	if let Some(runs) = requested_min_runs() {
+		return runs;
+	}
+	match env::var(RUNS_VAR) {
+		Ok(runs) => runs
+			.parse::<usize>()
+			.ok()
+			.filter(|&runs| runs > 0)
+			.unwrap_or_else(|| {
+				panic!("{RUNS_VAR} must be a positive number of runs, got {runs:?}")
+			}),
+		Err(_) => 1,
+	}
+}
+
+/// The median of sorted timings.
+pub(crate) fn median(sorted: &[f64]) -> f64 {
+	let mid = sorted.len() / 2;
+	if sorted.len() % 2 == 0 {
+		(sorted[mid - 1] + sorted[mid]) / 2.0
+	} else {
+		sorted[mid]
+	}
+}
+
+/// Statistics of one phase's timings over repeated runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingStats {
+	/// Shortest time in seconds.
+	pub min: f64,
+	/// Median time in seconds.
+	pub median: f64,
+	/// Mean time in seconds.
+	pub mean: f64,
+	/// Sample standard deviation of the times in seconds, zero for a single run.
+	pub std_dev: f64,
+	/// Median absolute deviation relative to the median, scaled to a coefficient of variation.
+	pub spread: f64,
+	/// Number of runs more than [`OUTLIER_DEVIATIONS`] deviations from the median.
+	pub outliers: usize,
+	/// Number of runs.
+	pub runs: usize,
+}
+
+impl TimingStats {
+	/// Computes the statistics of a set of timings in seconds.
+	pub fn new(seconds: &[f64]) -> Self {
+		let mut sorted = seconds.to_vec();
+		sorted.sort_by(f64::total_cmp);
+		let middle = median(&sorted);
+		let mut deviations = sorted
+			.iter()
+			.map(|seconds| (seconds - middle).abs())
+			.collect::<Vec<_>>();
+		deviations.sort_by(f64::total_cmp);
+		let deviation = median(&deviations) * MAD_TO_STD;
+		let outliers = deviations
+			.iter()
+			.filter(|&&d| deviation > 0.0 && d > OUTLIER_DEVIATIONS * deviation)
+			.count();
+		let mean = sorted.iter().sum::<f64>() / sorted.len() as f64;
+		let variance = sorted
+			.iter()
+			.map(|seconds| (seconds - mean).powi(2))
+			.sum::<f64>()
+			/ (sorted.len().max(2) - 1) as f64;
+		Self {
+			min: sorted[0],
+			median: middle,
+			mean,
+			std_dev: variance.sqrt(),
+			spread: deviation / middle,
+			outliers,
+			runs: seconds.len(),
+		}
+	}
+
+	/// Whether the timings spread less than [`SPREAD_THRESHOLD`].
+	pub fn is_stable(&self) -> bool {
+		self.spread <= SPREAD_THRESHOLD
+	}
+}
+
+fn label(timing: &PhaseTiming) -> String {
+	if timing.series.is_empty() {
+		timing.phase.clone()
+	} else {
+		format!("{}, {}", timing.series, timing.phase)
+	}
+}
+
+/// Groups the phases of each run by their position in it, which identifies the same phase across
+/// runs of one configuration.
+fn phase_samples(runs: &[Vec<PhaseTiming>]) -> Vec<(&PhaseTiming, Vec<f64>)> {
+	let n_phases = runs.iter().map(Vec::len).min().unwrap_or(0);
+	(0..n_phases)
+		.map(|i| (&runs[0][i], runs.iter().map(|run| run[i].seconds).collect()))
+		.collect()
+}
+
+/// Runs a configuration until its phase timings are stable, or exactly as often as `--repeats`
+/// says, as described in the module documentation, then prints the statistics of every phase. Runs
+/// once without printing statistics when neither `--repeats` nor `SNARK_BENCH_RUNS` is set.
+pub fn run_until_stable(mut run: impl FnMut()) {
+	let repeats = requested_repeats();
+	let min_runs = repeats.unwrap_or_else(min_runs);
+	// An explicit `--repeats`, even of one run, always reports its statistics.
+	if repeats.is_none() && min_runs == 1 {
+		run();
+		return;
+	}
+
+	let mut runs = Vec::new();
+	loop {
+		let start = recorded_phases();
+		run();
+		runs.push(phase_timings_since(start));
+
+		if runs.len() < min_runs {
+			continue;
+		}
+		if repeats.is_some() {
+			break;
+		}
+		let unstable = phase_samples(&runs)
+			.into_iter()
+			.filter(|(_, seconds)| !TimingStats::new(seconds).is_stable())
+			.map(|(timing, _)| label(timing))
+			.collect::<Vec<_>>();
+		if unstable.is_empty() || runs.len() >= min_runs * MAX_RUN_FACTOR {
+			break;
+		}
+		println!("Re-running, unstable phases: {}", unstable.join("; "));
+	}
+
+	println!("Phase stability over {} runs:", runs.len());
+	for (timing, seconds) in phase_samples(&runs) {
+		let stats = TimingStats::new(&seconds);
+		println!(
+			"  {}: min={:.4}s, median={:.4}s, mean={:.4}s, stddev={:.4}s, spread={:.1}%, \
+			 outliers={}{}",
+			label(timing),
+			stats.min,
+			stats.median,
+			stats.mean,
+			stats.std_dev,
+			stats.spread * 100.0,
+			stats.outliers,
+			if stats.is_stable() { "" } else { " UNSTABLE" }
+		);
+		if !stats.is_stable() {
+			record_warning(format!(
+				"{} spread {:.1}% over {} runs, above the {:.0}% threshold",
+				label(timing),
+				stats.spread * 100.0,
+				stats.runs,
+				SPREAD_THRESHOLD * 100.0
+			));
+		}
+		record_samples(timing, seconds);
+	}
+}
@@ -0,0 +1,145 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Benchmark suites described in TOML, for the `suite` command of `snark_bench`.
+//!
+//! A suite lists the proof systems to run, each with the parameters of its sweep, so that one
+//! invocation runs a whole matrix of configurations instead of one example at its built-in sizes:
+//!
+//! ```toml
+//! repeats = 3
+//!
+//! [[run]]
+//! system = "fri-binius"
+//! n_vars = [20, 22, 24, 26]
+//! log_inv_rate = [1, 2]
+//! field = ["AESTowerField8b", "AESTowerField32b"]
+//! threads = "1,4,max"
+//!
+//! [[run]]
+//! system = "plonky3-fri"
+//! n_vars = [20, 24]
+//! hash = ["keccak"]
+//! repeats = 5
+//! ```
+//!
+//! Every entry runs the examples of its system, as the subcommand of that name does, once per
+//! inverse rate in `log_inv_rate`, each sweeping all of `n_vars` and the listed fields, hash
+//! functions, composition degrees, and bit widths, see [`crate::params`], and once per thread count
+//! in `threads` if it is set, as `--threads` does. The parameters an entry leaves out keep the
+//! defaults of its examples. `repeats` sets the minimum number of runs of every
+//! configuration, see [`crate::stability`], for the whole suite or one entry.
+
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+
+/// A suite of benchmark runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suite {
+	/// Minimum number of runs of every configuration, unless an entry sets its own.
+	pub repeats: Option<usize>,
+	/// The entries of the suite, in the order they run.
+	#[serde(default, rename = "run")]
+	pub runs: Vec<SuiteRun>,
+}
+
+/// One entry of a suite, a proof system and the parameters of its sweep.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SuiteRun {
+	/// Name of the proof system, as its `snark_bench` subcommand.
+	pub system: String,
+	/// Log2 of the problem sizes.
+	#[serde(default)]
+	pub n_vars: Vec<usize>,
+	/// Log2 inverse code rates, each run separately.
+	#[serde(default)]
+	pub log_inv_rate: Vec<usize>,
+	/// Degrees of the sumcheck compositions.
+	#[serde(default)]
+	pub degree: Vec<usize>,
+	/// Bit widths of the committed values.
+	#[serde(default)]
+	pub n_bits: Vec<usize>,
+	/// Names of the fields to run.
+	#[serde(default)]
+	pub field: Vec<String>,
+	/// Names of the hash functions to run.
+	#[serde(default)]
+	pub hash: Vec<String>,
+	/// Thread counts to run the examples with, as `--threads` takes them, such as `"1,2,4,max"`.
+	pub threads: Option<String>,
+	/// Minimum number of runs of every configuration.
+	pub repeats: Option<usize>,
+}
+
+/// One run of the examples of a system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+	/// Name of the proof system.
+	pub system: String,
+	/// The parameter options passed to its examples.
+	pub args: Vec<String>,
+	/// Minimum number of runs of every configuration, if set.
+	pub repeats: Option<usize>,
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+	values
+		.iter()
+		.map(ToString::to_string)
+		.collect::<Vec<_>>()
+		.join(",")
+}
+
+impl Suite {
+	/// Reads the suite described by the TOML file at `path`.
+	pub fn load(path: &Path) -> Result<Self, String> {
+		let text = fs::read_to_string(path)
+			.map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+		let suite = toml::from_str::<Self>(&text)
+			.map_err(|err| format!("failed to parse {}: {err}", path.display()))?;
+		if suite.runs.is_empty() {
+			return Err(format!("{} has no [[run]] entries", path.display()));
+		}
+		Ok(suite)
+	}
+
+	/// The runs of the suite, in order.
+	pub fn invocations(&self) -> Vec<Invocation> {
+		let mut invocations = Vec::new();
+		for run in &self.runs {
+			let mut args = Vec::new();
+			let mut list = |name: &str, values: String| {
+				if !values.is_empty() {
+					args.extend([format!("--{name}"), values]);
+				}
+			};
+			list("n-vars", join(&run.n_vars));
+			list("degree", join(&run.degree));
+			list("n-bits", join(&run.n_bits));
+			list("field", run.field.join(","));
+			list("hash", run.hash.join(","));
+			list("threads", run.threads.clone().unwrap_or_default());
+
+			let rates = if run.log_inv_rate.is_empty() {
+				vec![None]
+			} else {
+				run.log_inv_rate.iter().copied().map(Some).collect()
+			};
+			for log_inv_rate in rates {
+				let mut args = args.clone();
+				if let Some(log_inv_rate) = log_inv_rate {
+					args.extend(["--log-inv-rate".to_string(), log_inv_rate.to_string()]);
+				}
+				invocations.push(Invocation {
+					system: run.system.clone(),
+					args,
+					repeats: run.repeats.or(self.repeats),
+				});
+			}
+		}
+		invocations
+	}
+}
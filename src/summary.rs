@@ -0,0 +1,203 @@
+// Copyright 2024 Irreducible Inc.
+
+//! A Markdown summary of a run, with its headline numbers and the caveats that come with them.
+//!
+//! The JSON results hold every sample, which is more than a reader of shared numbers wants and
+//! less than they need: nothing in the records says whether the machine throttled, whether a
+//! phase varied too much to trust, or at what security level the backends were configured.
+//! [`write_summary`] writes a `SUMMARY.md` that lists, for every run:
+//!
+//! - the median wall time of every phase and the proof size, per configuration and size;
+//! - the FRI parameters and security level of every configuration that recorded them, see
+//!   [`crate::security`];
+//! - the machine and build, see [`crate::environment`], and the locked dependency commits;
+//! - the warnings raised during the run, such as thermal throttling, a frequency governor other
+//!   than `performance`, or phases whose spread exceeds the stability threshold.
+//!
+//! Each run writes its summary next to its results, and the `summary` report format writes one for
+//! results loaded from files.
+
+use std::{collections::BTreeMap, io, time::Duration};
+
+use bytesize::ByteSize;
+
+use crate::{
+	google_benchmark::iso8601,
+	proof_size::PROOF_SIZE_METRIC,
+	report::{label, PhaseTimings},
+	results::{Record, Results},
+};
+
+/// Metrics of the security parameters of a configuration, with the headers of their columns.
+const SECURITY_METRICS: [(&str, &str); 4] = [
+	("log_inv_rate", "log₂ 1/rate"),
+	("fri_queries", "Queries"),
+	("pow_bits", "PoW bits"),
+	("security_bits", "Security bits"),
+];
+
+/// A size, as a power of two when it is one.
+pub(crate) fn size_cell(n: u64) -> String {
+	if n.is_power_of_two() {
+		format!("2^{}", n.trailing_zeros())
+	} else {
+		n.to_string()
+	}
+}
+
+/// Escapes the characters that end a Markdown table cell or start inline formatting.
+fn escape_markdown(text: &str) -> String {
+	text.replace('|', "\\|")
+		.replace('*', "\\*")
+		.replace('_', "\\_")
+}
+
+fn write_run(run: &Results, out: &mut impl io::Write) -> io::Result<()> {
+	let runs = std::slice::from_ref(run);
+	if run.run_id.is_empty() {
+		writeln!(out, "## {}", run.example)?;
+	} else {
+		writeln!(out, "## {} ({})", run.example, run.run_id)?;
+	}
+	writeln!(out)?;
+	write!(out, "Started {}", iso8601(run.unix_time))?;
+	if !run.machine.is_empty() {
+		write!(out, " on {}", run.machine)?;
+	}
+	writeln!(out, ".")?;
+	writeln!(out)?;
+
+	// The median of the last value of every metric per series and size, as the tables show them.
+	let mut metrics = BTreeMap::<(String, &str, Option<u64>), Vec<f64>>::new();
+	for record in &run.records {
+		if let Record::Metric(record) = record {
+			let metric = std::iter::once(PROOF_SIZE_METRIC)
+				.chain(SECURITY_METRICS.iter().map(|&(metric, _)| metric))
+				.find(|&metric| metric == record.metric);
+			if let Some(metric) = metric {
+				metrics
+					.entry((label(&record.backend, &record.config), metric, record.n_elements))
+					.or_default()
+					.push(record.value);
+			}
+		}
+	}
+
+	let timings = PhaseTimings::new(runs);
+	writeln!(out, "### Results")?;
+	writeln!(out)?;
+	if timings.series.is_empty() {
+		writeln!(out, "No phases were timed.")?;
+	} else {
+		writeln!(out, "Median wall time of every phase, and proof size.")?;
+		writeln!(out)?;
+		let with_proof_size = metrics
+			.keys()
+			.any(|(_, metric, _)| *metric == PROOF_SIZE_METRIC);
+		let mut header = vec!["Configuration".to_string(), "n".to_string()];
+		header.extend(timings.phases.iter().cloned());
+		if with_proof_size {
+			header.push("proof size".to_string());
+		}
+		writeln!(out, "| {} |", header.join(" | "))?;
+		writeln!(out, "|---|{}", "--:|".repeat(header.len() - 1))?;
+		for n in timings.sizes(None) {
+			for series in &timings.series {
+				let times = timings
+					.phases
+					.iter()
+					.map(|phase| timings.median(series, phase, n))
+					.collect::<Vec<_>>();
+				if times.iter().all(Option::is_none) {
+					continue;
+				}
+				let mut cells = vec![escape_markdown(series), size_cell(n)];
+				cells.extend(times.into_iter().map(|seconds| match seconds {
+					Some(seconds) => format!("{:.2?}", Duration::from_secs_f64(seconds)),
+					None => "–".to_string(),
+				}));
+				if with_proof_size {
+					let bytes = metrics.get(&(series.clone(), PROOF_SIZE_METRIC, Some(n)));
+					cells.push(match bytes.and_then(|values| values.last()) {
+						Some(&bytes) => ByteSize(bytes as u64).to_string(),
+						None => "–".to_string(),
+					});
+				}
+				writeln!(out, "| {} |", cells.join(" | "))?;
+			}
+		}
+	}
+	writeln!(out)?;
+
+	writeln!(out, "### Security parameters")?;
+	writeln!(out)?;
+	let mut security = BTreeMap::<&str, BTreeMap<&str, f64>>::new();
+	for ((series, metric, _), values) in &metrics {
+		if *metric != PROOF_SIZE_METRIC {
+			if let Some(&value) = values.last() {
+				security
+					.entry(series.as_str())
+					.or_default()
+					.insert(metric, value);
+			}
+		}
+	}
+	if security.is_empty() {
+		writeln!(out, "No configuration recorded its security parameters.")?;
+	} else {
+		let headers = SECURITY_METRICS
+			.iter()
+			.map(|&(_, header)| header)
+			.collect::<Vec<_>>();
+		writeln!(out, "| Configuration | {} |", headers.join(" | "))?;
+		writeln!(out, "|---|{}", "--:|".repeat(headers.len()))?;
+		for (series, values) in &security {
+			let cells = SECURITY_METRICS
+				.iter()
+				.map(|&(metric, _)| match values.get(metric) {
+					Some(value) if value.fract() == 0.0 => format!("{value}"),
+					Some(value) => format!("{value:.1}"),
+					None => "–".to_string(),
+				})
+				.collect::<Vec<_>>();
+			writeln!(out, "| {} | {} |", escape_markdown(series), cells.join(" | "))?;
+		}
+	}
+	writeln!(out)?;
+
+	writeln!(out, "### Environment")?;
+	writeln!(out)?;
+	if run.environment.is_empty() {
+		writeln!(out, "Not recorded.")?;
+	}
+	for (key, value) in &run.environment {
+		writeln!(out, "- {}: {}", key.replace('_', " "), value)?;
+	}
+	for (dependency, commit) in &run.revisions {
+		writeln!(out, "- {dependency} revision: `{commit}`")?;
+	}
+	writeln!(out)?;
+
+	writeln!(out, "### Warnings")?;
+	writeln!(out)?;
+	if run.warnings.is_empty() {
+		writeln!(out, "None.")?;
+	}
+	for warning in &run.warnings {
+		writeln!(out, "- {warning}")?;
+	}
+	writeln!(out)
+}
+
+/// Writes a Markdown summary of every run of the results.
+pub fn write_summary(results: &[Results], mut out: impl io::Write) -> Result<(), String> {
+	let mut write = || -> io::Result<()> {
+		writeln!(out, "# Benchmark summary")?;
+		writeln!(out)?;
+		for run in results {
+			write_run(run, &mut out)?;
+		}
+		out.flush()
+	};
+	write().map_err(|err| format!("failed to write the summary: {err}"))
+}
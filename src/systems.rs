@@ -0,0 +1,125 @@
+// Copyright 2024 Irreducible Inc.
+
+//! The benchmarks of every proof system, which the examples and `snark_bench` run.
+//!
+//! Every module benchmarks one backend, or several on the same problem, over the sizes and
+//! parameters of [`crate::params`], and its `run` is the whole of the example of the same name.
+//! The `<system>` and `suite` subcommands of `snark_bench` run the same functions in their own
+//! process through [`run_example`], which passes them a command line of their own with
+//! [`with_command_line`] and clears the results an earlier run in the process recorded, so that a
+//! subcommand needs neither a toolchain nor a build of the examples. The stwo benchmark is only
+//! built with the `stwo` feature.
+
+pub mod binius_sumcheck;
+pub mod bn254_fr_sumcheck;
+pub mod fri_binius_pcs;
+pub mod goldilocks_fri_pcs;
+pub mod huge_ntt;
+pub mod jolt_hyrax_pcs;
+pub mod plonky3_fri_pcs;
+#[cfg(feature = "stwo")]
+pub mod stwo_pcs;
+
+use std::panic;
+
+use crate::{
+	params::with_command_line, results::clear_results, scaling::clear_phases,
+	significance::clear_samples,
+};
+
+/// A proof system with a subcommand of `snark_bench`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct System {
+	/// Name of the system and its subcommand, such as `fri-binius`.
+	pub name: &'static str,
+	/// The examples that benchmark the system, in the order they run.
+	pub examples: &'static [&'static str],
+	/// The Cargo feature the examples need, if any.
+	pub feature: Option<&'static str>,
+}
+
+/// The proof systems that have a subcommand.
+pub const SYSTEMS: [System; 7] = [
+	System {
+		name: "fri-binius",
+		examples: &["fri_binius_pcs"],
+		feature: None,
+	},
+	System {
+		name: "plonky3-fri",
+		examples: &["plonky3_fri_pcs"],
+		feature: None,
+	},
+	System {
+		name: "plonky2-fri",
+		examples: &["goldilocks_fri_pcs"],
+		feature: None,
+	},
+	System {
+		name: "stwo",
+		examples: &["stwo_pcs"],
+		feature: Some("stwo"),
+	},
+	System {
+		name: "jolt-hyrax",
+		examples: &["jolt_hyrax_pcs"],
+		feature: None,
+	},
+	System {
+		name: "sumcheck",
+		examples: &["binius_sumcheck", "bn254_fr_sumcheck"],
+		feature: None,
+	},
+	System {
+		name: "ntt",
+		examples: &["huge_ntt"],
+		feature: None,
+	},
+];
+
+/// The proof system named `name`.
+pub fn system(name: &str) -> Option<&'static System> {
+	SYSTEMS.iter().find(|system| system.name == name)
+}
+
+/// The benchmarks of the example named `name`, if they are built in.
+fn example_run(name: &str) -> Option<fn()> {
+	match name {
+		"binius_sumcheck" => Some(binius_sumcheck::run),
+		"bn254_fr_sumcheck" => Some(bn254_fr_sumcheck::run),
+		"fri_binius_pcs" => Some(fri_binius_pcs::run),
+		"goldilocks_fri_pcs" => Some(goldilocks_fri_pcs::run),
+		"huge_ntt" => Some(huge_ntt::run),
+		"jolt_hyrax_pcs" => Some(jolt_hyrax_pcs::run),
+		"plonky3_fri_pcs" => Some(plonky3_fri_pcs::run),
+		#[cfg(feature = "stwo")]
+		"stwo_pcs" => Some(stwo_pcs::run),
+		_ => None,
+	}
+}
+
+impl System {
+	/// Checks that the benchmarks of every example of the system are built in.
+	pub fn check_built(&self) -> Result<(), String> {
+		if self.examples.iter().all(|name| example_run(name).is_some()) {
+			return Ok(());
+		}
+		match self.feature {
+			Some(feature) => {
+				Err(format!("{} needs building with `--features {feature}`", self.name))
+			}
+			None => Err(format!("{} is not built in", self.name)),
+		}
+	}
+}
+
+/// Runs the benchmarks of the example named `name` in this process, as if the example had been
+/// started with `args`, and fails if they are not built in or panic.
+pub fn run_example(name: &str, args: &[String]) -> Result<(), String> {
+	let run = example_run(name).ok_or_else(|| format!("{name} is not built in"))?;
+	clear_results();
+	clear_phases();
+	clear_samples();
+	// The panic message is already printed, and the failed run leaves nothing to clean up.
+	with_command_line(args, || panic::catch_unwind(run)).map_err(|_| format!("{name} failed"))
+}
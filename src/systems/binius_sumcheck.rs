@@ -0,0 +1,273 @@
+//! Run and measure timing of the binius sumcheck prover and verifier over binary tower fields.
+
+use std::{any::type_name, iter::repeat_with, mem::size_of};
+
+use ark_std::{end_timer, start_timer};
+use binius_core::{
+	fiat_shamir::HasherChallenger,
+	polynomial::MultilinearComposite,
+	protocols::{
+		sumcheck::{
+			batch_prove, batch_verify, immediate_switchover_heuristic,
+			prove::RegularSumcheckProver, CompositeSumClaim, SumcheckClaim,
+		},
+		test_utils::TestProductComposition,
+	},
+	transcript::{TranscriptReader, TranscriptWriter},
+};
+use binius_field::{
+	arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField, BinaryField128b,
+	BinaryField128bPolyval, BinaryField8b, ExtensionField, Field, PackedExtension, PackedField,
+	PackedFieldIndexable, RepackedExtension, TowerField,
+};
+use binius_hal::make_portable_backend;
+use binius_math::{
+	CompositionPolyOS, IsomorphicEvaluationDomainFactory, MLEDirectAdapter, MultilinearExtension,
+	MultilinearPoly,
+};
+use groestl::Groestl256;
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::{
+	allocator::report_allocator,
+	cpu_state::check_cpu_environment,
+	fuzz::fuzz_seeds,
+	heap_profile::init_heap_profile,
+	numa::configure_numa,
+	output::init_output,
+	params::{field_selected, requested_degrees, requested_n_vars},
+	phase::Phase,
+	proof_size::{
+		components::{FINAL_EVALUATIONS, SUMCHECK_MESSAGES},
+		record_proof_size,
+	},
+	results::init_results,
+	scaling::{begin_series, report_scaling},
+	significance::report_comparisons,
+	simd::report_simd,
+	stability::run_until_stable,
+	throughput::record_witness,
+	timeline::init_timeline,
+	trace::init_tracing,
+};
+
+fn generate_random_multilinears<P>(
+	mut rng: impl Rng,
+	n_vars: usize,
+	n_multilinears: usize,
+) -> Vec<MLEDirectAdapter<P>>
+where
+	P: PackedField + RepackedExtension<P>,
+{
+	repeat_with(|| {
+		let values = repeat_with(|| P::random(&mut rng))
+			.take(1 << (n_vars - P::LOG_WIDTH))
+			.collect::<Vec<_>>();
+		MultilinearExtension::from_values(values).unwrap().into()
+	})
+	.take(n_multilinears)
+	.collect()
+}
+
+fn compute_composite_sum<F, P, M, Composition>(multilinears: &[M], composition: Composition) -> F
+where
+	F: Field,
+	P: PackedField<Scalar = F>,
+	M: MultilinearPoly<P> + Send + Sync,
+	Composition: CompositionPolyOS<P>,
+{
+	let n_vars = multilinears
+		.first()
+		.map(|multilinear| multilinear.n_vars())
+		.unwrap_or_default();
+	for multilinear in multilinears.iter() {
+		assert_eq!(multilinear.n_vars(), n_vars);
+	}
+
+	let multilinears = multilinears.iter().collect::<Vec<_>>();
+	let witness = MultilinearComposite::new(n_vars, composition, multilinears.clone()).unwrap();
+	(0..(1 << n_vars))
+		.into_par_iter()
+		.map(|j| witness.evaluate_on_hypercube(j).unwrap())
+		.sum()
+}
+
+fn profile_sumcheck<F, FDomain, FChallenge, P>(
+	id: &str,
+	n_vars: usize,
+	degree: usize,
+	mut rng: impl Rng,
+) where
+	F: TowerField + ExtensionField<FDomain>,
+	FDomain: BinaryField,
+	FChallenge: Field
+		+ PackedField<Scalar = FChallenge>
+		+ From<F>
+		+ Into<F>
+		+ ExtensionField<BinaryField8b>
+		+ PackedExtension<BinaryField8b, PackedSubfield: PackedFieldIndexable>,
+	P: PackedField<Scalar = F> + PackedExtension<FDomain> + RepackedExtension<P>,
+{
+	println!("{id}, n_vars={n_vars}, degree={degree}");
+	begin_series("binius", format!("{id}, degree={degree}"));
+	record_witness(type_name::<F>(), rayon::current_num_threads());
+
+	let n_multilinears = degree;
+	let composition = TestProductComposition::new(n_multilinears);
+	let n_elements = 1 << n_vars;
+
+	let phase = Phase::start("generating polys");
+	let timer = start_timer!(|| "generating polys");
+	let scope = tracing::debug_span!("generating polys").entered();
+	let multilins = generate_random_multilinears::<P>(&mut rng, n_vars, n_multilinears);
+	drop(scope);
+	end_timer!(timer);
+	phase.finish(n_elements);
+
+	let phase = Phase::start("evaluating initial claim");
+	let timer = start_timer!(|| "evaluating initial claim");
+	let scope = tracing::debug_span!("evaluating initial claim").entered();
+
+	let sum = compute_composite_sum(&multilins, &composition);
+	drop(scope);
+	end_timer!(timer);
+	phase.finish(n_elements);
+
+	let claim = SumcheckClaim::new(
+		n_vars,
+		n_multilinears,
+		vec![CompositeSumClaim {
+			composition: &composition,
+			sum,
+		}],
+	)
+	.unwrap();
+
+	let backend = make_portable_backend();
+	let domain_factory = IsomorphicEvaluationDomainFactory::<FDomain>::default();
+	let prover = RegularSumcheckProver::<FDomain, _, _, _, _>::new(
+		multilins.iter().collect(),
+		[CompositeSumClaim {
+			composition: &composition,
+			sum,
+		}],
+		domain_factory,
+		immediate_switchover_heuristic,
+		&backend,
+	)
+	.unwrap();
+
+	let mut prover_transcript = TranscriptWriter::<HasherChallenger<Groestl256>>::default();
+
+	let phase = Phase::start("prove");
+	let timer = start_timer!(|| "prove");
+	let scope = tracing::debug_span!("prove").entered();
+	let prover_reduced_claims = batch_prove(vec![prover], &mut prover_transcript).unwrap();
+	drop(scope);
+	end_timer!(timer);
+	phase.finish(n_elements);
+
+	let transcript_bytes = prover_transcript.finalize();
+
+	// The transcript holds the round polynomials followed by the final multilinear evaluations.
+	let proof_size = transcript_bytes.len();
+	let final_evals_size = prover_reduced_claims
+		.multilinear_evals
+		.iter()
+		.map(|evals| evals.len() * size_of::<F>())
+		.sum::<usize>();
+	record_proof_size(
+		proof_size as u64,
+		&[
+			(SUMCHECK_MESSAGES, (proof_size - final_evals_size) as u64),
+			(FINAL_EVALUATIONS, final_evals_size as u64),
+		],
+	);
+
+	let mut verifier_transcript =
+		TranscriptReader::<HasherChallenger<Groestl256>>::new(transcript_bytes);
+
+	let phase = Phase::start("verify");
+	let timer = start_timer!(|| "verify");
+	let scope = tracing::debug_span!("verify").entered();
+	let verifier_reduced_claims = batch_verify(&[claim], &mut verifier_transcript).unwrap();
+	drop(scope);
+	end_timer!(timer);
+	phase.finish(n_elements);
+
+	// Check that challengers are in the same state
+	assert_eq!(prover_reduced_claims, verifier_reduced_claims);
+	verifier_transcript.finalize().unwrap();
+}
+
+fn run_all_bases<R: Rng>(n_vars: usize, degree: usize, mut make_rng: impl FnMut() -> R) {
+	// profile_sumcheck::<
+	// 	BinaryField128bPolyval,
+	// 	BinaryField128bPolyval,
+	// 	BinaryField128b,
+	// 	PackedBinaryPolyval1x128b,
+	// >("sumcheck 128b (POLYVAL basis)", n_vars, degree, make_rng());
+	// profile_sumcheck::<
+	// 	BinaryField128b,
+	// 	BinaryField8b,
+	// 	BinaryField128b,
+	// 	PackedBinaryField1x128b,
+	// >("sumcheck 128b (tower basis)", n_vars, degree, make_rng());
+	if field_selected("BinaryField128bPolyval") {
+		profile_sumcheck::<
+			BinaryField128bPolyval,
+			BinaryField128bPolyval,
+			BinaryField128b,
+			PackedType<OptimalUnderlier, BinaryField128bPolyval>,
+		>("sumcheck 128b (POLYVAL basis)", n_vars, degree, make_rng());
+	}
+	if field_selected("BinaryField128b") {
+		profile_sumcheck::<
+			BinaryField128b,
+			BinaryField8b,
+			BinaryField128b,
+			PackedType<OptimalUnderlier, BinaryField128b>,
+		>("sumcheck 128b (tower basis)", n_vars, degree, make_rng());
+	}
+	// profile_sumcheck::<
+	// 	AESTowerField128b,
+	// 	AESTowerField8b,
+	// 	BinaryField128b,
+	// 	ByteSlicedAES32x128b,
+	// >("sumcheck 128b (Byte sliced)", n_vars, degree, make_rng());
+}
+
+/// Runs the benchmarks of the example, see [`crate::systems`].
+pub fn run() {
+	init_output("binius_sumcheck");
+	let _trace = init_tracing("binius_sumcheck");
+	let _timeline = init_timeline("binius_sumcheck");
+	let _results = init_results("binius_sumcheck");
+	let heap_profile = init_heap_profile("binius_sumcheck");
+	check_cpu_environment();
+	configure_numa();
+	report_simd();
+	report_allocator();
+
+	if let Some(seeds) = fuzz_seeds() {
+		for seed in seeds {
+			println!("seed={seed}");
+			for degree in [2, 3, 4] {
+				run_all_bases(12, degree, || StdRng::seed_from_u64(seed));
+			}
+		}
+		return;
+	}
+
+	for n_vars in requested_n_vars(&[20, 24, 28]) {
+		for degree in requested_degrees(&[2, 3, 4]) {
+			run_until_stable(|| run_all_bases(n_vars, degree, thread_rng));
+		}
+		if heap_profile.is_some() {
+			break;
+		}
+	}
+	report_scaling();
+	report_comparisons();
+}
@@ -0,0 +1,178 @@
+// Copyright (c) Microsoft Corporation.
+// Copyright 2023 Ulvetanna Inc.
+
+//! Run and measure timing of the jolt sumcheck prover and verifier over BN254 Fr.
+
+use std::iter::repeat_with;
+
+use ark_bn254::Fr;
+use ark_serialize::CanonicalSerialize;
+use ark_std::{cfg_into_iter, end_timer, start_timer, One, UniformRand, Zero};
+use jolt_core::{
+	poly::dense_mlpoly::DensePolynomial, subprotocols::sumcheck::SumcheckInstanceProof,
+	utils::transcript::ProofTranscript,
+};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::{
+	allocator::report_allocator,
+	cpu_state::check_cpu_environment,
+	fuzz::fuzz_seeds,
+	heap_profile::init_heap_profile,
+	numa::configure_numa,
+	output::init_output,
+	params::{requested_degrees, requested_n_vars},
+	phase::Phase,
+	proof_size::{
+		components::{FINAL_EVALUATIONS, SUMCHECK_MESSAGES},
+		record_proof_size,
+	},
+	results::init_results,
+	scaling::{begin_series, report_scaling},
+	significance::report_comparisons,
+	simd::report_simd,
+	stability::run_until_stable,
+	throughput::record_witness,
+	timeline::init_timeline,
+	trace::init_tracing,
+};
+
+fn profile_sumcheck<const ALPHA: usize>(num_vars: usize, mut rng: impl Rng) {
+	println!("n_vars={num_vars}, degree={ALPHA}");
+	begin_series("jolt", format!("bn254 sumcheck, degree={ALPHA}"));
+	record_witness("BN254 Fr", rayon::current_num_threads());
+
+	let num_evals = 1 << num_vars;
+
+	let phase = Phase::start("generating polys");
+	let gen_timer = start_timer!(|| "generating polys");
+	let gen_scope = tracing::debug_span!("generating polys").entered();
+	let polys = repeat_with(|| {
+		// Seed one RNG per chunk so that generation stays parallel and reproducible.
+		let seed = rng.gen::<u64>();
+		let mut values = vec![Fr::zero(); num_evals];
+		values
+			.par_chunks_mut(1 << 12)
+			.enumerate()
+			.for_each(|(i, chunk)| {
+				let mut rng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+				for value in chunk {
+					*value = Fr::rand(&mut rng);
+				}
+			});
+		DensePolynomial::new(values)
+	})
+	.take(ALPHA)
+	.collect::<Vec<_>>();
+	drop(gen_scope);
+	end_timer!(gen_timer);
+	phase.finish(num_evals as u64);
+
+	let phase = Phase::start("evaluating initial claim");
+	let claim_timer = start_timer!(|| "evaluating initial claim");
+	let claim_scope = tracing::debug_span!("evaluating initial claim").entered();
+	let claim = cfg_into_iter!(0..num_evals)
+		.map(|i| polys.iter().map(|poly| poly[i]).product::<Fr>())
+		.sum();
+	drop(claim_scope);
+	end_timer!(claim_timer);
+	phase.finish(num_evals as u64);
+
+	let comb_func_prod =
+		|polys: &[Fr]| -> Fr { polys.iter().fold(Fr::one(), |acc, poly| acc * *poly) };
+
+	let mut transcript = ProofTranscript::new(b"test");
+	let mut prove_polys = polys.clone();
+
+	let phase = Phase::start("prove sumcheck");
+	let prove_timer = start_timer!(|| "prove sumcheck");
+	let prove_scope = tracing::debug_span!("prove sumcheck").entered();
+	let (proof, prove_randomness, final_poly_evals) = SumcheckInstanceProof::<Fr>::prove_arbitrary(
+		&claim,
+		num_vars,
+		&mut prove_polys,
+		comb_func_prod,
+		ALPHA,
+		&mut transcript,
+	);
+	drop(prove_scope);
+	end_timer!(prove_timer);
+	phase.finish(num_evals as u64);
+
+	// Jolt returns the final evaluations separately from the round polynomials; a verifier needs
+	// both, so both count towards the argument size.
+	let round_polys_size = proof.compressed_size();
+	let final_evals_size = final_poly_evals.compressed_size();
+	record_proof_size(
+		(round_polys_size + final_evals_size) as u64,
+		&[
+			(SUMCHECK_MESSAGES, round_polys_size as u64),
+			(FINAL_EVALUATIONS, final_evals_size as u64),
+		],
+	);
+
+	let mut transcript = ProofTranscript::new(b"test");
+
+	let phase = Phase::start("verify sumcheck");
+	let verify_timer = start_timer!(|| "verify sumcheck");
+	let verify_scope = tracing::debug_span!("verify sumcheck").entered();
+	let verify_result = proof.verify(claim, num_vars, ALPHA, &mut transcript);
+	drop(verify_scope);
+	end_timer!(verify_timer);
+	phase.finish(num_evals as u64);
+
+	assert!(verify_result.is_ok());
+
+	let (verify_evaluation, verify_randomness) = verify_result.unwrap();
+	assert_eq!(prove_randomness, verify_randomness);
+
+	let oracle_query = polys
+		.iter()
+		.map(|poly| poly.evaluate(prove_randomness.as_slice()))
+		.product();
+	assert_eq!(verify_evaluation, oracle_query);
+
+	println!();
+}
+
+/// Runs the benchmarks of the example, see [`crate::systems`].
+pub fn run() {
+	init_output("bn254_fr_sumcheck");
+	let _trace = init_tracing("bn254_fr_sumcheck");
+	let _timeline = init_timeline("bn254_fr_sumcheck");
+	let _results = init_results("bn254_fr_sumcheck");
+	let heap_profile = init_heap_profile("bn254_fr_sumcheck");
+	check_cpu_environment();
+	configure_numa();
+	report_simd();
+	report_allocator();
+
+	if let Some(seeds) = fuzz_seeds() {
+		for seed in seeds {
+			println!("seed={seed}");
+			profile_sumcheck::<2>(12, StdRng::seed_from_u64(seed));
+			profile_sumcheck::<3>(12, StdRng::seed_from_u64(seed));
+			profile_sumcheck::<4>(12, StdRng::seed_from_u64(seed));
+		}
+		return;
+	}
+
+	let degrees = requested_degrees(&[2, 3, 4]);
+	for n_vars in requested_n_vars(&[20, 24, 28]) {
+		for &degree in &degrees {
+			// The degree is a const parameter of the prover, so only these are available.
+			match degree {
+				2 => run_until_stable(|| profile_sumcheck::<2>(n_vars, thread_rng())),
+				3 => run_until_stable(|| profile_sumcheck::<3>(n_vars, thread_rng())),
+				4 => run_until_stable(|| profile_sumcheck::<4>(n_vars, thread_rng())),
+				_ => panic!("bn254_fr_sumcheck supports degrees 2, 3, and 4, got {degree}"),
+			}
+		}
+		if heap_profile.is_some() {
+			break;
+		}
+	}
+	report_scaling();
+	report_comparisons();
+}
@@ -0,0 +1,348 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Run and measure timing of the binius FRI polynomial commitment scheme over binary tower
+//! fields.
+
+use std::iter::repeat_with;
+
+use ark_std::{end_timer, start_timer};
+use binius_core::{
+	fiat_shamir::HasherChallenger,
+	merkle_tree::{BinaryMerkleTreeProver, MerkleTreeProver},
+	oracle::MultilinearOracleSet,
+	piop,
+	protocols::{evalcheck::EvalcheckMultilinearClaim, fri::CommitOutput},
+	ring_switch,
+	ring_switch::{EvalClaimSystem, ReducedClaim, ReducedWitness},
+	tower::{AESTowerFamily, PackedTop, TowerFamily, TowerUnderlier},
+	transcript::{
+		AdviceReader, AdviceWriter, CanRead, CanWrite, Proof, TranscriptReader, TranscriptWriter,
+	},
+};
+use binius_field::{
+	arch::OptimalUnderlier,
+	as_packed_field::{PackScalar, PackedType},
+	underlier::UnderlierType,
+	AESTowerField32b, AESTowerField8b, BinaryField1b, ExtensionField, Field, PackedExtension,
+	PackedField, PackedFieldIndexable, TowerField,
+};
+use binius_hal::ComputationBackendExt;
+use binius_hash::{Groestl256, GroestlDigest, HashDigest, HasherDigest};
+use binius_math::{DefaultEvaluationDomainFactory, MultilinearExtension};
+use binius_utils::rayon::adjust_thread_pool;
+use p3_symmetric::{CompressionFunction, PseudoCompressionFunction};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+
+use crate::{
+	allocator::report_allocator,
+	cost_model::report_verifier_cost,
+	cpu_state::check_cpu_environment,
+	fuzz::fuzz_seeds,
+	hash_count::{Counting, HashCounts},
+	heap::{retained_heap, HeapBytes},
+	heap_profile::init_heap_profile,
+	numa::configure_numa,
+	output::init_output,
+	params::{field_selected, requested_log_inv_rate, requested_n_vars},
+	phase::Phase,
+	proof_size::record_proof_size,
+	results::{init_results, record_metric},
+	scaling::{begin_series, report_scaling},
+	security::{report_security_cost, FriSecurity, SecurityTarget},
+	significance::report_comparisons,
+	simd::report_simd,
+	stability::run_until_stable,
+	throughput::record_witness,
+	timeline::init_timeline,
+	trace::init_tracing,
+};
+
+pub type GroestlDigestAES = GroestlDigest<AESTowerField8b>;
+pub type GroestlHasher<P> = Groestl256<P, AESTowerField8b>;
+
+#[derive(Debug, Default, Clone)]
+pub struct GroestlDigestCompression;
+
+impl PseudoCompressionFunction<GroestlDigestAES, 2> for GroestlDigestCompression {
+	fn compress(&self, input: [GroestlDigestAES; 2]) -> GroestlDigestAES {
+		HasherDigest::<GroestlDigestAES, GroestlHasher<GroestlDigestAES>>::hash(&input[..])
+	}
+}
+
+impl CompressionFunction<GroestlDigestAES, 2> for GroestlDigestCompression {}
+
+/// The cryptographic extension field that the constraint system protocol is defined over.
+pub type FExt<Tower> = <Tower as TowerFamily>::B128;
+
+/// The evaluation domain used in sumcheck protocols.
+///
+/// This is fixed to be 8-bits, which is large enough to handle all reasonable sumcheck
+/// constraint degrees, even with a moderate number of skipped rounds using the univariate skip
+/// technique.
+pub type FDomain<Tower> = <Tower as TowerFamily>::B8;
+
+/// The Reed–Solomon alphabet used for FRI encoding.
+///
+/// This is fixed to be 32-bits, which is large enough to handle trace sizes up to 64 GiB
+/// of committed data.
+pub type FEncode<Tower> = <Tower as TowerFamily>::B32;
+
+fn test_commit_prove_verify_success<U, Tower, F>(
+	n_vars: usize,
+	log_inv_rate: usize,
+	mut rng: impl Rng,
+) where
+	U: UnderlierType + TowerUnderlier<Tower> + PackScalar<F> + PackScalar<AESTowerField8b>,
+	Tower: TowerFamily,
+	F: TowerField,
+	FExt<Tower>: PackedTop<Tower>
+		+ ExtensionField<F>
+		+ ExtensionField<AESTowerField8b>
+		+ PackedExtension<F>
+		+ PackedExtension<AESTowerField8b, PackedSubfield: PackedFieldIndexable>,
+	PackedType<U, FExt<Tower>>: PackedFieldIndexable,
+{
+	let backend = binius_hal::make_portable_backend();
+	let n_elements = 1 << n_vars;
+
+	let phase = Phase::start("generate");
+	let gen_timer = start_timer!(|| "generate");
+	let multilin = tracing::debug_span!("generate").in_scope(|| {
+		MultilinearExtension::from_values(
+			repeat_with(|| <PackedType<U, F>>::random(&mut rng))
+				.take(1 << (n_vars - <PackedType<U, F>>::LOG_WIDTH))
+				.collect(),
+		)
+		.unwrap()
+	});
+	assert_eq!(multilin.n_vars(), n_vars);
+	end_timer!(gen_timer);
+	phase.finish(n_elements);
+
+	let eval_point = repeat_with(|| <FExt<Tower> as Field>::random(&mut rng))
+		.take(n_vars)
+		.collect::<Vec<_>>();
+
+	let eval_query = backend
+		.multilinear_query::<PackedType<U, FExt<Tower>>>(&eval_point)
+		.unwrap();
+	let eval = multilin.evaluate(&eval_query).unwrap();
+
+	let mut oracles = MultilinearOracleSet::new();
+	let oracle_id = oracles.add_committed(n_vars, F::TOWER_LEVEL);
+
+	let merkle_prover = BinaryMerkleTreeProver::<_, Counting<GroestlHasher<_>>, _>::new(Counting(
+		GroestlDigestCompression::default(),
+	));
+	let merkle_scheme = merkle_prover.scheme();
+
+	let (commit_meta, oracle_to_commit_index) = piop::make_oracle_commit_meta(&oracles).unwrap();
+
+	// Constructing the FRI parameters sets up the Reed–Solomon code and its additive NTT.
+	let phase = Phase::start("precompute NTT");
+	let precompute_timer = start_timer!(|| "precompute NTT");
+	// binius FRI has no grinding, so all of the target is reached with queries.
+	let target = SecurityTarget::requested();
+	let (fri_params, twiddles_size) = retained_heap(|| {
+		piop::make_commit_params_with_optimal_arity::<_, FEncode<Tower>, _>(
+			&commit_meta,
+			merkle_scheme,
+			target.binius_security_bits(log_inv_rate),
+			log_inv_rate,
+		)
+		.unwrap()
+	});
+	end_timer!(precompute_timer);
+	phase.finish(n_elements);
+	// Besides a few sizes, the parameters hold the twiddles of the NTT, whose heap is only measured
+	// with the `alloc_count` feature.
+	println!("Twiddle table size = {twiddles_size}");
+	if let HeapBytes(Some(bytes)) = twiddles_size {
+		record_metric("twiddle_bytes", bytes as f64, "bytes");
+	}
+	let security = FriSecurity {
+		log_inv_rate,
+		n_queries: fri_params.n_test_queries(),
+		pow_bits: 0,
+		soundness: target.soundness,
+	};
+
+	let committed_multilins = [multilin.specialize_arc_dyn::<PackedType<U, FExt<Tower>>>()];
+
+	let hash_counts = HashCounts::current();
+	let phase = Phase::start("commit");
+	let commit_timer = start_timer!(|| format!("commit, n_vars={}", n_vars));
+	let commit_scope = tracing::debug_span!("commit").entered();
+	let CommitOutput {
+		commitment,
+		committed,
+		codeword,
+	} = piop::commit(&fri_params, &merkle_prover, &committed_multilins).unwrap();
+	drop(commit_scope);
+	end_timer!(commit_timer);
+	println!("Hash calls: {}", HashCounts::current().since(&hash_counts));
+	let commit_seconds = phase.finish(n_elements);
+
+	let mut proof = Proof {
+		transcript: TranscriptWriter::<HasherChallenger<groestl::Groestl256>>::default(),
+		advice: AdviceWriter::default(),
+	};
+	proof.transcript.write_packed(commitment.clone());
+
+	let eval_claims = [EvalcheckMultilinearClaim {
+		poly: oracles.oracle(oracle_id),
+		eval_point: eval_point.into(),
+		eval,
+	}];
+	let system = EvalClaimSystem::new(&commit_meta, oracle_to_commit_index, &eval_claims).unwrap();
+	let domain_factory = DefaultEvaluationDomainFactory::<FDomain<Tower>>::default();
+
+	let hash_counts = HashCounts::current();
+	let phase = Phase::start("prove");
+	let prove_timer = start_timer!(|| "prove");
+	let prove_scope = tracing::debug_span!("prove").entered();
+	let ReducedWitness {
+		transparents: transparent_multilins,
+		sumcheck_claims,
+	} = ring_switch::prove::<_, _, _, Tower, _, _, _>(
+		&system,
+		&committed_multilins,
+		&mut proof,
+		&backend,
+	)
+	.unwrap();
+
+	piop::prove(
+		&fri_params,
+		&merkle_prover,
+		domain_factory,
+		&commit_meta,
+		committed,
+		&codeword,
+		&committed_multilins,
+		&transparent_multilins,
+		&sumcheck_claims,
+		&mut proof,
+		&backend,
+	)
+	.unwrap();
+	drop(prove_scope);
+	end_timer!(prove_timer);
+	println!("Hash calls: {}", HashCounts::current().since(&hash_counts));
+	let prove_seconds = phase.finish(n_elements);
+	report_security_cost(&security, commit_seconds + prove_seconds);
+
+	// The proof is everything the prover wrote to the transcript and the advice.
+	let transcript = proof.transcript.finalize();
+	let advice = proof.advice.finalize();
+	record_proof_size((transcript.len() + advice.len()) as u64, &[]);
+	let mut proof = Proof {
+		transcript: TranscriptReader::<HasherChallenger<groestl::Groestl256>>::new(transcript),
+		advice: AdviceReader::new(advice),
+	};
+	let commitment = proof.transcript.read_packed().unwrap();
+
+	let hash_counts = HashCounts::current();
+	let phase = Phase::start("verify");
+	let verify_timer = start_timer!(|| "verify");
+	let verify_scope = tracing::debug_span!("verify").entered();
+
+	let ReducedClaim {
+		transparents,
+		sumcheck_claims,
+	} = ring_switch::verify::<_, Tower, _, _>(&system, &mut proof).unwrap();
+
+	piop::verify(
+		&commit_meta,
+		merkle_scheme,
+		&fri_params,
+		&commitment,
+		&transparents,
+		&sumcheck_claims,
+		&mut proof,
+	)
+	.unwrap();
+	drop(verify_scope);
+	end_timer!(verify_timer);
+	let verifier_hashes = HashCounts::current().since(&hash_counts);
+	println!("Hash calls: {verifier_hashes}");
+	phase.finish(n_elements);
+	report_verifier_cost(&verifier_hashes.into());
+
+	// The verifier must consume exactly the transcript the prover wrote.
+	proof.transcript.finalize().unwrap();
+
+	println!();
+}
+
+fn run_all_fields<R: Rng>(n_vars: usize, log_inv_rate: usize, mut make_rng: impl FnMut() -> R) {
+	let target = SecurityTarget::requested();
+	if field_selected("BinaryField1b") {
+		println!("field=BinaryField1b n_vars={n_vars}");
+		begin_series("binius", target.series_config("field=BinaryField1b"));
+		record_witness("BinaryField1b", rayon::current_num_threads());
+		test_commit_prove_verify_success::<OptimalUnderlier, AESTowerFamily, BinaryField1b>(
+			n_vars,
+			log_inv_rate,
+			make_rng(),
+		);
+	}
+
+	if field_selected("AESTowerField8b") {
+		println!("field=AESTowerField8b n_vars={n_vars}");
+		begin_series("binius", target.series_config("field=AESTowerField8b"));
+		record_witness("AESTowerField8b", rayon::current_num_threads());
+		test_commit_prove_verify_success::<OptimalUnderlier, AESTowerFamily, AESTowerField8b>(
+			n_vars,
+			log_inv_rate,
+			make_rng(),
+		);
+	}
+
+	if field_selected("AESTowerField32b") {
+		println!("field=AESTowerField32b n_vars={n_vars}");
+		begin_series("binius", target.series_config("field=AESTowerField32b"));
+		record_witness("AESTowerField32b", rayon::current_num_threads());
+		test_commit_prove_verify_success::<OptimalUnderlier, AESTowerFamily, AESTowerField32b>(
+			n_vars,
+			log_inv_rate,
+			make_rng(),
+		);
+	}
+}
+
+/// Runs the benchmarks of the example, see [`crate::systems`].
+pub fn run() {
+	init_output("fri_binius_pcs");
+	let _trace = init_tracing("fri_binius_pcs");
+	let _timeline = init_timeline("fri_binius_pcs");
+	let _results = init_results("fri_binius_pcs");
+	let heap_profile = init_heap_profile("fri_binius_pcs");
+	check_cpu_environment();
+	configure_numa();
+	report_simd();
+	report_allocator();
+
+	adjust_thread_pool()
+		.as_ref()
+		.expect("failed to init thread pool");
+
+	let log_inv_rate = requested_log_inv_rate(2);
+
+	if let Some(seeds) = fuzz_seeds() {
+		for seed in seeds {
+			println!("seed={seed}");
+			run_all_fields(12, log_inv_rate, || StdRng::seed_from_u64(seed));
+		}
+		return;
+	}
+
+	for n_vars in requested_n_vars(&[20, 24, 28]) {
+		run_until_stable(|| run_all_fields(n_vars, log_inv_rate, thread_rng));
+		if heap_profile.is_some() {
+			break;
+		}
+	}
+	report_scaling();
+	report_comparisons();
+}
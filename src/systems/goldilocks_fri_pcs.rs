@@ -0,0 +1,460 @@
+// Copyright 2023 Ulvetanna Inc.
+
+//! Run and measure timing of plonky2 FRI polynomial commitment scheme on batches of polynomials.
+
+use std::{any::type_name, hint::black_box, iter::repeat_with, mem::size_of};
+
+use ark_std::{end_timer, start_timer};
+use bytesize::ByteSize;
+use plonky2::{
+	field::{
+		fft::{fft, fft_root_table},
+		goldilocks_field::GoldilocksField,
+		polynomial::{PolynomialCoeffs, PolynomialValues},
+		types::Field,
+	},
+	fri::{
+		oracle::PolynomialBatch,
+		proof::FriProof,
+		structure::{
+			FriBatchInfo, FriInstanceInfo, FriOpeningBatch, FriOpenings, FriOracleInfo,
+			FriPolynomialInfo,
+		},
+		verifier::verify_fri_proof,
+	},
+	iop::challenger::Challenger,
+	plonk::config::{GenericConfig, Hasher, KeccakGoldilocksConfig, PoseidonGoldilocksConfig},
+	util::timing::TimingTree,
+};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use serde::Serialize;
+use starky::config::StarkConfig;
+
+use crate::{
+	advisor::{
+		chain, ntt_butterflies, report_prediction, seconds_per_op, PrimitiveCosts, Workload,
+	},
+	allocator::report_allocator,
+	cost_model::report_verifier_cost,
+	cpu_state::check_cpu_environment,
+	fuzz::fuzz_seeds,
+	hash_count::{CountingConfig, HashCounts},
+	heap_profile::init_heap_profile,
+	numa::configure_numa,
+	output::init_output,
+	params::{hash_selected, requested_n_bits, requested_n_vars},
+	phase::Phase,
+	proof_size::{
+		components::{
+			FINAL_POLYNOMIAL, FRI_COMMITMENTS, MERKLE_PATHS, PROOF_OF_WORK, QUERY_VALUES,
+		},
+		record_proof_size,
+	},
+	results::{init_results, record_metric},
+	scaling::{begin_series, report_scaling},
+	security::{report_security_cost, SecurityTarget},
+	significance::report_comparisons,
+	simd::report_simd,
+	stability::run_until_stable,
+	throughput::record_witness,
+	timeline::init_timeline,
+	trace::init_tracing,
+	verifier_profile::verify_cold,
+};
+
+/// Log size of the NTT the primitive costs are calibrated with.
+const CALIBRATION_LOG_SIZE: usize = 14;
+
+/// Hash calls the primitive costs are calibrated with.
+const CALIBRATION_HASHES: u64 = 1 << 10;
+
+/// Field multiplications the primitive costs are calibrated with.
+const CALIBRATION_MULS: u64 = 1 << 16;
+
+/// Bytes of the bincode serialization of a proof or a part of it.
+fn serialized_size(value: &impl Serialize) -> u64 {
+	bincode::serialized_size(value).unwrap()
+}
+
+/// Measures the primitives of a plonky2 configuration hashing leaves of `batch_size` elements.
+fn calibrate<C: GenericConfig<2, F = GoldilocksField>>(
+	batch_size: usize,
+	rng: &mut impl Rng,
+) -> PrimitiveCosts {
+	let mut random = || GoldilocksField::from_noncanonical_u64(rng.gen());
+	let coeffs = PolynomialCoeffs::new(
+		repeat_with(&mut random)
+			.take(1 << CALIBRATION_LOG_SIZE)
+			.collect(),
+	);
+	let leaf = repeat_with(&mut random)
+		.take(batch_size)
+		.collect::<Vec<_>>();
+	let factor = random();
+	let digest = C::Hasher::hash_no_pad(&leaf);
+	PrimitiveCosts {
+		butterfly: seconds_per_op(ntt_butterflies(CALIBRATION_LOG_SIZE, 1), || {
+			black_box(fft(coeffs.clone()));
+		}),
+		digest: seconds_per_op(CALIBRATION_HASHES, || {
+			for _ in 0..CALIBRATION_HASHES {
+				black_box(C::Hasher::hash_no_pad(black_box(&leaf)));
+			}
+		}),
+		compression: seconds_per_op(CALIBRATION_HASHES, move || {
+			black_box(chain(CALIBRATION_HASHES, digest, |h| C::Hasher::two_to_one(h, h)));
+		}),
+		field_mul: seconds_per_op(CALIBRATION_MULS, move || {
+			black_box(chain(CALIBRATION_MULS, factor, |x| x * factor));
+		}),
+		// plonky2 parallelizes over its global rayon pool.
+		threads: rayon::current_num_threads(),
+	}
+}
+
+/// Profiles the configuration `C` with its Merkle hashes counted, labeled by the name of `C` rather
+/// than that of the counting wrapper, so that series continue those recorded before counting.
+fn profile_commit_prove_verify<C: GenericConfig<2, F = GoldilocksField>>(
+	degree_bits: usize,
+	n_bits: usize,
+	batch_size: usize,
+	print_proof_size: bool,
+	rng: impl Rng,
+) {
+	profile_counting::<CountingConfig<C>>(
+		type_name::<C>(),
+		degree_bits,
+		n_bits,
+		batch_size,
+		print_proof_size,
+		rng,
+	)
+}
+
+fn profile_counting<C: GenericConfig<2, F = GoldilocksField>>(
+	config_name: &str,
+	degree_bits: usize,
+	n_bits: usize,
+	batch_size: usize,
+	print_proof_size: bool,
+	mut rng: impl Rng,
+) {
+	// Keep the rate of the standard configuration, but query and grind for the common target.
+	let mut fri_config = StarkConfig::standard_fast_config().fri_config;
+	fri_config.cap_height = 0;
+	let target = SecurityTarget::requested();
+	let security = target.fri(fri_config.rate_bits);
+	fri_config.num_query_rounds = security.n_queries;
+	fri_config.proof_of_work_bits = security.pow_bits as u32;
+
+	let fri_params = fri_config.fri_params(degree_bits, false);
+
+	let n_vals = 1 << degree_bits;
+	let n_elements = (n_vals * batch_size) as u64;
+	let costs = calibrate::<C>(batch_size, &mut rng);
+
+	println!(
+		"config={}, degree_bits={}, n_bits={}, batch_size={}, rate_bits={}",
+		config_name, degree_bits, n_bits, batch_size, fri_config.rate_bits
+	);
+	begin_series(
+		"plonky2",
+		target.series_config(&format!(
+			"config={config_name}, n_bits={n_bits}, batch_size={batch_size}"
+		)),
+	);
+	record_witness("Goldilocks", costs.threads);
+
+	let phase = Phase::start("precompute root table");
+	let precompute_timer = start_timer!(|| "precompute root table");
+	let precompute_scope = tracing::debug_span!("precompute root table").entered();
+	let root_table = fft_root_table(n_vals << fri_config.rate_bits);
+	drop(precompute_scope);
+	end_timer!(precompute_timer);
+	phase.finish(n_elements);
+	let root_table_size = root_table
+		.iter()
+		.map(|roots| roots.len() * size_of::<GoldilocksField>())
+		.sum::<usize>();
+	println!("Root table size = {}", ByteSize(root_table_size as u64));
+	record_metric("twiddle_bytes", root_table_size as f64, "bytes");
+
+	let phase = Phase::start("gen data");
+	let gen_timer = start_timer!(|| "gen data");
+	let gen_scope = tracing::debug_span!("gen data").entered();
+	let poly_values = repeat_with(|| {
+		PolynomialValues::new(
+			repeat_with(|| {
+				if n_bits == 64 {
+					GoldilocksField::from_noncanonical_u64(rng.gen())
+				} else {
+					GoldilocksField::from_noncanonical_u64(rng.gen_range(0..(1u64 << n_bits)))
+				}
+			})
+			.take(n_vals)
+			.collect(),
+		)
+	})
+	.take(batch_size)
+	.collect::<Vec<_>>();
+	drop(gen_scope);
+	end_timer!(gen_timer);
+	phase.finish(n_elements);
+
+	let hash_counts = HashCounts::current();
+	let phase = Phase::start("commit");
+	let commit_timer = start_timer!(|| "commit");
+	let commit_scope = tracing::debug_span!("commit").entered();
+	let mut timing_tree = TimingTree::default();
+	let committed = PolynomialBatch::<_, C, 2>::from_values(
+		poly_values,
+		fri_config.rate_bits,
+		false,
+		fri_config.cap_height,
+		&mut timing_tree,
+		Some(&root_table),
+	);
+	drop(commit_scope);
+	end_timer!(commit_timer);
+	let commit_hashes = HashCounts::current().since(&hash_counts);
+	println!("Hash calls: {commit_hashes}");
+	let commit_seconds = phase.finish(n_elements);
+	// Each polynomial is interpolated and then evaluated on the coset of the LDE domain.
+	let log_lde_size = degree_bits + fri_config.rate_bits;
+	let commit_workload = Workload {
+		butterflies: ntt_butterflies(degree_bits, batch_size)
+			+ ntt_butterflies(log_lde_size, batch_size),
+		digests: commit_hashes.digests,
+		compressions: commit_hashes.compressions,
+		field_muls: n_elements,
+	};
+	report_prediction("commit", &commit_workload, &costs, commit_seconds);
+
+	let mut challenger = Challenger::<GoldilocksField, C::Hasher>::new();
+	challenger.observe_cap::<C::Hasher>(&committed.merkle_tree.cap);
+
+	let zeta = challenger.get_extension_challenge::<2>();
+
+	// The verifier replays the transcript from the commitment on, and must sample the same point.
+	let mut verify_challenger = Challenger::<GoldilocksField, C::Hasher>::new();
+	verify_challenger.observe_cap::<C::Hasher>(&committed.merkle_tree.cap);
+	assert_eq!(verify_challenger.get_extension_challenge::<2>(), zeta);
+
+	let instance = FriInstanceInfo {
+		oracles: vec![FriOracleInfo {
+			num_polys: batch_size,
+			blinding: false,
+		}],
+		batches: vec![FriBatchInfo {
+			point: zeta,
+			polynomials: (0..batch_size)
+				.map(|i| FriPolynomialInfo {
+					oracle_index: 0,
+					polynomial_index: i,
+				})
+				.collect(),
+		}],
+	};
+
+	let hash_counts = HashCounts::current();
+	let phase = Phase::start("prove");
+	let prove_timer = start_timer!(|| "prove");
+	let prove_scope = tracing::debug_span!("prove").entered();
+	let proof = PolynomialBatch::prove_openings(
+		&instance,
+		&[&committed],
+		&mut challenger,
+		&fri_params,
+		&mut timing_tree,
+	);
+	drop(prove_scope);
+	end_timer!(prove_timer);
+	let prove_hashes = HashCounts::current().since(&hash_counts);
+	println!("Hash calls: {prove_hashes}");
+	let prove_seconds = phase.finish(n_elements);
+	report_security_cost(&security, commit_seconds + prove_seconds);
+	// Opening combines the polynomials into one quotient in coefficient form, extends it to the LDE
+	// domain, and folds it.
+	let prove_workload = Workload {
+		butterflies: ntt_butterflies(log_lde_size, 1),
+		digests: prove_hashes.digests,
+		compressions: prove_hashes.compressions,
+		field_muls: n_elements + (2 << log_lde_size),
+	};
+	report_prediction("prove", &prove_workload, &costs, prove_seconds);
+
+	if print_proof_size {
+		let merkle_paths = proof
+			.query_round_proofs
+			.iter()
+			.flat_map(|round| {
+				let initial = round
+					.initial_trees_proof
+					.evals_proofs
+					.iter()
+					.map(|(_, merkle_proof)| serialized_size(merkle_proof));
+				initial.chain(
+					round
+						.steps
+						.iter()
+						.map(|step| serialized_size(&step.merkle_proof)),
+				)
+			})
+			.sum();
+		let query_values = proof
+			.query_round_proofs
+			.iter()
+			.flat_map(|round| {
+				let initial = round
+					.initial_trees_proof
+					.evals_proofs
+					.iter()
+					.map(|(evals, _)| serialized_size(evals));
+				initial.chain(round.steps.iter().map(|step| serialized_size(&step.evals)))
+			})
+			.sum();
+		record_proof_size(
+			serialized_size(&proof),
+			&[
+				(FRI_COMMITMENTS, serialized_size(&proof.commit_phase_merkle_caps)),
+				(MERKLE_PATHS, merkle_paths),
+				(QUERY_VALUES, query_values),
+				(FINAL_POLYNOMIAL, serialized_size(&proof.final_poly)),
+				(PROOF_OF_WORK, serialized_size(&proof.pow_witness)),
+			],
+		);
+	}
+
+	let cold_challenger = verify_challenger.clone();
+	let challenges = verify_challenger.fri_challenges::<C, 2>(
+		&proof.commit_phase_merkle_caps,
+		&proof.final_poly,
+		proof.pow_witness,
+		fri_params.degree_bits,
+		&fri_config,
+	);
+	// Having absorbed the same proof, both transcripts are in the same state.
+	assert_eq!(verify_challenger.get_hash(), challenger.get_hash());
+
+	let evals = committed
+		.polynomials
+		.iter()
+		.map(|poly| poly.to_extension::<2>().eval(zeta))
+		.collect::<Vec<_>>();
+	let openings = FriOpenings {
+		batches: vec![FriOpeningBatch { values: evals }],
+	};
+
+	let hash_counts = HashCounts::current();
+	let phase = Phase::start("verify");
+	let verify_timer = start_timer!(|| "verify");
+	let verify_scope = tracing::debug_span!("verify").entered();
+	verify_fri_proof::<GoldilocksField, C, 2>(
+		&instance,
+		&openings,
+		&challenges,
+		&[committed.merkle_tree.cap.clone()],
+		&proof,
+		&fri_params,
+	)
+	.unwrap();
+	drop(verify_scope);
+	end_timer!(verify_timer);
+	let verifier_hashes = HashCounts::current().since(&hash_counts);
+	println!("Hash calls: {verifier_hashes}");
+	phase.finish(n_elements);
+	report_verifier_cost(&verifier_hashes.into());
+
+	// Unlike the phase above, the cold verifier also derives the FRI challenges from the proof.
+	let caps = [committed.merkle_tree.cap.clone()];
+	verify_cold(&bincode::serialize(&proof).unwrap(), n_elements, |bytes| {
+		let proof = bincode::deserialize::<FriProof<GoldilocksField, C::Hasher, 2>>(bytes).unwrap();
+		let mut challenger = cold_challenger;
+		let challenges = challenger.fri_challenges::<C, 2>(
+			&proof.commit_phase_merkle_caps,
+			&proof.final_poly,
+			proof.pow_witness,
+			fri_params.degree_bits,
+			&fri_config,
+		);
+		verify_fri_proof::<GoldilocksField, C, 2>(
+			&instance,
+			&openings,
+			&challenges,
+			&caps,
+			&proof,
+			&fri_params,
+		)
+		.unwrap();
+	});
+
+	println!();
+}
+
+/// Runs the benchmarks of the example, see [`crate::systems`].
+pub fn run() {
+	init_output("goldilocks_fri_pcs");
+	let _trace = init_tracing("goldilocks_fri_pcs");
+	let _timeline = init_timeline("goldilocks_fri_pcs");
+	let _results = init_results("goldilocks_fri_pcs");
+	let heap_profile = init_heap_profile("goldilocks_fri_pcs");
+	check_cpu_environment();
+	configure_numa();
+	report_simd();
+	report_allocator();
+
+	let batch_size = 256;
+
+	if let Some(seeds) = fuzz_seeds() {
+		for seed in seeds {
+			println!("seed={seed}");
+			profile_commit_prove_verify::<PoseidonGoldilocksConfig>(
+				12,
+				64,
+				batch_size,
+				false,
+				StdRng::seed_from_u64(seed),
+			);
+			profile_commit_prove_verify::<KeccakGoldilocksConfig>(
+				12,
+				64,
+				batch_size,
+				false,
+				StdRng::seed_from_u64(seed),
+			);
+		}
+		return;
+	}
+
+	for degree_bits in requested_n_vars(&[12, 16, 20]) {
+		for n_bits in requested_n_bits(&[64]) {
+			if hash_selected("poseidon") {
+				run_until_stable(|| {
+					profile_commit_prove_verify::<PoseidonGoldilocksConfig>(
+						degree_bits,
+						n_bits,
+						batch_size,
+						true,
+						thread_rng(),
+					)
+				});
+			}
+			if hash_selected("keccak") {
+				run_until_stable(|| {
+					profile_commit_prove_verify::<KeccakGoldilocksConfig>(
+						degree_bits,
+						n_bits,
+						batch_size,
+						true,
+						thread_rng(),
+					)
+				});
+			}
+		}
+		if heap_profile.is_some() {
+			break;
+		}
+	}
+	report_scaling();
+	report_comparisons();
+}
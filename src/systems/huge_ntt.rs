@@ -0,0 +1,151 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Run and measure very large NTTs, from 2^28 to 2^30 elements.
+//!
+//! This is the regime of 1 GiB+ trace commitments, where the transforms are bound by memory
+//! bandwidth and TLB reach rather than arithmetic, so results at smaller sizes extrapolate poorly.
+//! These sizes are too slow for Criterion, so each transform runs once and reports its page
+//! faults and effective throughput alongside the time.
+
+use std::{iter::repeat_with, mem::size_of, time::Instant};
+
+use ark_std::{end_timer, start_timer};
+use binius_field::{
+	arch::OptimalUnderlier, as_packed_field::PackedType, BinaryField32b, PackedField,
+};
+use binius_ntt::{AdditiveNTT, SingleThreadedNTT};
+use bytesize::ByteSize;
+use p3_baby_bear::BabyBear;
+use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_matrix::dense::RowMajorMatrix;
+use rand::thread_rng;
+
+use crate::{
+	allocator::report_allocator,
+	bandwidth::{bandwidth_fraction, report_bandwidth},
+	cpu_state::check_cpu_environment,
+	heap_profile::init_heap_profile,
+	huge_pages::report_huge_page_usage,
+	numa::configure_numa,
+	output::init_output,
+	params::requested_n_vars,
+	phase::Phase,
+	results::init_results,
+	scaling::{begin_series, report_scaling},
+	significance::report_comparisons,
+	simd::report_simd,
+	stability::run_until_stable,
+	throughput::record_witness,
+	timeline::init_timeline,
+	trace::init_tracing,
+};
+
+/// Runs `transform` once over `n_elements` elements, reporting its time, resources, page faults,
+/// and throughput over `data_bytes` as a fraction of peak memory bandwidth.
+fn measure_transform<R>(
+	label: &str,
+	n_elements: u64,
+	data_bytes: usize,
+	transform: impl FnOnce() -> R,
+) -> R {
+	let phase = Phase::start(label);
+	let timer = start_timer!(|| label.to_string());
+	let start = Instant::now();
+	let result = transform();
+	let elapsed = start.elapsed();
+	end_timer!(timer);
+	phase.finish(n_elements);
+
+	let seconds = elapsed.as_secs_f64();
+	print!("Throughput = {}/s", ByteSize((data_bytes as f64 / seconds) as u64));
+	match bandwidth_fraction(data_bytes as f64, seconds) {
+		Some(fraction) => println!(", {:.0}% of peak bandwidth", fraction * 100.0),
+		None => println!(),
+	}
+	result
+}
+
+fn profile_plonky3(log_n: usize) {
+	println!("plonky3 Radix2DitParallel BB31 log_n={log_n}");
+	begin_series("plonky3", "Radix2DitParallel BB31");
+	let threads = if cfg!(feature = "p3_parallel") {
+		rayon::current_num_threads()
+	} else {
+		1
+	};
+	record_witness("BabyBear", threads);
+
+	let phase = Phase::start("gen data");
+	let gen_timer = start_timer!(|| "gen data");
+	let gen_scope = tracing::debug_span!("gen data").entered();
+	let matrix = RowMajorMatrix::<BabyBear>::rand(&mut thread_rng(), 1 << log_n, 1);
+	drop(gen_scope);
+	end_timer!(gen_timer);
+	phase.finish(1 << log_n);
+
+	let dft = Radix2DitParallel::<BabyBear>::default();
+	measure_transform("dft", 1 << log_n, size_of::<BabyBear>() << log_n, || dft.dft_batch(matrix));
+
+	println!();
+}
+
+fn profile_binius(log_n: usize) {
+	type P = PackedType<OptimalUnderlier, BinaryField32b>;
+
+	println!("binius additive NTT Tower 32b log_n={log_n}");
+	begin_series("binius", "additive NTT Tower 32b");
+	record_witness("BinaryField32b", rayon::current_num_threads());
+
+	let mut rng = thread_rng();
+	let phase = Phase::start("gen data");
+	let gen_timer = start_timer!(|| "gen data");
+	let gen_scope = tracing::debug_span!("gen data").entered();
+	let mut data = repeat_with(|| P::random(&mut rng))
+		.take(1 << (log_n - P::LOG_WIDTH))
+		.collect::<Vec<_>>();
+	drop(gen_scope);
+	end_timer!(gen_timer);
+	phase.finish(1 << log_n);
+
+	let phase = Phase::start("precompute twiddles");
+	let precompute_timer = start_timer!(|| "precompute twiddles");
+	let precompute_scope = tracing::debug_span!("precompute twiddles").entered();
+	let ntt = SingleThreadedNTT::<BinaryField32b>::new(log_n)
+		.unwrap()
+		.precompute_twiddles()
+		.multithreaded();
+	drop(precompute_scope);
+	end_timer!(precompute_timer);
+	phase.finish(1 << log_n);
+
+	measure_transform("ntt", 1 << log_n, size_of::<BinaryField32b>() << log_n, || {
+		ntt.forward_transform(&mut data, 0, 0).unwrap()
+	});
+
+	println!();
+}
+
+/// Runs the benchmarks of the example, see [`crate::systems`].
+pub fn run() {
+	init_output("huge_ntt");
+	let _trace = init_tracing("huge_ntt");
+	let _timeline = init_timeline("huge_ntt");
+	let _results = init_results("huge_ntt");
+	let heap_profile = init_heap_profile("huge_ntt");
+	check_cpu_environment();
+	configure_numa();
+	report_simd();
+	report_allocator();
+	report_bandwidth();
+
+	for log_n in requested_n_vars(&[28, 29, 30]) {
+		run_until_stable(|| profile_plonky3(log_n));
+		run_until_stable(|| profile_binius(log_n));
+		if heap_profile.is_some() {
+			break;
+		}
+	}
+	report_huge_page_usage();
+	report_scaling();
+	report_comparisons();
+}
@@ -0,0 +1,141 @@
+// Copyright (c) Microsoft Corporation.
+// Copyright 2023-2024 Ulvetanna Inc.
+
+//! Run and measure timing of the jolt Hyrax polynomial commitment scheme over BN254.
+
+use std::iter::repeat_with;
+
+use ark_bn254::{Fr, G1Projective};
+use ark_serialize::CanonicalSerialize;
+use ark_std::{end_timer, start_timer, UniformRand};
+use jolt_core::{
+	poly::{
+		commitment::{
+			commitment_scheme::{BatchType, CommitShape, CommitmentScheme},
+			hyrax::HyraxScheme,
+		},
+		dense_mlpoly::DensePolynomial,
+	},
+	utils::transcript::ProofTranscript,
+};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+
+use crate::{
+	allocator::report_allocator,
+	cpu_state::check_cpu_environment,
+	fuzz::fuzz_seeds,
+	heap_profile::init_heap_profile,
+	numa::configure_numa,
+	output::init_output,
+	params::{requested_n_bits, requested_n_vars},
+	phase::Phase,
+	proof_size::record_proof_size,
+	results::init_results,
+	scaling::{begin_series, report_scaling},
+	significance::report_comparisons,
+	simd::report_simd,
+	stability::run_until_stable,
+	throughput::record_witness,
+	timeline::init_timeline,
+	trace::init_tracing,
+};
+
+fn profile_lasso(n_vars: usize, n_bits: usize, mut rng: impl Rng) {
+	let num_evals = 1 << n_vars;
+
+	type PCS = HyraxScheme<G1Projective>;
+	let pcs_setup = PCS::setup(&[CommitShape::new(num_evals, BatchType::Small)]);
+	let mat_width = pcs_setup.generators.len();
+	let mat_width_log2 = mat_width.ilog2();
+
+	println!("n_vars={n_vars}, n_bits={n_bits}, mat_width_log2={mat_width_log2}");
+	begin_series("jolt", format!("hyrax, n_bits={n_bits}"));
+	record_witness("BN254 Fr", rayon::current_num_threads());
+
+	let phase = Phase::start("gen_data");
+	let gen_timer = start_timer!(|| format!("gen_data, n_vars={n_vars}, n_bits={n_bits}"));
+	let gen_scope = tracing::debug_span!("gen_data").entered();
+	let poly = DensePolynomial::new(
+		repeat_with(|| Fr::from(rng.gen_range(0..(1u128 << n_bits))))
+			.take(num_evals)
+			.collect(),
+	);
+	drop(gen_scope);
+	end_timer!(gen_timer);
+	phase.finish(num_evals as u64);
+
+	let phase = Phase::start("commit");
+	let commit_timer = start_timer!(|| format!("commit"));
+	let commit_scope = tracing::debug_span!("commit").entered();
+	let commitment = PCS::commit(&poly, &pcs_setup);
+	drop(commit_scope);
+	end_timer!(commit_timer);
+	phase.finish(num_evals as u64);
+
+	let r = repeat_with(|| Fr::rand(&mut rng))
+		.take(n_vars)
+		.collect::<Vec<_>>();
+	let eval = poly.evaluate(&r);
+
+	let phase = Phase::start("prove");
+	let prove_timer = start_timer!(|| format!("prove"));
+	let prove_scope = tracing::debug_span!("prove").entered();
+	let mut prover_transcript = ProofTranscript::new(b"example");
+	let proof = PCS::prove(&pcs_setup, &poly, &r, &mut prover_transcript);
+	drop(prove_scope);
+	end_timer!(prove_timer);
+	phase.finish(num_evals as u64);
+
+	let phase = Phase::start("verify");
+	let verify_timer = start_timer!(|| format!("verify"));
+	let verify_scope = tracing::debug_span!("verify").entered();
+	let mut verifier_transcript = ProofTranscript::new(b"example");
+	let verify_result =
+		PCS::verify(&proof, &pcs_setup, &mut verifier_transcript, &r, &eval, &commitment);
+	assert!(verify_result.is_ok());
+	drop(verify_scope);
+	end_timer!(verify_timer);
+	phase.finish(num_evals as u64);
+	// Having absorbed the same proof, the fresh verifier transcript samples the same challenges.
+	assert_eq!(
+		verifier_transcript.challenge_scalar::<Fr>(),
+		prover_transcript.challenge_scalar::<Fr>()
+	);
+	record_proof_size(proof.compressed_size() as u64, &[]);
+
+	println!();
+}
+
+/// Runs the benchmarks of the example, see [`crate::systems`].
+pub fn run() {
+	init_output("jolt_hyrax_pcs");
+	let _trace = init_tracing("jolt_hyrax_pcs");
+	let _timeline = init_timeline("jolt_hyrax_pcs");
+	let _results = init_results("jolt_hyrax_pcs");
+	let heap_profile = init_heap_profile("jolt_hyrax_pcs");
+	check_cpu_environment();
+	configure_numa();
+	report_simd();
+	report_allocator();
+
+	if let Some(seeds) = fuzz_seeds() {
+		for seed in seeds {
+			println!("seed={seed}");
+			for n_bits in [1, 8, 32, 64] {
+				profile_lasso(12, n_bits, StdRng::seed_from_u64(seed));
+			}
+		}
+		return;
+	}
+
+	for n_vars in requested_n_vars(&[16, 20, 24, 28]) {
+		for n_bits in requested_n_bits(&[1, 8, 32, 64]) {
+			run_until_stable(|| profile_lasso(n_vars, n_bits, thread_rng()));
+		}
+		if heap_profile.is_some() {
+			break;
+		}
+	}
+	report_scaling();
+	report_comparisons();
+}
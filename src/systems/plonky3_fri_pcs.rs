@@ -0,0 +1,465 @@
+// Copyright 2023 Ulvetanna Inc.
+
+//! Run and measure timing of the Plonky3 FRI polynomial commitment scheme over BabyBear.
+
+use std::hint::black_box;
+
+use ark_std::{end_timer, start_timer};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::{
+	CanObserve, DuplexChallenger, FieldChallenger, HashChallenger, SerializingChallenger32,
+};
+use p3_commit::{ExtensionMmcs, Mmcs, Pcs, PolynomialSpace};
+use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_field::{extension::BinomialExtensionField, AbstractField, ExtensionField, Field};
+use p3_fri::{BatchOpening, FriConfig, FriProof, TwoAdicFriPcs};
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{
+	CompressionFunctionFromHasher, CryptographicHasher, PaddingFreeSponge,
+	PseudoCompressionFunction, SerializingHasher32, TruncatedPermutation,
+};
+use rand::{
+	distributions::{Distribution, Standard},
+	rngs::StdRng,
+	thread_rng, Rng, SeedableRng,
+};
+use serde::Serialize;
+
+use crate::{
+	advisor::{
+		chain, ntt_butterflies, report_prediction, seconds_per_op, PrimitiveCosts, Workload,
+	},
+	allocator::report_allocator,
+	cost_model::report_verifier_cost,
+	cpu_state::check_cpu_environment,
+	fuzz::fuzz_seeds,
+	hash_count::{Counting, HashCounts},
+	heap_profile::init_heap_profile,
+	numa::configure_numa,
+	output::init_output,
+	params::{hash_selected, requested_log_inv_rate, requested_n_vars},
+	phase::Phase,
+	proof_size::{
+		components::{
+			FINAL_POLYNOMIAL, FRI_COMMITMENTS, MERKLE_PATHS, PROOF_OF_WORK, QUERY_VALUES,
+		},
+		record_proof_size,
+	},
+	results::init_results,
+	scaling::{begin_series, report_scaling},
+	security::{report_security_cost, FriSecurity, SecurityTarget},
+	significance::report_comparisons,
+	simd::report_simd,
+	stability::run_until_stable,
+	throughput::record_witness,
+	timeline::init_timeline,
+	trace::init_tracing,
+	verifier_profile::verify_cold,
+};
+
+/// Log size of the NTT the primitive costs are calibrated with.
+const CALIBRATION_LOG_SIZE: usize = 14;
+
+/// Hash calls the primitive costs are calibrated with.
+const CALIBRATION_HASHES: u64 = 1 << 10;
+
+/// Field multiplications the primitive costs are calibrated with.
+const CALIBRATION_MULS: u64 = 1 << 16;
+
+/// Measures the NTT and extension field multiplications shared by the BabyBear configurations,
+/// leaving the hash costs to the caller.
+fn calibrate_field(log_batch_size: usize, rng: &mut impl Rng) -> PrimitiveCosts {
+	type Val = BabyBear;
+	type Challenge = BinomialExtensionField<Val, 4>;
+
+	let batch_size = 1 << log_batch_size;
+	let dft = Radix2DitParallel::<Val>::default();
+	let matrix = RowMajorMatrix::<Val>::rand(rng, 1 << CALIBRATION_LOG_SIZE, batch_size);
+	let factor = rng.gen::<Val>();
+	PrimitiveCosts {
+		butterfly: seconds_per_op(ntt_butterflies(CALIBRATION_LOG_SIZE, batch_size), move || {
+			black_box(dft.dft_batch(matrix.clone()));
+		}),
+		field_mul: seconds_per_op(CALIBRATION_MULS, || {
+			black_box(chain(CALIBRATION_MULS, Challenge::one(), |x| x * factor));
+		}),
+		threads: if cfg!(feature = "p3_parallel") {
+			rayon::current_num_threads()
+		} else {
+			1
+		},
+		..PrimitiveCosts::default()
+	}
+}
+
+fn serialized_size(value: &impl Serialize) -> u64 {
+	bincode::serialized_size(value).unwrap()
+}
+
+/// Proofs whose serialized size can be broken down into the shared proof components.
+trait ProofParts {
+	fn parts(&self) -> Vec<(&'static str, u64)>;
+}
+
+impl<Val, Challenge, InputMmcs, FriMmcs> ProofParts
+	for FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>
+where
+	Val: Field,
+	Challenge: Field,
+	InputMmcs: Mmcs<Val>,
+	FriMmcs: Mmcs<Challenge>,
+{
+	fn parts(&self) -> Vec<(&'static str, u64)> {
+		let mut merkle_paths = 0;
+		let mut query_values = 0;
+		for query in &self.query_proofs {
+			for opening in &query.input_proof {
+				merkle_paths += serialized_size(&opening.opening_proof);
+				query_values += serialized_size(&opening.opened_values);
+			}
+			for step in &query.commit_phase_openings {
+				merkle_paths += serialized_size(&step.opening_proof);
+				query_values += serialized_size(&step.sibling_value);
+			}
+		}
+		vec![
+			(FRI_COMMITMENTS, serialized_size(&self.commit_phase_commits)),
+			(MERKLE_PATHS, merkle_paths),
+			(QUERY_VALUES, query_values),
+			(FINAL_POLYNOMIAL, serialized_size(&self.final_poly)),
+			(PROOF_OF_WORK, serialized_size(&self.pow_witness)),
+		]
+	}
+}
+
+fn run_commit_prove_verify_fri_pcs<Val, Challenge, Challenger, P, R>(
+	pcs: P,
+	challenger: Challenger,
+	log_degree: usize,
+	log_batch_size: usize,
+	security: FriSecurity,
+	costs: PrimitiveCosts,
+	mut rng: R,
+) where
+	P: Pcs<Challenge, Challenger> + Sync,
+	P::Proof: ProofParts,
+	P::Commitment: Send,
+	P::Domain: PolynomialSpace<Val = Val> + Send + Sync,
+	Val: Field,
+	Standard: Distribution<Val>,
+	Challenge: ExtensionField<Val>,
+	Challenger: Clone + CanObserve<P::Commitment> + FieldChallenger<Val> + Sync,
+	R: Rng,
+{
+	let mut p_challenger = challenger.clone();
+
+	let degree = 1 << log_degree;
+	let batch_size = 1 << log_batch_size;
+	let n_elements = (degree * batch_size) as u64;
+	let domain = pcs.natural_domain_for_degree(degree);
+
+	let phase = Phase::start("gen_data");
+	let gen_timer = start_timer!(|| "gen_data");
+	let gen_scope = tracing::debug_span!("gen_data").entered();
+	let matrix = RowMajorMatrix::<Val>::rand(&mut rng, degree, batch_size);
+	drop(gen_scope);
+	end_timer!(gen_timer);
+	phase.finish(n_elements);
+
+	let hash_counts = HashCounts::current();
+	let phase = Phase::start("commit");
+	let commit_timer = start_timer!(|| "commit");
+	let commit_scope = tracing::debug_span!("commit").entered();
+	let (commitment, committed) = pcs.commit(vec![(domain, matrix)]);
+	drop(commit_scope);
+	end_timer!(commit_timer);
+	let commit_hashes = HashCounts::current().since(&hash_counts);
+	println!("Hash calls: {commit_hashes}");
+	let commit_seconds = phase.finish(n_elements);
+	// The LDE interpolates each column and evaluates it on the coset of the larger domain.
+	let log_lde_size = log_degree + security.log_inv_rate;
+	let commit_workload = Workload {
+		butterflies: ntt_butterflies(log_degree, batch_size)
+			+ ntt_butterflies(log_lde_size, batch_size),
+		digests: commit_hashes.digests,
+		compressions: commit_hashes.compressions,
+		field_muls: n_elements,
+	};
+	report_prediction("commit", &commit_workload, &costs, commit_seconds);
+
+	p_challenger.observe(commitment.clone());
+
+	let zeta: Challenge = p_challenger.sample_ext_element();
+
+	let hash_counts = HashCounts::current();
+	let phase = Phase::start("prove");
+	let prove_timer = start_timer!(|| "prove");
+	let prove_scope = tracing::debug_span!("prove").entered();
+	let (opening_by_round, proof) =
+		pcs.open(vec![(&committed, vec![vec![zeta]])], &mut p_challenger);
+	drop(prove_scope);
+	end_timer!(prove_timer);
+	let prove_hashes = HashCounts::current().since(&hash_counts);
+	println!("Hash calls: {prove_hashes}");
+	let prove_seconds = phase.finish(n_elements);
+	report_security_cost(&security, commit_seconds + prove_seconds);
+	// Opening evaluates every column at zeta, reduces the columns over the LDE domain into one
+	// quotient, and folds it, each of which is about one multiplication per element.
+	let prove_workload = Workload {
+		butterflies: 0,
+		digests: prove_hashes.digests,
+		compressions: prove_hashes.compressions,
+		field_muls: n_elements + ((batch_size as u64 + 2) << log_lde_size),
+	};
+	report_prediction("prove", &prove_workload, &costs, prove_seconds);
+
+	assert_eq!(opening_by_round.len(), 1);
+	let point_openings = opening_by_round[0][0][0].clone();
+
+	// Verify the proof.
+	let mut v_challenger = challenger.clone();
+	v_challenger.observe(commitment.clone());
+	let verifier_zeta: Challenge = v_challenger.sample_ext_element();
+	assert_eq!(verifier_zeta, zeta);
+
+	let hash_counts = HashCounts::current();
+	let phase = Phase::start("verify");
+	let verify_timer = start_timer!(|| "verify");
+	let verify_scope = tracing::debug_span!("verify").entered();
+	pcs.verify(
+		vec![(commitment.clone(), vec![(domain, vec![(zeta, point_openings.clone())])])],
+		&proof,
+		&mut v_challenger,
+	)
+	.unwrap();
+	drop(verify_scope);
+	end_timer!(verify_timer);
+	let verifier_hashes = HashCounts::current().since(&hash_counts);
+	println!("Hash calls: {verifier_hashes}");
+	phase.finish(n_elements);
+	report_verifier_cost(&verifier_hashes.into());
+
+	// The cold verifier starts from the commitment and the claimed openings, and replays the
+	// transcript from the commitment on.
+	verify_cold(&bincode::serialize(&proof).unwrap(), n_elements, |bytes| {
+		let proof = bincode::deserialize::<P::Proof>(bytes).unwrap();
+		let mut v_challenger = challenger.clone();
+		v_challenger.observe(commitment.clone());
+		let zeta: Challenge = v_challenger.sample_ext_element();
+		pcs.verify(
+			vec![(commitment, vec![(domain, vec![(zeta, point_openings)])])],
+			&proof,
+			&mut v_challenger,
+		)
+		.unwrap();
+	});
+
+	record_proof_size(serialized_size(&proof), &proof.parts());
+
+	println!();
+}
+
+fn profile_commit_prove_verify_fri_pcs_poseidon2(
+	log_degree: usize,
+	log_batch_size: usize,
+	log_inv_rate: usize,
+	mut rng: impl Rng,
+) {
+	type Val = BabyBear;
+	type Challenge = BinomialExtensionField<Val, 4>;
+
+	type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+	type MyHash = Counting<PaddingFreeSponge<Perm, 16, 8, 8>>;
+	type MyCompress = Counting<TruncatedPermutation<Perm, 2, 8, 16>>;
+
+	type ValMmcs =
+		MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+	type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+	type Dft = Radix2DitParallel<Val>;
+	type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+	type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+	let perm = Perm::new_from_rng_128(
+		Poseidon2ExternalMatrixGeneral,
+		DiffusionMatrixBabyBear::default(),
+		&mut rng,
+	);
+	let hash = Counting(PaddingFreeSponge::new(perm.clone()));
+	let compress = Counting(TruncatedPermutation::new(perm.clone()));
+
+	// The tree hashes packed rows, one leaf per SIMD lane, so the calls are calibrated packed.
+	type Packed = <Val as Field>::Packing;
+	let mut costs = calibrate_field(log_batch_size, &mut rng);
+	let leaf = vec![Packed::one(); 1 << log_batch_size];
+	costs.digest = seconds_per_op(CALIBRATION_HASHES, || {
+		for _ in 0..CALIBRATION_HASHES {
+			black_box(hash.0.hash_iter(black_box(&leaf).iter().copied()));
+		}
+	});
+	costs.compression = seconds_per_op(CALIBRATION_HASHES, || {
+		for _ in 0..CALIBRATION_HASHES {
+			black_box(compress.0.compress(black_box([[Packed::one(); 8]; 2])));
+		}
+	});
+
+	let val_mmcs = ValMmcs::new(hash, compress);
+	let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+	let target = SecurityTarget::requested();
+	let security = target.fri(log_inv_rate);
+	let fri_config = FriConfig {
+		log_blowup: log_inv_rate,
+		num_queries: security.n_queries,
+		proof_of_work_bits: security.pow_bits,
+		mmcs: challenge_mmcs,
+	};
+
+	let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+	let challenger = Challenger::new(perm.clone());
+
+	println!("plonky3 with poseidon2 merkle log_coeffs={}", log_degree + log_batch_size);
+	begin_series("plonky3", target.series_config("poseidon2 merkle"));
+	record_witness("BabyBear", costs.threads);
+	run_commit_prove_verify_fri_pcs(
+		pcs,
+		challenger,
+		log_degree,
+		log_batch_size,
+		security,
+		costs,
+		rng,
+	);
+}
+
+fn profile_commit_prove_verify_fri_pcs_keccak(
+	log_degree: usize,
+	log_batch_size: usize,
+	log_inv_rate: usize,
+	mut rng: impl Rng,
+) {
+	type Val = BabyBear;
+	type Challenge = BinomialExtensionField<Val, 4>;
+
+	type ByteHash = Keccak256Hash;
+	type MyHash = Counting<SerializingHasher32<ByteHash>>;
+	type MyCompress = Counting<CompressionFunctionFromHasher<ByteHash, 2, 32>>;
+
+	type ValMmcs = MerkleTreeMmcs<Val, u8, MyHash, MyCompress, 32>;
+	type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+	type Dft = Radix2DitParallel<Val>;
+	type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+	type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+	let byte_hash = ByteHash {};
+	let hash = Counting(SerializingHasher32::new(Keccak256Hash {}));
+	let compress = Counting(CompressionFunctionFromHasher::new(byte_hash));
+
+	let mut costs = calibrate_field(log_batch_size, &mut rng);
+	let leaf = vec![Val::one(); 1 << log_batch_size];
+	costs.digest = seconds_per_op(CALIBRATION_HASHES, || {
+		for _ in 0..CALIBRATION_HASHES {
+			black_box(hash.0.hash_iter(black_box(&leaf).iter().copied()));
+		}
+	});
+	costs.compression = seconds_per_op(CALIBRATION_HASHES, || {
+		for _ in 0..CALIBRATION_HASHES {
+			black_box(compress.0.compress(black_box([[0u8; 32]; 2])));
+		}
+	});
+
+	let val_mmcs = ValMmcs::new(hash, compress);
+	let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+	let target = SecurityTarget::requested();
+	let security = target.fri(log_inv_rate);
+	let fri_config = FriConfig {
+		log_blowup: log_inv_rate,
+		num_queries: security.n_queries,
+		proof_of_work_bits: security.pow_bits,
+		mmcs: challenge_mmcs,
+	};
+
+	let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+	let challenger = Challenger::from_hasher(vec![], byte_hash);
+
+	println!("plonky3 with keccak merkle log_coeffs={}", log_degree + log_batch_size);
+	begin_series("plonky3", target.series_config("keccak merkle"));
+	record_witness("BabyBear", costs.threads);
+	run_commit_prove_verify_fri_pcs(
+		pcs,
+		challenger,
+		log_degree,
+		log_batch_size,
+		security,
+		costs,
+		rng,
+	);
+}
+
+/// Runs the benchmarks of the example, see [`crate::systems`].
+pub fn run() {
+	init_output("plonky3_fri_pcs");
+	let _trace = init_tracing("plonky3_fri_pcs");
+	let _timeline = init_timeline("plonky3_fri_pcs");
+	let _results = init_results("plonky3_fri_pcs");
+	let heap_profile = init_heap_profile("plonky3_fri_pcs");
+	check_cpu_environment();
+	configure_numa();
+	report_simd();
+	report_allocator();
+
+	let log_batch_size = 4;
+	let log_inv_rate = requested_log_inv_rate(2);
+
+	if let Some(seeds) = fuzz_seeds() {
+		for seed in seeds {
+			println!("seed={seed}");
+			profile_commit_prove_verify_fri_pcs_poseidon2(
+				12 - log_batch_size,
+				log_batch_size,
+				log_inv_rate,
+				StdRng::seed_from_u64(seed),
+			);
+			profile_commit_prove_verify_fri_pcs_keccak(
+				12 - log_batch_size,
+				log_batch_size,
+				log_inv_rate,
+				StdRng::seed_from_u64(seed),
+			);
+		}
+		return;
+	}
+
+	for log_degree in requested_n_vars(&[20, 24, 28]) {
+		if hash_selected("poseidon2") {
+			run_until_stable(|| {
+				profile_commit_prove_verify_fri_pcs_poseidon2(
+					log_degree - log_batch_size,
+					log_batch_size,
+					log_inv_rate,
+					thread_rng(),
+				)
+			});
+		}
+		if hash_selected("keccak") {
+			run_until_stable(|| {
+				profile_commit_prove_verify_fri_pcs_keccak(
+					log_degree - log_batch_size,
+					log_batch_size,
+					log_inv_rate,
+					thread_rng(),
+				)
+			});
+		}
+		if heap_profile.is_some() {
+			break;
+		}
+	}
+	report_scaling();
+	report_comparisons();
+}
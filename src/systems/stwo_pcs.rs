@@ -0,0 +1,201 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Run and measure timing of the stwo circle FRI polynomial commitment scheme over Mersenne31.
+
+use std::mem::size_of_val;
+
+use ark_std::{end_timer, start_timer, UniformRand};
+use bytesize::ByteSize;
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use stwo::core::{
+	backend::{simd::SimdBackend, Col, Column},
+	channel::Blake2sChannel,
+	circle::CirclePoint,
+	fields::{m31::BaseField, qm31::SecureField},
+	fri::FriConfig,
+	pcs::{CommitmentSchemeProver, CommitmentSchemeVerifier, PcsConfig, TreeVec},
+	poly::{
+		circle::{CanonicCoset, CircleEvaluation, PolyOps},
+		BitReversedOrder,
+	},
+	vcs::blake2_merkle::Blake2sMerkleChannel,
+	ColumnVec,
+};
+
+use crate::{
+	allocator::report_allocator,
+	cpu_state::check_cpu_environment,
+	fuzz::fuzz_seeds,
+	heap_profile::init_heap_profile,
+	numa::configure_numa,
+	output::init_output,
+	params::{requested_log_inv_rate, requested_n_vars},
+	phase::Phase,
+	proof_size::record_proof_size,
+	results::{init_results, record_metric},
+	scaling::{begin_series, report_scaling},
+	security::{report_security_cost, SecurityTarget},
+	significance::report_comparisons,
+	simd::report_simd,
+	stability::run_until_stable,
+	throughput::record_witness,
+	timeline::init_timeline,
+	trace::init_tracing,
+};
+
+fn run_commit_prove_verify_stwo_pcs(
+	log_n_rows: u32,
+	log_batch_size: u32,
+	log_blowup_factor: u32,
+	mut rng: impl Rng,
+) {
+	println!("stwo pcs with log_coeffs={}", log_n_rows + log_batch_size);
+	let target = SecurityTarget::requested();
+	begin_series("stwo", target.series_config(""));
+	record_witness("M31", 1);
+	let n_elements = 1 << (log_n_rows + log_batch_size);
+
+	// Precompute twiddles.
+	let phase = Phase::start("precompute twiddles");
+	let precompute_timer = start_timer!(|| "precompute twiddles");
+	let precompute_scope = tracing::debug_span!("precompute twiddles").entered();
+	let twiddles = SimdBackend::precompute_twiddles(
+		CanonicCoset::new(log_n_rows + log_blowup_factor)
+			.circle_domain()
+			.half_coset,
+	);
+	drop(precompute_scope);
+	end_timer!(precompute_timer);
+	phase.finish(n_elements);
+	let twiddles_size =
+		(twiddles.twiddles.len() + twiddles.itwiddles.len()) * size_of_val(&twiddles.twiddles[0]);
+	println!("Twiddle table size = {}", ByteSize(twiddles_size as u64));
+	record_metric("twiddle_bytes", twiddles_size as f64, "bytes");
+
+	// Setup protocol.
+	let prover_channel = &mut Blake2sChannel::default();
+	let security = target.fri(log_blowup_factor as usize);
+	let pcs_config = PcsConfig {
+		pow_bits: security.pow_bits as u32,
+		fri_config: FriConfig {
+			log_last_layer_degree_bound: 0,
+			log_blowup_factor,
+			n_queries: security.n_queries,
+		},
+	};
+	let prove_commitment_scheme =
+		&mut CommitmentSchemeProver::<_, Blake2sMerkleChannel>::new(pcs_config, &twiddles);
+
+	// Generate trace.
+	let phase = Phase::start("generate trace");
+	let gen_trace_timer = start_timer!(|| "generate trace");
+	let gen_trace_scope = tracing::debug_span!("generate trace").entered();
+	let domain = CanonicCoset::new(log_n_rows).circle_domain();
+	let mut trace = (0..1 << log_batch_size)
+		.map(|_| Col::<SimdBackend, BaseField>::zeros(1 << log_n_rows))
+		.collect::<Vec<_>>();
+	for col in trace.iter_mut() {
+		for val in col.as_mut_slice() {
+			*val = BaseField::rand(&mut rng);
+		}
+	}
+	drop(gen_trace_scope);
+	end_timer!(gen_trace_timer);
+	phase.finish(n_elements);
+
+	// Commit trace
+	let phase = Phase::start("commit trace");
+	let commit_timer = start_timer!(|| "commit trace");
+	let commit_scope = tracing::debug_span!("commit trace").entered();
+	let trace = trace
+		.into_iter()
+		.map(|eval| CircleEvaluation::<SimdBackend, BaseField, BitReversedOrder>::new(domain, eval))
+		.collect::<Vec<_>>();
+
+	let mut tree_builder = prove_commitment_scheme.tree_builder();
+	tree_builder.extend_evals(trace);
+	tree_builder.commit(prover_channel);
+	drop(commit_scope);
+	end_timer!(commit_timer);
+	let commit_seconds = phase.finish(n_elements);
+
+	// Prove
+	let phase = Phase::start("prove");
+	let proove_timer = start_timer!(|| "prove");
+	let prove_scope = tracing::debug_span!("prove").entered();
+	let sample_point = CirclePoint::<SecureField>::get_random_point(prover_channel);
+	let sample_points = vec![ColumnVec::<Vec<CirclePoint<SecureField>>>::from(
+		(0..1 << log_batch_size)
+			.map(|_| vec![sample_point])
+			.collect::<Vec<_>>(),
+	)];
+	let sample_points = TreeVec::new(sample_points);
+	let proof = prove_commitment_scheme.prove_values(sample_points.clone(), prover_channel);
+	drop(prove_scope);
+	end_timer!(proove_timer);
+	let prove_seconds = phase.finish(n_elements);
+	report_security_cost(&security, commit_seconds + prove_seconds);
+	record_proof_size(bincode::serialized_size(&proof).unwrap(), &[]);
+
+	// Verify
+	let phase = Phase::start("verify");
+	let verify_timer = start_timer!(|| "verify");
+	let verify_scope = tracing::debug_span!("verify").entered();
+	let channel = &mut Blake2sChannel::default();
+	let commitment_scheme: &mut CommitmentSchemeVerifier<Blake2sMerkleChannel> =
+		&mut CommitmentSchemeVerifier::<Blake2sMerkleChannel>::new(pcs_config);
+	commitment_scheme.commit(
+		prove_commitment_scheme.roots()[0],
+		&vec![log_n_rows; 1 << log_batch_size],
+		channel,
+	);
+	commitment_scheme
+		.verify_values(sample_points, proof, channel)
+		.unwrap();
+	drop(verify_scope);
+	end_timer!(verify_timer);
+	phase.finish(n_elements);
+
+	// Both channels must have absorbed the same messages.
+	assert_eq!(prover_channel.digest(), channel.digest());
+}
+
+/// Runs the benchmarks of the example, see [`crate::systems`].
+pub fn run() {
+	init_output("stwo_pcs");
+	let _trace = init_tracing("stwo_pcs");
+	let _timeline = init_timeline("stwo_pcs");
+	let _results = init_results("stwo_pcs");
+	let heap_profile = init_heap_profile("stwo_pcs");
+	check_cpu_environment();
+	configure_numa();
+	report_simd();
+	report_allocator();
+
+	let log_batch_size = 4;
+	let log_inv_rate = requested_log_inv_rate(1);
+
+	if let Some(seeds) = fuzz_seeds() {
+		for seed in seeds {
+			println!("seed={seed}");
+			run_commit_prove_verify_stwo_pcs(
+				12,
+				log_batch_size,
+				log_inv_rate,
+				StdRng::seed_from_u64(seed),
+			);
+		}
+		return;
+	}
+
+	for log_degree in requested_n_vars(&[20, 24]) {
+		run_until_stable(|| {
+			run_commit_prove_verify_stwo_pcs(log_degree, log_batch_size, log_inv_rate, thread_rng())
+		});
+		if heap_profile.is_some() {
+			break;
+		}
+	}
+	report_scaling();
+	report_comparisons();
+}
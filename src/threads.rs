@@ -0,0 +1,77 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Thread counts for the thread-scaling benchmarks, and helpers to run code on a single pinned
+//! thread.
+
+use std::thread;
+
+/// Thread counts swept by the scaling benchmarks: powers of two up to the available parallelism,
+/// plus the available parallelism itself.
+pub fn thread_counts() -> Vec<usize> {
+	let max_threads = thread::available_parallelism()
+		.map(|n| n.get())
+		.unwrap_or(1);
+	let mut counts = (0..)
+		.map(|i| 1 << i)
+		.take_while(|&n| n < max_threads)
+		.collect::<Vec<_>>();
+	counts.push(max_threads);
+	counts
+}
+
+/// Parses a comma-separated list of thread counts, such as `1,2,4,max`, where `max` stands for the
+/// available parallelism.
+pub fn parse_thread_counts(list: &str) -> Result<Vec<usize>, String> {
+	list.split(',')
+		.map(|count| match count.trim() {
+			"max" => Ok(thread::available_parallelism().map_or(1, |n| n.get())),
+			count => match count.parse() {
+				Ok(0) | Err(_) => Err(format!("invalid thread count {count:?} in {list:?}")),
+				Ok(count) => Ok(count),
+			},
+		})
+		.collect()
+}
+
+/// Pins the calling thread to one CPU core, returning whether the platform supports it. macOS only
+/// takes affinity hints, so threads are never pinned there.
+pub fn pin_current_thread(core: usize) -> bool {
+	#[cfg(target_os = "linux")]
+	{
+		let mut set = unsafe { std::mem::zeroed::<libc::cpu_set_t>() };
+		unsafe { libc::CPU_SET(core, &mut set) };
+		unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0 }
+	}
+	#[cfg(windows)]
+	{
+		use windows_sys::Win32::System::Threading::{GetCurrentThread, SetThreadAffinityMask};
+
+		// The mask only covers the cores of the thread's processor group, at most 64.
+		core < usize::BITS as usize
+			&& unsafe { SetThreadAffinityMask(GetCurrentThread(), 1 << core) } != 0
+	}
+	#[cfg(not(any(target_os = "linux", windows)))]
+	{
+		let _ = core;
+		false
+	}
+}
+
+/// Runs `f` on a new thread with a stack of `stack_bytes`, pinned to `core` where the platform
+/// allows, and returns its result.
+///
+/// This emulates verifiers on constrained devices, which run on one core with a small stack. A
+/// stack overflow aborts the process, so a working-set budget that is too small fails loudly.
+pub fn run_pinned<R: Send>(core: usize, stack_bytes: usize, f: impl FnOnce() -> R + Send) -> R {
+	thread::scope(|scope| {
+		thread::Builder::new()
+			.stack_size(stack_bytes)
+			.spawn_scoped(scope, || {
+				pin_current_thread(core);
+				f()
+			})
+			.unwrap()
+			.join()
+			.unwrap()
+	})
+}
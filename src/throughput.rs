@@ -0,0 +1,244 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Throughput of every phase in the same standard units, whatever the size of a phase counts.
+//!
+//! The examples size their phases in field elements of very different widths, and the Criterion
+//! groups declare their throughput in packed elements, scalar elements, or bytes, so raw rates do
+//! not compare across backends. [`normalize`] converts the median time of every phase into:
+//!
+//! - nanoseconds per element, `seconds * 1e9 / n`;
+//! - elements per second per core, `n / seconds / threads`, where the thread count of the
+//!   configuration is known;
+//! - megabytes of witness per second, `n * element_bits / 8 / 1e6 / seconds`, with decimal
+//!   megabytes, where the width of the elements is known.
+//!
+//! The witness is counted at the width its elements take in memory rather than their information
+//! content: binary tower elements are bit-packed, so `BinaryField1b` counts 1 bit and
+//! `BinaryField8b` 8, while the 31-bit BabyBear, KoalaBear, and Mersenne31 elements count the 32
+//! bits of the word they are stored in, Goldilocks 64 bits, and the 254-bit BN254 scalars the 256
+//! bits of their four limbs. Extension fields written `<base>^<degree>`, such as `BB31^4`, count
+//! the degree times the width of the base. [`FIELD_BITS`] lists the field names recognized.
+//!
+//! The width and thread count of a configuration come from its `element_bits` and `threads`
+//! metrics, which the examples record through [`record_witness`]. The Criterion import records 8
+//! bits for the groups that declare their throughput in bytes, and a thread count for every
+//! benchmark. Without the metric, the width is that of the field named in the backend,
+//! configuration, or phase, such as `BB31` in the `plonky3 multiply` group.
+
+use std::{collections::BTreeMap, io};
+
+use crate::{
+	report::{label, median_of},
+	results::{record_result, MetricRecord, Record, Results},
+	scaling::current_series,
+};
+
+/// Metric of the width in bits of the elements a configuration counts.
+pub const ELEMENT_BITS_METRIC: &str = "element_bits";
+
+/// Metric of the number of threads a configuration runs on.
+pub const THREADS_METRIC: &str = "threads";
+
+/// Widths in bits of the fields by the names they go by in types, configurations, and benchmark
+/// names.
+pub const FIELD_BITS: &[(&[&str], f64)] = &[
+	(&["BinaryField1b", "Tower 1b"], 1.0),
+	(&["BinaryField2b", "Tower 2b"], 2.0),
+	(&["BinaryField4b", "Tower 4b"], 4.0),
+	(&["BinaryField8b", "AESTowerField8b", "Tower 8b"], 8.0),
+	(&["BinaryField16b", "AESTowerField16b", "Tower 16b"], 16.0),
+	(&["BinaryField32b", "AESTowerField32b", "Tower 32b"], 32.0),
+	(&["BinaryField64b", "AESTowerField64b", "Tower 64b"], 64.0),
+	(
+		&[
+			"BinaryField128b",
+			"AESTowerField128b",
+			"BinaryField128bPolyval",
+			"Tower 128b",
+			"POLYVAL",
+		],
+		128.0,
+	),
+	(
+		&[
+			"BabyBear",
+			"BB31",
+			"KoalaBear",
+			"KB31",
+			"Mersenne31",
+			"M31",
+			"MR31",
+		],
+		32.0,
+	),
+	(&["Goldilocks", "GoldilocksField", "GL64"], 64.0),
+	(&["BN254", "B254", "Fr"], 256.0),
+];
+
+/// Lowercase words of a name, split at everything but letters, digits, and `^`.
+fn words(name: &str) -> Vec<String> {
+	name.split(|c: char| !c.is_alphanumeric() && c != '^')
+		.filter(|word| !word.is_empty())
+		.map(str::to_lowercase)
+		.collect()
+}
+
+/// The width in bits of the elements of the first field in [`FIELD_BITS`] that `name` mentions,
+/// such as a type name, `field=BinaryField1b`, or `BB31^4 log_n=20`.
+pub fn field_bits(name: &str) -> Option<f64> {
+	let words = words(name);
+	for &(aliases, bits) in FIELD_BITS {
+		for alias in aliases {
+			let alias = alias.split(' ').map(str::to_lowercase).collect::<Vec<_>>();
+			let (last, init) = alias.split_last().unwrap();
+			for window in words.windows(alias.len()) {
+				let (base, degree) = match window[alias.len() - 1].split_once('^') {
+					Some((base, degree)) => (base, degree.parse::<f64>().ok()),
+					None => (window[alias.len() - 1].as_str(), Some(1.0)),
+				};
+				if base == last && window[..init.len()] == *init {
+					if let Some(degree) = degree {
+						return Some(bits * degree);
+					}
+				}
+			}
+		}
+	}
+	None
+}
+
+/// Records a metric of the whole configuration of the current series, independent of its size.
+fn record_series_metric(metric: &str, value: f64, unit: &str) {
+	let series = current_series();
+	record_result(Record::Metric(MetricRecord {
+		backend: series.backend,
+		config: series.config,
+		n_elements: None,
+		metric: metric.to_string(),
+		value,
+		unit: unit.to_string(),
+	}));
+}
+
+/// Records the field of the elements the current series counts, by a name in [`FIELD_BITS`] such
+/// as a type name, and the number of threads it runs on, so that its throughput can be normalized.
+pub fn record_witness(field: &str, threads: usize) {
+	match field_bits(field) {
+		Some(bits) => record_series_metric(ELEMENT_BITS_METRIC, bits, "bits"),
+		None => println!("Unknown field {field}, its witness throughput is not normalized"),
+	}
+	record_series_metric(THREADS_METRIC, threads as f64, "count");
+}
+
+/// Throughput of one phase of one configuration at one size, in the standard units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedThroughput {
+	/// Proving library.
+	pub backend: String,
+	/// Parameters of the configuration other than the problem size.
+	pub config: String,
+	/// Name of the phase.
+	pub phase: String,
+	/// Number of elements the phase processed.
+	pub n_elements: u64,
+	/// Median wall time of the phase.
+	pub seconds: f64,
+	/// Wall time per element, in nanoseconds.
+	pub ns_per_element: f64,
+	/// Elements processed per second and thread, if the thread count is known.
+	pub elements_per_core_second: Option<f64>,
+	/// Megabytes of witness processed per second, if the width of the elements is known.
+	pub witness_mb_per_second: Option<f64>,
+}
+
+/// Normalizes the throughput of every phase of every configuration at every size, in the order
+/// they first ran.
+pub fn normalize(results: &[Results]) -> Vec<NormalizedThroughput> {
+	let mut metrics = BTreeMap::<(String, String, &str), f64>::new();
+	let mut seconds = BTreeMap::<(String, String, String, u64), Vec<f64>>::new();
+	let mut order = Vec::new();
+	for record in results.iter().flat_map(|run| &run.records) {
+		match record {
+			Record::Metric(record) => {
+				for metric in [ELEMENT_BITS_METRIC, THREADS_METRIC] {
+					if record.metric == metric {
+						metrics.insert(
+							(record.backend.clone(), record.config.clone(), metric),
+							record.value,
+						);
+					}
+				}
+			}
+			Record::Phase(record) => {
+				let key = (
+					record.backend.clone(),
+					record.config.clone(),
+					record.phase.clone(),
+					record.n_elements,
+				);
+				if !seconds.contains_key(&key) {
+					order.push(key.clone());
+				}
+				seconds.entry(key).or_default().push(record.seconds);
+			}
+		}
+	}
+
+	order
+		.into_iter()
+		.map(|key| {
+			let median = median_of(&seconds[&key]);
+			let (backend, config, phase, n_elements) = key;
+			let metric = |metric| {
+				metrics
+					.get(&(backend.clone(), config.clone(), metric))
+					.copied()
+			};
+			let element_bits = metric(ELEMENT_BITS_METRIC)
+				.or_else(|| field_bits(&format!("{backend} {config} {phase}")));
+			let n = n_elements as f64;
+			NormalizedThroughput {
+				ns_per_element: median * 1e9 / n,
+				elements_per_core_second: metric(THREADS_METRIC)
+					.filter(|&threads| threads > 0.0)
+					.map(|threads| n / median / threads),
+				witness_mb_per_second: element_bits.map(|bits| n * bits / 8.0 / 1e6 / median),
+				backend,
+				config,
+				phase,
+				n_elements,
+				seconds: median,
+			}
+		})
+		.collect()
+}
+
+/// Writes a text table of the normalized throughput of every phase.
+pub fn write_throughput(results: &[Results], mut out: impl io::Write) -> io::Result<()> {
+	let rows = normalize(results);
+	let labels = rows
+		.iter()
+		.map(|row| format!("{}, {}", label(&row.backend, &row.config), row.phase))
+		.collect::<Vec<_>>();
+	let width = labels.iter().map(String::len).max().unwrap_or(0);
+	let optional = |value: Option<f64>, scale: f64| {
+		value.map_or("-".to_string(), |value| format!("{:.2}", value / scale))
+	};
+
+	writeln!(
+		out,
+		"{:width$} {:>12} {:>12} {:>16} {:>12}",
+		"", "n", "ns/elem", "Melem/s/core", "MB/s"
+	)?;
+	for (row, label) in rows.iter().zip(&labels) {
+		writeln!(
+			out,
+			"{label:width$} {:>12} {:>12.3} {:>16} {:>12}",
+			row.n_elements,
+			row.ns_per_element,
+			optional(row.elements_per_core_second, 1e6),
+			optional(row.witness_mb_per_second, 1.0),
+		)?;
+	}
+	out.flush()
+}
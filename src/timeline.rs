@@ -0,0 +1,267 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Per-thread CPU utilization timelines of the examples.
+//!
+//! Wall time and total CPU time do not show when a prover runs on one core while the others idle,
+//! as in a single-threaded FRI query phase after a parallel commit. When
+//! `SNARK_BENCH_TIMELINE=<directory>` is set, [`init_timeline`] samples the CPU time of every
+//! thread of the process from Linux procfs at a fixed interval, and writes the utilization of each
+//! thread in each interval to `<directory>/<example>-<unix time>.csv` at the end of the run. Each
+//! [`crate::phase::Phase`] also prints how many threads were busy during it, so serial phases stand
+//! out next to their timings.
+
+use std::{
+	collections::HashMap,
+	env, fmt, fs,
+	io::{BufWriter, Write},
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex, OnceLock,
+	},
+	thread::{self, JoinHandle},
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Environment variable holding the directory to write timelines to.
+pub const TIMELINE_DIR_VAR: &str = "SNARK_BENCH_TIMELINE";
+
+/// Time between samples.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Utilization above which a thread counts as busy in an interval.
+const BUSY_UTILIZATION: f64 = 0.5;
+
+/// Utilization of each thread over one sampling interval.
+struct Interval {
+	/// End of the interval, since the timeline started.
+	end: Duration,
+	/// Thread ids and the fraction of the interval each spent on a CPU.
+	threads: Vec<(u32, f64)>,
+}
+
+/// Samples recorded so far, shared by the sampler thread and the phases reading them.
+struct Timeline {
+	start: Instant,
+	intervals: Mutex<Vec<Interval>>,
+	names: Mutex<HashMap<u32, String>>,
+}
+
+static TIMELINE: OnceLock<Timeline> = OnceLock::new();
+
+/// Reads the nanoseconds each thread of the process has spent on a CPU, skipping `skip_tid`.
+fn thread_cpu_times(skip_tid: u32) -> Vec<(u32, u64)> {
+	let Ok(tasks) = fs::read_dir("/proc/self/task") else {
+		return Vec::new();
+	};
+	tasks
+		.filter_map(|entry| {
+			let entry = entry.ok()?;
+			let tid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+			if tid == skip_tid {
+				return None;
+			}
+			let schedstat = fs::read_to_string(entry.path().join("schedstat")).ok()?;
+			let cpu_ns = schedstat.split_whitespace().next()?.parse().ok()?;
+			Some((tid, cpu_ns))
+		})
+		.collect()
+}
+
+fn thread_name(tid: u32) -> String {
+	fs::read_to_string(format!("/proc/self/task/{tid}/comm"))
+		.map(|name| name.trim().to_string())
+		.unwrap_or_default()
+}
+
+impl Timeline {
+	/// Records one interval from the CPU times at its end and at the end of the previous one.
+	fn record(&self, previous: &HashMap<u32, u64>, current: &[(u32, u64)], elapsed: Duration) {
+		let mut names = self.names.lock().unwrap();
+		let threads = current
+			.iter()
+			.map(|&(tid, cpu_ns)| {
+				names.entry(tid).or_insert_with(|| thread_name(tid));
+				let busy_ns = cpu_ns.saturating_sub(previous.get(&tid).copied().unwrap_or(cpu_ns));
+				(tid, (busy_ns as f64 / elapsed.as_nanos() as f64).min(1.0))
+			})
+			.collect();
+		self.intervals.lock().unwrap().push(Interval {
+			end: self.start.elapsed(),
+			threads,
+		});
+	}
+
+	fn write_csv(&self, path: &Path) -> std::io::Result<()> {
+		let names = self.names.lock().unwrap();
+		let mut file = BufWriter::new(fs::File::create(path)?);
+		writeln!(file, "time_ms,tid,thread,utilization")?;
+		for interval in self.intervals.lock().unwrap().iter() {
+			for &(tid, utilization) in &interval.threads {
+				writeln!(
+					file,
+					"{:.1},{tid},{},{utilization:.3}",
+					interval.end.as_secs_f64() * 1e3,
+					names.get(&tid).map_or("", String::as_str)
+				)?;
+			}
+		}
+		file.flush()
+	}
+}
+
+/// Thread utilization summarized over a phase.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Utilization {
+	/// Start of the phase in the timeline, in milliseconds.
+	pub start_ms: f64,
+	/// End of the phase in the timeline, in milliseconds.
+	pub end_ms: f64,
+	/// Mean number of CPUs in use, summed over threads.
+	pub mean_cpus: f64,
+	/// Fraction of the intervals in which at most one thread was busy.
+	pub serial_fraction: f64,
+}
+
+impl fmt::Display for Utilization {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"mean CPUs={:.1}, serial={:.0}% of the time (timeline {:.0}-{:.0} ms)",
+			self.mean_cpus,
+			self.serial_fraction * 100.0,
+			self.start_ms,
+			self.end_ms
+		)
+	}
+}
+
+/// The current position in the timeline, or `None` if no timeline is being recorded.
+pub fn timeline_position() -> Option<Duration> {
+	Some(TIMELINE.get()?.start.elapsed())
+}
+
+/// Summarizes the intervals recorded since `start`, a position from [`timeline_position`].
+pub fn utilization_since(start: Duration) -> Option<Utilization> {
+	let timeline = TIMELINE.get()?;
+	let end = timeline.start.elapsed();
+	let intervals = timeline.intervals.lock().unwrap();
+	let in_phase = intervals
+		.iter()
+		.filter(|interval| interval.end > start && interval.end <= end + SAMPLE_INTERVAL)
+		.collect::<Vec<_>>();
+	if in_phase.is_empty() {
+		return None;
+	}
+	let n_intervals = in_phase.len() as f64;
+	let total_cpus = in_phase
+		.iter()
+		.map(|interval| interval.threads.iter().map(|&(_, u)| u).sum::<f64>())
+		.sum::<f64>();
+	let serial = in_phase
+		.iter()
+		.filter(|interval| {
+			let busy = interval
+				.threads
+				.iter()
+				.filter(|&&(_, u)| u >= BUSY_UTILIZATION);
+			busy.count() <= 1
+		})
+		.count();
+	Some(Utilization {
+		start_ms: start.as_secs_f64() * 1e3,
+		end_ms: end.as_secs_f64() * 1e3,
+		mean_cpus: total_cpus / n_intervals,
+		serial_fraction: serial as f64 / n_intervals,
+	})
+}
+
+/// Samples per-thread CPU utilization until dropped, then writes the timeline.
+pub struct TimelineGuard {
+	path: PathBuf,
+	stop: Arc<AtomicBool>,
+	handle: Option<JoinHandle<()>>,
+}
+
+/// Starts recording the CPU timeline if enabled and supported by the platform.
+///
+/// The timeline is only written once the returned guard is dropped, so it must be held until the
+/// end of `main`.
+pub fn init_timeline(name: &str) -> Option<TimelineGuard> {
+	let dir = env::var(TIMELINE_DIR_VAR).ok()?;
+	if !cfg!(target_os = "linux") {
+		println!("Skipping CPU timeline: per-thread CPU time is only read on Linux");
+		return None;
+	}
+	fs::create_dir_all(&dir).unwrap_or_else(|err| panic!("failed to create {dir:?}: {err}"));
+
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap()
+		.as_secs();
+	let path = Path::new(&dir).join(format!("{name}-{timestamp}.csv"));
+
+	let timeline = TIMELINE.get_or_init(|| Timeline {
+		start: Instant::now(),
+		intervals: Mutex::new(Vec::new()),
+		names: Mutex::new(HashMap::new()),
+	});
+	// An earlier example run in this process wrote its intervals already.
+	timeline.intervals.lock().unwrap().clear();
+	let stop = Arc::new(AtomicBool::new(false));
+	let handle = thread::Builder::new()
+		.name("cpu-timeline".into())
+		.spawn({
+			let stop = stop.clone();
+			move || {
+				// The sampler excludes itself, so that it does not count as a busy thread.
+				let own_tid = current_tid().unwrap_or(0);
+				let mut previous = thread_cpu_times(own_tid)
+					.into_iter()
+					.collect::<HashMap<_, _>>();
+				let mut last = Instant::now();
+				while !stop.load(Ordering::Acquire) {
+					thread::sleep(SAMPLE_INTERVAL);
+					let now = Instant::now();
+					let current = thread_cpu_times(own_tid);
+					timeline.record(&previous, &current, now - last);
+					last = now;
+					previous = current.into_iter().collect();
+				}
+			}
+		})
+		.expect("failed to spawn the CPU timeline sampler");
+
+	Some(TimelineGuard {
+		path,
+		stop,
+		handle: Some(handle),
+	})
+}
+
+fn current_tid() -> Option<u32> {
+	#[cfg(target_os = "linux")]
+	{
+		Some(unsafe { libc::gettid() } as u32)
+	}
+	#[cfg(not(target_os = "linux"))]
+	{
+		None
+	}
+}
+
+impl Drop for TimelineGuard {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Release);
+		if let Some(handle) = self.handle.take() {
+			handle.join().unwrap();
+		}
+		let timeline = TIMELINE
+			.get()
+			.expect("the timeline is initialized with the guard");
+		match timeline.write_csv(&self.path) {
+			Ok(()) => println!("Wrote CPU timeline to {}", self.path.display()),
+			Err(err) => println!("Failed to write CPU timeline to {}: {err}", self.path.display()),
+		}
+	}
+}
@@ -0,0 +1,51 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Chrome trace export of the examples' `tracing` spans.
+//!
+//! When `SNARK_BENCH_TRACE=<directory>` is set, [`init_tracing`] installs a subscriber that
+//! records every span at debug level and above, including those inside the proving libraries, and
+//! writes them to `<directory>/<example>-<unix time>.json` in the Chrome trace format. The file can
+//! be opened in Perfetto (<https://ui.perfetto.dev>) or `chrome://tracing`. Every timed phase is a
+//! `phase` span with the phase, backend, and configuration as arguments, enclosing the spans the
+//! example and the proving library open during it.
+
+use std::{
+	env, fs,
+	path::Path,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::{filter::LevelFilter, prelude::*};
+
+/// Environment variable holding the directory to write traces to.
+pub const TRACE_DIR_VAR: &str = "SNARK_BENCH_TRACE";
+
+/// Installs the Chrome trace subscriber if tracing is enabled.
+///
+/// The trace is only complete once the returned guard is dropped, so it must be held until the
+/// end of `main`.
+pub fn init_tracing(name: &str) -> Option<FlushGuard> {
+	let dir = env::var(TRACE_DIR_VAR).ok()?;
+	if tracing::dispatcher::has_been_set() {
+		println!("Skipping Chrome trace of {name}: only the first example of a process is traced");
+		return None;
+	}
+	fs::create_dir_all(&dir).unwrap_or_else(|err| panic!("failed to create {dir:?}: {err}"));
+
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap()
+		.as_secs();
+	let path = Path::new(&dir).join(format!("{name}-{timestamp}.json"));
+	println!("Writing Chrome trace to {}", path.display());
+
+	let (layer, guard) = ChromeLayerBuilder::new()
+		.file(path)
+		.include_args(true)
+		.build();
+	tracing_subscriber::registry()
+		.with(layer.with_filter(LevelFilter::DEBUG))
+		.init();
+	Some(guard)
+}
@@ -0,0 +1,194 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Runs of the benchmark targets in child processes under different settings, and the comparison
+//! of their results.
+//!
+//! Some settings only take effect for a whole process: the target features a build enables, see
+//! [`crate::isa`], and the NUMA placement applied at startup, see [`crate::numa`]. [`run_variant`]
+//! builds and runs an example or bench in a child `cargo` process with the environment of one
+//! setting, and collects the results it writes to a directory of its own. The results of a bench
+//! are imported from its Criterion directory with [`crate::criterion_import`]. The runs are kept
+//! out of the history database, since they do not measure the machine as it is normally
+//! configured. [`write_variant_table`] then lists, per backend and configuration, the time of every
+//! phase under the first setting and the speedup of each other setting over it, with the geometric
+//! mean over the phases.
+
+use std::{
+	collections::BTreeMap, env, ffi::OsStr, fs, io, path::Path, process::Command, time::Duration,
+};
+
+use crate::{
+	criterion_import::load_criterion,
+	history::HISTORY_DB_VAR,
+	report::PhaseTimings,
+	results::{load_results, Results, RESULTS_DIR_VAR},
+};
+
+/// A benchmark target of the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BenchTarget {
+	/// An example, which writes its own results.
+	Example(String),
+	/// A Criterion bench, whose results are imported.
+	Bench(String),
+}
+
+impl BenchTarget {
+	/// Name of the example or bench.
+	pub fn name(&self) -> &str {
+		match self {
+			Self::Example(name) | Self::Bench(name) => name,
+		}
+	}
+}
+
+/// The `cargo` command that builds and runs `target` with the given Cargo features and environment
+/// variables, writing its results to `dir`, which is created.
+pub fn variant_command(
+	target: &BenchTarget,
+	features: Option<&str>,
+	envs: &[(&str, &OsStr)],
+	dir: &Path,
+) -> Result<Command, String> {
+	fs::create_dir_all(dir).map_err(|err| format!("failed to create {}: {err}", dir.display()))?;
+
+	let mut cargo = Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+	match target {
+		BenchTarget::Example(name) => cargo.args(["run", "--release", "--example", name]),
+		BenchTarget::Bench(name) => cargo.args(["bench", "--bench", name]),
+	};
+	if let Some(features) = features {
+		cargo.args(["--features", features]);
+	}
+	cargo
+		.envs(envs.iter().copied())
+		.env(RESULTS_DIR_VAR, dir)
+		.env("CRITERION_HOME", dir.join("criterion"))
+		.env_remove(HISTORY_DB_VAR);
+	Ok(cargo)
+}
+
+/// Builds and runs `target` with the given Cargo features and environment variables, passing it
+/// `args`, under the setting named `setting`, writing its results to `dir`, and returns them.
+pub fn run_variant(
+	setting: &str,
+	target: &BenchTarget,
+	features: Option<&str>,
+	envs: &[(&str, &OsStr)],
+	args: &[String],
+	dir: &Path,
+) -> Result<Vec<Results>, String> {
+	let mut cargo = variant_command(target, features, envs, dir)?;
+	if !args.is_empty() {
+		cargo.arg("--").args(args);
+	}
+	println!("== {setting}: {}", target.name());
+	let status = cargo
+		.status()
+		.map_err(|err| format!("failed to run cargo: {err}"))?;
+	if !status.success() {
+		return Err(format!("{} failed under {setting}: {status}", target.name()));
+	}
+
+	match target {
+		BenchTarget::Example(_) => load_results(&[dir.to_path_buf()]),
+		BenchTarget::Bench(_) => {
+			let results = load_criterion(&dir.join("criterion"))?;
+			results.write_to_dir(dir)?;
+			Ok(vec![results])
+		}
+	}
+}
+
+/// Writes, for every backend and configuration, the median time of every phase under the first of
+/// the named `variants` and the speedup of every other variant over it, with the geometric mean
+/// speedup over the phases. `kind` names what the variants vary, such as `ISA level`.
+pub fn write_variant_table(
+	kind: &str,
+	variants: &[(String, Vec<Results>)],
+	mut out: impl io::Write,
+) -> Result<(), String> {
+	let timings = variants
+		.iter()
+		.map(|(_, results)| PhaseTimings::new(results))
+		.collect::<Vec<_>>();
+	let Some(lowest) = timings.first() else {
+		return Err(format!("no {kind}s were run"));
+	};
+
+	// The speedups of every other variant over the first, by series and then phase and size, in
+	// the order the series and phases ran under the first.
+	let mut rows = BTreeMap::<usize, Vec<(String, f64, Vec<Option<f64>>)>>::new();
+	for (index, series) in lowest.series.iter().enumerate() {
+		for phase in &lowest.phases {
+			for n in lowest.sizes(Some(phase)) {
+				let Some(seconds) = lowest.median(series, phase, n) else {
+					continue;
+				};
+				let speedups = timings[1..]
+					.iter()
+					.map(|variant| {
+						variant
+							.median(series, phase, n)
+							.map(|variant_seconds| seconds / variant_seconds)
+					})
+					.collect();
+				rows.entry(index)
+					.or_default()
+					.push((format!("{phase} n={n}"), seconds, speedups));
+			}
+		}
+	}
+	if rows.is_empty() {
+		return Err(format!("no phases ran under the first {kind}"));
+	}
+
+	let names = variants.iter().map(|(name, _)| name).collect::<Vec<_>>();
+	let width = rows
+		.values()
+		.flatten()
+		.map(|(label, _, _)| label.len() + 2)
+		.chain(lowest.series.iter().map(String::len))
+		.chain(["geometric mean".len() + 2])
+		.max()
+		.unwrap_or(0);
+	let cell = |speedup: Option<f64>| speedup.map_or("-".to_string(), |s| format!("{s:.2}x"));
+
+	let mut write = || -> io::Result<()> {
+		writeln!(out, "Speedup of each {kind} over {}, as its time over theirs:", names[0])?;
+		for (index, rows) in &rows {
+			writeln!(out)?;
+			write!(out, "{:width$} {:>12}", lowest.series[*index], format!("{} time", names[0]))?;
+			for name in &names[1..] {
+				write!(out, " {name:>10}")?;
+			}
+			writeln!(out)?;
+			for (label, seconds, speedups) in rows {
+				write!(
+					out,
+					"  {label:w$} {:>12}",
+					format!("{:.2?}", Duration::from_secs_f64(*seconds)),
+					w = width - 2
+				)?;
+				for &speedup in speedups {
+					write!(out, " {:>10}", cell(speedup))?;
+				}
+				writeln!(out)?;
+			}
+			write!(out, "  {:w$} {:>12}", "geometric mean", "", w = width - 2)?;
+			for variant in 0..names.len() - 1 {
+				let speedups = rows
+					.iter()
+					.filter_map(|(_, _, speedups)| speedups[variant])
+					.collect::<Vec<_>>();
+				let mean = (!speedups.is_empty()).then(|| {
+					(speedups.iter().map(|s| s.ln()).sum::<f64>() / speedups.len() as f64).exp()
+				});
+				write!(out, " {:>10}", cell(mean))?;
+			}
+			writeln!(out)?;
+		}
+		out.flush()
+	};
+	write().map_err(|err| format!("failed to write the {kind} table: {err}"))
+}
@@ -0,0 +1,186 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Single-core, cold-start verification, the way light clients and verifiers next to a chain run.
+//!
+//! The examples verify on a warm machine: the proof is still the in-memory value the prover built,
+//! the caches hold what proving left in them, and the rayon pool spreads the work over every core.
+//! A light client receives the proof as bytes and verifies it on one core with nothing cached.
+//! When `SNARK_BENCH_VERIFIER_PROFILE` is set to a CPU index, [`verify_cold`] times an extra
+//! `verify cold` phase from the serialized proof: it evicts the caches, then deserializes and
+//! verifies on a single-thread rayon pool pinned to that CPU, so that parallel iterators inside
+//! the verifier stay on it too.
+//!
+//! Setting it to `<cpu>@<MHz>`, such as `2@2000`, also fixes the frequency of that CPU for the
+//! phase through its cpufreq limits, which needs root. Where the limits cannot be written, a
+//! warning is recorded and the CPU runs at whatever its governor picks.
+
+use std::{
+	env, fmt, fs,
+	path::{Path, PathBuf},
+};
+
+use bytesize::ByteSize;
+
+use crate::{
+	cache::evict_caches, cpu_state::CPU_DIR, phase::Phase, results::record_warning,
+	threads::pin_current_thread,
+};
+
+/// Environment variable selecting the CPU, and optionally the frequency, of cold verification.
+pub const VERIFIER_PROFILE_VAR: &str = "SNARK_BENCH_VERIFIER_PROFILE";
+
+/// Name of the phase [`verify_cold`] records.
+pub const COLD_VERIFY_PHASE: &str = "verify cold";
+
+/// Stack of the verifier thread, the default stack of the main thread on Linux, on which the
+/// warm verification runs.
+const VERIFIER_STACK_BYTES: usize = 8 << 20;
+
+/// Where cold verification runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifierProfile {
+	/// The CPU the verifier is pinned to.
+	pub cpu: usize,
+	/// The frequency to fix the CPU at, in MHz.
+	pub frequency_mhz: Option<u64>,
+}
+
+impl fmt::Display for VerifierProfile {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "CPU {}", self.cpu)?;
+		if let Some(mhz) = self.frequency_mhz {
+			write!(f, " at {mhz} MHz")?;
+		}
+		Ok(())
+	}
+}
+
+impl VerifierProfile {
+	/// Parses a profile of the form `<cpu>` or `<cpu>@<MHz>`.
+	pub fn parse(value: &str) -> Result<Self, String> {
+		let (cpu, frequency) = match value.split_once('@') {
+			Some((cpu, mhz)) => (cpu, Some(mhz)),
+			None => (value, None),
+		};
+		let cpu = cpu
+			.trim()
+			.parse()
+			.map_err(|err| format!("invalid CPU index {cpu:?}: {err}"))?;
+		let frequency_mhz = frequency
+			.map(|mhz| {
+				mhz.trim()
+					.parse()
+					.map_err(|err| format!("invalid frequency {mhz:?}: {err}"))
+			})
+			.transpose()?;
+		Ok(Self { cpu, frequency_mhz })
+	}
+
+	/// Reads the profile requested through [`VERIFIER_PROFILE_VAR`], if any.
+	pub fn from_env() -> Option<Self> {
+		let value = env::var(VERIFIER_PROFILE_VAR).ok()?;
+		Some(Self::parse(&value).unwrap_or_else(|err| {
+			panic!("{VERIFIER_PROFILE_VAR} must be `<cpu>` or `<cpu>@<MHz>`: {err}")
+		}))
+	}
+}
+
+fn cpufreq(cpu: usize, file: &str) -> PathBuf {
+	Path::new(CPU_DIR).join(format!("cpu{cpu}/cpufreq/{file}"))
+}
+
+fn read_khz(cpu: usize, file: &str) -> Option<String> {
+	Some(
+		fs::read_to_string(cpufreq(cpu, file))
+			.ok()?
+			.trim()
+			.to_string(),
+	)
+}
+
+/// The frequency limits of one CPU pinned to a single frequency, restored when dropped.
+struct FrequencyLock {
+	cpu: usize,
+	floor_khz: String,
+	min_khz: String,
+	max_khz: String,
+}
+
+impl FrequencyLock {
+	/// Fixes the frequency of `cpu` at `mhz`, returning `None` if its limits cannot be written.
+	fn new(cpu: usize, mhz: u64) -> Option<Self> {
+		let lock = Self {
+			cpu,
+			floor_khz: read_khz(cpu, "cpuinfo_min_freq")?,
+			min_khz: read_khz(cpu, "scaling_min_freq")?,
+			max_khz: read_khz(cpu, "scaling_max_freq")?,
+		};
+		let khz = (mhz * 1000).to_string();
+		// Dropping the lock restores whichever limits were already written.
+		lock.set(&khz, &khz).then_some(lock)
+	}
+
+	/// Sets the limits of the CPU, lowering the minimum first so that it never exceeds the maximum.
+	fn set(&self, min_khz: &str, max_khz: &str) -> bool {
+		let write = |file, khz: &str| fs::write(cpufreq(self.cpu, file), khz).is_ok();
+		write("scaling_min_freq", &self.floor_khz)
+			&& write("scaling_max_freq", max_khz)
+			&& write("scaling_min_freq", min_khz)
+	}
+}
+
+impl Drop for FrequencyLock {
+	fn drop(&mut self) {
+		self.set(&self.min_khz, &self.max_khz);
+	}
+}
+
+/// Times the `verify cold` phase of `verify` on the serialized `proof`, if a verifier profile is
+/// requested through [`VERIFIER_PROFILE_VAR`], and returns its result.
+///
+/// `verify` must deserialize the proof from the bytes it is given, so that the phase counts it, and
+/// hold only what a verifier has before receiving a proof, such as the commitment and parameters.
+pub fn verify_cold<R: Send>(
+	proof: &[u8],
+	n_elements: u64,
+	verify: impl FnOnce(&[u8]) -> R + Send,
+) -> Option<R> {
+	let profile = VerifierProfile::from_env()?;
+	let pool = rayon::ThreadPoolBuilder::new()
+		.num_threads(1)
+		.stack_size(VERIFIER_STACK_BYTES)
+		.build()
+		.expect("failed to start the verifier thread");
+	if !pool.install(|| pin_current_thread(profile.cpu)) {
+		record_warning(format!(
+			"could not pin cold verification to CPU {}, it ran on any core",
+			profile.cpu
+		));
+	}
+	let _frequency = profile.frequency_mhz.and_then(|mhz| {
+		let Some(lock) = FrequencyLock::new(profile.cpu, mhz) else {
+			record_warning(format!(
+				"could not fix the frequency of CPU {}, cold verification ran at the frequency its \
+				 governor picked",
+				profile.cpu
+			));
+			return None;
+		};
+		// The kernel clamps the limits to the frequencies the CPU supports.
+		let khz = read_khz(profile.cpu, "scaling_max_freq").unwrap_or_default();
+		if khz != (mhz * 1000).to_string() {
+			record_warning(format!(
+				"CPU {} was limited to {khz} kHz rather than {mhz} MHz",
+				profile.cpu
+			));
+		}
+		Some(lock)
+	});
+	println!("Cold verification on {profile} from a proof of {}", ByteSize(proof.len() as u64));
+
+	evict_caches();
+	let phase = Phase::start(COLD_VERIFY_PHASE);
+	let result = pool.install(|| verify(proof));
+	phase.finish(n_elements);
+	Some(result)
+}
@@ -0,0 +1,109 @@
+// Copyright 2024 Irreducible Inc.
+
+//! Process counters and machine details from the Win32 API, for the measurements that read
+//! `/proc` and sysfs on Linux.
+
+use std::{
+	ffi::OsString,
+	mem::{size_of, zeroed},
+	os::windows::ffi::OsStringExt,
+	ptr,
+	time::Duration,
+};
+
+use windows_sys::Win32::{
+	Foundation::FILETIME,
+	System::{
+		ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
+		Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ},
+		SystemInformation::{
+			ComputerNameDnsHostname, GetComputerNameExW, GlobalMemoryStatusEx, MEMORYSTATUSEX,
+		},
+		Threading::{GetCurrentProcess, GetProcessIoCounters, GetProcessTimes, IO_COUNTERS},
+	},
+};
+
+/// The memory counters of the current process: its working set and page faults.
+pub(crate) fn memory_counters() -> Option<PROCESS_MEMORY_COUNTERS> {
+	let mut counters = unsafe { zeroed::<PROCESS_MEMORY_COUNTERS>() };
+	let size = size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+	// SAFETY: the counters are a `PROCESS_MEMORY_COUNTERS` of the size passed, and the handle of
+	// the current process needs no closing.
+	(unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) } != 0)
+		.then_some(counters)
+}
+
+/// The I/O counters of the current process, which count every read and write it issued, whether
+/// the file system cache served it or a device.
+pub(crate) fn io_counters() -> Option<IO_COUNTERS> {
+	let mut counters = unsafe { zeroed::<IO_COUNTERS>() };
+	// SAFETY: the counters are a valid `IO_COUNTERS`.
+	(unsafe { GetProcessIoCounters(GetCurrentProcess(), &mut counters) } != 0).then_some(counters)
+}
+
+fn duration(time: FILETIME) -> Duration {
+	let ticks = (time.dwHighDateTime as u64) << 32 | time.dwLowDateTime as u64;
+	// A FILETIME counts intervals of 100 ns.
+	Duration::from_nanos(ticks * 100)
+}
+
+/// The user and kernel CPU time of the current process, over all its threads.
+pub(crate) fn process_times() -> Option<(Duration, Duration)> {
+	let [mut creation, mut exit, mut kernel, mut user] = unsafe { zeroed::<[FILETIME; 4]>() };
+	// SAFETY: every pointer is to a valid `FILETIME`.
+	if unsafe {
+		GetProcessTimes(GetCurrentProcess(), &mut creation, &mut exit, &mut kernel, &mut user)
+	} == 0
+	{
+		return None;
+	}
+	Some((duration(user), duration(kernel)))
+}
+
+/// The physical memory of the machine.
+pub(crate) fn physical_memory() -> Option<u64> {
+	let mut status = unsafe { zeroed::<MEMORYSTATUSEX>() };
+	status.dwLength = size_of::<MEMORYSTATUSEX>() as u32;
+	// SAFETY: the status is a `MEMORYSTATUSEX` with its length set.
+	(unsafe { GlobalMemoryStatusEx(&mut status) } != 0).then_some(status.ullTotalPhys)
+}
+
+/// The DNS host name of the machine.
+pub(crate) fn host_name() -> Option<String> {
+	let mut name = [0u16; 256];
+	let mut len = name.len() as u32;
+	// SAFETY: the buffer holds `len` characters, and on success `len` is set to the number written
+	// before the terminating null.
+	if unsafe { GetComputerNameExW(ComputerNameDnsHostname, name.as_mut_ptr(), &mut len) } == 0 {
+		return None;
+	}
+	let host = OsString::from_wide(&name[..len as usize]);
+	Some(host.to_string_lossy().into_owned())
+}
+
+/// The model name of the first CPU, from the registry.
+pub(crate) fn cpu_model() -> Option<String> {
+	let wide = |text: &str| text.encode_utf16().chain([0]).collect::<Vec<_>>();
+	let key = wide(r"HARDWARE\DESCRIPTION\System\CentralProcessor\0");
+	let value = wide("ProcessorNameString");
+	let mut name = [0u16; 256];
+	let mut size = (name.len() * size_of::<u16>()) as u32;
+	// SAFETY: the key and value names are null-terminated, and the buffer holds `size` bytes.
+	let status = unsafe {
+		RegGetValueW(
+			HKEY_LOCAL_MACHINE,
+			key.as_ptr(),
+			value.as_ptr(),
+			RRF_RT_REG_SZ,
+			ptr::null_mut(),
+			name.as_mut_ptr().cast(),
+			&mut size,
+		)
+	};
+	if status != 0 {
+		return None;
+	}
+	let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+	let model = OsString::from_wide(&name[..len]);
+	Some(model.to_string_lossy().trim().to_string())
+}